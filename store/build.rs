@@ -1,6 +1,9 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = prost_build::Config::new();
+    // an invoice is much larger than its error code
+    config.boxed(".store.GetInvoiceResponse.status.invoice");
     tonic_build::configure()
-        .compile(&["../proto/schema.proto"], &["../proto"])
+        .compile_with_config(config, &["../proto/schema.proto"], &["../proto"])
         .unwrap();
 
     Ok(())
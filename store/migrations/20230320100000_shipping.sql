@@ -0,0 +1,43 @@
+ALTER TABLE article ADD COLUMN IF NOT EXISTS weight integer NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS shipping_method (
+  id uuid NOT NULL PRIMARY KEY,
+  name text NOT NULL UNIQUE,
+  description text NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS shipping_cost_rule (
+  id uuid NOT NULL PRIMARY KEY,
+  shipping_method_id uuid NOT NULL REFERENCES shipping_method(id) ON DELETE CASCADE,
+  min_weight integer NOT NULL DEFAULT 0,
+  max_weight integer,
+  min_subtotal decimal NOT NULL DEFAULT 0,
+  max_subtotal decimal,
+  cost decimal NOT NULL
+);
+
+ALTER TABLE customer_order
+  ADD COLUMN IF NOT EXISTS shipping_method_id uuid REFERENCES shipping_method(id) ON DELETE RESTRICT,
+  ADD COLUMN IF NOT EXISTS shipping_cost decimal NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS shipment (
+  order_id uuid NOT NULL PRIMARY KEY REFERENCES customer_order(id) ON DELETE RESTRICT,
+  carrier text NOT NULL,
+  tracking_code text NOT NULL,
+  shipped_at timestamp NOT NULL
+);
+
+-- default shipping methods
+
+INSERT INTO shipping_method (id, name, description) VALUES
+  ('6a0b1f4e-2d0c-4b7a-9a55-0f3c1f6f3c01', 'standard', 'Standard delivery in 3-5 working days'),
+  ('6a0b1f4e-2d0c-4b7a-9a55-0f3c1f6f3c02', 'express', 'Express delivery in 1-2 working days')
+ON CONFLICT DO NOTHING;
+
+INSERT INTO shipping_cost_rule (id, shipping_method_id, min_weight, max_weight, min_subtotal, max_subtotal, cost) VALUES
+  ('0d5e7a52-8f7e-4c1e-8f0b-6c1e1a9b7d01', '6a0b1f4e-2d0c-4b7a-9a55-0f3c1f6f3c01', 0, 2000, 0, 50, 4.90),
+  ('0d5e7a52-8f7e-4c1e-8f0b-6c1e1a9b7d02', '6a0b1f4e-2d0c-4b7a-9a55-0f3c1f6f3c01', 2000, NULL, 0, 50, 9.90),
+  ('0d5e7a52-8f7e-4c1e-8f0b-6c1e1a9b7d03', '6a0b1f4e-2d0c-4b7a-9a55-0f3c1f6f3c01', 0, NULL, 50, NULL, 0),
+  ('0d5e7a52-8f7e-4c1e-8f0b-6c1e1a9b7d04', '6a0b1f4e-2d0c-4b7a-9a55-0f3c1f6f3c02', 0, 5000, 0, NULL, 12.90),
+  ('0d5e7a52-8f7e-4c1e-8f0b-6c1e1a9b7d05', '6a0b1f4e-2d0c-4b7a-9a55-0f3c1f6f3c02', 5000, NULL, 0, NULL, 19.90)
+ON CONFLICT DO NOTHING;
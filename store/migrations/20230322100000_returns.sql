@@ -0,0 +1,38 @@
+ALTER TYPE order_status ADD VALUE IF NOT EXISTS 'return_requested';
+ALTER TYPE order_status ADD VALUE IF NOT EXISTS 'refunded';
+ALTER TYPE order_status ADD VALUE IF NOT EXISTS 'partially_refunded';
+
+DO $$ BEGIN
+  CREATE TYPE return_status AS ENUM (
+      'requested',
+      'approved',
+      'rejected'
+  );
+  EXCEPTION
+      WHEN duplicate_object THEN null;
+END $$;
+
+CREATE TABLE IF NOT EXISTS order_return (
+  id uuid NOT NULL PRIMARY KEY,
+  order_id uuid NOT NULL REFERENCES customer_order(id) ON DELETE RESTRICT,
+  status return_status NOT NULL,
+  reason text NOT NULL,
+  created_at timestamp NOT NULL,
+  resolved_at timestamp
+);
+
+CREATE TABLE IF NOT EXISTS order_return_article (
+  id uuid NOT NULL PRIMARY KEY,
+  return_id uuid NOT NULL REFERENCES order_return(id) ON DELETE RESTRICT,
+  order_article_id uuid NOT NULL REFERENCES order_article(id) ON DELETE RESTRICT,
+  quantity integer NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS refund (
+  id uuid NOT NULL PRIMARY KEY,
+  order_id uuid NOT NULL REFERENCES customer_order(id) ON DELETE RESTRICT,
+  return_id uuid NOT NULL UNIQUE REFERENCES order_return(id) ON DELETE RESTRICT,
+  transaction_id text NOT NULL,
+  amount decimal NOT NULL,
+  created_at timestamp NOT NULL
+);
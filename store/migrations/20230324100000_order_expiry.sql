@@ -0,0 +1,4 @@
+ALTER TYPE order_status ADD VALUE IF NOT EXISTS 'expired';
+
+CREATE INDEX IF NOT EXISTS customer_order_status_created_at_idx
+  ON customer_order (status, created_at);
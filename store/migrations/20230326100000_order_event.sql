@@ -0,0 +1,39 @@
+DO $$ BEGIN
+  CREATE TYPE order_event_kind AS ENUM (
+      'created',
+      'status_changed',
+      'transaction_changed'
+  );
+  EXCEPTION
+      WHEN duplicate_object THEN null;
+END $$;
+
+DO $$ BEGIN
+  CREATE TYPE order_event_actor AS ENUM (
+      'customer',
+      'payment_gateway',
+      'back_office',
+      'system'
+  );
+  EXCEPTION
+      WHEN duplicate_object THEN null;
+END $$;
+
+CREATE TABLE IF NOT EXISTS order_event (
+  id uuid NOT NULL PRIMARY KEY,
+  order_id uuid NOT NULL REFERENCES customer_order(id) ON DELETE RESTRICT,
+  kind order_event_kind NOT NULL,
+  actor order_event_actor NOT NULL,
+  status order_status NOT NULL,
+  transaction_id text,
+  created_at timestamp NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS order_event_order_id_created_at_idx
+  ON order_event (order_id, created_at);
+
+-- orders placed before the history was introduced start with their creation
+INSERT INTO order_event (id, order_id, kind, actor, status, transaction_id, created_at)
+  SELECT gen_random_uuid(), o.id, 'created', 'customer', o.status, o.transaction_id, o.created_at
+  FROM customer_order o
+  WHERE NOT EXISTS (SELECT 1 FROM order_event e WHERE e.order_id = o.id);
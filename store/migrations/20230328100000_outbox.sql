@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS outbox_event (
+  id uuid NOT NULL PRIMARY KEY,
+  aggregate_id uuid NOT NULL,
+  event_type text NOT NULL,
+  payload jsonb NOT NULL,
+  created_at timestamp NOT NULL,
+  delivered_at timestamp,
+  attempts integer NOT NULL DEFAULT 0,
+  last_error text
+);
+
+CREATE INDEX IF NOT EXISTS outbox_event_pending_idx
+  ON outbox_event (created_at) WHERE delivered_at IS NULL;
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS cart (
+  id uuid NOT NULL PRIMARY KEY,
+  customer_id uuid UNIQUE REFERENCES customer(id) ON DELETE CASCADE,
+  created_at timestamp NOT NULL,
+  updated_at timestamp NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS cart_item (
+  cart_id uuid NOT NULL REFERENCES cart(id) ON DELETE CASCADE,
+  article_id uuid NOT NULL REFERENCES article(id) ON DELETE CASCADE,
+  quantity integer NOT NULL,
+  PRIMARY KEY (cart_id, article_id)
+);
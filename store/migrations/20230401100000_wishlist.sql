@@ -0,0 +1,16 @@
+ALTER TABLE article ADD COLUMN IF NOT EXISTS archived_at timestamp;
+
+CREATE TABLE IF NOT EXISTS wishlist (
+  id uuid NOT NULL PRIMARY KEY,
+  customer_id uuid NOT NULL REFERENCES customer(id) ON DELETE CASCADE,
+  name varchar(255) NOT NULL,
+  created_at timestamp NOT NULL,
+  UNIQUE (customer_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS wishlist_item (
+  wishlist_id uuid NOT NULL REFERENCES wishlist(id) ON DELETE CASCADE,
+  article_id uuid NOT NULL REFERENCES article(id),
+  added_at timestamp NOT NULL,
+  PRIMARY KEY (wishlist_id, article_id)
+);
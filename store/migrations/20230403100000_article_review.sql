@@ -0,0 +1,31 @@
+DO $$ BEGIN
+  CREATE TYPE review_status AS ENUM (
+      'pending',
+      'approved',
+      'rejected'
+  );
+  EXCEPTION
+      WHEN duplicate_object THEN null;
+END $$;
+
+CREATE TABLE IF NOT EXISTS article_review (
+  id uuid NOT NULL PRIMARY KEY,
+  article_id uuid NOT NULL REFERENCES article(id) ON DELETE CASCADE,
+  customer_id uuid NOT NULL REFERENCES customer(id) ON DELETE CASCADE,
+  rating smallint NOT NULL CHECK (rating BETWEEN 1 AND 5),
+  title varchar(255) NOT NULL,
+  body text NOT NULL,
+  status review_status NOT NULL,
+  created_at timestamp NOT NULL,
+  moderated_at timestamp,
+  UNIQUE (article_id, customer_id)
+);
+
+CREATE INDEX IF NOT EXISTS article_review_article_id_status_idx ON article_review (article_id, status, created_at);
+
+-- aggregated rating of the approved reviews, refreshed on moderation
+CREATE TABLE IF NOT EXISTS article_rating (
+  article_id uuid NOT NULL PRIMARY KEY REFERENCES article(id) ON DELETE CASCADE,
+  average decimal NOT NULL,
+  count integer NOT NULL
+);
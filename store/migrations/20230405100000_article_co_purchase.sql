@@ -0,0 +1,7 @@
+-- amount of paid orders in which both articles were bought; rebuilt periodically from order_article
+CREATE TABLE IF NOT EXISTS article_co_purchase (
+  article_id uuid NOT NULL REFERENCES article(id) ON DELETE CASCADE,
+  related_article_id uuid NOT NULL REFERENCES article(id) ON DELETE CASCADE,
+  score integer NOT NULL,
+  PRIMARY KEY (article_id, related_article_id)
+);
@@ -0,0 +1,37 @@
+-- last invoice number issued in each year; the row is locked by the transaction issuing an
+-- invoice, so that numbers are sequential and gap-free
+CREATE TABLE IF NOT EXISTS invoice_sequence (
+  year integer NOT NULL PRIMARY KEY,
+  last_number integer NOT NULL
+);
+
+-- invoices are snapshots: they don't change when the seller, the customer or the articles do
+CREATE TABLE IF NOT EXISTS invoice (
+  id uuid NOT NULL PRIMARY KEY,
+  order_id uuid NOT NULL UNIQUE REFERENCES customer_order(id) ON DELETE RESTRICT,
+  year integer NOT NULL,
+  number integer NOT NULL,
+  issued_at timestamp NOT NULL,
+  seller_name text NOT NULL,
+  seller_address text NOT NULL,
+  seller_vat_number text NOT NULL,
+  customer_email text NOT NULL,
+  transaction_id text NOT NULL,
+  shipping_cost decimal NOT NULL,
+  tax_rate decimal NOT NULL,
+  taxable_amount decimal NOT NULL,
+  tax_amount decimal NOT NULL,
+  total decimal NOT NULL,
+  UNIQUE (year, number)
+);
+
+CREATE TABLE IF NOT EXISTS invoice_line (
+  invoice_id uuid NOT NULL REFERENCES invoice(id) ON DELETE CASCADE,
+  position integer NOT NULL,
+  article_id uuid NOT NULL,
+  name text NOT NULL,
+  quantity integer NOT NULL,
+  unit_price decimal NOT NULL,
+  total decimal NOT NULL,
+  PRIMARY KEY (invoice_id, position)
+);
@@ -0,0 +1,3 @@
+-- customers list their orders newest first
+CREATE INDEX IF NOT EXISTS customer_order_customer_id_created_at_idx
+  ON customer_order (customer_id, created_at);
@@ -0,0 +1,2 @@
+-- relays claim pending events until the lease expires, instead of locking them while delivering
+ALTER TABLE outbox_event ADD COLUMN IF NOT EXISTS locked_until timestamp;
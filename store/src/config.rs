@@ -2,11 +2,44 @@
 //!
 //! App configuration
 
+use rust_decimal::Decimal;
+
 /// App configuration read from environment
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
     pub database_url: String,
     pub server_url: String,
+    /// Delay in milliseconds before the mock payment gateway reports the payment outcome
+    #[serde(default = "Config::default_payment_callback_delay_ms")]
+    pub payment_callback_delay_ms: u64,
+    /// Seconds after which an unpaid order expires
+    #[serde(default = "Config::default_order_ttl_secs")]
+    pub order_ttl_secs: u64,
+    /// Interval in seconds between two runs of the unpaid orders expiry job
+    #[serde(default = "Config::default_order_expiry_interval_secs")]
+    pub order_expiry_interval_secs: u64,
+    /// Sink where outbox events are relayed: `stdout`, `file` or `webhook`
+    #[serde(default = "Config::default_outbox_sink")]
+    pub outbox_sink: String,
+    /// Path of the file events are appended to, when using the `file` sink
+    pub outbox_file_path: Option<String>,
+    /// URL events are posted to, when using the `webhook` sink
+    pub outbox_webhook_url: Option<String>,
+    /// Interval in milliseconds between two runs of the outbox relay
+    #[serde(default = "Config::default_outbox_relay_interval_ms")]
+    pub outbox_relay_interval_ms: u64,
+    /// Interval in seconds between two rebuilds of the "frequently bought together" recommendations
+    #[serde(default = "Config::default_recommendation_rebuild_interval_secs")]
+    pub recommendation_rebuild_interval_secs: u64,
+    /// Seller name printed on the invoices
+    pub invoice_seller_name: String,
+    /// Seller address printed on the invoices
+    pub invoice_seller_address: String,
+    /// Seller VAT number printed on the invoices
+    pub invoice_seller_vat_number: String,
+    /// Percentage of tax included in the article prices
+    #[serde(default = "Config::default_invoice_tax_rate")]
+    pub invoice_tax_rate: Decimal,
 }
 
 impl Config {
@@ -14,6 +47,34 @@ impl Config {
         envy::from_env()
             .map_err(|e| anyhow::anyhow!("could not load config from environment: {}", e))
     }
+
+    fn default_payment_callback_delay_ms() -> u64 {
+        2000
+    }
+
+    fn default_order_ttl_secs() -> u64 {
+        3600
+    }
+
+    fn default_order_expiry_interval_secs() -> u64 {
+        60
+    }
+
+    fn default_outbox_sink() -> String {
+        "stdout".to_string()
+    }
+
+    fn default_outbox_relay_interval_ms() -> u64 {
+        1000
+    }
+
+    fn default_recommendation_rebuild_interval_secs() -> u64 {
+        3600
+    }
+
+    fn default_invoice_tax_rate() -> Decimal {
+        Decimal::from(22)
+    }
 }
 
 #[cfg(test)]
@@ -5,11 +5,21 @@ use std::sync::Arc;
 use thiserror::Error;
 
 mod tables;
+#[cfg(any(test, feature = "test-db"))]
+mod test_db;
 
 pub type DatabaseResult<T> = Result<T, DatabaseError>;
 type PgPool = Pool<Postgres>;
 
-pub use tables::{Article, Customer, CustomerOrder, OrderArticle, OrderStatus};
+pub use tables::{
+    Article, ArticleCoPurchase, ArticleRating, ArticleReview, Cart, CartItem, Customer,
+    CustomerOrder, Invoice, InvoiceLine, InvoiceSeller, OrderArticle, OrderEvent, OrderEventActor,
+    OrderEventKind, OrderFilter, OrderReturn, OrderReturnArticle, OrderStats, OrderStatus,
+    OutboxEvent, Refund, ReportGranularity, ReturnStatus, RevenueBucket, ReviewStatus, SalesReport,
+    Shipment, ShippingCostRule, ShippingMethod, TopArticle, Wishlist, WishlistItem,
+};
+#[cfg(any(test, feature = "test-db"))]
+pub use test_db::TestDb;
 
 #[derive(Debug, Error)]
 pub enum DatabaseError {
@@ -1,3 +1,4 @@
+use chrono::NaiveDateTime;
 use rust_decimal::Decimal;
 use uuid::Uuid;
 
@@ -9,6 +10,10 @@ pub struct Article {
     pub name: String,
     pub description: String,
     pub unit_price: Decimal,
+    /// Article weight in grams
+    pub weight: i32,
+    /// Archived articles can't be bought anymore, but they are still referenced by orders and wishlists
+    pub archived_at: Option<NaiveDateTime>,
 }
 
 impl Article {
@@ -21,45 +26,173 @@ impl Article {
             .map_err(DatabaseError::from)
     }
 
-    /// Find article by name
-    pub async fn find_by_name(
+    /// Insert new `Article` to database
+    pub async fn insert(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        name: impl ToString,
+        description: impl ToString,
+        unit_price: Decimal,
+        weight: i32,
+    ) -> DatabaseResult<Self> {
+        let article = Self {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: description.to_string(),
+            unit_price,
+            weight,
+            archived_at: None,
+        };
+        debug!("inserting a new article {} to repository", article.id);
+        let rows = sqlx::query(
+            "INSERT INTO article (id, name, description, unit_price, weight) VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(article.id)
+        .bind(&article.name)
+        .bind(&article.description)
+        .bind(article.unit_price)
+        .bind(article.weight)
+        .execute(db)
+        .await
+        .map_err(DatabaseError::from)?
+        .rows_affected();
+        if rows != 1 {
+            return Err(DatabaseError::TooManyInserts);
+        }
+
+        Ok(article)
+    }
+
+    /// Whether the article has been archived
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+
+    /// Archive the article, so that it can't be bought anymore
+    #[cfg(test)]
+    pub async fn archive(db: &StoreDb, id: &Uuid) -> DatabaseResult<()> {
+        debug!("archiving article {id}");
+        let rows = sqlx::query("UPDATE article SET archived_at = $1 WHERE id = $2")
+            .bind(chrono::Utc::now().naive_utc())
+            .bind(id)
+            .execute(db.pool())
+            .await
+            .map_err(DatabaseError::from)?
+            .rows_affected();
+        if rows != 1 {
+            return Err(DatabaseError::TooManyInserts);
+        }
+
+        Ok(())
+    }
+
+    /// Find the articles, not archived, whose name ends with `name` if set, ordered by name and id,
+    /// starting after the `(name, id)` position of the last article of the previous page
+    pub async fn find_page(
         db: &StoreDb,
-        name: &str,
-        offset: i64,
+        name: Option<&str>,
+        after: Option<(String, Uuid)>,
         limit: i64,
     ) -> DatabaseResult<Vec<Article>> {
-        sqlx::query_as(r#"SELECT * FROM article WHERE name LIKE $1 OFFSET $2 LIMIT $3"#)
-            .bind(format!("%{name}"))
-            .bind(offset)
-            .bind(limit)
-            .fetch_all(db.pool())
-            .await
-            .map_err(DatabaseError::from)
+        let (after_name, after_id) = after.unzip();
+        sqlx::query_as(
+            r#"SELECT * FROM article WHERE archived_at IS NULL AND ($1::text IS NULL OR name LIKE $1)
+            AND ($2::text IS NULL OR (name, id) > ($2, $3))
+            ORDER BY name, id LIMIT $4"#,
+        )
+        .bind(name.map(|name| format!("%{name}")))
+        .bind(after_name)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(db.pool())
+        .await
+        .map_err(DatabaseError::from)
     }
 
-    /// Get all articles with pagination
-    pub async fn get_all(db: &StoreDb, offset: i64, limit: i64) -> DatabaseResult<Vec<Article>> {
-        sqlx::query_as(r#"SELECT * FROM article OFFSET $1 LIMIT $2"#)
-            .bind(offset)
-            .bind(limit)
-            .fetch_all(db.pool())
-            .await
-            .map_err(DatabaseError::from)
+    /// Count the articles, not archived, whose name ends with `name` if set
+    pub async fn count(db: &StoreDb, name: Option<&str>) -> DatabaseResult<i64> {
+        sqlx::query_scalar(
+            r#"SELECT COUNT(*) FROM article WHERE archived_at IS NULL AND ($1::text IS NULL OR name LIKE $1)"#,
+        )
+        .bind(name.map(|name| format!("%{name}")))
+        .fetch_one(db.pool())
+        .await
+        .map_err(DatabaseError::from)
+    }
+
+    /// Get the articles, not archived, with an id greater than `after`, ordered by id
+    pub async fn find_after(
+        db: &StoreDb,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<Article>> {
+        sqlx::query_as(
+            r#"SELECT * FROM article WHERE archived_at IS NULL AND ($1::uuid IS NULL OR id > $1)
+            ORDER BY id LIMIT $2"#,
+        )
+        .bind(after)
+        .bind(limit)
+        .fetch_all(db.pool())
+        .await
+        .map_err(DatabaseError::from)
+    }
+
+    /// Find the ids of the articles, not archived, named exactly as one of `names`.
+    /// When more articles have the same name, the oldest id is returned
+    pub async fn find_ids_by_names(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        names: &[String],
+    ) -> DatabaseResult<Vec<(Uuid, String)>> {
+        sqlx::query_as(
+            r#"SELECT DISTINCT ON (name) id, name FROM article
+            WHERE name = ANY($1) AND archived_at IS NULL
+            ORDER BY name, id"#,
+        )
+        .bind(names)
+        .fetch_all(db)
+        .await
+        .map_err(DatabaseError::from)
+    }
+
+    /// Insert the articles or update the existing ones with the same id.
+    ///
+    /// Returns the id of the articles which have been inserted or changed, and whether they
+    /// have been inserted; articles which are already up to date are not returned
+    pub async fn upsert_many(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        articles: &[Article],
+    ) -> DatabaseResult<Vec<(Uuid, bool)>> {
+        debug!("upserting {} articles", articles.len());
+        sqlx::query_as(
+            r#"INSERT INTO article (id, name, description, unit_price, weight)
+            SELECT * FROM UNNEST($1::uuid[], $2::text[], $3::text[], $4::decimal[], $5::integer[])
+            ON CONFLICT (id) DO UPDATE SET name = EXCLUDED.name, description = EXCLUDED.description,
+                unit_price = EXCLUDED.unit_price, weight = EXCLUDED.weight
+            WHERE (article.name, article.description, article.unit_price, article.weight)
+                IS DISTINCT FROM (EXCLUDED.name, EXCLUDED.description, EXCLUDED.unit_price, EXCLUDED.weight)
+            RETURNING id, (xmax = 0) AS inserted"#,
+        )
+        .bind(articles.iter().map(|x| x.id).collect::<Vec<_>>())
+        .bind(articles.iter().map(|x| x.name.clone()).collect::<Vec<_>>())
+        .bind(articles.iter().map(|x| x.description.clone()).collect::<Vec<_>>())
+        .bind(articles.iter().map(|x| x.unit_price).collect::<Vec<_>>())
+        .bind(articles.iter().map(|x| x.weight).collect::<Vec<_>>())
+        .fetch_all(db)
+        .await
+        .map_err(DatabaseError::from)
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::database::TestDb;
 
     use pretty_assertions::assert_eq;
-    use std::env;
 
     #[tokio::test]
     async fn should_find_article_by_id() {
-        let db = StoreDb::connect(&env::var("DATABASE_URL").expect("DATABASE_URL not found"))
-            .await
-            .expect("failed to connect to database");
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
 
         let article = insert_article(&db, "bubblegums").await;
         assert_eq!(
@@ -72,10 +205,87 @@ mod test {
     }
 
     #[tokio::test]
-    async fn should_not_find_article_by_id() {
-        let db = StoreDb::connect(&env::var("DATABASE_URL").expect("DATABASE_URL not found"))
+    async fn should_insert_article() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let article = Article::insert(
+            &db,
+            "bubblegums",
+            "Lorem Ipsum",
+            rust_decimal_macros::dec!(4.99),
+            250,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            Article::find_by_id(&db, &article.id).await.unwrap(),
+            Some(article)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_upsert_articles() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let mut existing = insert_article(&db, "pencil").await;
+        let unchanged = insert_article(&db, "rubber").await;
+        existing.unit_price = rust_decimal_macros::dec!(1.50);
+        existing.weight = 100;
+        let created = Article {
+            id: Uuid::new_v4(),
+            name: "sharpener".to_string(),
+            description: "Lorem Ipsum".to_string(),
+            unit_price: rust_decimal_macros::dec!(3),
+            weight: 20,
+            archived_at: None,
+        };
+        let mut upserted =
+            Article::upsert_many(&db, &[existing.clone(), unchanged.clone(), created.clone()])
+                .await
+                .unwrap();
+        upserted.sort_by_key(|(_, inserted)| *inserted);
+        assert_eq!(upserted, vec![(existing.id, false), (created.id, true)]);
+        assert_eq!(
+            Article::find_by_id(&db, &existing.id).await.unwrap(),
+            Some(existing)
+        );
+        assert_eq!(
+            Article::find_by_id(&db, &created.id).await.unwrap(),
+            Some(created.clone())
+        );
+        assert_eq!(
+            Article::find_ids_by_names(db.pool(), std::slice::from_ref(&created.name))
+                .await
+                .unwrap(),
+            vec![(created.id, created.name)]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_find_articles_after_id() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let mut articles = [
+            insert_article(&db, "cat").await,
+            insert_article(&db, "dog").await,
+            insert_article(&db, "hamster").await,
+        ];
+        articles.sort_by_key(|x| x.id);
+        let first = Article::find_after(&db, None, 2).await.unwrap();
+        assert_eq!(first, articles[..2]);
+        let next = Article::find_after(&db, Some(first[1].id), 2)
             .await
-            .expect("failed to connect to database");
+            .unwrap();
+        assert_eq!(next, articles[2..]);
+    }
+
+    #[tokio::test]
+    async fn should_not_find_article_by_id() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
 
         assert!(Article::find_by_id(&db, &Uuid::new_v4())
             .await
@@ -84,34 +294,69 @@ mod test {
     }
 
     #[tokio::test]
-    async fn should_find_article_by_name() {
-        let db = StoreDb::connect(&env::var("DATABASE_URL").expect("DATABASE_URL not found"))
+    async fn should_archive_article() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let article = insert_article(&db, "bubblegums").await;
+        Article::archive(&db, &article.id).await.unwrap();
+        let archived = Article::find_by_id(&db, &article.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(archived.is_archived());
+        assert!(Article::find_page(&db, Some("bubblegums"), None, 64)
             .await
-            .expect("failed to connect to database");
+            .unwrap()
+            .is_empty());
+        assert_eq!(Article::count(&db, Some("bubblegums")).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn should_find_article_by_name() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
         insert_article(&db, "cat").await;
         insert_article(&db, "dog").await;
         insert_article(&db, "maine coon cat").await;
+        let cats = Article::find_page(&db, Some("cat"), None, 64)
+            .await
+            .unwrap();
         assert_eq!(
-            Article::find_by_name(&db, "cat", 0, 64)
-                .await
-                .unwrap()
-                .len(),
-            2
+            cats.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(),
+            vec!["cat", "maine coon cat"]
         );
-        assert_eq!(
-            Article::find_by_name(&db, "cat", 1, 64)
+        assert_eq!(Article::count(&db, Some("cat")).await.unwrap(), 2);
+        assert_eq!(Article::count(&db, None).await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn should_find_articles_page_after_cursor() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+        // same name, so that the id breaks the tie
+        let mut cats = [
+            insert_article(&db, "cat").await,
+            insert_article(&db, "cat").await,
+            insert_article(&db, "maine coon cat").await,
+        ];
+        insert_article(&db, "dog").await;
+        cats.sort_by(|a, b| (&a.name, a.id).cmp(&(&b.name, b.id)));
+
+        let first_page = Article::find_page(&db, Some("cat"), None, 2).await.unwrap();
+        assert_eq!(first_page, cats[..2]);
+        let last = first_page.last().unwrap();
+        let second_page =
+            Article::find_page(&db, Some("cat"), Some((last.name.clone(), last.id)), 2)
+                .await
+                .unwrap();
+        assert_eq!(second_page, cats[2..]);
+        let last = second_page.last().unwrap();
+        assert!(
+            Article::find_page(&db, Some("cat"), Some((last.name.clone(), last.id)), 2)
                 .await
                 .unwrap()
-                .len(),
-            1
-        );
-        assert_eq!(
-            Article::find_by_name(&db, "cat", 0, 1).await.unwrap().len(),
-            1
-        );
-        assert_eq!(
-            Article::find_by_name(&db, "cat", 2, 4).await.unwrap().len(),
-            0
+                .is_empty()
         );
     }
 
@@ -121,6 +366,8 @@ mod test {
             name: name.to_string(),
             description: "Lorem Ipsum".to_string(),
             unit_price: rust_decimal_macros::dec!(23.04),
+            weight: 0,
+            archived_at: None,
         };
         let rows = sqlx::query(
             "INSERT INTO article (id, name, description, unit_price) VALUES ($1, $2, $3, $4)",
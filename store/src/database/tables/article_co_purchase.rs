@@ -0,0 +1,162 @@
+use uuid::Uuid;
+
+use super::{Article, DatabaseError, DatabaseResult, StoreDb};
+
+/// Co-occurrence of two articles in the paid orders.
+///
+/// The table is rebuilt from `order_article` by `ArticleCoPurchase::rebuild`
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct ArticleCoPurchase {
+    pub article_id: Uuid,
+    pub related_article_id: Uuid,
+    /// Amount of orders in which both articles were bought
+    pub score: i32,
+}
+
+impl ArticleCoPurchase {
+    /// Find the articles most frequently bought together with `article_ids`, excluding
+    /// `article_ids` themselves and the archived articles
+    pub async fn find_related_articles(
+        db: &StoreDb,
+        article_ids: &[Uuid],
+        limit: i64,
+    ) -> DatabaseResult<Vec<Article>> {
+        sqlx::query_as(
+            r#"SELECT article.* FROM article
+            INNER JOIN (
+                SELECT related_article_id, SUM(score) AS score FROM article_co_purchase
+                WHERE article_id = ANY($1) AND NOT related_article_id = ANY($1)
+                GROUP BY related_article_id
+            ) related ON related.related_article_id = article.id
+            WHERE article.archived_at IS NULL
+            ORDER BY related.score DESC, article.id LIMIT $2"#,
+        )
+        .bind(article_ids)
+        .bind(limit)
+        .fetch_all(db.pool())
+        .await
+        .map_err(DatabaseError::from)
+    }
+
+    /// Rebuild the co-purchases from the articles of the paid orders.
+    /// Returns the amount of article pairs found
+    pub async fn rebuild(db: &StoreDb) -> DatabaseResult<u64> {
+        let mut transaction = db.pool().begin().await?;
+        // concurrent rebuilds would otherwise insert the same pairs
+        sqlx::query("LOCK TABLE article_co_purchase IN EXCLUSIVE MODE")
+            .execute(&mut transaction)
+            .await?;
+        sqlx::query("DELETE FROM article_co_purchase")
+            .execute(&mut transaction)
+            .await?;
+        let pairs = sqlx::query(
+            r#"INSERT INTO article_co_purchase (article_id, related_article_id, score)
+            SELECT a.article_id, b.article_id, COUNT(DISTINCT a.order_id) FROM order_article a
+            INNER JOIN order_article b ON b.order_id = a.order_id AND b.article_id <> a.article_id
+            INNER JOIN customer_order ON customer_order.id = a.order_id
+            WHERE customer_order.status NOT IN ('created', 'payment_refused', 'expired')
+            GROUP BY a.article_id, b.article_id"#,
+        )
+        .execute(&mut transaction)
+        .await?
+        .rows_affected();
+        transaction.commit().await?;
+        debug!("rebuilt {pairs} article co-purchases");
+
+        Ok(pairs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::{
+        Customer, CustomerOrder, OrderArticle, OrderEventActor, OrderStatus, TestDb,
+    };
+
+    use pretty_assertions::assert_eq;
+    use rust_decimal::Decimal;
+
+    #[tokio::test]
+    async fn should_find_articles_bought_together() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let pen = insert_article(&db, "pen").await;
+        let ink = insert_article(&db, "ink").await;
+        let paper = insert_article(&db, "paper").await;
+        let eraser = insert_article(&db, "eraser").await;
+        // pen is bought twice with ink and once with paper; eraser only in an unpaid order
+        for (articles, status) in [
+            (vec![&pen, &ink], OrderStatus::Preparing),
+            (vec![&pen, &ink, &paper], OrderStatus::Shipped),
+            (vec![&pen, &eraser], OrderStatus::Created),
+        ] {
+            let order = CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
+                .await
+                .unwrap();
+            for article in articles {
+                OrderArticle::insert(&db, &order.id, &article.id, 1, article.unit_price)
+                    .await
+                    .unwrap();
+            }
+            if status != OrderStatus::Created {
+                CustomerOrder::update_status(&db, &order.id, status, OrderEventActor::System)
+                    .await
+                    .unwrap();
+            }
+        }
+        ArticleCoPurchase::rebuild(&db).await.unwrap();
+
+        assert_eq!(
+            ArticleCoPurchase::find_related_articles(&db, &[pen.id], 10)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|x| x.id)
+                .collect::<Vec<_>>(),
+            vec![ink.id, paper.id]
+        );
+        assert_eq!(
+            ArticleCoPurchase::find_related_articles(&db, &[pen.id, ink.id], 10)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|x| x.id)
+                .collect::<Vec<_>>(),
+            vec![paper.id]
+        );
+    }
+
+    async fn insert_article(db: &StoreDb, name: &str) -> Article {
+        let article = Article {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: "Lorem Ipsum".to_string(),
+            unit_price: rust_decimal_macros::dec!(23.04),
+            weight: 0,
+            archived_at: None,
+        };
+        let rows = sqlx::query(
+            "INSERT INTO article (id, name, description, unit_price) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(article.id)
+        .bind(&article.name)
+        .bind(&article.description)
+        .bind(article.unit_price)
+        .execute(db.pool())
+        .await
+        .map_err(DatabaseError::from)
+        .unwrap()
+        .rows_affected();
+        if rows != 1 {
+            panic!("too many inserts");
+        }
+
+        article
+    }
+}
@@ -0,0 +1,336 @@
+use chrono::{NaiveDateTime, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::{now, DatabaseError, DatabaseResult, StoreDb};
+
+/// Review of an article written by a customer who received it.
+///
+/// Reviews are shown and count towards the article rating only once approved
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct ArticleReview {
+    pub id: Uuid,
+    pub article_id: Uuid,
+    pub customer_id: Uuid,
+    pub rating: i16,
+    pub title: String,
+    pub body: String,
+    pub status: ReviewStatus,
+    pub created_at: NaiveDateTime,
+    pub moderated_at: Option<NaiveDateTime>,
+}
+
+/// Review moderation status
+#[derive(Debug, Clone, Copy, Eq, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "review_status", rename_all = "snake_case")]
+pub enum ReviewStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+/// Average rating of the approved reviews of an article
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct ArticleRating {
+    pub article_id: Uuid,
+    pub average: Decimal,
+    pub count: i32,
+}
+
+impl ArticleReview {
+    /// Find `ArticleReview` by `id` and lock it until the end of the transaction
+    pub async fn find_by_id_for_update(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        id: &Uuid,
+    ) -> DatabaseResult<Option<Self>> {
+        sqlx::query_as(r#"SELECT * FROM article_review WHERE id = $1 FOR UPDATE"#)
+            .bind(id)
+            .fetch_optional(db)
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Find the approved reviews of an article, newest first, starting after the
+    /// `(created_at, id)` position of the last review of the previous page
+    pub async fn find_approved_by_article_id(
+        db: &StoreDb,
+        article_id: &Uuid,
+        after: Option<(NaiveDateTime, Uuid)>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<Self>> {
+        let (after_created_at, after_id) = after.unzip();
+        sqlx::query_as(
+            r#"SELECT * FROM article_review WHERE article_id = $1 AND status = 'approved'
+            AND ($2::timestamp IS NULL OR (created_at, id) < ($2, $3))
+            ORDER BY created_at DESC, id DESC LIMIT $4"#,
+        )
+        .bind(article_id)
+        .bind(after_created_at)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(db.pool())
+        .await
+        .map_err(DatabaseError::from)
+    }
+
+    /// Insert a new pending review.
+    /// Returns `None` if the customer has already reviewed the article
+    pub async fn insert(
+        db: &StoreDb,
+        article_id: &Uuid,
+        customer_id: &Uuid,
+        rating: i16,
+        title: &str,
+        body: &str,
+    ) -> DatabaseResult<Option<Self>> {
+        let review = Self {
+            id: Uuid::new_v4(),
+            article_id: *article_id,
+            customer_id: *customer_id,
+            rating,
+            title: title.to_string(),
+            body: body.to_string(),
+            status: ReviewStatus::Pending,
+            created_at: now(),
+            moderated_at: None,
+        };
+        debug!(
+            "inserting a new review {} of {article_id} by {customer_id} to repository",
+            review.id
+        );
+        let rows = sqlx::query(
+            r#"INSERT INTO article_review (id, article_id, customer_id, rating, title, body, status, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (article_id, customer_id) DO NOTHING"#,
+        )
+        .bind(review.id)
+        .bind(review.article_id)
+        .bind(review.customer_id)
+        .bind(review.rating)
+        .bind(&review.title)
+        .bind(&review.body)
+        .bind(review.status)
+        .bind(review.created_at)
+        .execute(db.pool())
+        .await
+        .map_err(DatabaseError::from)?
+        .rows_affected();
+        match rows {
+            0 => Ok(None),
+            1 => Ok(Some(review)),
+            _ => Err(DatabaseError::TooManyInserts),
+        }
+    }
+
+    /// Set the moderation status of the review
+    pub async fn moderate(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        id: &Uuid,
+        status: ReviewStatus,
+    ) -> DatabaseResult<()> {
+        debug!("moderating review {id} as {:?}", status);
+        let rows =
+            sqlx::query("UPDATE article_review SET status = $1, moderated_at = $2 WHERE id = $3")
+                .bind(status)
+                .bind(Utc::now().naive_utc())
+                .bind(id)
+                .execute(db)
+                .await
+                .map_err(DatabaseError::from)?
+                .rows_affected();
+        if rows != 1 {
+            return Err(DatabaseError::TooManyInserts);
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the article has been shipped to the customer in one of their orders
+    pub async fn is_verified_purchase(
+        db: &StoreDb,
+        customer_id: &Uuid,
+        article_id: &Uuid,
+    ) -> DatabaseResult<bool> {
+        let (verified,): (bool,) = sqlx::query_as(
+            r#"SELECT EXISTS (
+                SELECT 1 FROM order_article
+                INNER JOIN customer_order ON customer_order.id = order_article.order_id
+                WHERE customer_order.customer_id = $1 AND order_article.article_id = $2
+                AND customer_order.status = 'shipped'
+            )"#,
+        )
+        .bind(customer_id)
+        .bind(article_id)
+        .fetch_one(db.pool())
+        .await
+        .map_err(DatabaseError::from)?;
+
+        Ok(verified)
+    }
+}
+
+impl ArticleRating {
+    /// Find the rating of an article; articles without approved reviews have no rating
+    pub async fn find_by_article_id(
+        db: &StoreDb,
+        article_id: &Uuid,
+    ) -> DatabaseResult<Option<Self>> {
+        sqlx::query_as(r#"SELECT * FROM article_rating WHERE article_id = $1"#)
+            .bind(article_id)
+            .fetch_optional(db.pool())
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Recompute the rating of the article from its approved reviews
+    pub async fn refresh(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        article_id: &Uuid,
+    ) -> DatabaseResult<()> {
+        debug!("refreshing rating of article {article_id}");
+        sqlx::query(
+            r#"INSERT INTO article_rating (article_id, average, count)
+            SELECT $1, COALESCE(ROUND(AVG(rating), 2), 0), COUNT(*) FROM article_review WHERE article_id = $1 AND status = 'approved'
+            ON CONFLICT (article_id) DO UPDATE SET average = EXCLUDED.average, count = EXCLUDED.count"#,
+        )
+        .bind(article_id)
+        .execute(db)
+        .await
+        .map_err(DatabaseError::from)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::{
+        Article, Customer, CustomerOrder, OrderArticle, OrderEventActor, OrderStatus, TestDb,
+    };
+
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn should_verify_purchase_of_shipped_articles_only() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let article = insert_article(&db, "panzerotti").await;
+        let order = CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
+            .await
+            .unwrap();
+        OrderArticle::insert(&db, &order.id, &article.id, 1, article.unit_price)
+            .await
+            .unwrap();
+        assert!(
+            !ArticleReview::is_verified_purchase(&db, &customer.id, &article.id)
+                .await
+                .unwrap()
+        );
+
+        CustomerOrder::update_status(
+            &db,
+            &order.id,
+            OrderStatus::Shipped,
+            OrderEventActor::BackOffice,
+        )
+        .await
+        .unwrap();
+        assert!(
+            ArticleReview::is_verified_purchase(&db, &customer.id, &article.id)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_refresh_rating_with_approved_reviews() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let article = insert_article(&db, "panzerotti").await;
+        let mut reviews = Vec::new();
+        for (email, rating) in [
+            ("customer1@prima.it", 5),
+            ("customer2@prima.it", 4),
+            ("customer3@prima.it", 1),
+        ] {
+            let customer = Customer::insert(&db, email, "abcdef").await.unwrap();
+            let review = ArticleReview::insert(&db, &article.id, &customer.id, rating, "t", "b")
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(
+                ArticleReview::insert(&db, &article.id, &customer.id, rating, "t", "b")
+                    .await
+                    .unwrap()
+                    .is_none()
+            );
+            reviews.push(review);
+        }
+        ArticleReview::moderate(&db, &reviews[0].id, ReviewStatus::Approved)
+            .await
+            .unwrap();
+        ArticleReview::moderate(&db, &reviews[1].id, ReviewStatus::Approved)
+            .await
+            .unwrap();
+        ArticleReview::moderate(&db, &reviews[2].id, ReviewStatus::Rejected)
+            .await
+            .unwrap();
+        ArticleRating::refresh(&db, &article.id).await.unwrap();
+
+        let rating = ArticleRating::find_by_article_id(&db, &article.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rating.count, 2);
+        assert_eq!(rating.average, rust_decimal_macros::dec!(4.50));
+        let approved = ArticleReview::find_approved_by_article_id(&db, &article.id, None, 1)
+            .await
+            .unwrap();
+        assert_eq!(approved.len(), 1);
+        let last = &approved[0];
+        let next = ArticleReview::find_approved_by_article_id(
+            &db,
+            &article.id,
+            Some((last.created_at, last.id)),
+            64,
+        )
+        .await
+        .unwrap();
+        assert_eq!(next.len(), 1);
+        assert_ne!(next[0].id, last.id);
+    }
+
+    async fn insert_article(db: &StoreDb, name: &str) -> Article {
+        let article = Article {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: "Lorem Ipsum".to_string(),
+            unit_price: rust_decimal_macros::dec!(23.04),
+            weight: 0,
+            archived_at: None,
+        };
+        let rows = sqlx::query(
+            "INSERT INTO article (id, name, description, unit_price) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(article.id)
+        .bind(&article.name)
+        .bind(&article.description)
+        .bind(article.unit_price)
+        .execute(db.pool())
+        .await
+        .map_err(DatabaseError::from)
+        .unwrap()
+        .rows_affected();
+        if rows != 1 {
+            panic!("too many inserts");
+        }
+
+        article
+    }
+}
@@ -0,0 +1,348 @@
+use chrono::{NaiveDateTime, Utc};
+use uuid::Uuid;
+
+use super::{DatabaseError, DatabaseResult};
+
+/// Shopping cart. A cart without customer belongs to an anonymous visitor
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct Cart {
+    pub id: Uuid,
+    pub customer_id: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Article inside a cart with its quantity
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct CartItem {
+    pub cart_id: Uuid,
+    pub article_id: Uuid,
+    pub quantity: i32,
+}
+
+impl Cart {
+    /// Get the cart of the customer; the cart is created if the customer has none
+    pub async fn get_or_create_for_customer(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        customer_id: &Uuid,
+    ) -> DatabaseResult<Self> {
+        let now = Utc::now().naive_utc();
+        sqlx::query_as(
+            r#"INSERT INTO cart (id, customer_id, created_at, updated_at) VALUES ($1, $2, $3, $3)
+            ON CONFLICT (customer_id) DO UPDATE SET updated_at = cart.updated_at RETURNING *"#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(customer_id)
+        .bind(now)
+        .fetch_one(db)
+        .await
+        .map_err(DatabaseError::from)
+    }
+
+    /// Get the anonymous cart with `id`; the cart is created if it doesn't exist.
+    /// Returns `None` if `id` belongs to a customer's cart
+    pub async fn get_or_create_anonymous(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        id: &Uuid,
+    ) -> DatabaseResult<Option<Self>> {
+        let now = Utc::now().naive_utc();
+        let cart: Self = sqlx::query_as(
+            r#"INSERT INTO cart (id, customer_id, created_at, updated_at) VALUES ($1, NULL, $2, $2)
+            ON CONFLICT (id) DO UPDATE SET updated_at = cart.updated_at RETURNING *"#,
+        )
+        .bind(id)
+        .bind(now)
+        .fetch_one(db)
+        .await
+        .map_err(DatabaseError::from)?;
+
+        Ok(cart.customer_id.is_none().then_some(cart))
+    }
+
+    /// Find the anonymous cart with `id`
+    pub async fn find_anonymous(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        id: &Uuid,
+    ) -> DatabaseResult<Option<Self>> {
+        sqlx::query_as(r#"SELECT * FROM cart WHERE id = $1 AND customer_id IS NULL"#)
+            .bind(id)
+            .fetch_optional(db)
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Set the last update time of the cart to now
+    pub async fn touch(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        id: &Uuid,
+    ) -> DatabaseResult<()> {
+        sqlx::query("UPDATE cart SET updated_at = $1 WHERE id = $2")
+            .bind(Utc::now().naive_utc())
+            .bind(id)
+            .execute(db)
+            .await
+            .map_err(DatabaseError::from)?;
+
+        Ok(())
+    }
+
+    /// Delete cart with its items
+    pub async fn delete(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        id: &Uuid,
+    ) -> DatabaseResult<()> {
+        debug!("deleting cart {id}");
+        sqlx::query("DELETE FROM cart WHERE id = $1")
+            .bind(id)
+            .execute(db)
+            .await
+            .map_err(DatabaseError::from)?;
+
+        Ok(())
+    }
+}
+
+impl CartItem {
+    /// Find the items of a cart
+    pub async fn find_by_cart_id(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        cart_id: &Uuid,
+    ) -> DatabaseResult<Vec<CartItem>> {
+        sqlx::query_as(r#"SELECT * FROM cart_item WHERE cart_id = $1 ORDER BY article_id"#)
+            .bind(cart_id)
+            .fetch_all(db)
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Add `quantity` items of the article to the cart
+    pub async fn add(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        cart_id: &Uuid,
+        article_id: &Uuid,
+        quantity: i32,
+    ) -> DatabaseResult<()> {
+        debug!("adding {quantity} items of {article_id} to cart {cart_id}");
+        sqlx::query(
+            r#"INSERT INTO cart_item (cart_id, article_id, quantity) VALUES ($1, $2, $3)
+            ON CONFLICT (cart_id, article_id) DO UPDATE SET quantity = cart_item.quantity + EXCLUDED.quantity"#,
+        )
+        .bind(cart_id)
+        .bind(article_id)
+        .bind(quantity)
+        .execute(db)
+        .await
+        .map_err(DatabaseError::from)?;
+
+        Ok(())
+    }
+
+    /// Set the quantity of the article in the cart
+    pub async fn set_quantity(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        cart_id: &Uuid,
+        article_id: &Uuid,
+        quantity: i32,
+    ) -> DatabaseResult<()> {
+        debug!("setting quantity of {article_id} in cart {cart_id} to {quantity}");
+        sqlx::query(
+            r#"INSERT INTO cart_item (cart_id, article_id, quantity) VALUES ($1, $2, $3)
+            ON CONFLICT (cart_id, article_id) DO UPDATE SET quantity = EXCLUDED.quantity"#,
+        )
+        .bind(cart_id)
+        .bind(article_id)
+        .bind(quantity)
+        .execute(db)
+        .await
+        .map_err(DatabaseError::from)?;
+
+        Ok(())
+    }
+
+    /// Remove the article from the cart
+    pub async fn remove(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        cart_id: &Uuid,
+        article_id: &Uuid,
+    ) -> DatabaseResult<()> {
+        debug!("removing {article_id} from cart {cart_id}");
+        sqlx::query("DELETE FROM cart_item WHERE cart_id = $1 AND article_id = $2")
+            .bind(cart_id)
+            .bind(article_id)
+            .execute(db)
+            .await
+            .map_err(DatabaseError::from)?;
+
+        Ok(())
+    }
+
+    /// Remove all the items from the cart
+    pub async fn clear(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        cart_id: &Uuid,
+    ) -> DatabaseResult<()> {
+        debug!("clearing cart {cart_id}");
+        sqlx::query("DELETE FROM cart_item WHERE cart_id = $1")
+            .bind(cart_id)
+            .execute(db)
+            .await
+            .map_err(DatabaseError::from)?;
+
+        Ok(())
+    }
+
+    /// Move the items of cart `from` into cart `into`, summing the quantities of the articles in both carts
+    pub async fn merge(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        from: &Uuid,
+        into: &Uuid,
+    ) -> DatabaseResult<()> {
+        debug!("merging cart {from} into cart {into}");
+        sqlx::query(
+            r#"INSERT INTO cart_item (cart_id, article_id, quantity)
+            SELECT $2, article_id, quantity FROM cart_item WHERE cart_id = $1
+            ON CONFLICT (cart_id, article_id) DO UPDATE SET quantity = cart_item.quantity + EXCLUDED.quantity"#,
+        )
+        .bind(from)
+        .bind(into)
+        .execute(db)
+        .await
+        .map_err(DatabaseError::from)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::{Article, Customer, StoreDb, TestDb};
+
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn should_get_or_create_customer_cart() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let cart = Cart::get_or_create_for_customer(&db, &customer.id)
+            .await
+            .unwrap();
+        assert_eq!(cart.customer_id, Some(customer.id));
+        assert_eq!(
+            Cart::get_or_create_for_customer(&db, &customer.id)
+                .await
+                .unwrap()
+                .id,
+            cart.id
+        );
+        // customer cart can't be used as anonymous cart
+        assert!(Cart::get_or_create_anonymous(&db, &cart.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn should_update_cart_items() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let article = insert_article(&db, "panzerotti").await;
+        let cart = Cart::get_or_create_anonymous(&db, &Uuid::new_v4())
+            .await
+            .unwrap()
+            .unwrap();
+
+        CartItem::add(&db, &cart.id, &article.id, 2).await.unwrap();
+        CartItem::add(&db, &cart.id, &article.id, 3).await.unwrap();
+        let items = CartItem::find_by_cart_id(&db, &cart.id).await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].quantity, 5);
+
+        CartItem::set_quantity(&db, &cart.id, &article.id, 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            CartItem::find_by_cart_id(&db, &cart.id).await.unwrap()[0].quantity,
+            1
+        );
+
+        CartItem::remove(&db, &cart.id, &article.id).await.unwrap();
+        assert!(CartItem::find_by_cart_id(&db, &cart.id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_merge_carts() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let article = insert_article(&db, "panzerotti").await;
+        let customer_cart = Cart::get_or_create_for_customer(&db, &customer.id)
+            .await
+            .unwrap();
+        let anonymous_cart = Cart::get_or_create_anonymous(&db, &Uuid::new_v4())
+            .await
+            .unwrap()
+            .unwrap();
+        CartItem::add(&db, &customer_cart.id, &article.id, 1)
+            .await
+            .unwrap();
+        CartItem::add(&db, &anonymous_cart.id, &article.id, 2)
+            .await
+            .unwrap();
+
+        CartItem::merge(&db, &anonymous_cart.id, &customer_cart.id)
+            .await
+            .unwrap();
+        Cart::delete(&db, &anonymous_cart.id).await.unwrap();
+
+        let items = CartItem::find_by_cart_id(&db, &customer_cart.id)
+            .await
+            .unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].quantity, 3);
+        assert!(Cart::find_anonymous(&db, &anonymous_cart.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    async fn insert_article(db: &StoreDb, name: &str) -> Article {
+        let article = Article {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: "Lorem Ipsum".to_string(),
+            unit_price: rust_decimal_macros::dec!(23.04),
+            weight: 0,
+            archived_at: None,
+        };
+        let rows = sqlx::query(
+            "INSERT INTO article (id, name, description, unit_price) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(article.id)
+        .bind(&article.name)
+        .bind(&article.description)
+        .bind(article.unit_price)
+        .execute(db.pool())
+        .await
+        .map_err(DatabaseError::from)
+        .unwrap()
+        .rows_affected();
+        if rows != 1 {
+            panic!("too many inserts");
+        }
+
+        article
+    }
+}
@@ -1,7 +1,8 @@
-use chrono::{NaiveDateTime, Utc};
+use chrono::NaiveDateTime;
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
-use super::{DatabaseError, DatabaseResult, StoreDb};
+use super::{now, DatabaseError, DatabaseResult, StoreDb};
 
 #[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
 pub struct Customer {
@@ -14,7 +15,7 @@ pub struct Customer {
 impl Customer {
     /// Insert new `Customer` to database
     pub async fn insert(
-        db: &StoreDb,
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
         email: impl ToString,
         password: impl ToString,
     ) -> DatabaseResult<Self> {
@@ -27,7 +28,7 @@ impl Customer {
         .bind(&customer.email)
         .bind(&customer.password)
         .bind(customer.created_at)
-        .execute(db.pool())
+        .execute(db)
         .await
         .map_err(DatabaseError::from)?
         .rows_affected();
@@ -61,13 +62,20 @@ impl Customer {
             .map_err(DatabaseError::from)
     }
 
+    /// Hash a plain text password as it is stored in the `customer` table
+    pub fn hash_password(password: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(password.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
     /// Instantiates a new Customer
     fn new(email: impl ToString, password: impl ToString) -> Self {
         Self {
             id: Uuid::new_v4(),
             email: email.to_string(),
             password: password.to_string(),
-            created_at: Utc::now().naive_utc(),
+            created_at: now(),
         }
     }
 }
@@ -76,15 +84,14 @@ impl Customer {
 mod test {
 
     use super::*;
+    use crate::database::TestDb;
 
     use pretty_assertions::assert_eq;
-    use std::env;
 
     #[tokio::test]
     async fn should_create_new_user() {
-        let db = StoreDb::connect(&env::var("DATABASE_URL").expect("DATABASE_URL not found"))
-            .await
-            .expect("failed to connect to database");
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
         let new_customer = Customer::insert(&db, "bigluca@biglucainternational.com", "password123")
             .await
             .unwrap();
@@ -95,11 +102,18 @@ mod test {
         assert_eq!(new_customer.password.as_str(), "password123");
     }
 
+    #[test]
+    fn should_hash_password() {
+        assert_eq!(
+            Customer::hash_password("password123"),
+            "ef92b778bafe771e89245b89ecbc08a44a4e166c06659911881f383d4473e94f"
+        );
+    }
+
     #[tokio::test]
     async fn should_get_user_by_email() {
-        let db = StoreDb::connect(&env::var("DATABASE_URL").expect("DATABASE_URL not found"))
-            .await
-            .expect("failed to connect to database");
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
         let new_customer =
             Customer::insert(&db, "should_get_user_by_email@gmail.com", "password123")
                 .await
@@ -112,9 +126,8 @@ mod test {
 
     #[tokio::test]
     async fn should_get_user_by_email_and_password() {
-        let db = StoreDb::connect(&env::var("DATABASE_URL").expect("DATABASE_URL not found"))
-            .await
-            .expect("failed to connect to database");
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
         let new_customer = Customer::insert(&db, "christian.visintin1997@gmail.com", "password123")
             .await
             .unwrap();
@@ -130,9 +143,8 @@ mod test {
 
     #[tokio::test]
     async fn should_not_find_any_user() {
-        let db = StoreDb::connect(&env::var("DATABASE_URL").expect("DATABASE_URL not found"))
-            .await
-            .expect("failed to connect to database");
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
         assert!(
             Customer::find_by_email_and_password(&db, "test@prima.it", "abcdef")
                 .await
@@ -0,0 +1,286 @@
+use chrono::{Datelike, NaiveDateTime};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::{DatabaseError, DatabaseResult, StoreDb};
+
+/// Seller details and tax rate printed on the issued invoices
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvoiceSeller {
+    pub name: String,
+    pub address: String,
+    pub vat_number: String,
+    /// Percentage of tax included in the prices
+    pub tax_rate: Decimal,
+}
+
+/// Invoice of a paid order.
+///
+/// Invoices are snapshots, so they don't change when the seller, the customer or the articles do
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct Invoice {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub year: i32,
+    /// Sequential number, starting from 1 each year
+    pub number: i32,
+    pub issued_at: NaiveDateTime,
+    pub seller_name: String,
+    pub seller_address: String,
+    pub seller_vat_number: String,
+    pub customer_email: String,
+    pub transaction_id: String,
+    pub shipping_cost: Decimal,
+    pub tax_rate: Decimal,
+    pub taxable_amount: Decimal,
+    pub tax_amount: Decimal,
+    pub total: Decimal,
+}
+
+/// Article billed in an invoice
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct InvoiceLine {
+    pub invoice_id: Uuid,
+    pub position: i32,
+    pub article_id: Uuid,
+    pub name: String,
+    pub quantity: i32,
+    pub unit_price: Decimal,
+    pub total: Decimal,
+}
+
+impl Invoice {
+    /// Find the invoice of an order
+    pub async fn find_by_order_id(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        order_id: &Uuid,
+    ) -> DatabaseResult<Option<Self>> {
+        sqlx::query_as(r#"SELECT * FROM invoice WHERE order_id = $1"#)
+            .bind(order_id)
+            .fetch_optional(db)
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Issue the invoice of an order with the next number of the year of `issued_at`.
+    ///
+    /// Must run in the same transaction which records the payment: the year sequence stays
+    /// locked until commit and a rollback gives the number back, so that no gaps are left
+    pub async fn issue(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        order_id: &Uuid,
+        seller: &InvoiceSeller,
+        issued_at: NaiveDateTime,
+    ) -> DatabaseResult<Self> {
+        let id = Uuid::new_v4();
+        debug!("issuing invoice {id} for order {order_id}");
+        sqlx::query_as(
+            r#"WITH sequence AS (
+                INSERT INTO invoice_sequence (year, last_number) VALUES ($2, 1)
+                ON CONFLICT (year) DO UPDATE SET last_number = invoice_sequence.last_number + 1
+                RETURNING last_number
+            ), order_total AS (
+                SELECT customer_order.id, customer.email, COALESCE(customer_order.transaction_id, '') AS transaction_id,
+                    customer_order.shipping_cost,
+                    customer_order.shipping_cost + COALESCE(SUM(order_article.quantity * order_article.unit_price), 0) AS total
+                FROM customer_order
+                INNER JOIN customer ON customer.id = customer_order.customer_id
+                LEFT JOIN order_article ON order_article.order_id = customer_order.id
+                WHERE customer_order.id = $1
+                GROUP BY customer_order.id, customer.email
+            )
+            INSERT INTO invoice (id, order_id, year, number, issued_at, seller_name, seller_address, seller_vat_number,
+                customer_email, transaction_id, shipping_cost, tax_rate, taxable_amount, tax_amount, total)
+            SELECT $3, order_total.id, $2, sequence.last_number, $4, $5, $6, $7,
+                order_total.email, order_total.transaction_id, order_total.shipping_cost, $8,
+                ROUND(order_total.total * 100 / (100 + $8), 2),
+                order_total.total - ROUND(order_total.total * 100 / (100 + $8), 2),
+                order_total.total
+            FROM order_total, sequence
+            RETURNING *"#,
+        )
+        .bind(order_id)
+        .bind(issued_at.year())
+        .bind(id)
+        .bind(issued_at)
+        .bind(&seller.name)
+        .bind(&seller.address)
+        .bind(&seller.vat_number)
+        .bind(seller.tax_rate)
+        .fetch_one(db)
+        .await
+        .map_err(DatabaseError::from)
+    }
+
+    /// Invoice number as printed on the invoice, e.g. `2023/000042`
+    pub fn code(&self) -> String {
+        format!("{}/{:06}", self.year, self.number)
+    }
+}
+
+impl InvoiceLine {
+    /// Find the lines of an invoice in order
+    pub async fn find_by_invoice_id(db: &StoreDb, invoice_id: &Uuid) -> DatabaseResult<Vec<Self>> {
+        sqlx::query_as(r#"SELECT * FROM invoice_line WHERE invoice_id = $1 ORDER BY position"#)
+            .bind(invoice_id)
+            .fetch_all(db.pool())
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Copy the articles of the order into the lines of its invoice
+    pub async fn insert_from_order(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        invoice_id: &Uuid,
+        order_id: &Uuid,
+    ) -> DatabaseResult<()> {
+        debug!("inserting lines of order {order_id} into invoice {invoice_id}");
+        sqlx::query(
+            r#"INSERT INTO invoice_line (invoice_id, position, article_id, name, quantity, unit_price, total)
+            SELECT $1, (ROW_NUMBER() OVER (ORDER BY article.name, order_article.id))::integer, article.id, article.name,
+                order_article.quantity, order_article.unit_price, order_article.quantity * order_article.unit_price
+            FROM order_article
+            INNER JOIN article ON article.id = order_article.article_id
+            WHERE order_article.order_id = $2"#,
+        )
+        .bind(invoice_id)
+        .bind(order_id)
+        .execute(db)
+        .await
+        .map_err(DatabaseError::from)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::{Article, Customer, CustomerOrder, OrderArticle, TestDb};
+
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn should_issue_sequential_invoices() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let article = insert_article(&db, "panzerotti").await;
+        let seller = InvoiceSeller {
+            name: "Prima".to_string(),
+            address: "Via Roma 1, Milano".to_string(),
+            vat_number: "IT01234567890".to_string(),
+            tax_rate: dec!(22),
+        };
+        let issued_at = NaiveDate::from_ymd_opt(2023, 1, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        let mut invoices = Vec::new();
+        for _ in 0..2 {
+            let order = CustomerOrder::insert_order(&db, &customer.id, None, dec!(5))
+                .await
+                .unwrap();
+            OrderArticle::insert(&db, &order.id, &article.id, 2, article.unit_price)
+                .await
+                .unwrap();
+            let mut transaction = db.pool().begin().await.unwrap();
+            let invoice = Invoice::issue(&mut transaction, &order.id, &seller, issued_at)
+                .await
+                .unwrap();
+            InvoiceLine::insert_from_order(&mut transaction, &invoice.id, &order.id)
+                .await
+                .unwrap();
+            transaction.commit().await.unwrap();
+            invoices.push(invoice);
+        }
+        assert_eq!(invoices[0].number, 1);
+        assert_eq!(invoices[1].number, 2);
+        assert_eq!(invoices[0].year, 2023);
+        assert_eq!(invoices[0].customer_email, customer.email);
+        assert_eq!(invoices[0].total, dec!(25));
+        assert_eq!(invoices[0].taxable_amount, dec!(20.49));
+        assert_eq!(invoices[0].tax_amount, dec!(4.51));
+        assert_eq!(
+            Invoice::find_by_order_id(db.pool(), &invoices[0].order_id)
+                .await
+                .unwrap(),
+            Some(invoices[0].clone())
+        );
+
+        let lines = InvoiceLine::find_by_invoice_id(&db, &invoices[0].id)
+            .await
+            .unwrap();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].position, 1);
+        assert_eq!(lines[0].name, article.name);
+        assert_eq!(lines[0].total, dec!(20));
+    }
+
+    #[tokio::test]
+    async fn should_give_back_invoice_number_on_rollback() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let seller = InvoiceSeller {
+            name: "Prima".to_string(),
+            address: "Via Roma 1, Milano".to_string(),
+            vat_number: "IT01234567890".to_string(),
+            tax_rate: dec!(22),
+        };
+        let issued_at = NaiveDate::from_ymd_opt(2023, 1, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        let order = CustomerOrder::insert_order(&db, &customer.id, None, dec!(5))
+            .await
+            .unwrap();
+        let mut transaction = db.pool().begin().await.unwrap();
+        let rolled_back = Invoice::issue(&mut transaction, &order.id, &seller, issued_at)
+            .await
+            .unwrap();
+        transaction.rollback().await.unwrap();
+        let invoice = Invoice::issue(db.pool(), &order.id, &seller, issued_at)
+            .await
+            .unwrap();
+        assert_eq!(rolled_back.number, 1);
+        assert_eq!(invoice.number, 1);
+    }
+
+    async fn insert_article(db: &StoreDb, name: &str) -> Article {
+        let article = Article {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: "Lorem Ipsum".to_string(),
+            unit_price: dec!(10),
+            weight: 0,
+            archived_at: None,
+        };
+        let rows = sqlx::query(
+            "INSERT INTO article (id, name, description, unit_price) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(article.id)
+        .bind(&article.name)
+        .bind(&article.description)
+        .bind(article.unit_price)
+        .execute(db.pool())
+        .await
+        .map_err(DatabaseError::from)
+        .unwrap()
+        .rows_affected();
+        if rows != 1 {
+            panic!("too many inserts");
+        }
+
+        article
+    }
+}
@@ -4,12 +4,44 @@
 
 use super::{DatabaseError, DatabaseResult, StoreDb};
 
+use chrono::{NaiveDateTime, SubsecRound, Utc};
+
 mod article;
+mod article_co_purchase;
+mod article_review;
+mod cart;
 mod customer;
+mod invoice;
 mod order;
 mod order_article;
+mod order_event;
+mod order_return;
+mod outbox_event;
+mod refund;
+mod sales_report;
+mod shipment;
+mod shipping_method;
+mod wishlist;
 
 pub use article::Article;
+pub use article_co_purchase::ArticleCoPurchase;
+pub use article_review::{ArticleRating, ArticleReview, ReviewStatus};
+pub use cart::{Cart, CartItem};
 pub use customer::Customer;
-pub use order::{CustomerOrder, OrderStatus};
+pub use invoice::{Invoice, InvoiceLine, InvoiceSeller};
+pub use order::{CustomerOrder, OrderFilter, OrderStatus};
 pub use order_article::OrderArticle;
+pub use order_event::{OrderEvent, OrderEventActor, OrderEventKind};
+pub use order_return::{OrderReturn, OrderReturnArticle, ReturnStatus};
+pub use outbox_event::OutboxEvent;
+pub use refund::Refund;
+pub use sales_report::{OrderStats, ReportGranularity, RevenueBucket, SalesReport, TopArticle};
+pub use shipment::Shipment;
+pub use shipping_method::{ShippingCostRule, ShippingMethod};
+pub use wishlist::{Wishlist, WishlistItem};
+
+/// Current time truncated to microseconds, the precision timestamps are stored with,
+/// so that an inserted row is equal to the one read back
+fn now() -> NaiveDateTime {
+    Utc::now().naive_utc().trunc_subsecs(6)
+}
@@ -1,7 +1,8 @@
 use chrono::{NaiveDateTime, Utc};
+use rust_decimal::Decimal;
 use uuid::Uuid;
 
-use super::{DatabaseError, DatabaseResult, StoreDb};
+use super::{now, DatabaseError, DatabaseResult, OrderEventActor, OrderEventKind, StoreDb};
 
 #[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
 pub struct CustomerOrder {
@@ -10,6 +11,8 @@ pub struct CustomerOrder {
     pub created_at: NaiveDateTime,
     pub status: OrderStatus,
     pub transaction_id: Option<String>,
+    pub shipping_method_id: Option<Uuid>,
+    pub shipping_cost: Decimal,
 }
 
 /// Order status
@@ -20,6 +23,27 @@ pub enum OrderStatus {
     Preparing,
     PaymentRefused,
     Shipped,
+    ReturnRequested,
+    Refunded,
+    PartiallyRefunded,
+    Expired,
+}
+
+impl sqlx::postgres::PgHasArrayType for OrderStatus {
+    fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_name("_order_status")
+    }
+}
+
+/// Filter of the orders of a customer
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct OrderFilter {
+    /// Orders in one of these statuses; orders in any status when empty
+    pub statuses: Vec<OrderStatus>,
+    /// Orders created from this date included
+    pub created_from: Option<NaiveDateTime>,
+    /// Orders created before this date
+    pub created_to: Option<NaiveDateTime>,
 }
 
 impl CustomerOrder {
@@ -32,36 +56,119 @@ impl CustomerOrder {
             .map_err(DatabaseError::from)
     }
 
-    /// Find `Order` by customer id
+    /// Find `Order` by `id` and lock its row until the end of the transaction
+    pub async fn find_by_id_for_update(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        id: &Uuid,
+    ) -> DatabaseResult<Option<CustomerOrder>> {
+        sqlx::query_as(r#"SELECT * FROM customer_order WHERE id = $1 FOR UPDATE"#)
+            .bind(id)
+            .fetch_optional(db)
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Find the orders of a customer matching `filter`, newest first, starting after the
+    /// `(created_at, id)` position of the last order of the previous page
     pub async fn find_by_customer(
         db: &StoreDb,
         customer_id: &Uuid,
-        offset: i64,
+        filter: &OrderFilter,
+        after: Option<(NaiveDateTime, Uuid)>,
         limit: i64,
     ) -> DatabaseResult<Vec<CustomerOrder>> {
-        sqlx::query_as(r#"SELECT * FROM customer_order WHERE customer_id = $1 OFFSET $2 LIMIT $3"#)
-            .bind(customer_id)
-            .bind(offset)
-            .bind(limit)
-            .fetch_all(db.pool())
-            .await
-            .map_err(DatabaseError::from)
+        let (after_created_at, after_id) = after.unzip();
+        sqlx::query_as(
+            r#"SELECT * FROM customer_order WHERE customer_id = $1
+            AND (cardinality($2::order_status[]) = 0 OR status = ANY($2))
+            AND ($3::timestamp IS NULL OR created_at >= $3)
+            AND ($4::timestamp IS NULL OR created_at < $4)
+            AND ($5::timestamp IS NULL OR (created_at, id) < ($5, $6))
+            ORDER BY created_at DESC, id DESC LIMIT $7"#,
+        )
+        .bind(customer_id)
+        .bind(&filter.statuses)
+        .bind(filter.created_from)
+        .bind(filter.created_to)
+        .bind(after_created_at)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(db.pool())
+        .await
+        .map_err(DatabaseError::from)
     }
 
-    /// Insert a new order in the database
+    /// Count the orders of a customer matching `filter`
+    pub async fn count_by_customer(
+        db: &StoreDb,
+        customer_id: &Uuid,
+        filter: &OrderFilter,
+    ) -> DatabaseResult<i64> {
+        sqlx::query_scalar(
+            r#"SELECT COUNT(*) FROM customer_order WHERE customer_id = $1
+            AND (cardinality($2::order_status[]) = 0 OR status = ANY($2))
+            AND ($3::timestamp IS NULL OR created_at >= $3)
+            AND ($4::timestamp IS NULL OR created_at < $4)"#,
+        )
+        .bind(customer_id)
+        .bind(&filter.statuses)
+        .bind(filter.created_from)
+        .bind(filter.created_to)
+        .fetch_one(db.pool())
+        .await
+        .map_err(DatabaseError::from)
+    }
+
+    /// Find the orders created in `[from, to)`, oldest first, starting after the `(created_at, id)`
+    /// position of the last order of the previous batch
+    pub async fn find_created_between(
+        db: &StoreDb,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        after: Option<(NaiveDateTime, Uuid)>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<CustomerOrder>> {
+        let (after_created_at, after_id) = after.unwrap_or((from, Uuid::nil()));
+        sqlx::query_as(
+            r#"SELECT * FROM customer_order WHERE created_at >= $1 AND created_at < $2
+            AND (created_at, id) > ($3, $4)
+            ORDER BY created_at, id LIMIT $5"#,
+        )
+        .bind(from)
+        .bind(to)
+        .bind(after_created_at)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(db.pool())
+        .await
+        .map_err(DatabaseError::from)
+    }
+
+    /// Insert a new order in the database, recording its creation in the order history
     pub async fn insert_order(
         db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
         customer_id: &Uuid,
+        shipping_method_id: Option<&Uuid>,
+        shipping_cost: Decimal,
     ) -> DatabaseResult<Self> {
-        let order = Self::new(customer_id);
+        let order = Self::new(customer_id, shipping_method_id, shipping_cost);
         debug!("inserting a new order {} to repository", order.id);
         let rows = sqlx::query(
-            "INSERT INTO customer_order (id, customer_id, created_at, status) VALUES ($1, $2, $3, $4)",
+            r#"WITH inserted AS (
+                INSERT INTO customer_order (id, customer_id, created_at, status, shipping_method_id, shipping_cost) VALUES ($1, $2, $3, $4, $5, $6)
+                RETURNING id, status, transaction_id, created_at
+            ) INSERT INTO order_event (id, order_id, kind, actor, status, transaction_id, created_at)
+            SELECT $7, id, $8, $9, status, transaction_id, created_at FROM inserted"#,
         )
         .bind(order.id)
         .bind(order.customer_id)
         .bind(order.created_at)
         .bind(order.status)
+        .bind(order.shipping_method_id)
+        .bind(order.shipping_cost)
+        .bind(Uuid::new_v4())
+        .bind(OrderEventKind::Created)
+        .bind(OrderEventActor::Customer)
         .execute(db)
         .await
         .map_err(DatabaseError::from)?
@@ -73,20 +180,30 @@ impl CustomerOrder {
         Ok(order)
     }
 
-    /// Update order status
+    /// Update order status, recording the change made by `actor` in the order history
     pub async fn update_status(
         db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
         order_id: &Uuid,
         status: OrderStatus,
+        actor: OrderEventActor,
     ) -> DatabaseResult<()> {
         debug!("updating status to {:?} to rder {order_id} ", status);
-        let rows = sqlx::query("UPDATE customer_order SET status = $1 WHERE id = $2")
-            .bind(status)
-            .bind(order_id)
-            .execute(db)
-            .await
-            .map_err(DatabaseError::from)?
-            .rows_affected();
+        let rows = sqlx::query(
+            r#"WITH updated AS (
+                UPDATE customer_order SET status = $1 WHERE id = $2 RETURNING id, status, transaction_id
+            ) INSERT INTO order_event (id, order_id, kind, actor, status, transaction_id, created_at)
+            SELECT $3, id, $4, $5, status, transaction_id, $6 FROM updated"#,
+        )
+        .bind(status)
+        .bind(order_id)
+        .bind(Uuid::new_v4())
+        .bind(OrderEventKind::StatusChanged)
+        .bind(actor)
+        .bind(Utc::now().naive_utc())
+        .execute(db)
+        .await
+        .map_err(DatabaseError::from)?
+        .rows_affected();
         if rows != 1 {
             return Err(DatabaseError::TooManyInserts);
         }
@@ -94,19 +211,30 @@ impl CustomerOrder {
         Ok(())
     }
 
+    /// Update order transaction id, recording the change made by `actor` in the order history
     pub async fn update_transaction_id(
         db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
         order_id: &Uuid,
         transaction_id: &str,
+        actor: OrderEventActor,
     ) -> DatabaseResult<()> {
         debug!("updating transaction_id to {transaction_id} to rder {order_id} ");
-        let rows = sqlx::query("UPDATE customer_order SET transaction_id = $1 WHERE id = $2")
-            .bind(transaction_id)
-            .bind(order_id)
-            .execute(db)
-            .await
-            .map_err(DatabaseError::from)?
-            .rows_affected();
+        let rows = sqlx::query(
+            r#"WITH updated AS (
+                UPDATE customer_order SET transaction_id = $1 WHERE id = $2 RETURNING id, status, transaction_id
+            ) INSERT INTO order_event (id, order_id, kind, actor, status, transaction_id, created_at)
+            SELECT $3, id, $4, $5, status, transaction_id, $6 FROM updated"#,
+        )
+        .bind(transaction_id)
+        .bind(order_id)
+        .bind(Uuid::new_v4())
+        .bind(OrderEventKind::TransactionChanged)
+        .bind(actor)
+        .bind(Utc::now().naive_utc())
+        .execute(db)
+        .await
+        .map_err(DatabaseError::from)?
+        .rows_affected();
         if rows != 1 {
             return Err(DatabaseError::TooManyInserts);
         }
@@ -114,13 +242,48 @@ impl CustomerOrder {
         Ok(())
     }
 
-    fn new(customer_id: &Uuid) -> Self {
+    /// Set status to `Expired` for at most `limit` orders still in `Created` status and created
+    /// before `created_before`, recording the change in the order history. Returns the expired orders.
+    ///
+    /// Rows locked by other transactions are skipped, so the expiry can run on several replicas at once
+    pub async fn expire_created_before(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        created_before: NaiveDateTime,
+        limit: i64,
+    ) -> DatabaseResult<Vec<CustomerOrder>> {
+        debug!("expiring at most {limit} orders created before {created_before}");
+        sqlx::query_as(
+            r#"WITH expired AS (
+                UPDATE customer_order SET status = $1 WHERE id IN (
+                    SELECT id FROM customer_order WHERE status = $2 AND created_at < $3
+                    ORDER BY created_at LIMIT $4 FOR UPDATE SKIP LOCKED
+                ) RETURNING *
+            ), events AS (
+                INSERT INTO order_event (id, order_id, kind, actor, status, transaction_id, created_at)
+                SELECT gen_random_uuid(), id, $5, $6, status, transaction_id, $7 FROM expired
+            ) SELECT * FROM expired"#,
+        )
+        .bind(OrderStatus::Expired)
+        .bind(OrderStatus::Created)
+        .bind(created_before)
+        .bind(limit)
+        .bind(OrderEventKind::StatusChanged)
+        .bind(OrderEventActor::System)
+        .bind(Utc::now().naive_utc())
+        .fetch_all(db)
+        .await
+        .map_err(DatabaseError::from)
+    }
+
+    fn new(customer_id: &Uuid, shipping_method_id: Option<&Uuid>, shipping_cost: Decimal) -> Self {
         Self {
             id: Uuid::new_v4(),
             customer_id: *customer_id,
-            created_at: Utc::now().naive_utc(),
+            created_at: now(),
             status: OrderStatus::Created,
             transaction_id: None,
+            shipping_method_id: shipping_method_id.copied(),
+            shipping_cost,
         }
     }
 }
@@ -128,44 +291,77 @@ impl CustomerOrder {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::database::Customer;
+    use crate::database::{Customer, TestDb};
 
     use pretty_assertions::assert_eq;
-    use std::env;
 
     #[tokio::test]
     async fn should_create_order() {
-        let db = StoreDb::connect(&env::var("DATABASE_URL").expect("DATABASE_URL not found"))
-            .await
-            .expect("failed to connect to database");
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
 
-        let customer = Customer::insert(&db, "should_create_order@prima.it", "abcdef")
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
             .await
             .unwrap();
-        let order = CustomerOrder::insert_order(&db, &customer.id)
+        let order = CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
             .await
             .unwrap();
         assert_eq!(order.customer_id, customer.id);
     }
 
     #[tokio::test]
-    async fn should_update_order_status() {
-        let db = StoreDb::connect(&env::var("DATABASE_URL").expect("DATABASE_URL not found"))
+    async fn should_find_orders_created_between_in_batches() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
             .await
-            .expect("failed to connect to database");
+            .unwrap();
+        let mut orders = Vec::new();
+        for _ in 0..3 {
+            orders.push(
+                CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
+                    .await
+                    .unwrap(),
+            );
+        }
+        let from = orders[0].created_at - chrono::Duration::seconds(1);
+        let to = orders[2].created_at + chrono::Duration::seconds(1);
+        let mut found = Vec::new();
+        let mut after = None;
+        loop {
+            let batch = CustomerOrder::find_created_between(&db, from, to, after, 2)
+                .await
+                .unwrap();
+            if batch.is_empty() {
+                break;
+            }
+            after = batch.last().map(|x| (x.created_at, x.id));
+            found.extend(batch.into_iter().map(|x| x.id));
+        }
+        assert_eq!(found, orders.iter().map(|x| x.id).collect::<Vec<Uuid>>());
+    }
 
-        let customer = Customer::insert(&db, "should_update_order_status@prima.it", "abcdef")
+    #[tokio::test]
+    async fn should_update_order_status() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
             .await
             .unwrap();
-        let order = CustomerOrder::insert_order(&db, &customer.id)
+        let order = CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
             .await
             .unwrap();
 
-        assert!(
-            CustomerOrder::update_status(&db, &order.id, OrderStatus::Shipped)
-                .await
-                .is_ok()
-        );
+        assert!(CustomerOrder::update_status(
+            &db,
+            &order.id,
+            OrderStatus::Shipped,
+            OrderEventActor::BackOffice
+        )
+        .await
+        .is_ok());
 
         assert_eq!(
             CustomerOrder::find_by_id(&db, &order.id)
@@ -179,22 +375,24 @@ mod test {
 
     #[tokio::test]
     async fn should_update_transaction_id() {
-        let db = StoreDb::connect(&env::var("DATABASE_URL").expect("DATABASE_URL not found"))
-            .await
-            .expect("failed to connect to database");
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
 
-        let customer = Customer::insert(&db, "should_update_transaction_id@prima.it", "abcdef")
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
             .await
             .unwrap();
-        let order = CustomerOrder::insert_order(&db, &customer.id)
+        let order = CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
             .await
             .unwrap();
 
-        assert!(
-            CustomerOrder::update_transaction_id(&db, &order.id, "dummy")
-                .await
-                .is_ok()
-        );
+        assert!(CustomerOrder::update_transaction_id(
+            &db,
+            &order.id,
+            "dummy",
+            OrderEventActor::PaymentGateway
+        )
+        .await
+        .is_ok());
 
         assert_eq!(
             CustomerOrder::find_by_id(&db, &order.id)
@@ -209,15 +407,62 @@ mod test {
     }
 
     #[tokio::test]
-    async fn should_find_order_by_id() {
-        let db = StoreDb::connect(&env::var("DATABASE_URL").expect("DATABASE_URL not found"))
+    async fn should_expire_created_orders() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let created = CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
+            .await
+            .unwrap();
+        let paid = CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
             .await
-            .expect("failed to connect to database");
+            .unwrap();
+        CustomerOrder::update_status(
+            &db,
+            &paid.id,
+            OrderStatus::Preparing,
+            OrderEventActor::PaymentGateway,
+        )
+        .await
+        .unwrap();
 
-        let customer = Customer::insert(&db, "should_find_order_by_id@prima.it", "abcdef")
+        let expired = CustomerOrder::expire_created_before(&db, Utc::now().naive_utc(), i64::MAX)
             .await
             .unwrap();
-        let order = CustomerOrder::insert_order(&db, &customer.id)
+        assert_eq!(
+            expired.into_iter().map(|x| x.id).collect::<Vec<Uuid>>(),
+            vec![created.id]
+        );
+        assert_eq!(
+            CustomerOrder::find_by_id(&db, &created.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .status,
+            OrderStatus::Expired
+        );
+        assert_eq!(
+            CustomerOrder::find_by_id(&db, &paid.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .status,
+            OrderStatus::Preparing
+        );
+    }
+
+    #[tokio::test]
+    async fn should_find_order_by_id() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let order = CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
             .await
             .unwrap();
 
@@ -232,35 +477,120 @@ mod test {
 
     #[tokio::test]
     async fn should_find_orders_by_customer() {
-        let db = StoreDb::connect(&env::var("DATABASE_URL").expect("DATABASE_URL not found"))
-            .await
-            .expect("failed to connect to database");
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
 
-        let customer = Customer::insert(&db, "should_find_orders_by_customer@prima.it", "abcdef")
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
             .await
             .unwrap();
-        let customer_2 =
-            Customer::insert(&db, "should_find_orders_by_customer2@prima.it", "abcdef")
+        let customer_2 = Customer::insert(&db, "other@prima.it", "abcdef")
+            .await
+            .expect("failed to insert customer");
+
+        for _ in 0..3 {
+            CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
                 .await
-                .expect("failed to insert customer");
+                .expect("failed to insert order");
+        }
 
-        CustomerOrder::insert_order(&db, &customer.id)
+        CustomerOrder::insert_order(&db, &customer_2.id, None, Decimal::ZERO)
             .await
             .expect("failed to insert order");
-        CustomerOrder::insert_order(&db, &customer.id)
+
+        let all_orders = OrderFilter::default();
+        let orders = CustomerOrder::find_by_customer(&db, &customer.id, &all_orders, None, 256)
             .await
-            .expect("failed to insert order");
+            .unwrap();
+        assert_eq!(orders.len(), 3);
+        assert!(orders
+            .windows(2)
+            .all(|x| (x[0].created_at, x[0].id) > (x[1].created_at, x[1].id)));
+        let first_page = CustomerOrder::find_by_customer(&db, &customer.id, &all_orders, None, 2)
+            .await
+            .unwrap();
+        assert_eq!(first_page, orders[..2]);
+        let last = first_page.last().unwrap();
+        assert_eq!(
+            CustomerOrder::find_by_customer(
+                &db,
+                &customer.id,
+                &all_orders,
+                Some((last.created_at, last.id)),
+                2
+            )
+            .await
+            .unwrap(),
+            orders[2..]
+        );
+        assert_eq!(
+            CustomerOrder::count_by_customer(&db, &customer.id, &all_orders)
+                .await
+                .unwrap(),
+            3
+        );
+    }
+    #[tokio::test]
+    async fn should_filter_orders_by_customer() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
 
-        CustomerOrder::insert_order(&db, &customer_2.id)
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
             .await
-            .expect("failed to insert order");
+            .unwrap();
+        for _ in 0..3 {
+            CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
+                .await
+                .expect("failed to insert order");
+        }
+        let orders =
+            CustomerOrder::find_by_customer(&db, &customer.id, &OrderFilter::default(), None, 256)
+                .await
+                .unwrap();
+        CustomerOrder::update_status(
+            &db,
+            &orders[0].id,
+            OrderStatus::Preparing,
+            OrderEventActor::System,
+        )
+        .await
+        .unwrap();
 
+        let preparing = OrderFilter {
+            statuses: vec![OrderStatus::Preparing, OrderStatus::Shipped],
+            ..OrderFilter::default()
+        };
+        let found = CustomerOrder::find_by_customer(&db, &customer.id, &preparing, None, 256)
+            .await
+            .unwrap();
         assert_eq!(
-            CustomerOrder::find_by_customer(&db, &customer.id, 0, 256)
+            found.iter().map(|x| x.id).collect::<Vec<_>>(),
+            vec![orders[0].id]
+        );
+        assert_eq!(
+            CustomerOrder::count_by_customer(&db, &customer.id, &preparing)
                 .await
-                .unwrap()
-                .len(),
-            2
+                .unwrap(),
+            1
+        );
+
+        // orders are newest first, so the range holds the second order only
+        let created = OrderFilter {
+            created_from: Some(orders[1].created_at),
+            created_to: Some(orders[0].created_at),
+            ..OrderFilter::default()
+        };
+        let found = CustomerOrder::find_by_customer(&db, &customer.id, &created, None, 256)
+            .await
+            .unwrap();
+        assert_eq!(
+            found.iter().map(|x| x.id).collect::<Vec<_>>(),
+            vec![orders[1].id]
+        );
+        assert_eq!(
+            CustomerOrder::count_by_customer(&db, &customer.id, &created)
+                .await
+                .unwrap(),
+            1
         );
     }
 }
@@ -72,21 +72,19 @@ impl OrderArticle {
 mod test {
 
     use super::*;
-    use crate::database::{Article, Customer, CustomerOrder};
-    use std::env;
+    use crate::database::{Article, Customer, CustomerOrder, TestDb};
 
     use pretty_assertions::assert_eq;
 
     #[tokio::test]
     async fn should_insert_order_article() {
-        let db = StoreDb::connect(&env::var("DATABASE_URL").expect("DATABASE_URL not found"))
-            .await
-            .expect("failed to connect to database");
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
 
-        let customer = Customer::insert(&db, "should_insert_order_article@prima.it", "abcdef")
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
             .await
             .unwrap();
-        let order = CustomerOrder::insert_order(&db, &customer.id)
+        let order = CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
             .await
             .unwrap();
         let article = insert_article(&db, "panzerotti").await;
@@ -109,18 +107,13 @@ mod test {
 
     #[tokio::test]
     async fn should_find_order_articles_by_order_id() {
-        let db = StoreDb::connect(&env::var("DATABASE_URL").expect("DATABASE_URL not found"))
-            .await
-            .expect("failed to connect to database");
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
 
-        let customer = Customer::insert(
-            &db,
-            "should_find_order_articles_by_order_id@prima.it",
-            "abcdef",
-        )
-        .await
-        .unwrap();
-        let order = CustomerOrder::insert_order(&db, &customer.id)
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let order = CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
             .await
             .unwrap();
         let panzerotti = insert_article(&db, "panzerotti").await;
@@ -154,6 +147,8 @@ mod test {
             name: name.to_string(),
             description: "Lorem Ipsum".to_string(),
             unit_price: rust_decimal_macros::dec!(23.04),
+            weight: 0,
+            archived_at: None,
         };
         let rows = sqlx::query(
             "INSERT INTO article (id, name, description, unit_price) VALUES ($1, $2, $3, $4)",
@@ -0,0 +1,118 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use super::{DatabaseError, DatabaseResult, OrderStatus, StoreDb};
+
+/// An entry of the order history, recording the order state after each change.
+///
+/// Events are written by `CustomerOrder` in the same statement which changes the order.
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct OrderEvent {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub kind: OrderEventKind,
+    pub actor: OrderEventActor,
+    pub status: OrderStatus,
+    pub transaction_id: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+/// What changed in the order
+#[derive(Debug, Clone, Copy, Eq, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "order_event_kind", rename_all = "snake_case")]
+pub enum OrderEventKind {
+    Created,
+    StatusChanged,
+    TransactionChanged,
+}
+
+/// Who changed the order
+#[derive(Debug, Clone, Copy, Eq, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "order_event_actor", rename_all = "snake_case")]
+pub enum OrderEventActor {
+    Customer,
+    PaymentGateway,
+    BackOffice,
+    System,
+}
+
+impl OrderEvent {
+    /// Find the history of an order, oldest event first
+    pub async fn find_by_order_id(
+        db: &StoreDb,
+        order_id: &Uuid,
+    ) -> DatabaseResult<Vec<OrderEvent>> {
+        sqlx::query_as(r#"SELECT * FROM order_event WHERE order_id = $1 ORDER BY created_at"#)
+            .bind(order_id)
+            .fetch_all(db.pool())
+            .await
+            .map_err(DatabaseError::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::{Customer, CustomerOrder, TestDb};
+
+    use pretty_assertions::assert_eq;
+    use rust_decimal::Decimal;
+
+    #[tokio::test]
+    async fn should_record_order_history() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let order = CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
+            .await
+            .unwrap();
+        CustomerOrder::update_status(
+            &db,
+            &order.id,
+            OrderStatus::Preparing,
+            OrderEventActor::PaymentGateway,
+        )
+        .await
+        .unwrap();
+        CustomerOrder::update_transaction_id(
+            &db,
+            &order.id,
+            "dummy",
+            OrderEventActor::PaymentGateway,
+        )
+        .await
+        .unwrap();
+
+        let history = OrderEvent::find_by_order_id(&db, &order.id).await.unwrap();
+        assert_eq!(
+            history
+                .iter()
+                .map(|x| (x.kind, x.actor, x.status, x.transaction_id.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    OrderEventKind::Created,
+                    OrderEventActor::Customer,
+                    OrderStatus::Created,
+                    None
+                ),
+                (
+                    OrderEventKind::StatusChanged,
+                    OrderEventActor::PaymentGateway,
+                    OrderStatus::Preparing,
+                    None
+                ),
+                (
+                    OrderEventKind::TransactionChanged,
+                    OrderEventActor::PaymentGateway,
+                    OrderStatus::Preparing,
+                    Some("dummy")
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use chrono::{NaiveDateTime, Utc};
+use uuid::Uuid;
+
+use super::{now, DatabaseError, DatabaseResult};
+
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct OrderReturn {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub status: ReturnStatus,
+    pub reason: String,
+    pub created_at: NaiveDateTime,
+    pub resolved_at: Option<NaiveDateTime>,
+}
+
+/// Return status
+#[derive(Debug, Clone, Copy, Eq, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "return_status", rename_all = "snake_case")]
+pub enum ReturnStatus {
+    Requested,
+    Approved,
+    Rejected,
+}
+
+/// An order line (or part of it) included in a return
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct OrderReturnArticle {
+    pub id: Uuid,
+    pub return_id: Uuid,
+    pub order_article_id: Uuid,
+    pub quantity: i32,
+}
+
+impl OrderReturn {
+    /// Find `OrderReturn` by `id` and lock its row until the end of the transaction
+    pub async fn find_by_id_for_update(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        id: &Uuid,
+    ) -> DatabaseResult<Option<OrderReturn>> {
+        sqlx::query_as(r#"SELECT * FROM order_return WHERE id = $1 FOR UPDATE"#)
+            .bind(id)
+            .fetch_optional(db)
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Insert a new requested return for order
+    pub async fn insert(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        order_id: &Uuid,
+        reason: &str,
+    ) -> DatabaseResult<Self> {
+        let order_return = Self::new(order_id, reason);
+        debug!(
+            "inserting a new return {} for order {order_id} to repository",
+            order_return.id
+        );
+        let rows = sqlx::query(
+            "INSERT INTO order_return (id, order_id, status, reason, created_at) VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(order_return.id)
+        .bind(order_return.order_id)
+        .bind(order_return.status)
+        .bind(&order_return.reason)
+        .bind(order_return.created_at)
+        .execute(db)
+        .await
+        .map_err(DatabaseError::from)?
+        .rows_affected();
+        if rows != 1 {
+            return Err(DatabaseError::TooManyInserts);
+        }
+
+        Ok(order_return)
+    }
+
+    /// Resolve return setting its status to either approved or rejected
+    pub async fn resolve(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        id: &Uuid,
+        status: ReturnStatus,
+    ) -> DatabaseResult<()> {
+        debug!("resolving return {id} with status {:?}", status);
+        let rows =
+            sqlx::query("UPDATE order_return SET status = $1, resolved_at = $2 WHERE id = $3")
+                .bind(status)
+                .bind(Utc::now().naive_utc())
+                .bind(id)
+                .execute(db)
+                .await
+                .map_err(DatabaseError::from)?
+                .rows_affected();
+        if rows != 1 {
+            return Err(DatabaseError::TooManyInserts);
+        }
+
+        Ok(())
+    }
+
+    fn new(order_id: &Uuid, reason: &str) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            order_id: *order_id,
+            status: ReturnStatus::Requested,
+            reason: reason.to_string(),
+            created_at: now(),
+            resolved_at: None,
+        }
+    }
+}
+
+impl OrderReturnArticle {
+    /// Find the articles included in a return
+    pub async fn find_by_return_id(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        return_id: &Uuid,
+    ) -> DatabaseResult<Vec<OrderReturnArticle>> {
+        sqlx::query_as(r#"SELECT * FROM order_return_article WHERE return_id = $1"#)
+            .bind(return_id)
+            .fetch_all(db)
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Find the articles of an order which are part of a return which has not been rejected
+    pub async fn find_not_rejected_by_order_id(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        order_id: &Uuid,
+    ) -> DatabaseResult<Vec<OrderReturnArticle>> {
+        sqlx::query_as(
+            r#"SELECT ora.* FROM order_return_article ora
+            INNER JOIN order_return r ON r.id = ora.return_id
+            WHERE r.order_id = $1 AND r.status <> 'rejected'"#,
+        )
+        .bind(order_id)
+        .fetch_all(db)
+        .await
+        .map_err(DatabaseError::from)
+    }
+
+    /// Insert a new `OrderReturnArticle` record in the database
+    pub async fn insert(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        return_id: &Uuid,
+        order_article_id: &Uuid,
+        quantity: i32,
+    ) -> DatabaseResult<Self> {
+        let return_article = Self {
+            id: Uuid::new_v4(),
+            return_id: *return_id,
+            order_article_id: *order_article_id,
+            quantity,
+        };
+        debug!(
+            "inserting a new order_return_article {} to repository",
+            return_article.id
+        );
+        let rows = sqlx::query(
+            "INSERT INTO order_return_article (id, return_id, order_article_id, quantity) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(return_article.id)
+        .bind(return_article.return_id)
+        .bind(return_article.order_article_id)
+        .bind(return_article.quantity)
+        .execute(db)
+        .await
+        .map_err(DatabaseError::from)?
+        .rows_affected();
+        if rows != 1 {
+            return Err(DatabaseError::TooManyInserts);
+        }
+
+        Ok(return_article)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::{Customer, CustomerOrder, OrderArticle, StoreDb, TestDb};
+
+    use pretty_assertions::assert_eq;
+    use rust_decimal::Decimal;
+
+    #[tokio::test]
+    async fn should_request_and_resolve_return() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let order = CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
+            .await
+            .unwrap();
+        let order_return = OrderReturn::insert(&db, &order.id, "broken").await.unwrap();
+        assert_eq!(order_return.status, ReturnStatus::Requested);
+
+        assert!(
+            OrderReturn::resolve(&db, &order_return.id, ReturnStatus::Approved)
+                .await
+                .is_ok()
+        );
+        let order_return = OrderReturn::find_by_id_for_update(&db, &order_return.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(order_return.order_id, order.id);
+        assert_eq!(order_return.status, ReturnStatus::Approved);
+        assert!(order_return.resolved_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn should_find_not_rejected_return_articles() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let order = CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
+            .await
+            .unwrap();
+        let article_id = insert_article(&db).await;
+        let order_article = OrderArticle::insert(
+            &db,
+            &order.id,
+            &article_id,
+            4,
+            rust_decimal_macros::dec!(2.5),
+        )
+        .await
+        .unwrap();
+
+        let rejected = OrderReturn::insert(&db, &order.id, "changed my mind")
+            .await
+            .unwrap();
+        OrderReturnArticle::insert(&db, &rejected.id, &order_article.id, 2)
+            .await
+            .unwrap();
+        OrderReturn::resolve(&db, &rejected.id, ReturnStatus::Rejected)
+            .await
+            .unwrap();
+        let requested = OrderReturn::insert(&db, &order.id, "broken").await.unwrap();
+        OrderReturnArticle::insert(&db, &requested.id, &order_article.id, 1)
+            .await
+            .unwrap();
+
+        let not_rejected = OrderReturnArticle::find_not_rejected_by_order_id(db.pool(), &order.id)
+            .await
+            .unwrap();
+        assert_eq!(not_rejected.len(), 1);
+        assert_eq!(not_rejected[0].quantity, 1);
+        assert_eq!(
+            OrderReturnArticle::find_by_return_id(db.pool(), &rejected.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    async fn insert_article(db: &StoreDb) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO article (id, name, description, unit_price) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(id)
+        .bind("returned article")
+        .bind("Lorem Ipsum")
+        .bind(rust_decimal_macros::dec!(2.5))
+        .execute(db.pool())
+        .await
+        .expect("failed to insert article");
+
+        id
+    }
+}
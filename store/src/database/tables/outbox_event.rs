@@ -0,0 +1,268 @@
+use chrono::{NaiveDateTime, Utc};
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+use super::{now, DatabaseError, DatabaseResult};
+
+/// A domain event waiting to be relayed to the downstream consumers.
+///
+/// Events are inserted in the same transaction which changes the aggregate, and are marked as
+/// delivered only once the sink has accepted them. A relay claims pending events until
+/// `locked_until`, so that other relays skip them meanwhile.
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct OutboxEvent {
+    pub id: Uuid,
+    pub aggregate_id: Uuid,
+    pub event_type: String,
+    pub payload: JsonValue,
+    pub created_at: NaiveDateTime,
+    pub delivered_at: Option<NaiveDateTime>,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub locked_until: Option<NaiveDateTime>,
+}
+
+impl OutboxEvent {
+    /// Find `OutboxEvent` by `id`
+    #[cfg(test)]
+    pub async fn find_by_id(db: &super::StoreDb, id: &Uuid) -> DatabaseResult<Option<OutboxEvent>> {
+        sqlx::query_as(r#"SELECT * FROM outbox_event WHERE id = $1"#)
+            .bind(id)
+            .fetch_optional(db.pool())
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Claim at most `limit` events not delivered yet until `locked_until`; returns them oldest first.
+    /// Events claimed by other relays are skipped until their claim expires at `now`
+    pub async fn claim_pending(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        now: NaiveDateTime,
+        locked_until: NaiveDateTime,
+        limit: i64,
+    ) -> DatabaseResult<Vec<OutboxEvent>> {
+        let mut events: Vec<OutboxEvent> = sqlx::query_as(
+            r#"UPDATE outbox_event SET locked_until = $1 WHERE id IN (
+                SELECT id FROM outbox_event WHERE delivered_at IS NULL
+                AND (locked_until IS NULL OR locked_until <= $2)
+                ORDER BY created_at LIMIT $3 FOR UPDATE SKIP LOCKED
+            ) RETURNING *"#,
+        )
+        .bind(locked_until)
+        .bind(now)
+        .bind(limit)
+        .fetch_all(db)
+        .await
+        .map_err(DatabaseError::from)?;
+        events.sort_by_key(|x| (x.created_at, x.id));
+
+        Ok(events)
+    }
+
+    /// Release the claim on events, so that they can be claimed again right away
+    pub async fn release(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        ids: &[Uuid],
+    ) -> DatabaseResult<()> {
+        sqlx::query("UPDATE outbox_event SET locked_until = NULL WHERE id = ANY($1)")
+            .bind(ids)
+            .execute(db)
+            .await
+            .map_err(DatabaseError::from)?;
+
+        Ok(())
+    }
+
+    /// Insert a new `OutboxEvent` for the aggregate
+    pub async fn insert(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        aggregate_id: &Uuid,
+        event_type: &str,
+        payload: JsonValue,
+    ) -> DatabaseResult<Self> {
+        let event = Self {
+            id: Uuid::new_v4(),
+            aggregate_id: *aggregate_id,
+            event_type: event_type.to_string(),
+            payload,
+            created_at: now(),
+            delivered_at: None,
+            attempts: 0,
+            last_error: None,
+            locked_until: None,
+        };
+        debug!(
+            "inserting a new {event_type} outbox event {} for {aggregate_id} to repository",
+            event.id
+        );
+        let rows = sqlx::query(
+            "INSERT INTO outbox_event (id, aggregate_id, event_type, payload, created_at) VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(event.id)
+        .bind(event.aggregate_id)
+        .bind(&event.event_type)
+        .bind(&event.payload)
+        .bind(event.created_at)
+        .execute(db)
+        .await
+        .map_err(DatabaseError::from)?
+        .rows_affected();
+        if rows != 1 {
+            return Err(DatabaseError::TooManyInserts);
+        }
+
+        Ok(event)
+    }
+
+    /// Mark event as delivered and release its claim
+    pub async fn mark_delivered(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        id: &Uuid,
+    ) -> DatabaseResult<()> {
+        debug!("marking outbox event {id} as delivered");
+        let rows = sqlx::query(
+            "UPDATE outbox_event SET delivered_at = $1, attempts = attempts + 1, last_error = NULL, locked_until = NULL WHERE id = $2",
+        )
+        .bind(Utc::now().naive_utc())
+        .bind(id)
+        .execute(db)
+        .await
+        .map_err(DatabaseError::from)?
+        .rows_affected();
+        if rows != 1 {
+            return Err(DatabaseError::TooManyInserts);
+        }
+
+        Ok(())
+    }
+
+    /// Record a failed delivery attempt for event and release its claim
+    pub async fn mark_failed(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        id: &Uuid,
+        error: &str,
+    ) -> DatabaseResult<()> {
+        debug!("recording failed delivery for outbox event {id}: {error}");
+        let rows = sqlx::query(
+            "UPDATE outbox_event SET attempts = attempts + 1, last_error = $1, locked_until = NULL WHERE id = $2",
+        )
+        .bind(error)
+        .bind(id)
+        .execute(db)
+        .await
+        .map_err(DatabaseError::from)?
+        .rows_affected();
+        if rows != 1 {
+            return Err(DatabaseError::TooManyInserts);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::TestDb;
+
+    use chrono::SubsecRound;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn should_insert_and_deliver_outbox_event() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let aggregate_id = Uuid::new_v4();
+        let event = OutboxEvent::insert(
+            &db,
+            &aggregate_id,
+            "PaymentFailed",
+            serde_json::json!({ "order_id": aggregate_id }),
+        )
+        .await
+        .unwrap();
+
+        OutboxEvent::mark_failed(&db, &event.id, "connection refused")
+            .await
+            .unwrap();
+        let failed = OutboxEvent::find_by_id(&db, &event.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(failed.attempts, 1);
+        assert_eq!(failed.last_error.as_deref(), Some("connection refused"));
+        assert!(failed.delivered_at.is_none());
+
+        OutboxEvent::mark_delivered(&db, &event.id).await.unwrap();
+        let delivered = OutboxEvent::find_by_id(&db, &event.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(delivered.attempts, 2);
+        assert!(delivered.last_error.is_none());
+        assert!(delivered.delivered_at.is_some());
+        assert_eq!(delivered.payload, event.payload);
+    }
+
+    #[tokio::test]
+    async fn should_claim_pending_events_until_lease_expires() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let aggregate_id = Uuid::new_v4();
+        let mut events = Vec::new();
+        for event_type in ["PaymentSucceeded", "OrderShipped", "OrderRefunded"] {
+            events.push(
+                OutboxEvent::insert(&db, &aggregate_id, event_type, serde_json::json!({}))
+                    .await
+                    .unwrap(),
+            );
+        }
+        OutboxEvent::mark_delivered(&db, &events[0].id)
+            .await
+            .unwrap();
+
+        let now = Utc::now().naive_utc();
+        let locked_until = now + chrono::Duration::seconds(60);
+        let claimed = OutboxEvent::claim_pending(db.pool(), now, locked_until, 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            claimed.iter().map(|x| x.id).collect::<Vec<Uuid>>(),
+            vec![events[1].id]
+        );
+        assert_eq!(claimed[0].locked_until, Some(locked_until.trunc_subsecs(6)));
+        // claimed events are skipped by other relays until the lease expires
+        let claimed = OutboxEvent::claim_pending(db.pool(), now, locked_until, 64)
+            .await
+            .unwrap();
+        assert_eq!(
+            claimed.iter().map(|x| x.id).collect::<Vec<Uuid>>(),
+            vec![events[2].id]
+        );
+        assert!(OutboxEvent::claim_pending(db.pool(), now, locked_until, 64)
+            .await
+            .unwrap()
+            .is_empty());
+        let claimed = OutboxEvent::claim_pending(db.pool(), locked_until, locked_until, 64)
+            .await
+            .unwrap();
+        assert_eq!(
+            claimed.iter().map(|x| x.id).collect::<Vec<Uuid>>(),
+            vec![events[1].id, events[2].id]
+        );
+
+        OutboxEvent::release(db.pool(), &[events[2].id])
+            .await
+            .unwrap();
+        let claimed = OutboxEvent::claim_pending(db.pool(), now, locked_until, 64)
+            .await
+            .unwrap();
+        assert_eq!(
+            claimed.iter().map(|x| x.id).collect::<Vec<Uuid>>(),
+            vec![events[2].id]
+        );
+    }
+}
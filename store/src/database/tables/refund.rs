@@ -0,0 +1,111 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::{now, DatabaseError, DatabaseResult};
+
+/// A refund issued for an approved return, recorded against the order payment transaction
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct Refund {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub return_id: Uuid,
+    pub transaction_id: String,
+    pub amount: Decimal,
+    pub created_at: NaiveDateTime,
+}
+
+impl Refund {
+    /// Find all the refunds for an order
+    pub async fn find_by_order_id(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        order_id: &Uuid,
+    ) -> DatabaseResult<Vec<Refund>> {
+        sqlx::query_as(r#"SELECT * FROM refund WHERE order_id = $1 ORDER BY created_at"#)
+            .bind(order_id)
+            .fetch_all(db)
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Insert a new `Refund` for a return
+    pub async fn insert(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        order_id: &Uuid,
+        return_id: &Uuid,
+        transaction_id: &str,
+        amount: Decimal,
+    ) -> DatabaseResult<Self> {
+        let refund = Self {
+            id: Uuid::new_v4(),
+            order_id: *order_id,
+            return_id: *return_id,
+            transaction_id: transaction_id.to_string(),
+            amount,
+            created_at: now(),
+        };
+        debug!(
+            "inserting a new refund {} of {amount} for order {order_id} to repository",
+            refund.id
+        );
+        let rows = sqlx::query(
+            "INSERT INTO refund (id, order_id, return_id, transaction_id, amount, created_at) VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(refund.id)
+        .bind(refund.order_id)
+        .bind(refund.return_id)
+        .bind(&refund.transaction_id)
+        .bind(refund.amount)
+        .bind(refund.created_at)
+        .execute(db)
+        .await
+        .map_err(DatabaseError::from)?
+        .rows_affected();
+        if rows != 1 {
+            return Err(DatabaseError::TooManyInserts);
+        }
+
+        Ok(refund)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::{Customer, CustomerOrder, OrderReturn, TestDb};
+
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn should_insert_and_find_refunds() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let order = CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
+            .await
+            .unwrap();
+        let order_return = OrderReturn::insert(&db, &order.id, "broken").await.unwrap();
+
+        let refund = Refund::insert(
+            &db,
+            &order.id,
+            &order_return.id,
+            "dummy",
+            rust_decimal_macros::dec!(12.3),
+        )
+        .await
+        .unwrap();
+        assert_eq!(refund.amount, rust_decimal_macros::dec!(12.3));
+
+        let refunds = Refund::find_by_order_id(db.pool(), &order.id)
+            .await
+            .unwrap();
+        assert_eq!(refunds.len(), 1);
+        assert_eq!(refunds[0].id, refund.id);
+        assert_eq!(refunds[0].transaction_id.as_str(), "dummy");
+    }
+}
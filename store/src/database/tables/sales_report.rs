@@ -0,0 +1,274 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::{DatabaseError, DatabaseResult, StoreDb};
+
+/// Statuses of the orders which have been paid; refunded orders are included, since their
+/// refunds are reported separately
+const PAID_STATUSES: &str =
+    "('preparing', 'shipped', 'return_requested', 'refunded', 'partially_refunded')";
+
+/// Length of the periods revenue is grouped by
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ReportGranularity {
+    Day,
+    Week,
+    Month,
+}
+
+impl ReportGranularity {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+}
+
+/// Revenue of the orders placed in a period
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct RevenueBucket {
+    pub period_start: NaiveDateTime,
+    pub paid_orders: i64,
+    /// Articles and shipping cost of the paid orders
+    pub revenue: Decimal,
+    /// Refunds issued in the period
+    pub refunds: Decimal,
+}
+
+/// Article with the amount of items sold
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct TopArticle {
+    pub article_id: Uuid,
+    pub name: String,
+    pub quantity: i64,
+    pub revenue: Decimal,
+}
+
+/// Aggregated statistics of the orders placed in a date range
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct OrderStats {
+    pub orders: i64,
+    pub paid_orders: i64,
+    pub payment_failures: i64,
+    /// Average total of the paid orders, shipping included
+    pub average_order_value: Decimal,
+}
+
+impl OrderStats {
+    /// Ratio of the payment attempts which failed; zero if there were no attempts
+    pub fn payment_failure_rate(&self) -> Decimal {
+        let attempts = self.paid_orders + self.payment_failures;
+        if attempts == 0 {
+            return Decimal::ZERO;
+        }
+        (Decimal::from(self.payment_failures) / Decimal::from(attempts)).round_dp(4)
+    }
+}
+
+/// Sales reports computed from orders placed in `[from, to)`
+pub struct SalesReport;
+
+impl SalesReport {
+    /// Revenue of the paid orders grouped by period, oldest first
+    pub async fn revenue(
+        db: &StoreDb,
+        granularity: ReportGranularity,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> DatabaseResult<Vec<RevenueBucket>> {
+        let query = format!(
+            r#"WITH order_total AS (
+                SELECT customer_order.id, customer_order.created_at,
+                    customer_order.shipping_cost + COALESCE(SUM(order_article.quantity * order_article.unit_price), 0) AS total
+                FROM customer_order
+                LEFT JOIN order_article ON order_article.order_id = customer_order.id
+                WHERE customer_order.created_at >= $1 AND customer_order.created_at < $2
+                AND customer_order.status IN {PAID_STATUSES}
+                GROUP BY customer_order.id
+            ), revenue AS (
+                SELECT date_trunc($3, created_at) AS period_start, COUNT(*) AS paid_orders, SUM(total) AS revenue
+                FROM order_total GROUP BY 1
+            ), refunds AS (
+                SELECT date_trunc($3, created_at) AS period_start, SUM(amount) AS refunds
+                FROM refund WHERE created_at >= $1 AND created_at < $2 GROUP BY 1
+            )
+            SELECT period_start, COALESCE(revenue.paid_orders, 0) AS paid_orders,
+                COALESCE(revenue.revenue, 0) AS revenue, COALESCE(refunds.refunds, 0) AS refunds
+            FROM revenue FULL OUTER JOIN refunds USING (period_start)
+            ORDER BY period_start"#
+        );
+        sqlx::query_as(&query)
+            .bind(from)
+            .bind(to)
+            .bind(granularity.as_sql())
+            .fetch_all(db.pool())
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Articles with the most items sold in the paid orders
+    pub async fn top_articles(
+        db: &StoreDb,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        limit: i64,
+    ) -> DatabaseResult<Vec<TopArticle>> {
+        let query = format!(
+            r#"SELECT article.id AS article_id, article.name, SUM(order_article.quantity)::bigint AS quantity,
+                SUM(order_article.quantity * order_article.unit_price) AS revenue
+            FROM order_article
+            INNER JOIN customer_order ON customer_order.id = order_article.order_id
+            INNER JOIN article ON article.id = order_article.article_id
+            WHERE customer_order.created_at >= $1 AND customer_order.created_at < $2
+            AND customer_order.status IN {PAID_STATUSES}
+            GROUP BY article.id, article.name
+            ORDER BY quantity DESC, revenue DESC, article.id LIMIT $3"#
+        );
+        sqlx::query_as(&query)
+            .bind(from)
+            .bind(to)
+            .bind(limit)
+            .fetch_all(db.pool())
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Statistics of the orders placed in the date range
+    pub async fn order_stats(
+        db: &StoreDb,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> DatabaseResult<OrderStats> {
+        let query = format!(
+            r#"WITH order_total AS (
+                SELECT customer_order.id, customer_order.status,
+                    customer_order.shipping_cost + COALESCE(SUM(order_article.quantity * order_article.unit_price), 0) AS total
+                FROM customer_order
+                LEFT JOIN order_article ON order_article.order_id = customer_order.id
+                WHERE customer_order.created_at >= $1 AND customer_order.created_at < $2
+                GROUP BY customer_order.id
+            )
+            SELECT COUNT(*) AS orders,
+                COUNT(*) FILTER (WHERE status IN {PAID_STATUSES}) AS paid_orders,
+                COUNT(*) FILTER (WHERE status = 'payment_refused') AS payment_failures,
+                COALESCE(ROUND(AVG(total) FILTER (WHERE status IN {PAID_STATUSES}), 2), 0) AS average_order_value
+            FROM order_total"#
+        );
+        sqlx::query_as(&query)
+            .bind(from)
+            .bind(to)
+            .fetch_one(db.pool())
+            .await
+            .map_err(DatabaseError::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::{
+        Article, Customer, CustomerOrder, OrderArticle, OrderEventActor, OrderStatus, TestDb,
+    };
+
+    use chrono::Duration;
+    use pretty_assertions::assert_eq;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn should_compute_sales_reports() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let article = insert_article(&db, "panzerotti").await;
+        let mut orders = Vec::new();
+        for (quantity, status) in [
+            (1, OrderStatus::Preparing),
+            (3, OrderStatus::Shipped),
+            (2, OrderStatus::PaymentRefused),
+        ] {
+            let order = CustomerOrder::insert_order(&db, &customer.id, None, dec!(5))
+                .await
+                .unwrap();
+            OrderArticle::insert(&db, &order.id, &article.id, quantity, article.unit_price)
+                .await
+                .unwrap();
+            CustomerOrder::update_status(&db, &order.id, status, OrderEventActor::System)
+                .await
+                .unwrap();
+            orders.push(order);
+        }
+        let from = orders[0].created_at - Duration::seconds(1);
+        let to = orders[2].created_at + Duration::seconds(1);
+        let top_articles = SalesReport::top_articles(&db, from, to, 100).await.unwrap();
+        let top_article = top_articles
+            .into_iter()
+            .find(|x| x.article_id == article.id)
+            .unwrap();
+        assert_eq!(top_article.quantity, 4);
+        assert_eq!(top_article.revenue, dec!(40));
+
+        let stats = SalesReport::order_stats(&db, from, to).await.unwrap();
+        assert!(stats.orders >= 3);
+        assert!(stats.paid_orders >= 2);
+        assert!(stats.payment_failures >= 1);
+
+        let revenue = SalesReport::revenue(&db, ReportGranularity::Month, from, to)
+            .await
+            .unwrap();
+        assert!(!revenue.is_empty());
+        assert!(revenue.iter().map(|x| x.revenue).sum::<Decimal>() >= dec!(50));
+    }
+
+    #[test]
+    fn should_compute_payment_failure_rate() {
+        let stats = OrderStats {
+            orders: 10,
+            paid_orders: 3,
+            payment_failures: 1,
+            average_order_value: Decimal::ZERO,
+        };
+        assert_eq!(stats.payment_failure_rate(), dec!(0.25));
+        let stats = OrderStats {
+            orders: 1,
+            paid_orders: 0,
+            payment_failures: 0,
+            average_order_value: Decimal::ZERO,
+        };
+        assert_eq!(stats.payment_failure_rate(), Decimal::ZERO);
+    }
+
+    async fn insert_article(db: &StoreDb, name: &str) -> Article {
+        let article = Article {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: "Lorem Ipsum".to_string(),
+            unit_price: dec!(10),
+            weight: 0,
+            archived_at: None,
+        };
+        let rows = sqlx::query(
+            "INSERT INTO article (id, name, description, unit_price) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(article.id)
+        .bind(&article.name)
+        .bind(&article.description)
+        .bind(article.unit_price)
+        .execute(db.pool())
+        .await
+        .map_err(DatabaseError::from)
+        .unwrap()
+        .rows_affected();
+        if rows != 1 {
+            panic!("too many inserts");
+        }
+
+        article
+    }
+}
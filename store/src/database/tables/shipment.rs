@@ -0,0 +1,103 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use super::{now, DatabaseError, DatabaseResult, StoreDb};
+
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct Shipment {
+    pub order_id: Uuid,
+    pub carrier: String,
+    pub tracking_code: String,
+    pub shipped_at: NaiveDateTime,
+}
+
+impl Shipment {
+    /// Find `Shipment` by order id
+    pub async fn find_by_order_id(
+        db: &StoreDb,
+        order_id: &Uuid,
+    ) -> DatabaseResult<Option<Shipment>> {
+        sqlx::query_as(r#"SELECT * FROM shipment WHERE order_id = $1"#)
+            .bind(order_id)
+            .fetch_optional(db.pool())
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Insert a new `Shipment` for order
+    pub async fn insert(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        order_id: &Uuid,
+        carrier: &str,
+        tracking_code: &str,
+    ) -> DatabaseResult<Self> {
+        let shipment = Self::new(order_id, carrier, tracking_code);
+        debug!("inserting a new shipment for order {order_id} to repository");
+        let rows = sqlx::query(
+            "INSERT INTO shipment (order_id, carrier, tracking_code, shipped_at) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(shipment.order_id)
+        .bind(&shipment.carrier)
+        .bind(&shipment.tracking_code)
+        .bind(shipment.shipped_at)
+        .execute(db)
+        .await
+        .map_err(DatabaseError::from)?
+        .rows_affected();
+        if rows != 1 {
+            return Err(DatabaseError::TooManyInserts);
+        }
+
+        Ok(shipment)
+    }
+
+    fn new(order_id: &Uuid, carrier: &str, tracking_code: &str) -> Self {
+        Self {
+            order_id: *order_id,
+            carrier: carrier.to_string(),
+            tracking_code: tracking_code.to_string(),
+            shipped_at: now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::{Customer, CustomerOrder, TestDb};
+    use rust_decimal::Decimal;
+
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn should_insert_and_find_shipment() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let order = CustomerOrder::insert_order(&db, &customer.id, None, Decimal::ZERO)
+            .await
+            .unwrap();
+        assert!(Shipment::find_by_order_id(&db, &order.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        let shipment = Shipment::insert(&db, &order.id, "DHL", "JD014600006281230704")
+            .await
+            .unwrap();
+        assert_eq!(shipment.carrier.as_str(), "DHL");
+        assert_eq!(shipment.tracking_code.as_str(), "JD014600006281230704");
+        assert_eq!(
+            Shipment::find_by_order_id(&db, &order.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .order_id,
+            order.id
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::{DatabaseError, DatabaseResult, StoreDb};
+
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct ShippingMethod {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+}
+
+/// A shipping cost rule applies to orders whose weight (in grams) and subtotal are in range.
+/// Lower bounds are inclusive, upper bounds are exclusive; a missing upper bound means unbounded.
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct ShippingCostRule {
+    pub id: Uuid,
+    pub shipping_method_id: Uuid,
+    pub min_weight: i32,
+    pub max_weight: Option<i32>,
+    pub min_subtotal: Decimal,
+    pub max_subtotal: Option<Decimal>,
+    pub cost: Decimal,
+}
+
+impl ShippingMethod {
+    /// Find `ShippingMethod` by `id`
+    pub async fn find_by_id(db: &StoreDb, id: &Uuid) -> DatabaseResult<Option<ShippingMethod>> {
+        sqlx::query_as(r#"SELECT * FROM shipping_method WHERE id = $1"#)
+            .bind(id)
+            .fetch_optional(db.pool())
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Get all shipping methods
+    pub async fn get_all(db: &StoreDb) -> DatabaseResult<Vec<ShippingMethod>> {
+        sqlx::query_as(r#"SELECT * FROM shipping_method ORDER BY name"#)
+            .fetch_all(db.pool())
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Compute the shipping cost for an order with the provided `weight` and `subtotal`.
+    /// The cheapest matching rule wins; returns `None` if no rule applies.
+    pub fn cost_for(rules: &[ShippingCostRule], weight: i64, subtotal: Decimal) -> Option<Decimal> {
+        rules
+            .iter()
+            .filter(|rule| rule.matches(weight, subtotal))
+            .map(|rule| rule.cost)
+            .min()
+    }
+}
+
+impl ShippingCostRule {
+    /// Find all the cost rules for a shipping method
+    pub async fn find_by_shipping_method(
+        db: &StoreDb,
+        shipping_method_id: &Uuid,
+    ) -> DatabaseResult<Vec<ShippingCostRule>> {
+        sqlx::query_as(r#"SELECT * FROM shipping_cost_rule WHERE shipping_method_id = $1"#)
+            .bind(shipping_method_id)
+            .fetch_all(db.pool())
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Returns whether rule applies to an order with the provided `weight` and `subtotal`
+    pub fn matches(&self, weight: i64, subtotal: Decimal) -> bool {
+        weight >= self.min_weight as i64
+            && self
+                .max_weight
+                .map(|max| weight < max as i64)
+                .unwrap_or(true)
+            && subtotal >= self.min_subtotal
+            && self.max_subtotal.map(|max| subtotal < max).unwrap_or(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::database::TestDb;
+
+    use pretty_assertions::assert_eq;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn should_find_shipping_method_with_rules() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let shipping_method = insert_shipping_method(&db, "pickup").await;
+        insert_rule(&db, &shipping_method.id, 0, None, dec!(0), None, dec!(5.0)).await;
+        insert_rule(
+            &db,
+            &shipping_method.id,
+            0,
+            Some(100),
+            dec!(0),
+            None,
+            dec!(2.0),
+        )
+        .await;
+
+        assert_eq!(
+            ShippingMethod::find_by_id(&db, &shipping_method.id)
+                .await
+                .unwrap()
+                .unwrap(),
+            shipping_method
+        );
+        assert!(ShippingMethod::get_all(&db)
+            .await
+            .unwrap()
+            .contains(&shipping_method));
+        assert_eq!(
+            ShippingCostRule::find_by_shipping_method(&db, &shipping_method.id)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn should_match_rule() {
+        let rule = rule(100, Some(200), dec!(10), Some(dec!(50)), dec!(3.5));
+        assert!(rule.matches(100, dec!(10)));
+        assert!(rule.matches(199, dec!(49.99)));
+        assert!(!rule.matches(99, dec!(20)));
+        assert!(!rule.matches(200, dec!(20)));
+        assert!(!rule.matches(150, dec!(9.99)));
+        assert!(!rule.matches(150, dec!(50)));
+
+        let unbounded = rule_unbounded();
+        assert!(unbounded.matches(0, dec!(0)));
+        assert!(unbounded.matches(i64::MAX, dec!(100000)));
+    }
+
+    #[test]
+    fn should_compute_cheapest_shipping_cost() {
+        let rules = vec![
+            rule(0, Some(2000), dec!(0), Some(dec!(50)), dec!(4.90)),
+            rule(2000, None, dec!(0), Some(dec!(50)), dec!(9.90)),
+            rule(0, None, dec!(50), None, dec!(0)),
+        ];
+        assert_eq!(
+            ShippingMethod::cost_for(&rules, 500, dec!(20)),
+            Some(dec!(4.90))
+        );
+        assert_eq!(
+            ShippingMethod::cost_for(&rules, 2500, dec!(20)),
+            Some(dec!(9.90))
+        );
+        assert_eq!(
+            ShippingMethod::cost_for(&rules, 2500, dec!(80)),
+            Some(dec!(0))
+        );
+        assert_eq!(ShippingMethod::cost_for(&[], 2500, dec!(80)), None);
+    }
+
+    fn rule(
+        min_weight: i32,
+        max_weight: Option<i32>,
+        min_subtotal: Decimal,
+        max_subtotal: Option<Decimal>,
+        cost: Decimal,
+    ) -> ShippingCostRule {
+        ShippingCostRule {
+            id: Uuid::new_v4(),
+            shipping_method_id: Uuid::new_v4(),
+            min_weight,
+            max_weight,
+            min_subtotal,
+            max_subtotal,
+            cost,
+        }
+    }
+
+    fn rule_unbounded() -> ShippingCostRule {
+        rule(0, None, dec!(0), None, dec!(1))
+    }
+
+    async fn insert_shipping_method(db: &StoreDb, name: &str) -> ShippingMethod {
+        let shipping_method = ShippingMethod {
+            id: Uuid::new_v4(),
+            name: format!("{name}-{}", Uuid::new_v4()),
+            description: "Lorem Ipsum".to_string(),
+        };
+        sqlx::query("INSERT INTO shipping_method (id, name, description) VALUES ($1, $2, $3)")
+            .bind(shipping_method.id)
+            .bind(&shipping_method.name)
+            .bind(&shipping_method.description)
+            .execute(db.pool())
+            .await
+            .expect("failed to insert shipping method");
+
+        shipping_method
+    }
+
+    async fn insert_rule(
+        db: &StoreDb,
+        shipping_method_id: &Uuid,
+        min_weight: i32,
+        max_weight: Option<i32>,
+        min_subtotal: Decimal,
+        max_subtotal: Option<Decimal>,
+        cost: Decimal,
+    ) {
+        sqlx::query(
+            "INSERT INTO shipping_cost_rule (id, shipping_method_id, min_weight, max_weight, min_subtotal, max_subtotal, cost) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(shipping_method_id)
+        .bind(min_weight)
+        .bind(max_weight)
+        .bind(min_subtotal)
+        .bind(max_subtotal)
+        .bind(cost)
+        .execute(db.pool())
+        .await
+        .expect("failed to insert shipping cost rule");
+    }
+}
@@ -0,0 +1,266 @@
+use chrono::{NaiveDateTime, Utc};
+use uuid::Uuid;
+
+use super::{now, DatabaseError, DatabaseResult, StoreDb};
+
+/// Named list of articles bookmarked by a customer
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct Wishlist {
+    pub id: Uuid,
+    pub customer_id: Uuid,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+}
+
+/// Article inside a wishlist.
+///
+/// Items are kept when the article gets archived, so that the customer can see it as unavailable
+#[derive(sqlx::FromRow, Debug, Clone, Eq, PartialEq)]
+pub struct WishlistItem {
+    pub wishlist_id: Uuid,
+    pub article_id: Uuid,
+    pub added_at: NaiveDateTime,
+}
+
+impl Wishlist {
+    /// Find the wishlist with `id` owned by the customer
+    pub async fn find_by_id_and_customer(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        id: &Uuid,
+        customer_id: &Uuid,
+    ) -> DatabaseResult<Option<Self>> {
+        sqlx::query_as(r#"SELECT * FROM wishlist WHERE id = $1 AND customer_id = $2"#)
+            .bind(id)
+            .bind(customer_id)
+            .fetch_optional(db)
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Find the wishlists of the customer, oldest first
+    pub async fn find_by_customer_id(
+        db: &StoreDb,
+        customer_id: &Uuid,
+    ) -> DatabaseResult<Vec<Self>> {
+        sqlx::query_as(r#"SELECT * FROM wishlist WHERE customer_id = $1 ORDER BY created_at"#)
+            .bind(customer_id)
+            .fetch_all(db.pool())
+            .await
+            .map_err(DatabaseError::from)
+    }
+
+    /// Insert a new wishlist for the customer.
+    /// Returns `None` if the customer already has a wishlist with the same name
+    pub async fn insert(
+        db: &StoreDb,
+        customer_id: &Uuid,
+        name: &str,
+    ) -> DatabaseResult<Option<Self>> {
+        let wishlist = Self {
+            id: Uuid::new_v4(),
+            customer_id: *customer_id,
+            name: name.to_string(),
+            created_at: now(),
+        };
+        debug!(
+            "inserting a new wishlist {} named {name} for {customer_id} to repository",
+            wishlist.id
+        );
+        let rows = sqlx::query(
+            r#"INSERT INTO wishlist (id, customer_id, name, created_at) VALUES ($1, $2, $3, $4)
+            ON CONFLICT (customer_id, name) DO NOTHING"#,
+        )
+        .bind(wishlist.id)
+        .bind(wishlist.customer_id)
+        .bind(&wishlist.name)
+        .bind(wishlist.created_at)
+        .execute(db.pool())
+        .await
+        .map_err(DatabaseError::from)?
+        .rows_affected();
+        match rows {
+            0 => Ok(None),
+            1 => Ok(Some(wishlist)),
+            _ => Err(DatabaseError::TooManyInserts),
+        }
+    }
+
+    /// Delete wishlist with its items
+    pub async fn delete(db: &StoreDb, id: &Uuid) -> DatabaseResult<()> {
+        debug!("deleting wishlist {id}");
+        sqlx::query("DELETE FROM wishlist WHERE id = $1")
+            .bind(id)
+            .execute(db.pool())
+            .await
+            .map_err(DatabaseError::from)?;
+
+        Ok(())
+    }
+}
+
+impl WishlistItem {
+    /// Find the items of a wishlist, oldest first
+    pub async fn find_by_wishlist_id(
+        db: &StoreDb,
+        wishlist_id: &Uuid,
+    ) -> DatabaseResult<Vec<Self>> {
+        sqlx::query_as(
+            r#"SELECT * FROM wishlist_item WHERE wishlist_id = $1 ORDER BY added_at, article_id"#,
+        )
+        .bind(wishlist_id)
+        .fetch_all(db.pool())
+        .await
+        .map_err(DatabaseError::from)
+    }
+
+    /// Add the article to the wishlist; adding an article already in the wishlist has no effect
+    pub async fn add(db: &StoreDb, wishlist_id: &Uuid, article_id: &Uuid) -> DatabaseResult<()> {
+        debug!("adding {article_id} to wishlist {wishlist_id}");
+        sqlx::query(
+            r#"INSERT INTO wishlist_item (wishlist_id, article_id, added_at) VALUES ($1, $2, $3)
+            ON CONFLICT (wishlist_id, article_id) DO NOTHING"#,
+        )
+        .bind(wishlist_id)
+        .bind(article_id)
+        .bind(Utc::now().naive_utc())
+        .execute(db.pool())
+        .await
+        .map_err(DatabaseError::from)?;
+
+        Ok(())
+    }
+
+    /// Remove the article from the wishlist; returns whether the article was in the wishlist
+    pub async fn remove(
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        wishlist_id: &Uuid,
+        article_id: &Uuid,
+    ) -> DatabaseResult<bool> {
+        debug!("removing {article_id} from wishlist {wishlist_id}");
+        let rows =
+            sqlx::query("DELETE FROM wishlist_item WHERE wishlist_id = $1 AND article_id = $2")
+                .bind(wishlist_id)
+                .bind(article_id)
+                .execute(db)
+                .await
+                .map_err(DatabaseError::from)?
+                .rows_affected();
+
+        Ok(rows == 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::{Article, Customer, TestDb};
+
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn should_insert_wishlists_with_unique_names() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let wishlist = Wishlist::insert(&db, &customer.id, "birthday")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(Wishlist::insert(&db, &customer.id, "birthday")
+            .await
+            .unwrap()
+            .is_none());
+        Wishlist::insert(&db, &customer.id, "christmas")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Wishlist::find_by_customer_id(&db, &customer.id)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|x| x.name)
+                .collect::<Vec<_>>(),
+            vec!["birthday".to_string(), "christmas".to_string()]
+        );
+        assert_eq!(
+            Wishlist::find_by_id_and_customer(db.pool(), &wishlist.id, &customer.id)
+                .await
+                .unwrap(),
+            Some(wishlist.clone())
+        );
+        assert!(
+            Wishlist::find_by_id_and_customer(db.pool(), &wishlist.id, &Uuid::new_v4())
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_keep_archived_articles_in_wishlist() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let customer = Customer::insert(&db, "customer@prima.it", "abcdef")
+            .await
+            .unwrap();
+        let article = insert_article(&db, "panzerotti").await;
+        let wishlist = Wishlist::insert(&db, &customer.id, "default")
+            .await
+            .unwrap()
+            .unwrap();
+        WishlistItem::add(&db, &wishlist.id, &article.id)
+            .await
+            .unwrap();
+        WishlistItem::add(&db, &wishlist.id, &article.id)
+            .await
+            .unwrap();
+        Article::archive(&db, &article.id).await.unwrap();
+
+        let items = WishlistItem::find_by_wishlist_id(&db, &wishlist.id)
+            .await
+            .unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].article_id, article.id);
+
+        assert!(WishlistItem::remove(db.pool(), &wishlist.id, &article.id)
+            .await
+            .unwrap());
+        assert!(!WishlistItem::remove(db.pool(), &wishlist.id, &article.id)
+            .await
+            .unwrap());
+    }
+
+    async fn insert_article(db: &StoreDb, name: &str) -> Article {
+        let article = Article {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: "Lorem Ipsum".to_string(),
+            unit_price: rust_decimal_macros::dec!(23.04),
+            weight: 0,
+            archived_at: None,
+        };
+        let rows = sqlx::query(
+            "INSERT INTO article (id, name, description, unit_price) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(article.id)
+        .bind(&article.name)
+        .bind(&article.description)
+        .bind(article.unit_price)
+        .execute(db.pool())
+        .await
+        .map_err(DatabaseError::from)
+        .unwrap()
+        .rows_affected();
+        if rows != 1 {
+            panic!("too many inserts");
+        }
+
+        article
+    }
+}
@@ -0,0 +1,70 @@
+//! # File sink
+//!
+//! Appends each event to a local file as a JSON line
+
+use std::path::{Path, PathBuf};
+
+use tokio::fs::OpenOptions;
+use tokio::io::AsyncWriteExt;
+
+use super::{EventEnvelope, EventResult, EventSink};
+
+#[derive(Debug)]
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    /// Instantiates a new `FileSink` which appends events to the file at `path`
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl EventSink for FileSink {
+    async fn deliver(&self, event: &EventEnvelope) -> EventResult<()> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(&line).await?;
+        file.sync_data().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn should_append_events_to_file() {
+        let path = std::env::temp_dir().join(format!("outbox-{}.jsonl", Uuid::new_v4()));
+        let sink = FileSink::new(&path);
+        let event = EventEnvelope {
+            id: Uuid::new_v4(),
+            event_type: "PaymentFailed".to_string(),
+            aggregate_id: Uuid::new_v4(),
+            occurred_at: "2023-03-28T10:00:00.000000Z".to_string(),
+            payload: serde_json::json!({ "order_id": "dummy" }),
+        };
+        sink.deliver(&event).await.unwrap();
+        sink.deliver(&event).await.unwrap();
+
+        let content = tokio::fs::read_to_string(&path).await.unwrap();
+        let _ = tokio::fs::remove_file(&path).await;
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], serde_json::to_string(&event).unwrap());
+    }
+}
@@ -0,0 +1,169 @@
+//! # Events
+//!
+//! Domain events published by the store through a transactional outbox. Events are written to the
+//! outbox in the same transaction which changes the store state, then the `OutboxRelay` delivers
+//! them to an `EventSink` with at-least-once semantics.
+
+mod file;
+mod relay;
+mod stdout;
+mod webhook;
+
+pub use file::FileSink;
+pub use relay::OutboxRelay;
+pub use stdout::StdoutSink;
+pub use webhook::WebhookSink;
+
+use crate::database::{DatabaseResult, OutboxEvent};
+
+use rust_decimal::Decimal;
+use serde_json::Value as JsonValue;
+use thiserror::Error;
+use uuid::Uuid;
+
+pub type EventResult<T> = Result<T, EventError>;
+
+#[derive(Debug, Error)]
+pub enum EventError {
+    #[error("serialization error: {0}")]
+    Serialization(serde_json::Error),
+    #[error("io error: {0}")]
+    Io(std::io::Error),
+    #[error("http error: {0}")]
+    Http(String),
+}
+
+impl From<serde_json::Error> for EventError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Serialization(value)
+    }
+}
+
+impl From<std::io::Error> for EventError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Domain event raised by the store
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum DomainEvent {
+    OrderSubmitted {
+        order_id: Uuid,
+        customer_id: Uuid,
+        articles: Vec<SubmittedArticle>,
+        shipping_method_id: Option<Uuid>,
+        shipping_cost: Decimal,
+        total: Decimal,
+    },
+    PaymentSucceeded {
+        order_id: Uuid,
+        transaction_id: String,
+    },
+    PaymentFailed {
+        order_id: Uuid,
+    },
+    CustomerSignedUp {
+        customer_id: Uuid,
+        email: String,
+    },
+}
+
+/// Article of a submitted order
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct SubmittedArticle {
+    pub article_id: Uuid,
+    pub quantity: i32,
+    pub unit_price: Decimal,
+}
+
+impl DomainEvent {
+    /// Name of the event type
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            Self::OrderSubmitted { .. } => "OrderSubmitted",
+            Self::PaymentSucceeded { .. } => "PaymentSucceeded",
+            Self::PaymentFailed { .. } => "PaymentFailed",
+            Self::CustomerSignedUp { .. } => "CustomerSignedUp",
+        }
+    }
+
+    /// Id of the entity the event refers to
+    pub fn aggregate_id(&self) -> Uuid {
+        match self {
+            Self::OrderSubmitted { order_id, .. }
+            | Self::PaymentSucceeded { order_id, .. }
+            | Self::PaymentFailed { order_id } => *order_id,
+            Self::CustomerSignedUp { customer_id, .. } => *customer_id,
+        }
+    }
+
+    /// Write the event to the outbox. Must be called with the transaction which changes the store state
+    pub async fn publish(
+        &self,
+        db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    ) -> DatabaseResult<OutboxEvent> {
+        let payload = serde_json::to_value(self).expect("domain events are always serializable");
+        OutboxEvent::insert(db, &self.aggregate_id(), self.event_type(), payload).await
+    }
+}
+
+/// Event as delivered to the sinks
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct EventEnvelope {
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub aggregate_id: Uuid,
+    pub occurred_at: String,
+    pub payload: JsonValue,
+}
+
+impl From<OutboxEvent> for EventEnvelope {
+    fn from(value: OutboxEvent) -> Self {
+        Self {
+            id: value.id,
+            event_type: value.event_type,
+            aggregate_id: value.aggregate_id,
+            occurred_at: value
+                .created_at
+                .format("%Y-%m-%dT%H:%M:%S%.6fZ")
+                .to_string(),
+            payload: value.payload,
+        }
+    }
+}
+
+/// An event sink delivers events to the downstream consumers
+#[tonic::async_trait]
+pub trait EventSink: std::fmt::Debug + Send + Sync {
+    /// Deliver event. The event is considered delivered only if `Ok` is returned
+    async fn deliver(&self, event: &EventEnvelope) -> EventResult<()>;
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_serialize_domain_event_payload() {
+        let order_id = Uuid::new_v4();
+        let event = DomainEvent::PaymentSucceeded {
+            order_id,
+            transaction_id: "dummy".to_string(),
+        };
+        assert_eq!(event.event_type(), "PaymentSucceeded");
+        assert_eq!(event.aggregate_id(), order_id);
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({
+                "order_id": order_id,
+                "transaction_id": "dummy",
+            })
+        );
+    }
+}
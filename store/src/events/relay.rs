@@ -0,0 +1,197 @@
+//! # Outbox relay
+//!
+//! Periodically delivers the pending outbox events to the configured sink. An event is marked as
+//! delivered only after the sink accepted it, so each event is delivered at least once. Pending
+//! events are claimed for a lease before being delivered, so several relays can run at once on
+//! different replicas without holding any lock while the sink is called.
+//!
+//! Events are delivered in order only within a batch: while an event is claimed by another relay,
+//! or waits to be retried after a failure, later events of the same aggregate may be delivered
+//! before it. Consumers must not rely on the delivery order across batches.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::{EventEnvelope, EventSink};
+use crate::database::{DatabaseResult, OutboxEvent, StoreDb};
+
+#[derive(Debug)]
+pub struct OutboxRelay {
+    database: StoreDb,
+    sink: Box<dyn EventSink>,
+    interval: Duration,
+    batch_size: i64,
+    lease: Duration,
+}
+
+impl OutboxRelay {
+    /// Instantiates a new `OutboxRelay` which relays at most `batch_size` events every `interval`,
+    /// claiming them for `lease`
+    pub fn new(
+        database: StoreDb,
+        sink: Box<dyn EventSink>,
+        interval: Duration,
+        batch_size: i64,
+        lease: Duration,
+    ) -> Self {
+        Self {
+            database,
+            sink,
+            interval,
+            batch_size,
+            lease,
+        }
+    }
+
+    /// Run relay forever
+    pub async fn run(self) {
+        let lease = match chrono::Duration::from_std(self.lease) {
+            Ok(lease) => lease,
+            Err(err) => {
+                error!("invalid outbox relay lease {:?}: {err}", self.lease);
+                return;
+            }
+        };
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            match self.relay_pending(lease).await {
+                Ok(0) => {}
+                Ok(delivered) => debug!("relayed {delivered} outbox events"),
+                Err(err) => error!("could not relay outbox events: {err}"),
+            }
+        }
+    }
+
+    /// Deliver pending events; returns the amount of delivered events.
+    ///
+    /// Delivery stops at the first failure, so that the events of the batch are delivered in order
+    async fn relay_pending(&self, lease: chrono::Duration) -> DatabaseResult<usize> {
+        let now = Utc::now().naive_utc();
+        let events =
+            OutboxEvent::claim_pending(self.database.pool(), now, now + lease, self.batch_size)
+                .await?;
+        let mut delivered = 0;
+        let mut failure = None;
+        for event in events.iter() {
+            match self.sink.deliver(&EventEnvelope::from(event.clone())).await {
+                Ok(()) => delivered += 1,
+                Err(err) => {
+                    warn!("could not deliver outbox event {}: {err}", event.id);
+                    failure = Some(err.to_string());
+                    break;
+                }
+            }
+        }
+        // the outcome is recorded once the sink has been called, in a short transaction
+        let (delivered_events, undelivered_events) = events.split_at(delivered);
+        let mut transaction = self.database.pool().begin().await?;
+        for event in delivered_events.iter() {
+            OutboxEvent::mark_delivered(&mut transaction, &event.id).await?;
+        }
+        if let (Some(error), Some(event)) = (failure, undelivered_events.first()) {
+            OutboxEvent::mark_failed(&mut transaction, &event.id, &error).await?;
+        }
+        // events after the failed one have not been attempted and are left to the next run
+        let skipped: Vec<Uuid> = undelivered_events.iter().skip(1).map(|x| x.id).collect();
+        OutboxEvent::release(&mut transaction, &skipped).await?;
+        transaction.commit().await?;
+
+        Ok(delivered)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::TestDb;
+    use crate::events::{DomainEvent, EventError, EventResult};
+
+    use pretty_assertions::assert_eq;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use std::sync::Mutex;
+
+    /// Sink accepting a given amount of events, then failing
+    #[derive(Debug)]
+    struct FlakySink {
+        accepted: Mutex<usize>,
+    }
+
+    #[tonic::async_trait]
+    impl EventSink for FlakySink {
+        async fn deliver(&self, _event: &EventEnvelope) -> EventResult<()> {
+            let mut accepted = self.accepted.lock().unwrap();
+            if *accepted == 0 {
+                return Err(EventError::Http("service unavailable".to_string()));
+            }
+            *accepted -= 1;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn should_record_delivery_and_release_undelivered_events() {
+        let test_db = TestDb::create().await;
+        let db = test_db.database();
+
+        let order_id = Uuid::new_v4();
+        let mut events = Vec::new();
+        for event in [
+            DomainEvent::OrderSubmitted {
+                order_id,
+                customer_id: Uuid::new_v4(),
+                articles: vec![],
+                shipping_method_id: None,
+                shipping_cost: Decimal::ZERO,
+                total: dec!(10),
+            },
+            DomainEvent::PaymentFailed { order_id },
+            DomainEvent::PaymentSucceeded {
+                order_id,
+                transaction_id: "txn-1".to_string(),
+            },
+        ] {
+            events.push(event.publish(db.pool()).await.unwrap());
+        }
+        let relay = OutboxRelay::new(
+            db.clone(),
+            Box::new(FlakySink {
+                accepted: Mutex::new(1),
+            }),
+            Duration::from_secs(1),
+            64,
+            Duration::from_secs(60),
+        );
+        assert_eq!(
+            relay
+                .relay_pending(chrono::Duration::seconds(60))
+                .await
+                .unwrap(),
+            1
+        );
+
+        let mut relayed = Vec::new();
+        for event in events.iter() {
+            relayed.push(
+                OutboxEvent::find_by_id(&db, &event.id)
+                    .await
+                    .unwrap()
+                    .unwrap(),
+            );
+        }
+        assert!(relayed[0].delivered_at.is_some());
+        assert_eq!(relayed[1].attempts, 1);
+        assert_eq!(
+            relayed[1].last_error.as_deref(),
+            Some("http error: service unavailable")
+        );
+        assert!(relayed[1].delivered_at.is_none());
+        assert_eq!(relayed[2].attempts, 0);
+        // no event is left claimed, so the next run retries them right away
+        assert!(relayed.iter().all(|x| x.locked_until.is_none()));
+    }
+}
@@ -0,0 +1,16 @@
+//! # Stdout sink
+//!
+//! Writes each event to the standard output as a JSON line
+
+use super::{EventEnvelope, EventResult, EventSink};
+
+#[derive(Debug, Default)]
+pub struct StdoutSink;
+
+#[tonic::async_trait]
+impl EventSink for StdoutSink {
+    async fn deliver(&self, event: &EventEnvelope) -> EventResult<()> {
+        println!("{}", serde_json::to_string(event)?);
+        Ok(())
+    }
+}
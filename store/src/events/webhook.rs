@@ -0,0 +1,52 @@
+//! # Webhook sink
+//!
+//! Posts each event as JSON to an HTTP endpoint. Any non-2xx response is considered a failure
+
+use hyper::client::HttpConnector;
+use hyper::{header, Body, Client, Method, Request, Uri};
+
+use super::{EventEnvelope, EventError, EventResult, EventSink};
+
+#[derive(Debug)]
+pub struct WebhookSink {
+    url: Uri,
+    client: Client<HttpConnector>,
+}
+
+impl WebhookSink {
+    /// Instantiates a new `WebhookSink` which posts events to `url`
+    pub fn new(url: &str) -> EventResult<Self> {
+        let url = url
+            .parse()
+            .map_err(|e: hyper::http::uri::InvalidUri| EventError::Http(e.to_string()))?;
+        Ok(Self {
+            url,
+            client: Client::new(),
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl EventSink for WebhookSink {
+    async fn deliver(&self, event: &EventEnvelope) -> EventResult<()> {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(self.url.clone())
+            .header(header::CONTENT_TYPE, "application/json")
+            .header("X-Event-Id", event.id.to_string())
+            .body(Body::from(serde_json::to_vec(event)?))
+            .map_err(|e| EventError::Http(e.to_string()))?;
+        let response = self
+            .client
+            .request(request)
+            .await
+            .map_err(|e| EventError::Http(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(EventError::Http(format!(
+                "webhook responded with {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
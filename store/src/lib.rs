@@ -0,0 +1,58 @@
+//! # Store
+//!
+//! Store gRPC service. The library lets other crates run the service in-process, e.g. in their
+//! end-to-end tests, through `spawn_server`
+
+#[macro_use]
+extern crate serde;
+#[macro_use]
+extern crate tracing;
+
+mod config;
+mod database;
+mod events;
+mod payment;
+mod recommendation;
+mod repository;
+mod seed;
+mod service;
+
+pub use config::Config;
+#[cfg(feature = "test-db")]
+pub use database::TestDb;
+
+use service::StoreService;
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+
+/// Run the store service on `config.server_url` until it terminates
+pub async fn run(config: &Config) -> anyhow::Result<()> {
+    let service = StoreService::configure(config).await?;
+    info!("store service is ready");
+    service.run().await?;
+
+    Ok(())
+}
+
+/// Start the store service in background on `config.server_url`, which may have port 0;
+/// returns the address it listens on
+pub async fn spawn_server(config: &Config) -> anyhow::Result<SocketAddr> {
+    let service = StoreService::configure(config).await?;
+    let listener = TcpListener::bind(&config.server_url).await?;
+    let address = listener.local_addr()?;
+    tokio::spawn(async move {
+        if let Err(err) = service.run_with_listener(listener).await {
+            error!("store service terminated: {err}");
+        }
+    });
+
+    Ok(address)
+}
+
+/// Seed the database at `database_url`; `args` are the options of the `seed` command
+pub async fn seed(
+    database_url: &str,
+    args: impl IntoIterator<Item = String>,
+) -> anyhow::Result<()> {
+    seed::run(database_url, seed::SeedOptions::parse(args)?).await
+}
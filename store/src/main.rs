@@ -1,28 +1,29 @@
 const APP_AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
 const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-#[macro_use]
-extern crate serde;
 #[macro_use]
 extern crate tracing;
 
-mod config;
-mod database;
-mod service;
+use store::Config;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
     info!("store v{} - developed by {}", APP_VERSION, APP_AUTHORS);
 
-    let config = config::Config::try_from_env()?;
+    let mut args = std::env::args().skip(1);
+    let command = args.next();
+    let config = Config::try_from_env()?;
     info!("configuration parsed");
 
-    info!("initializing store service");
-    let service =
-        service::StoreService::configure(&config.server_url, &config.database_url).await?;
-    info!("store service is ready");
-    service.run().await?;
+    match command.as_deref() {
+        None => {
+            info!("initializing store service");
+            store::run(&config).await?;
+        }
+        Some("seed") => store::seed(&config.database_url, args).await?,
+        Some(command) => anyhow::bail!("unknown command `{command}`; usage: store [seed]"),
+    }
 
     Ok(())
 }
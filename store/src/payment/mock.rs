@@ -0,0 +1,164 @@
+//! # Mock payment gateway
+//!
+//! Local payment gateway which doesn't charge anything. The outcome only depends on the card number:
+//!
+//! - `4000000000000002` is always declined
+//! - card numbers which don't pass the Luhn check are declined
+//! - any other card number succeeds
+
+use std::time::Duration;
+
+use super::{
+    PaymentError, PaymentGateway, PaymentNotifier, PaymentOutcome, PaymentRequest, PaymentResult,
+};
+
+/// Card number which is always declined by the mock gateway
+pub const DECLINED_CARD_NUMBER: &str = "4000000000000002";
+
+#[derive(Debug)]
+pub struct MockPaymentGateway {
+    callback_delay: Duration,
+    notifier: PaymentNotifier,
+}
+
+impl MockPaymentGateway {
+    /// Instantiates a new `MockPaymentGateway` which reports outcomes after `callback_delay`
+    pub fn new(callback_delay: Duration, notifier: PaymentNotifier) -> Self {
+        Self {
+            callback_delay,
+            notifier,
+        }
+    }
+
+    /// Get the outcome for the provided payment
+    fn outcome(payment: &PaymentRequest) -> PaymentOutcome {
+        let card_number: String = payment
+            .card_number
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        if card_number == DECLINED_CARD_NUMBER || !Self::luhn_check(&card_number) {
+            PaymentOutcome::Failed {
+                order_id: payment.order_id,
+            }
+        } else {
+            PaymentOutcome::Succeeded {
+                order_id: payment.order_id,
+                transaction_id: format!("mock_{}", payment.order_id.simple()),
+            }
+        }
+    }
+
+    /// Validate card number with the Luhn algorithm
+    fn luhn_check(card_number: &str) -> bool {
+        if card_number.len() < 12 || !card_number.chars().all(|c| c.is_ascii_digit()) {
+            return false;
+        }
+        let sum: u32 = card_number
+            .chars()
+            .rev()
+            .filter_map(|c| c.to_digit(10))
+            .enumerate()
+            .map(|(i, digit)| match (i % 2 == 1, digit * 2) {
+                (true, doubled) if doubled > 9 => doubled - 9,
+                (true, doubled) => doubled,
+                (false, _) => digit,
+            })
+            .sum();
+        sum.is_multiple_of(10)
+    }
+}
+
+#[tonic::async_trait]
+impl PaymentGateway for MockPaymentGateway {
+    async fn initiate(&self, payment: PaymentRequest) -> PaymentResult<()> {
+        if self.notifier.is_closed() {
+            return Err(PaymentError::Unavailable(
+                "payment outcome receiver has been dropped".to_string(),
+            ));
+        }
+        debug!(
+            "mock payment of {} initiated for order {}",
+            payment.amount, payment.order_id
+        );
+        let outcome = Self::outcome(&payment);
+        let notifier = self.notifier.clone();
+        let callback_delay = self.callback_delay;
+        tokio::spawn(async move {
+            tokio::time::sleep(callback_delay).await;
+            debug!("reporting mock payment outcome {:?}", outcome);
+            if notifier.send(outcome).is_err() {
+                error!("could not report mock payment outcome: receiver has been dropped");
+            }
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    #[test]
+    fn should_validate_card_number() {
+        assert!(MockPaymentGateway::luhn_check("4242424242424242"));
+        assert!(MockPaymentGateway::luhn_check("5555555555554444"));
+        assert!(MockPaymentGateway::luhn_check(DECLINED_CARD_NUMBER));
+        assert!(!MockPaymentGateway::luhn_check("4242424242424241"));
+        assert!(!MockPaymentGateway::luhn_check("4242"));
+        assert!(!MockPaymentGateway::luhn_check("4242abcd42424242"));
+    }
+
+    #[test]
+    fn should_get_deterministic_outcome() {
+        let order_id = Uuid::new_v4();
+        assert_eq!(
+            MockPaymentGateway::outcome(&payment(order_id, "4242 4242 4242 4242")),
+            PaymentOutcome::Succeeded {
+                order_id,
+                transaction_id: format!("mock_{}", order_id.simple())
+            }
+        );
+        assert_eq!(
+            MockPaymentGateway::outcome(&payment(order_id, DECLINED_CARD_NUMBER)),
+            PaymentOutcome::Failed { order_id }
+        );
+        assert_eq!(
+            MockPaymentGateway::outcome(&payment(order_id, "1234567812345678")),
+            PaymentOutcome::Failed { order_id }
+        );
+    }
+
+    #[tokio::test]
+    async fn should_report_outcome_after_delay() {
+        let (notifier, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let gateway = MockPaymentGateway::new(Duration::from_millis(50), notifier);
+        let order_id = Uuid::new_v4();
+        assert!(gateway
+            .initiate(payment(order_id, "4242424242424242"))
+            .await
+            .is_ok());
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(
+            receiver.recv().await.unwrap(),
+            PaymentOutcome::Succeeded {
+                order_id,
+                transaction_id: format!("mock_{}", order_id.simple())
+            }
+        );
+    }
+
+    fn payment(order_id: Uuid, card_number: &str) -> PaymentRequest {
+        PaymentRequest {
+            order_id,
+            amount: Decimal::ONE,
+            card_number: card_number.to_string(),
+        }
+    }
+}
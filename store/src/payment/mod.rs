@@ -0,0 +1,51 @@
+//! # Payment
+//!
+//! Payment gateway abstraction. A gateway initiates the payment for an order and reports its
+//! outcome asynchronously, as a real provider would do through a callback.
+
+mod mock;
+
+pub use mock::MockPaymentGateway;
+
+use rust_decimal::Decimal;
+use thiserror::Error;
+use tokio::sync::mpsc::UnboundedSender;
+use uuid::Uuid;
+
+pub type PaymentResult<T> = Result<T, PaymentError>;
+
+/// Channel used by gateways to report payment outcomes
+pub type PaymentNotifier = UnboundedSender<PaymentOutcome>;
+
+#[derive(Debug, Error)]
+pub enum PaymentError {
+    #[error("payment gateway is unavailable: {0}")]
+    Unavailable(String),
+}
+
+/// Payment to initiate for an order
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PaymentRequest {
+    pub order_id: Uuid,
+    pub amount: Decimal,
+    pub card_number: String,
+}
+
+/// Outcome of a payment, reported by the gateway once available
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PaymentOutcome {
+    Succeeded {
+        order_id: Uuid,
+        transaction_id: String,
+    },
+    Failed {
+        order_id: Uuid,
+    },
+}
+
+/// A payment gateway initiates payments
+#[tonic::async_trait]
+pub trait PaymentGateway: std::fmt::Debug + Send + Sync {
+    /// Initiate payment for an order. The outcome is reported later through the `PaymentNotifier`
+    async fn initiate(&self, payment: PaymentRequest) -> PaymentResult<()>;
+}
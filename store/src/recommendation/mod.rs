@@ -0,0 +1,34 @@
+//! # Recommendation
+//!
+//! "Frequently bought together" recommendations. Article co-occurrences in the paid orders are
+//! periodically rebuilt into the `article_co_purchase` table, which is then queried to recommend
+//! the articles related to an article or to the content of a cart.
+
+use std::time::Duration;
+
+use crate::database::{ArticleCoPurchase, StoreDb};
+
+#[derive(Debug)]
+pub struct RecommendationBuilder {
+    database: StoreDb,
+    interval: Duration,
+}
+
+impl RecommendationBuilder {
+    /// Instantiates a new `RecommendationBuilder` which rebuilds co-purchases every `interval`
+    pub fn new(database: StoreDb, interval: Duration) -> Self {
+        Self { database, interval }
+    }
+
+    /// Run builder forever; the first rebuild happens immediately
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            match ArticleCoPurchase::rebuild(&self.database).await {
+                Ok(pairs) => info!("rebuilt recommendations: {pairs} articles pairs"),
+                Err(err) => error!("could not rebuild recommendations: {err}"),
+            }
+        }
+    }
+}
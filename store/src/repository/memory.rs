@@ -0,0 +1,1457 @@
+//! # In-memory repository
+//!
+//! Repository keeping its data in memory, to test the store service without a database
+
+use super::{
+    ArticleRepository, ArticleTransaction, CartRepository, CartTransaction, CustomerRepository,
+    InvoiceRepository, InvoiceTransaction, OrderArticleRepository, OrderRepository,
+    OrderTransaction, ReportRepository, RepositoryTransaction, ReturnTransaction, ReviewRepository,
+    ReviewTransaction, ShippingRepository, TransactionalRepository, WishlistRepository,
+    WishlistTransaction,
+};
+use crate::database::{
+    Article, ArticleCoPurchase, ArticleRating, ArticleReview, Cart, CartItem, Customer,
+    CustomerOrder, DatabaseError, DatabaseResult, Invoice, InvoiceLine, InvoiceSeller,
+    OrderArticle, OrderEvent, OrderEventActor, OrderEventKind, OrderFilter, OrderReturn,
+    OrderReturnArticle, OrderStats, OrderStatus, Refund, ReportGranularity, ReturnStatus,
+    RevenueBucket, ReviewStatus, Shipment, ShippingCostRule, ShippingMethod, TopArticle, Wishlist,
+    WishlistItem,
+};
+use crate::events::DomainEvent;
+
+use chrono::{Datelike, Duration, NaiveDateTime, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+use uuid::Uuid;
+
+/// Statuses of the orders which have been paid, as in the sales reports
+const PAID_STATUSES: [OrderStatus; 5] = [
+    OrderStatus::Preparing,
+    OrderStatus::Shipped,
+    OrderStatus::ReturnRequested,
+    OrderStatus::Refunded,
+    OrderStatus::PartiallyRefunded,
+];
+
+#[derive(Debug, Default, Clone)]
+struct State {
+    customers: Vec<Customer>,
+    articles: Vec<Article>,
+    ratings: Vec<ArticleRating>,
+    reviews: Vec<ArticleReview>,
+    co_purchases: Vec<ArticleCoPurchase>,
+    orders: Vec<CustomerOrder>,
+    order_articles: Vec<OrderArticle>,
+    order_events: Vec<OrderEvent>,
+    shipments: Vec<Shipment>,
+    shipping_methods: Vec<ShippingMethod>,
+    shipping_cost_rules: Vec<ShippingCostRule>,
+    carts: Vec<Cart>,
+    cart_items: Vec<CartItem>,
+    wishlists: Vec<Wishlist>,
+    wishlist_items: Vec<WishlistItem>,
+    returns: Vec<OrderReturn>,
+    return_articles: Vec<OrderReturnArticle>,
+    refunds: Vec<Refund>,
+    invoices: Vec<Invoice>,
+    invoice_lines: Vec<InvoiceLine>,
+    events: Vec<DomainEvent>,
+}
+
+impl State {
+    fn get_or_create_customer_cart(&mut self, customer_id: &Uuid) -> Cart {
+        if let Some(cart) = self
+            .carts
+            .iter()
+            .find(|x| x.customer_id.as_ref() == Some(customer_id))
+        {
+            return cart.clone();
+        }
+        let now = Utc::now().naive_utc();
+        let cart = Cart {
+            id: Uuid::new_v4(),
+            customer_id: Some(*customer_id),
+            created_at: now,
+            updated_at: now,
+        };
+        self.carts.push(cart.clone());
+
+        cart
+    }
+
+    fn find_customer_wishlist(&self, id: &Uuid, customer_id: &Uuid) -> Option<Wishlist> {
+        self.wishlists
+            .iter()
+            .find(|x| &x.id == id && &x.customer_id == customer_id)
+            .cloned()
+    }
+
+    fn remove_wishlist_item(&mut self, wishlist_id: &Uuid, article_id: &Uuid) -> bool {
+        let items = self.wishlist_items.len();
+        self.wishlist_items
+            .retain(|x| !(&x.wishlist_id == wishlist_id && &x.article_id == article_id));
+        self.wishlist_items.len() < items
+    }
+
+    fn add_cart_item(&mut self, cart_id: &Uuid, article_id: &Uuid, quantity: i32) {
+        match self
+            .cart_items
+            .iter_mut()
+            .find(|x| &x.cart_id == cart_id && &x.article_id == article_id)
+        {
+            Some(item) => item.quantity += quantity,
+            None => self.cart_items.push(CartItem {
+                cart_id: *cart_id,
+                article_id: *article_id,
+                quantity,
+            }),
+        }
+    }
+
+    fn touch_cart(&mut self, cart_id: &Uuid) {
+        if let Some(cart) = self.carts.iter_mut().find(|x| &x.id == cart_id) {
+            cart.updated_at = Utc::now().naive_utc();
+        }
+    }
+
+    /// Apply `update` to the order and record the change made by `actor` in the order history
+    fn update_order(
+        &mut self,
+        order_id: &Uuid,
+        kind: OrderEventKind,
+        actor: OrderEventActor,
+        update: impl FnOnce(&mut CustomerOrder),
+    ) -> DatabaseResult<()> {
+        let order = self
+            .orders
+            .iter_mut()
+            .find(|x| &x.id == order_id)
+            .ok_or(DatabaseError::TooManyInserts)?;
+        update(order);
+        let event = OrderEvent {
+            id: Uuid::new_v4(),
+            order_id: order.id,
+            kind,
+            actor,
+            status: order.status,
+            transaction_id: order.transaction_id.clone(),
+            created_at: Utc::now().naive_utc(),
+        };
+        self.order_events.push(event);
+
+        Ok(())
+    }
+
+    /// Paid orders created in `[from, to)`
+    fn paid_orders(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> impl Iterator<Item = &CustomerOrder> {
+        self.orders.iter().filter(move |x| {
+            x.created_at >= from && x.created_at < to && PAID_STATUSES.contains(&x.status)
+        })
+    }
+
+    /// Articles and shipping cost of the order
+    fn order_total(&self, order: &CustomerOrder) -> Decimal {
+        order.shipping_cost
+            + self
+                .order_articles
+                .iter()
+                .filter(|x| x.order_id == order.id)
+                .map(|x| Decimal::from(x.quantity) * x.unit_price)
+                .sum::<Decimal>()
+    }
+}
+
+/// Repository keeping its data in memory; clones share the same data
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryRepository {
+    state: Arc<Mutex<State>>,
+}
+
+/// Transaction working on a copy of the repository data, which replaces the repository data once
+/// committed. Transactions are not isolated from each other: the last one to commit wins
+#[derive(Debug)]
+pub struct InMemoryTransaction {
+    state: State,
+    repository: Arc<Mutex<State>>,
+}
+
+impl InMemoryRepository {
+    /// Add an article to the repository
+    pub fn add_article(&self, article: Article) {
+        self.state().articles.push(article);
+    }
+
+    /// Add the rating of an article to the repository
+    pub fn add_rating(&self, rating: ArticleRating) {
+        self.state().ratings.push(rating);
+    }
+
+    /// Add an order and its articles to the repository
+    pub fn add_order(&self, order: CustomerOrder, articles: Vec<OrderArticle>) {
+        let mut state = self.state();
+        state.orders.push(order);
+        state.order_articles.extend(articles);
+    }
+
+    /// Add a shipping method and its cost rules to the repository
+    pub fn add_shipping_method(&self, method: ShippingMethod, rules: Vec<ShippingCostRule>) {
+        let mut state = self.state();
+        state.shipping_methods.push(method);
+        state.shipping_cost_rules.extend(rules);
+    }
+
+    /// Add how many orders bought `related_article_id` together with `article_id`
+    pub fn add_co_purchase(&self, co_purchase: ArticleCoPurchase) {
+        self.state().co_purchases.push(co_purchase);
+    }
+
+    /// Add `quantity` items of the article to the cart of the customer
+    pub fn add_cart_item(&self, customer_id: &Uuid, article_id: &Uuid, quantity: i32) -> Cart {
+        let mut state = self.state();
+        let cart = state.get_or_create_customer_cart(customer_id);
+        state.add_cart_item(&cart.id, article_id, quantity);
+
+        cart
+    }
+
+    /// Events published by the repository, oldest first
+    pub fn published_events(&self) -> Vec<DomainEvent> {
+        self.state().events.clone()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .expect("in-memory repository lock is poisoned")
+    }
+
+    /// Whether the article is not archived and its name ends with `name`, mimicking `LIKE '%name'`
+    fn article_matches(article: &Article, name: Option<&str>) -> bool {
+        !article.is_archived()
+            && name
+                .map(|name| article.name.ends_with(name))
+                .unwrap_or(true)
+    }
+
+    /// Whether the order matches the status and creation date of `filter`
+    fn order_matches(order: &CustomerOrder, filter: &OrderFilter) -> bool {
+        (filter.statuses.is_empty() || filter.statuses.contains(&order.status))
+            && filter
+                .created_from
+                .map(|from| order.created_at >= from)
+                .unwrap_or(true)
+            && filter
+                .created_to
+                .map(|to| order.created_at < to)
+                .unwrap_or(true)
+    }
+
+    fn page<T>(items: impl Iterator<Item = T>, limit: i64) -> Vec<T> {
+        items.take(limit.max(0) as usize).collect()
+    }
+
+    /// Start of the period `date` belongs to, mimicking `date_trunc`
+    fn period_start(granularity: ReportGranularity, date: NaiveDateTime) -> NaiveDateTime {
+        let day = date.date();
+        let start = match granularity {
+            ReportGranularity::Day => day,
+            ReportGranularity::Week => {
+                day - Duration::days(day.weekday().num_days_from_monday() as i64)
+            }
+            ReportGranularity::Month => day.with_day(1).expect("first day of month is valid"),
+        };
+        start.and_hms_opt(0, 0, 0).expect("midnight is valid")
+    }
+
+    /// Revenue of the period `date` belongs to; the period is added to `periods` if missing
+    fn revenue_period(
+        periods: &mut BTreeMap<NaiveDateTime, RevenueBucket>,
+        granularity: ReportGranularity,
+        date: NaiveDateTime,
+    ) -> &mut RevenueBucket {
+        let period_start = Self::period_start(granularity, date);
+        periods.entry(period_start).or_insert(RevenueBucket {
+            period_start,
+            paid_orders: 0,
+            revenue: Decimal::ZERO,
+            refunds: Decimal::ZERO,
+        })
+    }
+
+    /// Round to cents as `ROUND(x, 2)` does
+    fn round_cents(amount: Decimal) -> Decimal {
+        amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+    }
+}
+
+#[tonic::async_trait]
+impl CustomerRepository for InMemoryRepository {
+    async fn find_customer_by_email(&self, email: &str) -> DatabaseResult<Option<Customer>> {
+        Ok(self
+            .state()
+            .customers
+            .iter()
+            .find(|x| x.email == email)
+            .cloned())
+    }
+
+    async fn find_customer_by_credentials(
+        &self,
+        email: &str,
+        password: &str,
+    ) -> DatabaseResult<Option<Customer>> {
+        Ok(self
+            .state()
+            .customers
+            .iter()
+            .find(|x| x.email == email && x.password == password)
+            .cloned())
+    }
+
+    async fn insert_customer(&self, email: &str, password: &str) -> DatabaseResult<Customer> {
+        let mut state = self.state();
+        // emails are unique, as in the customer table
+        if state.customers.iter().any(|x| x.email == email) {
+            return Err(DatabaseError::TooManyInserts);
+        }
+        let customer = Customer {
+            id: Uuid::new_v4(),
+            email: email.to_string(),
+            password: password.to_string(),
+            created_at: Utc::now().naive_utc(),
+        };
+        state.customers.push(customer.clone());
+        state.events.push(DomainEvent::CustomerSignedUp {
+            customer_id: customer.id,
+            email: customer.email.clone(),
+        });
+
+        Ok(customer)
+    }
+}
+
+#[tonic::async_trait]
+impl ArticleRepository for InMemoryRepository {
+    async fn find_article_by_id(&self, id: &Uuid) -> DatabaseResult<Option<Article>> {
+        Ok(self.state().articles.iter().find(|x| &x.id == id).cloned())
+    }
+
+    async fn find_articles(
+        &self,
+        name: Option<&str>,
+        after: Option<(String, Uuid)>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<Article>> {
+        let mut articles: Vec<Article> = self
+            .state()
+            .articles
+            .iter()
+            .filter(|x| Self::article_matches(x, name))
+            .filter(|x| {
+                after
+                    .as_ref()
+                    .map(|(after_name, after_id)| (&x.name, x.id) > (after_name, *after_id))
+                    .unwrap_or(true)
+            })
+            .cloned()
+            .collect();
+        articles.sort_by(|a, b| (&a.name, a.id).cmp(&(&b.name, b.id)));
+        Ok(Self::page(articles.into_iter(), limit))
+    }
+
+    async fn count_articles(&self, name: Option<&str>) -> DatabaseResult<i64> {
+        Ok(self
+            .state()
+            .articles
+            .iter()
+            .filter(|x| Self::article_matches(x, name))
+            .count() as i64)
+    }
+
+    async fn find_articles_after(
+        &self,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<Article>> {
+        let mut articles: Vec<Article> = self
+            .state()
+            .articles
+            .iter()
+            .filter(|x| !x.is_archived() && after.map(|after| x.id > after).unwrap_or(true))
+            .cloned()
+            .collect();
+        articles.sort_by_key(|x| x.id);
+        Ok(Self::page(articles.into_iter(), limit))
+    }
+
+    async fn find_article_rating(
+        &self,
+        article_id: &Uuid,
+    ) -> DatabaseResult<Option<ArticleRating>> {
+        Ok(self
+            .state()
+            .ratings
+            .iter()
+            .find(|x| &x.article_id == article_id)
+            .cloned())
+    }
+
+    async fn find_related_articles(
+        &self,
+        article_ids: &[Uuid],
+        limit: i64,
+    ) -> DatabaseResult<Vec<Article>> {
+        let state = self.state();
+        let mut scores: HashMap<Uuid, i64> = HashMap::new();
+        for co_purchase in state.co_purchases.iter().filter(|x| {
+            article_ids.contains(&x.article_id) && !article_ids.contains(&x.related_article_id)
+        }) {
+            *scores.entry(co_purchase.related_article_id).or_default() += co_purchase.score as i64;
+        }
+        let mut related: Vec<(i64, Article)> = state
+            .articles
+            .iter()
+            .filter(|x| !x.is_archived())
+            .filter_map(|x| scores.get(&x.id).map(|score| (*score, x.clone())))
+            .collect();
+        related.sort_by_key(|(score, article)| (std::cmp::Reverse(*score), article.id));
+        Ok(Self::page(related.into_iter().map(|(_, x)| x), limit))
+    }
+}
+
+#[tonic::async_trait]
+impl ReviewRepository for InMemoryRepository {
+    async fn find_approved_reviews(
+        &self,
+        article_id: &Uuid,
+        after: Option<(NaiveDateTime, Uuid)>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<ArticleReview>> {
+        let mut reviews: Vec<ArticleReview> = self
+            .state()
+            .reviews
+            .iter()
+            .filter(|x| {
+                &x.article_id == article_id
+                    && x.status == ReviewStatus::Approved
+                    && after
+                        .map(|after| (x.created_at, x.id) < after)
+                        .unwrap_or(true)
+            })
+            .cloned()
+            .collect();
+        reviews.sort_by_key(|x| std::cmp::Reverse((x.created_at, x.id)));
+        Ok(Self::page(reviews.into_iter(), limit))
+    }
+
+    async fn is_verified_purchase(
+        &self,
+        customer_id: &Uuid,
+        article_id: &Uuid,
+    ) -> DatabaseResult<bool> {
+        let state = self.state();
+        Ok(state.order_articles.iter().any(|x| {
+            &x.article_id == article_id
+                && state.orders.iter().any(|order| {
+                    order.id == x.order_id
+                        && &order.customer_id == customer_id
+                        && order.status == OrderStatus::Shipped
+                })
+        }))
+    }
+
+    async fn insert_review(
+        &self,
+        article_id: &Uuid,
+        customer_id: &Uuid,
+        rating: i16,
+        title: &str,
+        body: &str,
+    ) -> DatabaseResult<Option<ArticleReview>> {
+        let mut state = self.state();
+        // a customer reviews an article once, as in the article review table
+        if state
+            .reviews
+            .iter()
+            .any(|x| &x.article_id == article_id && &x.customer_id == customer_id)
+        {
+            return Ok(None);
+        }
+        let review = ArticleReview {
+            id: Uuid::new_v4(),
+            article_id: *article_id,
+            customer_id: *customer_id,
+            rating,
+            title: title.to_string(),
+            body: body.to_string(),
+            status: ReviewStatus::Pending,
+            created_at: Utc::now().naive_utc(),
+            moderated_at: None,
+        };
+        state.reviews.push(review.clone());
+
+        Ok(Some(review))
+    }
+}
+
+#[tonic::async_trait]
+impl OrderRepository for InMemoryRepository {
+    async fn find_order_by_id(&self, id: &Uuid) -> DatabaseResult<Option<CustomerOrder>> {
+        Ok(self.state().orders.iter().find(|x| &x.id == id).cloned())
+    }
+
+    async fn find_orders_by_customer(
+        &self,
+        customer_id: &Uuid,
+        filter: &OrderFilter,
+        after: Option<(NaiveDateTime, Uuid)>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<CustomerOrder>> {
+        let mut orders: Vec<CustomerOrder> = self
+            .state()
+            .orders
+            .iter()
+            .filter(|x| {
+                &x.customer_id == customer_id
+                    && Self::order_matches(x, filter)
+                    && after
+                        .map(|after| (x.created_at, x.id) < after)
+                        .unwrap_or(true)
+            })
+            .cloned()
+            .collect();
+        orders.sort_by_key(|x| std::cmp::Reverse((x.created_at, x.id)));
+        Ok(Self::page(orders.into_iter(), limit))
+    }
+
+    async fn count_orders_by_customer(
+        &self,
+        customer_id: &Uuid,
+        filter: &OrderFilter,
+    ) -> DatabaseResult<i64> {
+        Ok(self
+            .state()
+            .orders
+            .iter()
+            .filter(|x| &x.customer_id == customer_id && Self::order_matches(x, filter))
+            .count() as i64)
+    }
+
+    async fn find_orders_created_between(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        after: Option<(NaiveDateTime, Uuid)>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<CustomerOrder>> {
+        let after = after.unwrap_or((from, Uuid::nil()));
+        let mut orders: Vec<CustomerOrder> = self
+            .state()
+            .orders
+            .iter()
+            .filter(|x| x.created_at >= from && x.created_at < to && (x.created_at, x.id) > after)
+            .cloned()
+            .collect();
+        orders.sort_by_key(|x| (x.created_at, x.id));
+        Ok(Self::page(orders.into_iter(), limit))
+    }
+
+    async fn find_order_shipment(&self, order_id: &Uuid) -> DatabaseResult<Option<Shipment>> {
+        Ok(self
+            .state()
+            .shipments
+            .iter()
+            .find(|x| &x.order_id == order_id)
+            .cloned())
+    }
+
+    async fn find_order_events(&self, order_id: &Uuid) -> DatabaseResult<Vec<OrderEvent>> {
+        let mut events: Vec<OrderEvent> = self
+            .state()
+            .order_events
+            .iter()
+            .filter(|x| &x.order_id == order_id)
+            .cloned()
+            .collect();
+        events.sort_by_key(|x| x.created_at);
+        Ok(events)
+    }
+
+    async fn expire_orders_created_before(
+        &self,
+        created_before: NaiveDateTime,
+        limit: i64,
+    ) -> DatabaseResult<Vec<CustomerOrder>> {
+        let mut state = self.state();
+        let mut expired: Vec<CustomerOrder> = state
+            .orders
+            .iter()
+            .filter(|x| x.status == OrderStatus::Created && x.created_at < created_before)
+            .cloned()
+            .collect();
+        expired.sort_by_key(|x| x.created_at);
+        let mut expired = Self::page(expired.into_iter(), limit);
+        for order in expired.iter_mut() {
+            state.update_order(
+                &order.id,
+                OrderEventKind::StatusChanged,
+                OrderEventActor::System,
+                |x| x.status = OrderStatus::Expired,
+            )?;
+            order.status = OrderStatus::Expired;
+        }
+
+        Ok(expired)
+    }
+}
+
+#[tonic::async_trait]
+impl OrderArticleRepository for InMemoryRepository {
+    async fn find_order_articles(&self, order_id: &Uuid) -> DatabaseResult<Vec<OrderArticle>> {
+        Ok(self
+            .state()
+            .order_articles
+            .iter()
+            .filter(|x| &x.order_id == order_id)
+            .cloned()
+            .collect())
+    }
+}
+
+#[tonic::async_trait]
+impl ShippingRepository for InMemoryRepository {
+    async fn find_shipping_method(&self, id: &Uuid) -> DatabaseResult<Option<ShippingMethod>> {
+        Ok(self
+            .state()
+            .shipping_methods
+            .iter()
+            .find(|x| &x.id == id)
+            .cloned())
+    }
+
+    async fn find_shipping_methods(&self) -> DatabaseResult<Vec<ShippingMethod>> {
+        Ok(self.state().shipping_methods.clone())
+    }
+
+    async fn find_shipping_cost_rules(
+        &self,
+        shipping_method_id: &Uuid,
+    ) -> DatabaseResult<Vec<ShippingCostRule>> {
+        Ok(self
+            .state()
+            .shipping_cost_rules
+            .iter()
+            .filter(|x| &x.shipping_method_id == shipping_method_id)
+            .cloned()
+            .collect())
+    }
+}
+
+#[tonic::async_trait]
+impl CartRepository for InMemoryRepository {
+    async fn get_or_create_customer_cart(&self, customer_id: &Uuid) -> DatabaseResult<Cart> {
+        Ok(self.state().get_or_create_customer_cart(customer_id))
+    }
+
+    async fn find_cart_items(&self, cart_id: &Uuid) -> DatabaseResult<Vec<CartItem>> {
+        let mut items: Vec<CartItem> = self
+            .state()
+            .cart_items
+            .iter()
+            .filter(|x| &x.cart_id == cart_id)
+            .cloned()
+            .collect();
+        items.sort_by_key(|x| x.article_id);
+        Ok(items)
+    }
+
+    async fn get_or_create_anonymous_cart(&self, id: &Uuid) -> DatabaseResult<Option<Cart>> {
+        let mut state = self.state();
+        if let Some(cart) = state.carts.iter().find(|x| &x.id == id) {
+            return Ok(cart.customer_id.is_none().then(|| cart.clone()));
+        }
+        let now = Utc::now().naive_utc();
+        let cart = Cart {
+            id: *id,
+            customer_id: None,
+            created_at: now,
+            updated_at: now,
+        };
+        state.carts.push(cart.clone());
+
+        Ok(Some(cart))
+    }
+
+    async fn add_cart_item(
+        &self,
+        cart_id: &Uuid,
+        article_id: &Uuid,
+        quantity: i32,
+    ) -> DatabaseResult<()> {
+        self.state().add_cart_item(cart_id, article_id, quantity);
+        Ok(())
+    }
+
+    async fn set_cart_item_quantity(
+        &self,
+        cart_id: &Uuid,
+        article_id: &Uuid,
+        quantity: i32,
+    ) -> DatabaseResult<()> {
+        let mut state = self.state();
+        match state
+            .cart_items
+            .iter_mut()
+            .find(|x| &x.cart_id == cart_id && &x.article_id == article_id)
+        {
+            Some(item) => item.quantity = quantity,
+            None => state.add_cart_item(cart_id, article_id, quantity),
+        }
+        Ok(())
+    }
+
+    async fn remove_cart_item(&self, cart_id: &Uuid, article_id: &Uuid) -> DatabaseResult<()> {
+        self.state()
+            .cart_items
+            .retain(|x| !(&x.cart_id == cart_id && &x.article_id == article_id));
+        Ok(())
+    }
+
+    async fn touch_cart(&self, cart_id: &Uuid) -> DatabaseResult<()> {
+        self.state().touch_cart(cart_id);
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl WishlistRepository for InMemoryRepository {
+    async fn find_customer_wishlists(&self, customer_id: &Uuid) -> DatabaseResult<Vec<Wishlist>> {
+        let mut wishlists: Vec<Wishlist> = self
+            .state()
+            .wishlists
+            .iter()
+            .filter(|x| &x.customer_id == customer_id)
+            .cloned()
+            .collect();
+        wishlists.sort_by_key(|x| x.created_at);
+        Ok(wishlists)
+    }
+
+    async fn find_customer_wishlist(
+        &self,
+        id: &Uuid,
+        customer_id: &Uuid,
+    ) -> DatabaseResult<Option<Wishlist>> {
+        Ok(self.state().find_customer_wishlist(id, customer_id))
+    }
+
+    async fn insert_wishlist(
+        &self,
+        customer_id: &Uuid,
+        name: &str,
+    ) -> DatabaseResult<Option<Wishlist>> {
+        let mut state = self.state();
+        // wishlist names are unique per customer, as in the wishlist table
+        if state
+            .wishlists
+            .iter()
+            .any(|x| &x.customer_id == customer_id && x.name == name)
+        {
+            return Ok(None);
+        }
+        let wishlist = Wishlist {
+            id: Uuid::new_v4(),
+            customer_id: *customer_id,
+            name: name.to_string(),
+            created_at: Utc::now().naive_utc(),
+        };
+        state.wishlists.push(wishlist.clone());
+
+        Ok(Some(wishlist))
+    }
+
+    async fn delete_wishlist(&self, id: &Uuid) -> DatabaseResult<()> {
+        let mut state = self.state();
+        state.wishlists.retain(|x| &x.id != id);
+        state.wishlist_items.retain(|x| &x.wishlist_id != id);
+        Ok(())
+    }
+
+    async fn find_wishlist_items(&self, wishlist_id: &Uuid) -> DatabaseResult<Vec<WishlistItem>> {
+        let mut items: Vec<WishlistItem> = self
+            .state()
+            .wishlist_items
+            .iter()
+            .filter(|x| &x.wishlist_id == wishlist_id)
+            .cloned()
+            .collect();
+        items.sort_by_key(|x| (x.added_at, x.article_id));
+        Ok(items)
+    }
+
+    async fn add_wishlist_item(&self, wishlist_id: &Uuid, article_id: &Uuid) -> DatabaseResult<()> {
+        let mut state = self.state();
+        if !state
+            .wishlist_items
+            .iter()
+            .any(|x| &x.wishlist_id == wishlist_id && &x.article_id == article_id)
+        {
+            state.wishlist_items.push(WishlistItem {
+                wishlist_id: *wishlist_id,
+                article_id: *article_id,
+                added_at: Utc::now().naive_utc(),
+            });
+        }
+        Ok(())
+    }
+
+    async fn remove_wishlist_item(
+        &self,
+        wishlist_id: &Uuid,
+        article_id: &Uuid,
+    ) -> DatabaseResult<bool> {
+        Ok(self.state().remove_wishlist_item(wishlist_id, article_id))
+    }
+}
+
+#[tonic::async_trait]
+impl ReportRepository for InMemoryRepository {
+    async fn revenue_report(
+        &self,
+        granularity: ReportGranularity,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> DatabaseResult<Vec<RevenueBucket>> {
+        let state = self.state();
+        let mut periods: BTreeMap<NaiveDateTime, RevenueBucket> = BTreeMap::new();
+        for order in state.paid_orders(from, to) {
+            let period = Self::revenue_period(&mut periods, granularity, order.created_at);
+            period.paid_orders += 1;
+            period.revenue += state.order_total(order);
+        }
+        for refund in state
+            .refunds
+            .iter()
+            .filter(|x| x.created_at >= from && x.created_at < to)
+        {
+            Self::revenue_period(&mut periods, granularity, refund.created_at).refunds +=
+                refund.amount;
+        }
+
+        Ok(periods.into_values().collect())
+    }
+
+    async fn top_articles_report(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        limit: i64,
+    ) -> DatabaseResult<Vec<TopArticle>> {
+        let state = self.state();
+        let mut sold: HashMap<Uuid, TopArticle> = HashMap::new();
+        for order in state.paid_orders(from, to) {
+            for order_article in state
+                .order_articles
+                .iter()
+                .filter(|x| x.order_id == order.id)
+            {
+                let article = match state
+                    .articles
+                    .iter()
+                    .find(|x| x.id == order_article.article_id)
+                {
+                    Some(article) => article,
+                    None => continue,
+                };
+                let top_article = sold.entry(article.id).or_insert_with(|| TopArticle {
+                    article_id: article.id,
+                    name: article.name.clone(),
+                    quantity: 0,
+                    revenue: Decimal::ZERO,
+                });
+                top_article.quantity += order_article.quantity as i64;
+                top_article.revenue +=
+                    Decimal::from(order_article.quantity) * order_article.unit_price;
+            }
+        }
+        let mut sold: Vec<TopArticle> = sold.into_values().collect();
+        sold.sort_by(|a, b| {
+            b.quantity
+                .cmp(&a.quantity)
+                .then(b.revenue.cmp(&a.revenue))
+                .then(a.article_id.cmp(&b.article_id))
+        });
+        Ok(Self::page(sold.into_iter(), limit))
+    }
+
+    async fn order_stats_report(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> DatabaseResult<OrderStats> {
+        let state = self.state();
+        let orders = state
+            .orders
+            .iter()
+            .filter(|x| x.created_at >= from && x.created_at < to);
+        let paid_totals: Vec<Decimal> = state
+            .paid_orders(from, to)
+            .map(|x| state.order_total(x))
+            .collect();
+        let average_order_value = match paid_totals.len() {
+            0 => Decimal::ZERO,
+            paid => {
+                Self::round_cents(paid_totals.iter().sum::<Decimal>() / Decimal::from(paid as i64))
+            }
+        };
+
+        Ok(OrderStats {
+            orders: orders.clone().count() as i64,
+            paid_orders: paid_totals.len() as i64,
+            payment_failures: orders
+                .filter(|x| x.status == OrderStatus::PaymentRefused)
+                .count() as i64,
+            average_order_value,
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl InvoiceRepository for InMemoryRepository {
+    async fn find_order_invoice(&self, order_id: &Uuid) -> DatabaseResult<Option<Invoice>> {
+        Ok(self
+            .state()
+            .invoices
+            .iter()
+            .find(|x| &x.order_id == order_id)
+            .cloned())
+    }
+
+    async fn find_invoice_lines(&self, invoice_id: &Uuid) -> DatabaseResult<Vec<InvoiceLine>> {
+        let mut lines: Vec<InvoiceLine> = self
+            .state()
+            .invoice_lines
+            .iter()
+            .filter(|x| &x.invoice_id == invoice_id)
+            .cloned()
+            .collect();
+        lines.sort_by_key(|x| x.position);
+        Ok(lines)
+    }
+}
+
+#[tonic::async_trait]
+impl TransactionalRepository for InMemoryRepository {
+    type Transaction = InMemoryTransaction;
+
+    async fn begin(&self) -> DatabaseResult<InMemoryTransaction> {
+        Ok(InMemoryTransaction {
+            state: self.state().clone(),
+            repository: self.state.clone(),
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl OrderTransaction for InMemoryTransaction {
+    async fn find_order_for_update(&mut self, id: &Uuid) -> DatabaseResult<Option<CustomerOrder>> {
+        Ok(self.state.orders.iter().find(|x| &x.id == id).cloned())
+    }
+
+    async fn insert_order(
+        &mut self,
+        customer_id: &Uuid,
+        shipping_method_id: Option<&Uuid>,
+        shipping_cost: Decimal,
+    ) -> DatabaseResult<CustomerOrder> {
+        let order = CustomerOrder {
+            id: Uuid::new_v4(),
+            customer_id: *customer_id,
+            created_at: Utc::now().naive_utc(),
+            status: OrderStatus::Created,
+            transaction_id: None,
+            shipping_method_id: shipping_method_id.copied(),
+            shipping_cost,
+        };
+        self.state.orders.push(order.clone());
+        self.state.order_events.push(OrderEvent {
+            id: Uuid::new_v4(),
+            order_id: order.id,
+            kind: OrderEventKind::Created,
+            actor: OrderEventActor::Customer,
+            status: order.status,
+            transaction_id: None,
+            created_at: order.created_at,
+        });
+
+        Ok(order)
+    }
+
+    async fn insert_order_article(
+        &mut self,
+        order_id: &Uuid,
+        article_id: &Uuid,
+        quantity: i32,
+        unit_price: Decimal,
+    ) -> DatabaseResult<OrderArticle> {
+        let order_article = OrderArticle {
+            id: Uuid::new_v4(),
+            order_id: *order_id,
+            article_id: *article_id,
+            quantity,
+            unit_price,
+        };
+        self.state.order_articles.push(order_article.clone());
+
+        Ok(order_article)
+    }
+
+    async fn update_order_status(
+        &mut self,
+        order_id: &Uuid,
+        status: OrderStatus,
+        actor: OrderEventActor,
+    ) -> DatabaseResult<()> {
+        self.state
+            .update_order(order_id, OrderEventKind::StatusChanged, actor, |order| {
+                order.status = status
+            })
+    }
+
+    async fn update_order_transaction_id(
+        &mut self,
+        order_id: &Uuid,
+        transaction_id: &str,
+        actor: OrderEventActor,
+    ) -> DatabaseResult<()> {
+        self.state.update_order(
+            order_id,
+            OrderEventKind::TransactionChanged,
+            actor,
+            |order| order.transaction_id = Some(transaction_id.to_string()),
+        )
+    }
+
+    async fn insert_shipment(
+        &mut self,
+        order_id: &Uuid,
+        carrier: &str,
+        tracking_code: &str,
+    ) -> DatabaseResult<Shipment> {
+        // an order is shipped once, as in the shipment table
+        if self.state.shipments.iter().any(|x| &x.order_id == order_id) {
+            return Err(DatabaseError::TooManyInserts);
+        }
+        let shipment = Shipment {
+            order_id: *order_id,
+            carrier: carrier.to_string(),
+            tracking_code: tracking_code.to_string(),
+            shipped_at: Utc::now().naive_utc(),
+        };
+        self.state.shipments.push(shipment.clone());
+
+        Ok(shipment)
+    }
+}
+
+#[tonic::async_trait]
+impl CartTransaction for InMemoryTransaction {
+    async fn get_or_create_customer_cart(&mut self, customer_id: &Uuid) -> DatabaseResult<Cart> {
+        Ok(self.state.get_or_create_customer_cart(customer_id))
+    }
+
+    async fn find_anonymous_cart(&mut self, id: &Uuid) -> DatabaseResult<Option<Cart>> {
+        Ok(self
+            .state
+            .carts
+            .iter()
+            .find(|x| &x.id == id && x.customer_id.is_none())
+            .cloned())
+    }
+
+    async fn add_cart_item(
+        &mut self,
+        cart_id: &Uuid,
+        article_id: &Uuid,
+        quantity: i32,
+    ) -> DatabaseResult<()> {
+        self.state.add_cart_item(cart_id, article_id, quantity);
+        Ok(())
+    }
+
+    async fn merge_cart_items(&mut self, from: &Uuid, into: &Uuid) -> DatabaseResult<()> {
+        let items: Vec<CartItem> = self
+            .state
+            .cart_items
+            .iter()
+            .filter(|x| &x.cart_id == from)
+            .cloned()
+            .collect();
+        for item in items.into_iter() {
+            self.state
+                .add_cart_item(into, &item.article_id, item.quantity);
+        }
+        Ok(())
+    }
+
+    async fn clear_cart(&mut self, cart_id: &Uuid) -> DatabaseResult<()> {
+        self.state.cart_items.retain(|x| &x.cart_id != cart_id);
+        Ok(())
+    }
+
+    async fn touch_cart(&mut self, cart_id: &Uuid) -> DatabaseResult<()> {
+        self.state.touch_cart(cart_id);
+        Ok(())
+    }
+
+    async fn delete_cart(&mut self, cart_id: &Uuid) -> DatabaseResult<()> {
+        self.state.carts.retain(|x| &x.id != cart_id);
+        self.state.cart_items.retain(|x| &x.cart_id != cart_id);
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl WishlistTransaction for InMemoryTransaction {
+    async fn find_customer_wishlist(
+        &mut self,
+        id: &Uuid,
+        customer_id: &Uuid,
+    ) -> DatabaseResult<Option<Wishlist>> {
+        Ok(self.state.find_customer_wishlist(id, customer_id))
+    }
+
+    async fn remove_wishlist_item(
+        &mut self,
+        wishlist_id: &Uuid,
+        article_id: &Uuid,
+    ) -> DatabaseResult<bool> {
+        Ok(self.state.remove_wishlist_item(wishlist_id, article_id))
+    }
+}
+
+#[tonic::async_trait]
+impl ReturnTransaction for InMemoryTransaction {
+    async fn find_return_for_update(&mut self, id: &Uuid) -> DatabaseResult<Option<OrderReturn>> {
+        Ok(self.state.returns.iter().find(|x| &x.id == id).cloned())
+    }
+
+    async fn insert_return(
+        &mut self,
+        order_id: &Uuid,
+        reason: &str,
+    ) -> DatabaseResult<OrderReturn> {
+        let order_return = OrderReturn {
+            id: Uuid::new_v4(),
+            order_id: *order_id,
+            status: ReturnStatus::Requested,
+            reason: reason.to_string(),
+            created_at: Utc::now().naive_utc(),
+            resolved_at: None,
+        };
+        self.state.returns.push(order_return.clone());
+
+        Ok(order_return)
+    }
+
+    async fn insert_return_article(
+        &mut self,
+        return_id: &Uuid,
+        order_article_id: &Uuid,
+        quantity: i32,
+    ) -> DatabaseResult<OrderReturnArticle> {
+        let return_article = OrderReturnArticle {
+            id: Uuid::new_v4(),
+            return_id: *return_id,
+            order_article_id: *order_article_id,
+            quantity,
+        };
+        self.state.return_articles.push(return_article.clone());
+
+        Ok(return_article)
+    }
+
+    async fn resolve_return(&mut self, id: &Uuid, status: ReturnStatus) -> DatabaseResult<()> {
+        match self.state.returns.iter_mut().find(|x| &x.id == id) {
+            Some(order_return) => {
+                order_return.status = status;
+                order_return.resolved_at = Some(Utc::now().naive_utc());
+                Ok(())
+            }
+            None => Err(DatabaseError::TooManyInserts),
+        }
+    }
+
+    async fn find_return_articles(
+        &mut self,
+        return_id: &Uuid,
+    ) -> DatabaseResult<Vec<OrderReturnArticle>> {
+        Ok(self
+            .state
+            .return_articles
+            .iter()
+            .filter(|x| &x.return_id == return_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn find_not_rejected_return_articles(
+        &mut self,
+        order_id: &Uuid,
+    ) -> DatabaseResult<Vec<OrderReturnArticle>> {
+        let state = &self.state;
+        Ok(state
+            .return_articles
+            .iter()
+            .filter(|x| {
+                state.returns.iter().any(|order_return| {
+                    order_return.id == x.return_id
+                        && &order_return.order_id == order_id
+                        && order_return.status != ReturnStatus::Rejected
+                })
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn find_refunds(&mut self, order_id: &Uuid) -> DatabaseResult<Vec<Refund>> {
+        let mut refunds: Vec<Refund> = self
+            .state
+            .refunds
+            .iter()
+            .filter(|x| &x.order_id == order_id)
+            .cloned()
+            .collect();
+        refunds.sort_by_key(|x| x.created_at);
+        Ok(refunds)
+    }
+
+    async fn insert_refund(
+        &mut self,
+        order_id: &Uuid,
+        return_id: &Uuid,
+        transaction_id: &str,
+        amount: Decimal,
+    ) -> DatabaseResult<Refund> {
+        let refund = Refund {
+            id: Uuid::new_v4(),
+            order_id: *order_id,
+            return_id: *return_id,
+            transaction_id: transaction_id.to_string(),
+            amount,
+            created_at: Utc::now().naive_utc(),
+        };
+        self.state.refunds.push(refund.clone());
+
+        Ok(refund)
+    }
+}
+
+#[tonic::async_trait]
+impl ReviewTransaction for InMemoryTransaction {
+    async fn find_review_for_update(&mut self, id: &Uuid) -> DatabaseResult<Option<ArticleReview>> {
+        Ok(self.state.reviews.iter().find(|x| &x.id == id).cloned())
+    }
+
+    async fn moderate_review(&mut self, id: &Uuid, status: ReviewStatus) -> DatabaseResult<()> {
+        match self.state.reviews.iter_mut().find(|x| &x.id == id) {
+            Some(review) => {
+                review.status = status;
+                review.moderated_at = Some(Utc::now().naive_utc());
+                Ok(())
+            }
+            None => Err(DatabaseError::TooManyInserts),
+        }
+    }
+
+    async fn refresh_article_rating(&mut self, article_id: &Uuid) -> DatabaseResult<()> {
+        let ratings: Vec<i16> = self
+            .state
+            .reviews
+            .iter()
+            .filter(|x| &x.article_id == article_id && x.status == ReviewStatus::Approved)
+            .map(|x| x.rating)
+            .collect();
+        let average = match ratings.len() {
+            0 => Decimal::ZERO,
+            count => InMemoryRepository::round_cents(
+                Decimal::from(ratings.iter().map(|x| *x as i64).sum::<i64>())
+                    / Decimal::from(count as i64),
+            ),
+        };
+        let rating = ArticleRating {
+            article_id: *article_id,
+            average,
+            count: ratings.len() as i32,
+        };
+        match self
+            .state
+            .ratings
+            .iter_mut()
+            .find(|x| &x.article_id == article_id)
+        {
+            Some(existing) => *existing = rating,
+            None => self.state.ratings.push(rating),
+        }
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl ArticleTransaction for InMemoryTransaction {
+    async fn find_article_ids_by_names(
+        &mut self,
+        names: &[String],
+    ) -> DatabaseResult<Vec<(Uuid, String)>> {
+        let mut ids: BTreeMap<String, Uuid> = BTreeMap::new();
+        for article in self
+            .state
+            .articles
+            .iter()
+            .filter(|x| !x.is_archived() && names.contains(&x.name))
+        {
+            let id = ids.entry(article.name.clone()).or_insert(article.id);
+            *id = (*id).min(article.id);
+        }
+        Ok(ids.into_iter().map(|(name, id)| (id, name)).collect())
+    }
+
+    async fn upsert_articles(&mut self, articles: &[Article]) -> DatabaseResult<Vec<(Uuid, bool)>> {
+        let mut upserted = Vec::with_capacity(articles.len());
+        for article in articles.iter() {
+            match self.state.articles.iter_mut().find(|x| x.id == article.id) {
+                Some(existing) => {
+                    if (
+                        &existing.name,
+                        &existing.description,
+                        existing.unit_price,
+                        existing.weight,
+                    ) != (
+                        &article.name,
+                        &article.description,
+                        article.unit_price,
+                        article.weight,
+                    ) {
+                        existing.name = article.name.clone();
+                        existing.description = article.description.clone();
+                        existing.unit_price = article.unit_price;
+                        existing.weight = article.weight;
+                        upserted.push((article.id, false));
+                    }
+                }
+                None => {
+                    self.state.articles.push(article.clone());
+                    upserted.push((article.id, true));
+                }
+            }
+        }
+        Ok(upserted)
+    }
+}
+
+#[tonic::async_trait]
+impl InvoiceTransaction for InMemoryTransaction {
+    async fn issue_invoice(
+        &mut self,
+        order_id: &Uuid,
+        seller: &InvoiceSeller,
+        issued_at: NaiveDateTime,
+    ) -> DatabaseResult<Invoice> {
+        let state = &mut self.state;
+        // an order has a single invoice, as in the invoice table
+        if state.invoices.iter().any(|x| &x.order_id == order_id) {
+            return Err(DatabaseError::TooManyInserts);
+        }
+        let order = state
+            .orders
+            .iter()
+            .find(|x| &x.id == order_id)
+            .ok_or(DatabaseError::TooManyInserts)?;
+        let customer = state
+            .customers
+            .iter()
+            .find(|x| x.id == order.customer_id)
+            .ok_or(DatabaseError::TooManyInserts)?;
+        let year = issued_at.year();
+        let total = state.order_total(order);
+        let taxable_amount = InMemoryRepository::round_cents(
+            total * Decimal::ONE_HUNDRED / (Decimal::ONE_HUNDRED + seller.tax_rate),
+        );
+        let invoice = Invoice {
+            id: Uuid::new_v4(),
+            order_id: *order_id,
+            year,
+            number: state
+                .invoices
+                .iter()
+                .filter(|x| x.year == year)
+                .map(|x| x.number)
+                .max()
+                .unwrap_or_default()
+                + 1,
+            issued_at,
+            seller_name: seller.name.clone(),
+            seller_address: seller.address.clone(),
+            seller_vat_number: seller.vat_number.clone(),
+            customer_email: customer.email.clone(),
+            transaction_id: order.transaction_id.clone().unwrap_or_default(),
+            shipping_cost: order.shipping_cost,
+            tax_rate: seller.tax_rate,
+            taxable_amount,
+            tax_amount: total - taxable_amount,
+            total,
+        };
+        // lines are ordered by article name, as in the invoice line insert
+        let mut lines: Vec<(&Article, &OrderArticle)> = state
+            .order_articles
+            .iter()
+            .filter(|x| &x.order_id == order_id)
+            .filter_map(|order_article| {
+                state
+                    .articles
+                    .iter()
+                    .find(|x| x.id == order_article.article_id)
+                    .map(|article| (article, order_article))
+            })
+            .collect();
+        lines.sort_by(|(a, x), (b, y)| (&a.name, x.id).cmp(&(&b.name, y.id)));
+        let lines: Vec<InvoiceLine> = lines
+            .into_iter()
+            .enumerate()
+            .map(|(position, (article, order_article))| InvoiceLine {
+                invoice_id: invoice.id,
+                position: position as i32 + 1,
+                article_id: article.id,
+                name: article.name.clone(),
+                quantity: order_article.quantity,
+                unit_price: order_article.unit_price,
+                total: Decimal::from(order_article.quantity) * order_article.unit_price,
+            })
+            .collect();
+        state.invoice_lines.extend(lines);
+        state.invoices.push(invoice.clone());
+
+        Ok(invoice)
+    }
+}
+
+#[tonic::async_trait]
+impl RepositoryTransaction for InMemoryTransaction {
+    async fn publish_event(&mut self, event: &DomainEvent) -> DatabaseResult<()> {
+        self.state.events.push(event.clone());
+        Ok(())
+    }
+
+    async fn commit(self) -> DatabaseResult<()> {
+        *self
+            .repository
+            .lock()
+            .expect("in-memory repository lock is poisoned") = self.state;
+        Ok(())
+    }
+}
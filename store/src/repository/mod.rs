@@ -0,0 +1,548 @@
+//! # Repository
+//!
+//! Access to the store data behind traits, so that the store service can run against Postgres or,
+//! in tests, against an in-memory backend.
+//!
+//! Writes which must be atomic, such as submitting or paying an order, run in a
+//! `RepositoryTransaction`.
+
+#[cfg(test)]
+mod memory;
+mod postgres;
+
+#[cfg(test)]
+pub use memory::InMemoryRepository;
+pub use postgres::PostgresRepository;
+
+use crate::database::{
+    Article, ArticleRating, ArticleReview, Cart, CartItem, Customer, CustomerOrder, DatabaseResult,
+    Invoice, InvoiceLine, InvoiceSeller, OrderArticle, OrderEvent, OrderEventActor, OrderFilter,
+    OrderReturn, OrderReturnArticle, OrderStats, OrderStatus, Refund, ReportGranularity,
+    ReturnStatus, RevenueBucket, ReviewStatus, Shipment, ShippingCostRule, ShippingMethod,
+    TopArticle, Wishlist, WishlistItem,
+};
+use crate::events::DomainEvent;
+
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+/// Customers repository
+#[tonic::async_trait]
+pub trait CustomerRepository: std::fmt::Debug + Send + Sync {
+    /// Find customer by email
+    async fn find_customer_by_email(&self, email: &str) -> DatabaseResult<Option<Customer>>;
+
+    /// Find customer by email and hashed password
+    async fn find_customer_by_credentials(
+        &self,
+        email: &str,
+        password: &str,
+    ) -> DatabaseResult<Option<Customer>>;
+
+    /// Insert a new customer with an hashed password and publish its `CustomerSignedUp` event
+    async fn insert_customer(&self, email: &str, password: &str) -> DatabaseResult<Customer>;
+}
+
+/// Articles repository
+#[tonic::async_trait]
+pub trait ArticleRepository: std::fmt::Debug + Send + Sync {
+    /// Find article by id; archived articles are returned too
+    async fn find_article_by_id(&self, id: &Uuid) -> DatabaseResult<Option<Article>>;
+
+    /// Find the articles, not archived, whose name ends with `name` if set, ordered by name and id,
+    /// starting after the `(name, id)` position of the last article of the previous page
+    async fn find_articles(
+        &self,
+        name: Option<&str>,
+        after: Option<(String, Uuid)>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<Article>>;
+
+    /// Count the articles, not archived, whose name ends with `name` if set
+    async fn count_articles(&self, name: Option<&str>) -> DatabaseResult<i64>;
+
+    /// Get the articles, not archived, with an id greater than `after`, ordered by id
+    async fn find_articles_after(
+        &self,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<Article>>;
+
+    /// Find the rating of an article
+    async fn find_article_rating(&self, article_id: &Uuid)
+        -> DatabaseResult<Option<ArticleRating>>;
+
+    /// Find the articles, not archived, most frequently bought together with `article_ids`,
+    /// excluding `article_ids` themselves
+    async fn find_related_articles(
+        &self,
+        article_ids: &[Uuid],
+        limit: i64,
+    ) -> DatabaseResult<Vec<Article>>;
+}
+
+/// Article reviews repository
+#[tonic::async_trait]
+pub trait ReviewRepository: std::fmt::Debug + Send + Sync {
+    /// Find the approved reviews of an article, newest first, starting after the
+    /// `(created_at, id)` position of the last review of the previous page
+    async fn find_approved_reviews(
+        &self,
+        article_id: &Uuid,
+        after: Option<(NaiveDateTime, Uuid)>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<ArticleReview>>;
+
+    /// Returns whether the article has been shipped to the customer in one of their orders
+    async fn is_verified_purchase(
+        &self,
+        customer_id: &Uuid,
+        article_id: &Uuid,
+    ) -> DatabaseResult<bool>;
+
+    /// Insert a new pending review.
+    /// Returns `None` if the customer has already reviewed the article
+    async fn insert_review(
+        &self,
+        article_id: &Uuid,
+        customer_id: &Uuid,
+        rating: i16,
+        title: &str,
+        body: &str,
+    ) -> DatabaseResult<Option<ArticleReview>>;
+}
+
+/// Orders repository
+#[tonic::async_trait]
+pub trait OrderRepository: std::fmt::Debug + Send + Sync {
+    /// Find order by id
+    async fn find_order_by_id(&self, id: &Uuid) -> DatabaseResult<Option<CustomerOrder>>;
+
+    /// Find the orders of a customer matching `filter`, newest first, starting after the
+    /// `(created_at, id)` position of the last order of the previous page
+    async fn find_orders_by_customer(
+        &self,
+        customer_id: &Uuid,
+        filter: &OrderFilter,
+        after: Option<(NaiveDateTime, Uuid)>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<CustomerOrder>>;
+
+    /// Count the orders of a customer matching `filter`
+    async fn count_orders_by_customer(
+        &self,
+        customer_id: &Uuid,
+        filter: &OrderFilter,
+    ) -> DatabaseResult<i64>;
+
+    /// Find the orders created in `[from, to)`, oldest first, starting after the `(created_at, id)`
+    /// position of the last order of the previous batch
+    async fn find_orders_created_between(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        after: Option<(NaiveDateTime, Uuid)>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<CustomerOrder>>;
+
+    /// Find the shipment of an order
+    async fn find_order_shipment(&self, order_id: &Uuid) -> DatabaseResult<Option<Shipment>>;
+
+    /// Find the history of an order, oldest event first
+    async fn find_order_events(&self, order_id: &Uuid) -> DatabaseResult<Vec<OrderEvent>>;
+
+    /// Set status to `Expired` for at most `limit` orders still in `Created` status and created
+    /// before `created_before`, recording the change in the order history. Returns the expired orders
+    async fn expire_orders_created_before(
+        &self,
+        created_before: NaiveDateTime,
+        limit: i64,
+    ) -> DatabaseResult<Vec<CustomerOrder>>;
+}
+
+/// Ordered articles repository
+#[tonic::async_trait]
+pub trait OrderArticleRepository: std::fmt::Debug + Send + Sync {
+    /// Find the articles of an order
+    async fn find_order_articles(&self, order_id: &Uuid) -> DatabaseResult<Vec<OrderArticle>>;
+}
+
+/// Shipping methods repository
+#[tonic::async_trait]
+pub trait ShippingRepository: std::fmt::Debug + Send + Sync {
+    /// Find shipping method by id
+    async fn find_shipping_method(&self, id: &Uuid) -> DatabaseResult<Option<ShippingMethod>>;
+
+    /// Get all the shipping methods
+    async fn find_shipping_methods(&self) -> DatabaseResult<Vec<ShippingMethod>>;
+
+    /// Find the cost rules of a shipping method
+    async fn find_shipping_cost_rules(
+        &self,
+        shipping_method_id: &Uuid,
+    ) -> DatabaseResult<Vec<ShippingCostRule>>;
+}
+
+/// Carts repository
+#[tonic::async_trait]
+pub trait CartRepository: std::fmt::Debug + Send + Sync {
+    /// Get the cart of the customer; the cart is created if the customer has none
+    async fn get_or_create_customer_cart(&self, customer_id: &Uuid) -> DatabaseResult<Cart>;
+
+    /// Get the anonymous cart with `id`; the cart is created if it doesn't exist.
+    /// Returns `None` if `id` belongs to a customer's cart
+    async fn get_or_create_anonymous_cart(&self, id: &Uuid) -> DatabaseResult<Option<Cart>>;
+
+    /// Find the items of a cart
+    async fn find_cart_items(&self, cart_id: &Uuid) -> DatabaseResult<Vec<CartItem>>;
+
+    /// Add `quantity` items of the article to the cart
+    async fn add_cart_item(
+        &self,
+        cart_id: &Uuid,
+        article_id: &Uuid,
+        quantity: i32,
+    ) -> DatabaseResult<()>;
+
+    /// Set the quantity of the article in the cart
+    async fn set_cart_item_quantity(
+        &self,
+        cart_id: &Uuid,
+        article_id: &Uuid,
+        quantity: i32,
+    ) -> DatabaseResult<()>;
+
+    /// Remove the article from the cart
+    async fn remove_cart_item(&self, cart_id: &Uuid, article_id: &Uuid) -> DatabaseResult<()>;
+
+    /// Set the last update time of the cart to now
+    async fn touch_cart(&self, cart_id: &Uuid) -> DatabaseResult<()>;
+}
+
+/// Wishlists repository
+#[tonic::async_trait]
+pub trait WishlistRepository: std::fmt::Debug + Send + Sync {
+    /// Find the wishlists of the customer, oldest first
+    async fn find_customer_wishlists(&self, customer_id: &Uuid) -> DatabaseResult<Vec<Wishlist>>;
+
+    /// Find the wishlist with `id` of the customer
+    async fn find_customer_wishlist(
+        &self,
+        id: &Uuid,
+        customer_id: &Uuid,
+    ) -> DatabaseResult<Option<Wishlist>>;
+
+    /// Insert a new wishlist for the customer.
+    /// Returns `None` if the customer already has a wishlist with the same name
+    async fn insert_wishlist(
+        &self,
+        customer_id: &Uuid,
+        name: &str,
+    ) -> DatabaseResult<Option<Wishlist>>;
+
+    /// Delete wishlist with its items
+    async fn delete_wishlist(&self, id: &Uuid) -> DatabaseResult<()>;
+
+    /// Find the items of a wishlist, oldest first
+    async fn find_wishlist_items(&self, wishlist_id: &Uuid) -> DatabaseResult<Vec<WishlistItem>>;
+
+    /// Add the article to the wishlist; adding an article already in the wishlist has no effect
+    async fn add_wishlist_item(&self, wishlist_id: &Uuid, article_id: &Uuid) -> DatabaseResult<()>;
+
+    /// Remove the article from the wishlist; returns whether the article was in the wishlist
+    async fn remove_wishlist_item(
+        &self,
+        wishlist_id: &Uuid,
+        article_id: &Uuid,
+    ) -> DatabaseResult<bool>;
+}
+
+/// Sales reports computed from the orders placed in `[from, to)`
+#[tonic::async_trait]
+pub trait ReportRepository: std::fmt::Debug + Send + Sync {
+    /// Revenue of the paid orders grouped by period, oldest first
+    async fn revenue_report(
+        &self,
+        granularity: ReportGranularity,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> DatabaseResult<Vec<RevenueBucket>>;
+
+    /// Articles with the most items sold in the paid orders
+    async fn top_articles_report(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        limit: i64,
+    ) -> DatabaseResult<Vec<TopArticle>>;
+
+    /// Statistics of the orders placed in the date range
+    async fn order_stats_report(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> DatabaseResult<OrderStats>;
+}
+
+/// Invoices repository
+#[tonic::async_trait]
+pub trait InvoiceRepository: std::fmt::Debug + Send + Sync {
+    /// Find the invoice of an order
+    async fn find_order_invoice(&self, order_id: &Uuid) -> DatabaseResult<Option<Invoice>>;
+
+    /// Find the lines of an invoice in order
+    async fn find_invoice_lines(&self, invoice_id: &Uuid) -> DatabaseResult<Vec<InvoiceLine>>;
+}
+
+/// Repository whose writes can be grouped in a transaction
+#[tonic::async_trait]
+pub trait TransactionalRepository: std::fmt::Debug + Send + Sync {
+    type Transaction: RepositoryTransaction;
+
+    /// Begin a new transaction
+    async fn begin(&self) -> DatabaseResult<Self::Transaction>;
+}
+
+/// Orders read and written within a transaction
+#[tonic::async_trait]
+pub trait OrderTransaction: Send {
+    /// Find order by id and lock it until the end of the transaction
+    async fn find_order_for_update(&mut self, id: &Uuid) -> DatabaseResult<Option<CustomerOrder>>;
+
+    /// Insert a new order, recording its creation in the order history
+    async fn insert_order(
+        &mut self,
+        customer_id: &Uuid,
+        shipping_method_id: Option<&Uuid>,
+        shipping_cost: Decimal,
+    ) -> DatabaseResult<CustomerOrder>;
+
+    /// Insert an article of an order
+    async fn insert_order_article(
+        &mut self,
+        order_id: &Uuid,
+        article_id: &Uuid,
+        quantity: i32,
+        unit_price: Decimal,
+    ) -> DatabaseResult<OrderArticle>;
+
+    /// Update order status, recording the change made by `actor` in the order history
+    async fn update_order_status(
+        &mut self,
+        order_id: &Uuid,
+        status: OrderStatus,
+        actor: OrderEventActor,
+    ) -> DatabaseResult<()>;
+
+    /// Update order transaction id, recording the change made by `actor` in the order history
+    async fn update_order_transaction_id(
+        &mut self,
+        order_id: &Uuid,
+        transaction_id: &str,
+        actor: OrderEventActor,
+    ) -> DatabaseResult<()>;
+
+    /// Insert the shipment of an order
+    async fn insert_shipment(
+        &mut self,
+        order_id: &Uuid,
+        carrier: &str,
+        tracking_code: &str,
+    ) -> DatabaseResult<Shipment>;
+}
+
+/// Carts written within a transaction
+#[tonic::async_trait]
+pub trait CartTransaction: Send {
+    /// Get the cart of the customer; the cart is created if the customer has none
+    async fn get_or_create_customer_cart(&mut self, customer_id: &Uuid) -> DatabaseResult<Cart>;
+
+    /// Find the anonymous cart with `id`
+    async fn find_anonymous_cart(&mut self, id: &Uuid) -> DatabaseResult<Option<Cart>>;
+
+    /// Add `quantity` items of the article to the cart
+    async fn add_cart_item(
+        &mut self,
+        cart_id: &Uuid,
+        article_id: &Uuid,
+        quantity: i32,
+    ) -> DatabaseResult<()>;
+
+    /// Move the items of cart `from` into cart `into`, summing the quantities of the articles in both carts
+    async fn merge_cart_items(&mut self, from: &Uuid, into: &Uuid) -> DatabaseResult<()>;
+
+    /// Remove all the items from the cart
+    async fn clear_cart(&mut self, cart_id: &Uuid) -> DatabaseResult<()>;
+
+    /// Set the last update time of the cart to now
+    async fn touch_cart(&mut self, cart_id: &Uuid) -> DatabaseResult<()>;
+
+    /// Delete cart with its items
+    async fn delete_cart(&mut self, cart_id: &Uuid) -> DatabaseResult<()>;
+}
+
+/// Wishlists written within a transaction
+#[tonic::async_trait]
+pub trait WishlistTransaction: Send {
+    /// Find the wishlist with `id` of the customer
+    async fn find_customer_wishlist(
+        &mut self,
+        id: &Uuid,
+        customer_id: &Uuid,
+    ) -> DatabaseResult<Option<Wishlist>>;
+
+    /// Remove the article from the wishlist; returns whether the article was in the wishlist
+    async fn remove_wishlist_item(
+        &mut self,
+        wishlist_id: &Uuid,
+        article_id: &Uuid,
+    ) -> DatabaseResult<bool>;
+}
+
+/// Returns and refunds read and written within a transaction
+#[tonic::async_trait]
+pub trait ReturnTransaction: Send {
+    /// Find return by id and lock it until the end of the transaction
+    async fn find_return_for_update(&mut self, id: &Uuid) -> DatabaseResult<Option<OrderReturn>>;
+
+    /// Insert a new requested return for order
+    async fn insert_return(&mut self, order_id: &Uuid, reason: &str)
+        -> DatabaseResult<OrderReturn>;
+
+    /// Insert an article of a return
+    async fn insert_return_article(
+        &mut self,
+        return_id: &Uuid,
+        order_article_id: &Uuid,
+        quantity: i32,
+    ) -> DatabaseResult<OrderReturnArticle>;
+
+    /// Resolve return setting its status to either approved or rejected
+    async fn resolve_return(&mut self, id: &Uuid, status: ReturnStatus) -> DatabaseResult<()>;
+
+    /// Find the articles of a return
+    async fn find_return_articles(
+        &mut self,
+        return_id: &Uuid,
+    ) -> DatabaseResult<Vec<OrderReturnArticle>>;
+
+    /// Find the articles of an order which are part of a return which has not been rejected
+    async fn find_not_rejected_return_articles(
+        &mut self,
+        order_id: &Uuid,
+    ) -> DatabaseResult<Vec<OrderReturnArticle>>;
+
+    /// Find the refunds of an order, oldest first
+    async fn find_refunds(&mut self, order_id: &Uuid) -> DatabaseResult<Vec<Refund>>;
+
+    /// Insert a new refund for a return
+    async fn insert_refund(
+        &mut self,
+        order_id: &Uuid,
+        return_id: &Uuid,
+        transaction_id: &str,
+        amount: Decimal,
+    ) -> DatabaseResult<Refund>;
+}
+
+/// Reviews moderated within a transaction
+#[tonic::async_trait]
+pub trait ReviewTransaction: Send {
+    /// Find review by id and lock it until the end of the transaction
+    async fn find_review_for_update(&mut self, id: &Uuid) -> DatabaseResult<Option<ArticleReview>>;
+
+    /// Set the moderation status of the review
+    async fn moderate_review(&mut self, id: &Uuid, status: ReviewStatus) -> DatabaseResult<()>;
+
+    /// Recompute the rating of the article from its approved reviews
+    async fn refresh_article_rating(&mut self, article_id: &Uuid) -> DatabaseResult<()>;
+}
+
+/// Articles imported within a transaction
+#[tonic::async_trait]
+pub trait ArticleTransaction: Send {
+    /// Find the ids of the articles, not archived, named exactly as one of `names`.
+    /// When more articles have the same name, the oldest id is returned
+    async fn find_article_ids_by_names(
+        &mut self,
+        names: &[String],
+    ) -> DatabaseResult<Vec<(Uuid, String)>>;
+
+    /// Insert the articles or update the existing ones with the same id.
+    ///
+    /// Returns the id of the articles which have been inserted or changed, and whether they
+    /// have been inserted; articles which are already up to date are not returned
+    async fn upsert_articles(&mut self, articles: &[Article]) -> DatabaseResult<Vec<(Uuid, bool)>>;
+}
+
+/// Invoices issued within a transaction
+#[tonic::async_trait]
+pub trait InvoiceTransaction: Send {
+    /// Issue the invoice of a paid order with the next number of the year of `issued_at`, copying
+    /// the articles of the order into its lines.
+    ///
+    /// Must run in the same transaction which records the payment, so that a rollback gives the
+    /// number back and no gaps are left
+    async fn issue_invoice(
+        &mut self,
+        order_id: &Uuid,
+        seller: &InvoiceSeller,
+        issued_at: NaiveDateTime,
+    ) -> DatabaseResult<Invoice>;
+}
+
+/// Writes applied together once committed; they are discarded if the transaction is dropped
+/// before committing
+#[tonic::async_trait]
+pub trait RepositoryTransaction:
+    OrderTransaction
+    + CartTransaction
+    + WishlistTransaction
+    + ReturnTransaction
+    + ReviewTransaction
+    + ArticleTransaction
+    + InvoiceTransaction
+    + Send
+{
+    /// Write the event to the outbox, so that it is published once the transaction is committed
+    async fn publish_event(&mut self, event: &DomainEvent) -> DatabaseResult<()>;
+
+    /// Commit the transaction
+    async fn commit(self) -> DatabaseResult<()>;
+}
+
+/// All the repositories the store service depends on
+pub trait Repository:
+    CustomerRepository
+    + ArticleRepository
+    + OrderRepository
+    + OrderArticleRepository
+    + ShippingRepository
+    + CartRepository
+    + WishlistRepository
+    + ReviewRepository
+    + ReportRepository
+    + InvoiceRepository
+    + TransactionalRepository
+    + Clone
+    + 'static
+{
+}
+
+impl<T> Repository for T where
+    T: CustomerRepository
+        + ArticleRepository
+        + OrderRepository
+        + OrderArticleRepository
+        + ShippingRepository
+        + CartRepository
+        + WishlistRepository
+        + ReviewRepository
+        + ReportRepository
+        + InvoiceRepository
+        + TransactionalRepository
+        + Clone
+        + 'static
+{
+}
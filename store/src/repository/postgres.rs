@@ -0,0 +1,599 @@
+//! # Postgres repository
+//!
+//! Repository backed by the store database
+
+use super::{
+    ArticleRepository, ArticleTransaction, CartRepository, CartTransaction, CustomerRepository,
+    InvoiceRepository, InvoiceTransaction, OrderArticleRepository, OrderRepository,
+    OrderTransaction, ReportRepository, RepositoryTransaction, ReturnTransaction, ReviewRepository,
+    ReviewTransaction, ShippingRepository, TransactionalRepository, WishlistRepository,
+    WishlistTransaction,
+};
+use crate::database::{
+    Article, ArticleCoPurchase, ArticleRating, ArticleReview, Cart, CartItem, Customer,
+    CustomerOrder, DatabaseResult, Invoice, InvoiceLine, InvoiceSeller, OrderArticle, OrderEvent,
+    OrderEventActor, OrderFilter, OrderReturn, OrderReturnArticle, OrderStats, OrderStatus, Refund,
+    ReportGranularity, ReturnStatus, RevenueBucket, ReviewStatus, SalesReport, Shipment,
+    ShippingCostRule, ShippingMethod, StoreDb, TopArticle, Wishlist, WishlistItem,
+};
+use crate::events::DomainEvent;
+
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sqlx::Postgres;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct PostgresRepository {
+    database: StoreDb,
+}
+
+impl PostgresRepository {
+    pub fn new(database: StoreDb) -> Self {
+        Self { database }
+    }
+}
+
+/// Transaction on the store database
+pub struct PostgresTransaction {
+    transaction: sqlx::Transaction<'static, Postgres>,
+}
+
+#[tonic::async_trait]
+impl CustomerRepository for PostgresRepository {
+    async fn find_customer_by_email(&self, email: &str) -> DatabaseResult<Option<Customer>> {
+        Customer::find_by_email(&self.database, email).await
+    }
+
+    async fn find_customer_by_credentials(
+        &self,
+        email: &str,
+        password: &str,
+    ) -> DatabaseResult<Option<Customer>> {
+        Customer::find_by_email_and_password(&self.database, email, password).await
+    }
+
+    async fn insert_customer(&self, email: &str, password: &str) -> DatabaseResult<Customer> {
+        let mut transaction = self.database.pool().begin().await?;
+        let customer = Customer::insert(&mut transaction, email, password).await?;
+        DomainEvent::CustomerSignedUp {
+            customer_id: customer.id,
+            email: customer.email.clone(),
+        }
+        .publish(&mut transaction)
+        .await?;
+        transaction.commit().await?;
+
+        Ok(customer)
+    }
+}
+
+#[tonic::async_trait]
+impl ArticleRepository for PostgresRepository {
+    async fn find_article_by_id(&self, id: &Uuid) -> DatabaseResult<Option<Article>> {
+        Article::find_by_id(&self.database, id).await
+    }
+
+    async fn find_articles(
+        &self,
+        name: Option<&str>,
+        after: Option<(String, Uuid)>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<Article>> {
+        Article::find_page(&self.database, name, after, limit).await
+    }
+
+    async fn count_articles(&self, name: Option<&str>) -> DatabaseResult<i64> {
+        Article::count(&self.database, name).await
+    }
+
+    async fn find_articles_after(
+        &self,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<Article>> {
+        Article::find_after(&self.database, after, limit).await
+    }
+
+    async fn find_article_rating(
+        &self,
+        article_id: &Uuid,
+    ) -> DatabaseResult<Option<ArticleRating>> {
+        ArticleRating::find_by_article_id(&self.database, article_id).await
+    }
+
+    async fn find_related_articles(
+        &self,
+        article_ids: &[Uuid],
+        limit: i64,
+    ) -> DatabaseResult<Vec<Article>> {
+        ArticleCoPurchase::find_related_articles(&self.database, article_ids, limit).await
+    }
+}
+
+#[tonic::async_trait]
+impl ReviewRepository for PostgresRepository {
+    async fn find_approved_reviews(
+        &self,
+        article_id: &Uuid,
+        after: Option<(NaiveDateTime, Uuid)>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<ArticleReview>> {
+        ArticleReview::find_approved_by_article_id(&self.database, article_id, after, limit).await
+    }
+
+    async fn is_verified_purchase(
+        &self,
+        customer_id: &Uuid,
+        article_id: &Uuid,
+    ) -> DatabaseResult<bool> {
+        ArticleReview::is_verified_purchase(&self.database, customer_id, article_id).await
+    }
+
+    async fn insert_review(
+        &self,
+        article_id: &Uuid,
+        customer_id: &Uuid,
+        rating: i16,
+        title: &str,
+        body: &str,
+    ) -> DatabaseResult<Option<ArticleReview>> {
+        ArticleReview::insert(&self.database, article_id, customer_id, rating, title, body).await
+    }
+}
+
+#[tonic::async_trait]
+impl OrderRepository for PostgresRepository {
+    async fn find_order_by_id(&self, id: &Uuid) -> DatabaseResult<Option<CustomerOrder>> {
+        CustomerOrder::find_by_id(&self.database, id).await
+    }
+
+    async fn find_orders_by_customer(
+        &self,
+        customer_id: &Uuid,
+        filter: &OrderFilter,
+        after: Option<(NaiveDateTime, Uuid)>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<CustomerOrder>> {
+        CustomerOrder::find_by_customer(&self.database, customer_id, filter, after, limit).await
+    }
+
+    async fn count_orders_by_customer(
+        &self,
+        customer_id: &Uuid,
+        filter: &OrderFilter,
+    ) -> DatabaseResult<i64> {
+        CustomerOrder::count_by_customer(&self.database, customer_id, filter).await
+    }
+
+    async fn find_orders_created_between(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        after: Option<(NaiveDateTime, Uuid)>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<CustomerOrder>> {
+        CustomerOrder::find_created_between(&self.database, from, to, after, limit).await
+    }
+
+    async fn find_order_shipment(&self, order_id: &Uuid) -> DatabaseResult<Option<Shipment>> {
+        Shipment::find_by_order_id(&self.database, order_id).await
+    }
+
+    async fn find_order_events(&self, order_id: &Uuid) -> DatabaseResult<Vec<OrderEvent>> {
+        OrderEvent::find_by_order_id(&self.database, order_id).await
+    }
+
+    async fn expire_orders_created_before(
+        &self,
+        created_before: NaiveDateTime,
+        limit: i64,
+    ) -> DatabaseResult<Vec<CustomerOrder>> {
+        CustomerOrder::expire_created_before(&self.database, created_before, limit).await
+    }
+}
+
+#[tonic::async_trait]
+impl OrderArticleRepository for PostgresRepository {
+    async fn find_order_articles(&self, order_id: &Uuid) -> DatabaseResult<Vec<OrderArticle>> {
+        OrderArticle::find_by_order_id(&self.database, order_id).await
+    }
+}
+
+#[tonic::async_trait]
+impl ShippingRepository for PostgresRepository {
+    async fn find_shipping_method(&self, id: &Uuid) -> DatabaseResult<Option<ShippingMethod>> {
+        ShippingMethod::find_by_id(&self.database, id).await
+    }
+
+    async fn find_shipping_methods(&self) -> DatabaseResult<Vec<ShippingMethod>> {
+        ShippingMethod::get_all(&self.database).await
+    }
+
+    async fn find_shipping_cost_rules(
+        &self,
+        shipping_method_id: &Uuid,
+    ) -> DatabaseResult<Vec<ShippingCostRule>> {
+        ShippingCostRule::find_by_shipping_method(&self.database, shipping_method_id).await
+    }
+}
+
+#[tonic::async_trait]
+impl CartRepository for PostgresRepository {
+    async fn get_or_create_customer_cart(&self, customer_id: &Uuid) -> DatabaseResult<Cart> {
+        Cart::get_or_create_for_customer(self.database.pool(), customer_id).await
+    }
+
+    async fn get_or_create_anonymous_cart(&self, id: &Uuid) -> DatabaseResult<Option<Cart>> {
+        Cart::get_or_create_anonymous(self.database.pool(), id).await
+    }
+
+    async fn find_cart_items(&self, cart_id: &Uuid) -> DatabaseResult<Vec<CartItem>> {
+        CartItem::find_by_cart_id(self.database.pool(), cart_id).await
+    }
+
+    async fn add_cart_item(
+        &self,
+        cart_id: &Uuid,
+        article_id: &Uuid,
+        quantity: i32,
+    ) -> DatabaseResult<()> {
+        CartItem::add(self.database.pool(), cart_id, article_id, quantity).await
+    }
+
+    async fn set_cart_item_quantity(
+        &self,
+        cart_id: &Uuid,
+        article_id: &Uuid,
+        quantity: i32,
+    ) -> DatabaseResult<()> {
+        CartItem::set_quantity(self.database.pool(), cart_id, article_id, quantity).await
+    }
+
+    async fn remove_cart_item(&self, cart_id: &Uuid, article_id: &Uuid) -> DatabaseResult<()> {
+        CartItem::remove(self.database.pool(), cart_id, article_id).await
+    }
+
+    async fn touch_cart(&self, cart_id: &Uuid) -> DatabaseResult<()> {
+        Cart::touch(self.database.pool(), cart_id).await
+    }
+}
+
+#[tonic::async_trait]
+impl WishlistRepository for PostgresRepository {
+    async fn find_customer_wishlists(&self, customer_id: &Uuid) -> DatabaseResult<Vec<Wishlist>> {
+        Wishlist::find_by_customer_id(&self.database, customer_id).await
+    }
+
+    async fn find_customer_wishlist(
+        &self,
+        id: &Uuid,
+        customer_id: &Uuid,
+    ) -> DatabaseResult<Option<Wishlist>> {
+        Wishlist::find_by_id_and_customer(self.database.pool(), id, customer_id).await
+    }
+
+    async fn insert_wishlist(
+        &self,
+        customer_id: &Uuid,
+        name: &str,
+    ) -> DatabaseResult<Option<Wishlist>> {
+        Wishlist::insert(&self.database, customer_id, name).await
+    }
+
+    async fn delete_wishlist(&self, id: &Uuid) -> DatabaseResult<()> {
+        Wishlist::delete(&self.database, id).await
+    }
+
+    async fn find_wishlist_items(&self, wishlist_id: &Uuid) -> DatabaseResult<Vec<WishlistItem>> {
+        WishlistItem::find_by_wishlist_id(&self.database, wishlist_id).await
+    }
+
+    async fn add_wishlist_item(&self, wishlist_id: &Uuid, article_id: &Uuid) -> DatabaseResult<()> {
+        WishlistItem::add(&self.database, wishlist_id, article_id).await
+    }
+
+    async fn remove_wishlist_item(
+        &self,
+        wishlist_id: &Uuid,
+        article_id: &Uuid,
+    ) -> DatabaseResult<bool> {
+        WishlistItem::remove(self.database.pool(), wishlist_id, article_id).await
+    }
+}
+
+#[tonic::async_trait]
+impl ReportRepository for PostgresRepository {
+    async fn revenue_report(
+        &self,
+        granularity: ReportGranularity,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> DatabaseResult<Vec<RevenueBucket>> {
+        SalesReport::revenue(&self.database, granularity, from, to).await
+    }
+
+    async fn top_articles_report(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        limit: i64,
+    ) -> DatabaseResult<Vec<TopArticle>> {
+        SalesReport::top_articles(&self.database, from, to, limit).await
+    }
+
+    async fn order_stats_report(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> DatabaseResult<OrderStats> {
+        SalesReport::order_stats(&self.database, from, to).await
+    }
+}
+
+#[tonic::async_trait]
+impl InvoiceRepository for PostgresRepository {
+    async fn find_order_invoice(&self, order_id: &Uuid) -> DatabaseResult<Option<Invoice>> {
+        Invoice::find_by_order_id(self.database.pool(), order_id).await
+    }
+
+    async fn find_invoice_lines(&self, invoice_id: &Uuid) -> DatabaseResult<Vec<InvoiceLine>> {
+        InvoiceLine::find_by_invoice_id(&self.database, invoice_id).await
+    }
+}
+
+#[tonic::async_trait]
+impl TransactionalRepository for PostgresRepository {
+    type Transaction = PostgresTransaction;
+
+    async fn begin(&self) -> DatabaseResult<PostgresTransaction> {
+        Ok(PostgresTransaction {
+            transaction: self.database.pool().begin().await?,
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl OrderTransaction for PostgresTransaction {
+    async fn find_order_for_update(&mut self, id: &Uuid) -> DatabaseResult<Option<CustomerOrder>> {
+        CustomerOrder::find_by_id_for_update(&mut self.transaction, id).await
+    }
+
+    async fn insert_order(
+        &mut self,
+        customer_id: &Uuid,
+        shipping_method_id: Option<&Uuid>,
+        shipping_cost: Decimal,
+    ) -> DatabaseResult<CustomerOrder> {
+        CustomerOrder::insert_order(
+            &mut self.transaction,
+            customer_id,
+            shipping_method_id,
+            shipping_cost,
+        )
+        .await
+    }
+
+    async fn insert_order_article(
+        &mut self,
+        order_id: &Uuid,
+        article_id: &Uuid,
+        quantity: i32,
+        unit_price: Decimal,
+    ) -> DatabaseResult<OrderArticle> {
+        OrderArticle::insert(
+            &mut self.transaction,
+            order_id,
+            article_id,
+            quantity,
+            unit_price,
+        )
+        .await
+    }
+
+    async fn update_order_status(
+        &mut self,
+        order_id: &Uuid,
+        status: OrderStatus,
+        actor: OrderEventActor,
+    ) -> DatabaseResult<()> {
+        CustomerOrder::update_status(&mut self.transaction, order_id, status, actor).await
+    }
+
+    async fn update_order_transaction_id(
+        &mut self,
+        order_id: &Uuid,
+        transaction_id: &str,
+        actor: OrderEventActor,
+    ) -> DatabaseResult<()> {
+        CustomerOrder::update_transaction_id(&mut self.transaction, order_id, transaction_id, actor)
+            .await
+    }
+
+    async fn insert_shipment(
+        &mut self,
+        order_id: &Uuid,
+        carrier: &str,
+        tracking_code: &str,
+    ) -> DatabaseResult<Shipment> {
+        Shipment::insert(&mut self.transaction, order_id, carrier, tracking_code).await
+    }
+}
+
+#[tonic::async_trait]
+impl CartTransaction for PostgresTransaction {
+    async fn get_or_create_customer_cart(&mut self, customer_id: &Uuid) -> DatabaseResult<Cart> {
+        Cart::get_or_create_for_customer(&mut self.transaction, customer_id).await
+    }
+
+    async fn find_anonymous_cart(&mut self, id: &Uuid) -> DatabaseResult<Option<Cart>> {
+        Cart::find_anonymous(&mut self.transaction, id).await
+    }
+
+    async fn add_cart_item(
+        &mut self,
+        cart_id: &Uuid,
+        article_id: &Uuid,
+        quantity: i32,
+    ) -> DatabaseResult<()> {
+        CartItem::add(&mut self.transaction, cart_id, article_id, quantity).await
+    }
+
+    async fn merge_cart_items(&mut self, from: &Uuid, into: &Uuid) -> DatabaseResult<()> {
+        CartItem::merge(&mut self.transaction, from, into).await
+    }
+
+    async fn clear_cart(&mut self, cart_id: &Uuid) -> DatabaseResult<()> {
+        CartItem::clear(&mut self.transaction, cart_id).await
+    }
+
+    async fn touch_cart(&mut self, cart_id: &Uuid) -> DatabaseResult<()> {
+        Cart::touch(&mut self.transaction, cart_id).await
+    }
+
+    async fn delete_cart(&mut self, cart_id: &Uuid) -> DatabaseResult<()> {
+        Cart::delete(&mut self.transaction, cart_id).await
+    }
+}
+
+#[tonic::async_trait]
+impl WishlistTransaction for PostgresTransaction {
+    async fn find_customer_wishlist(
+        &mut self,
+        id: &Uuid,
+        customer_id: &Uuid,
+    ) -> DatabaseResult<Option<Wishlist>> {
+        Wishlist::find_by_id_and_customer(&mut self.transaction, id, customer_id).await
+    }
+
+    async fn remove_wishlist_item(
+        &mut self,
+        wishlist_id: &Uuid,
+        article_id: &Uuid,
+    ) -> DatabaseResult<bool> {
+        WishlistItem::remove(&mut self.transaction, wishlist_id, article_id).await
+    }
+}
+
+#[tonic::async_trait]
+impl ReturnTransaction for PostgresTransaction {
+    async fn find_return_for_update(&mut self, id: &Uuid) -> DatabaseResult<Option<OrderReturn>> {
+        OrderReturn::find_by_id_for_update(&mut self.transaction, id).await
+    }
+
+    async fn insert_return(
+        &mut self,
+        order_id: &Uuid,
+        reason: &str,
+    ) -> DatabaseResult<OrderReturn> {
+        OrderReturn::insert(&mut self.transaction, order_id, reason).await
+    }
+
+    async fn insert_return_article(
+        &mut self,
+        return_id: &Uuid,
+        order_article_id: &Uuid,
+        quantity: i32,
+    ) -> DatabaseResult<OrderReturnArticle> {
+        OrderReturnArticle::insert(&mut self.transaction, return_id, order_article_id, quantity)
+            .await
+    }
+
+    async fn resolve_return(&mut self, id: &Uuid, status: ReturnStatus) -> DatabaseResult<()> {
+        OrderReturn::resolve(&mut self.transaction, id, status).await
+    }
+
+    async fn find_return_articles(
+        &mut self,
+        return_id: &Uuid,
+    ) -> DatabaseResult<Vec<OrderReturnArticle>> {
+        OrderReturnArticle::find_by_return_id(&mut self.transaction, return_id).await
+    }
+
+    async fn find_not_rejected_return_articles(
+        &mut self,
+        order_id: &Uuid,
+    ) -> DatabaseResult<Vec<OrderReturnArticle>> {
+        OrderReturnArticle::find_not_rejected_by_order_id(&mut self.transaction, order_id).await
+    }
+
+    async fn find_refunds(&mut self, order_id: &Uuid) -> DatabaseResult<Vec<Refund>> {
+        Refund::find_by_order_id(&mut self.transaction, order_id).await
+    }
+
+    async fn insert_refund(
+        &mut self,
+        order_id: &Uuid,
+        return_id: &Uuid,
+        transaction_id: &str,
+        amount: Decimal,
+    ) -> DatabaseResult<Refund> {
+        Refund::insert(
+            &mut self.transaction,
+            order_id,
+            return_id,
+            transaction_id,
+            amount,
+        )
+        .await
+    }
+}
+
+#[tonic::async_trait]
+impl ReviewTransaction for PostgresTransaction {
+    async fn find_review_for_update(&mut self, id: &Uuid) -> DatabaseResult<Option<ArticleReview>> {
+        ArticleReview::find_by_id_for_update(&mut self.transaction, id).await
+    }
+
+    async fn moderate_review(&mut self, id: &Uuid, status: ReviewStatus) -> DatabaseResult<()> {
+        ArticleReview::moderate(&mut self.transaction, id, status).await
+    }
+
+    async fn refresh_article_rating(&mut self, article_id: &Uuid) -> DatabaseResult<()> {
+        ArticleRating::refresh(&mut self.transaction, article_id).await
+    }
+}
+
+#[tonic::async_trait]
+impl ArticleTransaction for PostgresTransaction {
+    async fn find_article_ids_by_names(
+        &mut self,
+        names: &[String],
+    ) -> DatabaseResult<Vec<(Uuid, String)>> {
+        Article::find_ids_by_names(&mut self.transaction, names).await
+    }
+
+    async fn upsert_articles(&mut self, articles: &[Article]) -> DatabaseResult<Vec<(Uuid, bool)>> {
+        Article::upsert_many(&mut self.transaction, articles).await
+    }
+}
+
+#[tonic::async_trait]
+impl InvoiceTransaction for PostgresTransaction {
+    async fn issue_invoice(
+        &mut self,
+        order_id: &Uuid,
+        seller: &InvoiceSeller,
+        issued_at: NaiveDateTime,
+    ) -> DatabaseResult<Invoice> {
+        let invoice = Invoice::issue(&mut self.transaction, order_id, seller, issued_at).await?;
+        InvoiceLine::insert_from_order(&mut self.transaction, &invoice.id, order_id).await?;
+
+        Ok(invoice)
+    }
+}
+
+#[tonic::async_trait]
+impl RepositoryTransaction for PostgresTransaction {
+    async fn publish_event(&mut self, event: &DomainEvent) -> DatabaseResult<()> {
+        event.publish(&mut self.transaction).await?;
+
+        Ok(())
+    }
+
+    async fn commit(self) -> DatabaseResult<()> {
+        self.transaction.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,353 @@
+//! # Seed
+//!
+//! Load fixtures into the store database through the same queries used by the service.
+//!
+//! Fixtures are generated from the articles and customer names listed in `tools/`; given the same
+//! seed and options the same articles, customers and orders are generated.
+
+use crate::database::{Article, Customer, CustomerOrder, OrderArticle, StoreDb};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use rust_decimal::Decimal;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Plain text password of the seeded customers
+pub const CUSTOMER_PASSWORD: &str = "Password123!";
+
+const USAGE: &str = "usage: store seed [--customers <n>] [--orders-per-customer <n>] [--articles-per-order <n>] [--seed <n>] [--articles-csv <path>] [--customers-csv <path>] [--reset]";
+
+/// Options of the `seed` command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedOptions {
+    /// CSV file with `Name;Description` of the articles
+    pub articles_csv: PathBuf,
+    /// CSV file with `Name;Surname` used to generate the customers emails
+    pub customers_csv: PathBuf,
+    pub customers: usize,
+    pub orders_per_customer: usize,
+    pub articles_per_order: usize,
+    /// Seed of the random generator
+    pub seed: u64,
+    /// Delete customers, articles and their orders before seeding
+    pub reset: bool,
+}
+
+impl Default for SeedOptions {
+    fn default() -> Self {
+        let tools_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tools");
+        Self {
+            articles_csv: tools_dir.join("articles.csv"),
+            customers_csv: tools_dir.join("customers.csv"),
+            customers: 20,
+            orders_per_customer: 3,
+            articles_per_order: 3,
+            seed: 1,
+            reset: false,
+        }
+    }
+}
+
+impl SeedOptions {
+    /// Parse options from the arguments following the `seed` command
+    pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--reset" {
+                options.reset = true;
+                continue;
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("missing value for `{arg}`; {USAGE}"))?;
+            match arg.as_str() {
+                "--customers" => options.customers = Self::parse_number(&arg, &value)?,
+                "--orders-per-customer" => {
+                    options.orders_per_customer = Self::parse_number(&arg, &value)?
+                }
+                "--articles-per-order" => {
+                    options.articles_per_order = Self::parse_number(&arg, &value)?
+                }
+                "--seed" => options.seed = Self::parse_number(&arg, &value)?,
+                "--articles-csv" => options.articles_csv = PathBuf::from(value),
+                "--customers-csv" => options.customers_csv = PathBuf::from(value),
+                _ => anyhow::bail!("unknown option `{arg}`; {USAGE}"),
+            }
+        }
+
+        Ok(options)
+    }
+
+    fn parse_number<T: std::str::FromStr>(arg: &str, value: &str) -> anyhow::Result<T> {
+        value
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid value `{value}` for `{arg}`; {USAGE}"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ArticleFixture {
+    name: String,
+    description: String,
+    unit_price: Decimal,
+    /// Weight in grams
+    weight: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OrderFixture {
+    /// Index of the customer in `Fixtures::customers`
+    customer: usize,
+    /// Index of the article in `Fixtures::articles` and quantity
+    articles: Vec<(usize, i32)>,
+}
+
+/// Data to load, generated before touching the database
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Fixtures {
+    articles: Vec<ArticleFixture>,
+    /// Customers emails
+    customers: Vec<String>,
+    orders: Vec<OrderFixture>,
+}
+
+impl Fixtures {
+    /// Generate fixtures from the articles and the customer names read from the CSV files
+    fn generate(
+        articles: Vec<(String, String)>,
+        names: Vec<(String, String)>,
+        options: &SeedOptions,
+    ) -> anyhow::Result<Self> {
+        if articles.is_empty() {
+            anyhow::bail!("no articles to seed");
+        }
+        if names.is_empty() && options.customers > 0 {
+            anyhow::bail!("no customer names to generate customers from");
+        }
+        let mut rng = StdRng::seed_from_u64(options.seed);
+        let articles: Vec<ArticleFixture> = articles
+            .into_iter()
+            .map(|(name, description)| ArticleFixture {
+                name,
+                description,
+                unit_price: Decimal::new(rng.gen_range(100..10000), 2),
+                weight: rng.gen_range(50..5000),
+            })
+            .collect();
+
+        // emails are unique, so name, surname and birth year combinations can't repeat
+        let max_customers = names.len() * names.len() * 55;
+        if options.customers > max_customers {
+            anyhow::bail!(
+                "can't generate more than {max_customers} customers from {} names",
+                names.len()
+            );
+        }
+        let mut emails = HashSet::with_capacity(options.customers);
+        let mut customers = Vec::with_capacity(options.customers);
+        while customers.len() < options.customers {
+            let (name, _) = names.choose(&mut rng).expect("names is not empty");
+            let (_, surname) = names.choose(&mut rng).expect("names is not empty");
+            let year = rng.gen_range(1950..2005);
+            let email = format!(
+                "{}.{}{year}@gmail.com",
+                name.to_lowercase(),
+                surname.to_lowercase()
+            );
+            if emails.insert(email.clone()) {
+                customers.push(email);
+            }
+        }
+
+        let mut orders = Vec::with_capacity(options.customers * options.orders_per_customer);
+        for customer in 0..customers.len() {
+            for _ in 0..options.orders_per_customer {
+                let articles = (0..options.articles_per_order)
+                    .map(|_| (rng.gen_range(0..articles.len()), rng.gen_range(1..5)))
+                    .collect();
+                orders.push(OrderFixture { customer, articles });
+            }
+        }
+
+        Ok(Self {
+            articles,
+            customers,
+            orders,
+        })
+    }
+}
+
+/// Parse a `;` separated CSV file with two columns and a header line
+async fn read_csv(path: &Path) -> anyhow::Result<Vec<(String, String)>> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| anyhow::anyhow!("could not read {}: {e}", path.display()))?;
+    parse_csv(&content).map_err(|e| anyhow::anyhow!("could not parse {}: {e}", path.display()))
+}
+
+fn parse_csv(content: &str) -> anyhow::Result<Vec<(String, String)>> {
+    content
+        .lines()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            line.split_once(';')
+                .map(|(first, second)| (first.trim().to_string(), second.trim().to_string()))
+                .ok_or_else(|| {
+                    anyhow::anyhow!("line {}: expected two `;` separated columns", index + 1)
+                })
+        })
+        .collect()
+}
+
+/// Generate the fixtures and load them into the database in a single transaction
+pub async fn run(database_url: &str, options: SeedOptions) -> anyhow::Result<()> {
+    let articles = read_csv(&options.articles_csv).await?;
+    let names = read_csv(&options.customers_csv).await?;
+    let fixtures = Fixtures::generate(articles, names, &options)?;
+    info!(
+        "seeding {} articles, {} customers and {} orders with seed {}",
+        fixtures.articles.len(),
+        fixtures.customers.len(),
+        fixtures.orders.len(),
+        options.seed
+    );
+
+    let db = StoreDb::connect(database_url).await?;
+    let mut transaction = db.pool().begin().await?;
+    if options.reset {
+        info!("deleting customers, articles and their orders");
+        sqlx::query("TRUNCATE customer, article, invoice_sequence CASCADE")
+            .execute(&mut transaction)
+            .await?;
+    }
+    let mut articles = Vec::with_capacity(fixtures.articles.len());
+    for article in fixtures.articles.iter() {
+        articles.push(
+            Article::insert(
+                &mut transaction,
+                &article.name,
+                &article.description,
+                article.unit_price,
+                article.weight,
+            )
+            .await?,
+        );
+    }
+    let password = Customer::hash_password(CUSTOMER_PASSWORD);
+    let mut customers = Vec::with_capacity(fixtures.customers.len());
+    for email in fixtures.customers.iter() {
+        customers.push(Customer::insert(&mut transaction, email, &password).await?);
+    }
+    for order in fixtures.orders.iter() {
+        let customer = &customers[order.customer];
+        let customer_order =
+            CustomerOrder::insert_order(&mut transaction, &customer.id, None, Decimal::ZERO)
+                .await?;
+        for (article, quantity) in order.articles.iter() {
+            let article = &articles[*article];
+            OrderArticle::insert(
+                &mut transaction,
+                &customer_order.id,
+                &article.id,
+                *quantity,
+                article.unit_price,
+            )
+            .await?;
+        }
+    }
+    transaction.commit().await?;
+
+    for customer in customers.iter() {
+        info!("seeded customer {} ({})", customer.email, customer.id);
+    }
+    info!("seed completed; customers can sign in with password `{CUSTOMER_PASSWORD}`");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_parse_seed_options() {
+        let options = SeedOptions::parse(
+            ["--customers", "5", "--seed", "42", "--reset"]
+                .into_iter()
+                .map(String::from),
+        )
+        .unwrap();
+        assert_eq!(options.customers, 5);
+        assert_eq!(options.seed, 42);
+        assert!(options.reset);
+        assert_eq!(options.orders_per_customer, 3);
+        assert!(SeedOptions::parse(["--customers".to_string()]).is_err());
+        assert!(SeedOptions::parse(["--customers", "many"].into_iter().map(String::from)).is_err());
+        assert!(SeedOptions::parse(["--unknown", "1"].into_iter().map(String::from)).is_err());
+    }
+
+    #[test]
+    fn should_parse_csv() {
+        assert_eq!(
+            parse_csv("Name;Surname\nAbigail;Jones\n\nAdam; Wong\n").unwrap(),
+            vec![
+                ("Abigail".to_string(), "Jones".to_string()),
+                ("Adam".to_string(), "Wong".to_string())
+            ]
+        );
+        assert!(parse_csv("Name;Surname\nAbigail\n").is_err());
+    }
+
+    #[test]
+    fn should_generate_same_fixtures_with_same_seed() {
+        let options = SeedOptions {
+            customers: 10,
+            ..SeedOptions::default()
+        };
+        let fixtures = Fixtures::generate(articles(), names(), &options).unwrap();
+        assert_eq!(
+            fixtures,
+            Fixtures::generate(articles(), names(), &options).unwrap()
+        );
+        assert_eq!(fixtures.articles.len(), 3);
+        assert_eq!(fixtures.customers.len(), 10);
+        assert_eq!(fixtures.customers.iter().collect::<HashSet<_>>().len(), 10);
+        assert_eq!(fixtures.orders.len(), 30);
+        assert!(fixtures.orders.iter().all(|x| x.articles.len() == 3));
+
+        let other_seed = SeedOptions { seed: 2, ..options };
+        assert_ne!(
+            fixtures,
+            Fixtures::generate(articles(), names(), &other_seed).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_not_generate_more_customers_than_names_allow() {
+        let options = SeedOptions {
+            customers: 56,
+            ..SeedOptions::default()
+        };
+        assert!(Fixtures::generate(articles(), vec![names().remove(0)], &options).is_err());
+    }
+
+    fn articles() -> Vec<(String, String)> {
+        ["Mug", "Pen", "Notebook"]
+            .into_iter()
+            .map(|x| (x.to_string(), format!("A {x}")))
+            .collect()
+    }
+
+    fn names() -> Vec<(String, String)> {
+        vec![
+            ("Abigail".to_string(), "Jones".to_string()),
+            ("Adam".to_string(), "Wong".to_string()),
+        ]
+    }
+}
@@ -0,0 +1,53 @@
+//! # Cursor
+//!
+//! Opaque cursors for keyset pagination. A cursor holds the sort key of the last item of a page;
+//! it is serialized and hex encoded so that clients only pass it back.
+
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+/// The cursor was not returned by `encode`, or it holds the key of another kind of item
+#[derive(Debug, Error, Eq, PartialEq)]
+#[error("invalid cursor {0}")]
+pub struct CursorError(String);
+
+/// Encode the sort key of an item into a cursor
+pub fn encode<K: Serialize>(key: &K) -> String {
+    hex::encode(serde_json::to_vec(key).expect("cursor keys are serializable"))
+}
+
+/// Decode the sort key of a cursor returned by `encode`
+pub fn decode<K: DeserializeOwned>(cursor: &str) -> Result<K, CursorError> {
+    hex::decode(cursor)
+        .ok()
+        .and_then(|key| serde_json::from_slice(&key).ok())
+        .ok_or_else(|| CursorError(cursor.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use chrono::NaiveDateTime;
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    #[test]
+    fn should_encode_and_decode_cursor() {
+        let key = (
+            NaiveDateTime::from_timestamp_opt(1680000000, 123456000).unwrap(),
+            Uuid::new_v4(),
+        );
+        assert_eq!(decode::<(NaiveDateTime, Uuid)>(&encode(&key)).unwrap(), key);
+    }
+
+    #[test]
+    fn should_not_decode_invalid_cursor() {
+        assert_eq!(
+            decode::<(String, Uuid)>("not a cursor"),
+            Err(CursorError("not a cursor".to_string()))
+        );
+        assert!(decode::<(String, Uuid)>(&encode(&("mug", 1))).is_err());
+    }
+}
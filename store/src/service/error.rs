@@ -1,3 +1,6 @@
+use prost::Message;
+use sqlx::postgres::PgDatabaseError;
+use std::collections::HashMap;
 use thiserror::Error;
 use tonic::transport::Error as TonicError;
 
@@ -10,6 +13,10 @@ pub enum ServiceError {
     Tonic(TonicError),
     #[error("invalid listener address")]
     InvalidAddress,
+    #[error("io error: {0}")]
+    Io(std::io::Error),
+    #[error("invalid outbox sink: {0}")]
+    InvalidOutboxSink(String),
 }
 
 impl From<crate::database::DatabaseError> for ServiceError {
@@ -24,10 +31,166 @@ impl From<TonicError> for ServiceError {
     }
 }
 
+impl From<std::io::Error> for ServiceError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+// -- rpc errors
+
+/// Domain of the errors raised by the store service
+pub const ERROR_DOMAIN: &str = "store";
+
+const UNIQUE_VIOLATION: &str = "23505";
+const FOREIGN_KEY_VIOLATION: &str = "23503";
+
+/// Error of a store service call; it is sent as a status with its gRPC code and an `ErrorInfo`,
+/// with the reason and the metadata of the error, encoded in the status details
+#[derive(Debug, Error)]
+pub enum RpcError {
+    #[error("invalid {field}: {description}")]
+    InvalidArgument { field: String, description: String },
+    #[error("{resource} {id} not found")]
+    NotFound { resource: String, id: String },
+    #[error("failed precondition: {0}")]
+    FailedPrecondition(String),
+    #[error("{resource} already exists")]
+    AlreadyExists { resource: String },
+    /// Unexpected error; its cause is logged, not sent to the caller
+    #[error("internal error")]
+    Internal,
+}
+
+impl RpcError {
+    /// Invalid value of a field of the request
+    pub fn invalid_argument(field: &str, description: impl ToString) -> Self {
+        Self::InvalidArgument {
+            field: field.to_string(),
+            description: description.to_string(),
+        }
+    }
+
+    /// Missing field of the request
+    pub fn missing(field: &str) -> Self {
+        Self::invalid_argument(field, "missing value")
+    }
+
+    /// Resource of a request which doesn't exist
+    pub fn not_found(resource: &str, id: impl ToString) -> Self {
+        Self::NotFound {
+            resource: resource.to_string(),
+            id: id.to_string(),
+        }
+    }
+
+    fn code(&self) -> tonic::Code {
+        match self {
+            Self::InvalidArgument { .. } => tonic::Code::InvalidArgument,
+            Self::NotFound { .. } => tonic::Code::NotFound,
+            Self::FailedPrecondition(_) => tonic::Code::FailedPrecondition,
+            Self::AlreadyExists { .. } => tonic::Code::AlreadyExists,
+            Self::Internal => tonic::Code::Internal,
+        }
+    }
+
+    fn error_info(&self) -> super::store::ErrorInfo {
+        let (reason, metadata) = match self {
+            Self::InvalidArgument { field, .. } => {
+                ("INVALID_ARGUMENT", HashMap::from([("field", field)]))
+            }
+            Self::NotFound { resource, id } => (
+                "NOT_FOUND",
+                HashMap::from([("resource", resource), ("id", id)]),
+            ),
+            Self::FailedPrecondition(_) => ("FAILED_PRECONDITION", HashMap::new()),
+            Self::AlreadyExists { resource } => {
+                ("ALREADY_EXISTS", HashMap::from([("resource", resource)]))
+            }
+            Self::Internal => ("INTERNAL", HashMap::new()),
+        };
+
+        super::store::ErrorInfo {
+            reason: reason.to_string(),
+            domain: ERROR_DOMAIN.to_string(),
+            metadata: metadata
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl From<RpcError> for tonic::Status {
+    fn from(value: RpcError) -> Self {
+        let details = value.error_info().encode_to_vec();
+        Self::with_details(value.code(), value.to_string(), details.into())
+    }
+}
+
+impl From<crate::database::DatabaseError> for RpcError {
+    fn from(value: crate::database::DatabaseError) -> Self {
+        let constraint_violation = match &value {
+            crate::database::DatabaseError::Db(sqlx::Error::Database(err)) => err
+                .try_downcast_ref::<PgDatabaseError>()
+                .map(|err| (err.code().to_string(), err.table().unwrap_or("record"))),
+            _ => None,
+        };
+        match constraint_violation {
+            Some((code, table)) if code == UNIQUE_VIOLATION => Self::AlreadyExists {
+                resource: table.to_string(),
+            },
+            Some((code, table)) if code == FOREIGN_KEY_VIOLATION => {
+                Self::FailedPrecondition(format!("{table} references a missing resource"))
+            }
+            _ => {
+                error!("{value}");
+                Self::Internal
+            }
+        }
+    }
+}
+
 // -- database error to tonic status
 
 impl From<crate::database::DatabaseError> for tonic::Status {
     fn from(value: crate::database::DatabaseError) -> Self {
-        Self::new(tonic::Code::Internal, value.to_string())
+        RpcError::from(value).into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_send_error_info_in_status_details() {
+        let status = tonic::Status::from(RpcError::invalid_argument("user_id", "bad uuid"));
+
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(status.message(), "invalid user_id: bad uuid");
+        let info = super::super::store::ErrorInfo::decode(status.details()).unwrap();
+        assert_eq!(info.reason, "INVALID_ARGUMENT");
+        assert_eq!(info.domain, ERROR_DOMAIN);
+        assert_eq!(
+            info.metadata.get("field").map(String::as_str),
+            Some("user_id")
+        );
+    }
+
+    #[test]
+    fn should_not_leak_database_errors() {
+        let status = tonic::Status::from(crate::database::DatabaseError::Db(
+            sqlx::Error::PoolTimedOut,
+        ));
+
+        assert_eq!(status.code(), tonic::Code::Internal);
+        assert_eq!(status.message(), "internal error");
+        let info = super::super::store::ErrorInfo::decode(status.details()).unwrap();
+        assert_eq!(info.reason, "INTERNAL");
+        assert!(info.metadata.is_empty());
     }
 }
@@ -2,79 +2,1132 @@
 //!
 //! gRPC service
 
+mod cursor;
 mod error;
 pub mod store {
     tonic::include_proto!("store");
 }
-use crate::database::{Article, Customer, CustomerOrder, OrderArticle, OrderStatus, StoreDb};
-pub use error::ServiceError;
+use crate::config::Config;
+use crate::database::{
+    Article, ArticleReview, Cart, Customer, CustomerOrder, DatabaseResult, Invoice, InvoiceLine,
+    InvoiceSeller, OrderEvent, OrderEventActor, OrderEventKind, OrderFilter, OrderStatus, Refund,
+    ReportGranularity, ReturnStatus, ReviewStatus, Shipment, ShippingMethod, StoreDb, Wishlist,
+};
+use crate::events::{
+    DomainEvent, EventSink, FileSink, OutboxRelay, StdoutSink, SubmittedArticle, WebhookSink,
+};
+use crate::payment::{MockPaymentGateway, PaymentGateway, PaymentOutcome, PaymentRequest};
+use crate::recommendation::RecommendationBuilder;
+use crate::repository::{
+    ArticleTransaction, CartTransaction, InvoiceTransaction, OrderTransaction, PostgresRepository,
+    Repository, RepositoryTransaction, ReturnTransaction, ReviewTransaction, WishlistTransaction,
+};
+pub use error::{RpcError, ServiceError};
 use store::store_service_server::{
     StoreService as ProtobufStoreService, StoreServiceServer as ProtobufStoreServiceServer,
 };
 
+use chrono::{NaiveDateTime, Utc};
 use email_address::EmailAddress;
-use sha2::{Digest, Sha256};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::{self, Sender, UnboundedReceiver};
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
 use tonic::{transport::Server as GrpcServer, Request, Response, Status};
 use uuid::Uuid;
 
 /// Result type for StoreService
 pub type StoreResult<T> = Result<T, ServiceError>;
 
+/// Maximum amount of orders expired by a single query
+const ORDER_EXPIRY_BATCH_SIZE: i64 = 100;
+/// Maximum amount of outbox events relayed by a single run of the relay
+const OUTBOX_RELAY_BATCH_SIZE: i64 = 100;
+/// How long the outbox events claimed by a relay are reserved to it; it bounds the delivery of a
+/// batch, after which the events are claimed again
+const OUTBOX_RELAY_LEASE: Duration = Duration::from_secs(60);
+/// Recommended articles returned when the request doesn't set a limit
+const DEFAULT_RECOMMENDATIONS: i64 = 5;
+/// Maximum amount of recommended articles returned by a single request
+const MAX_RECOMMENDATIONS: i64 = 50;
+/// Articles in the top articles report when the request doesn't set a limit
+const DEFAULT_TOP_ARTICLES: i64 = 10;
+/// Maximum amount of articles in a single top articles report
+const MAX_TOP_ARTICLES: i64 = 100;
+/// Orders read by a single query while exporting orders; also bounds the orders waiting to be
+/// sent to the client
+const ORDER_EXPORT_BATCH_SIZE: i64 = 100;
+/// Maximum amount of articles or orders in a single page
+const MAX_RESULTS_PER_PAGE: u32 = 100;
+/// Maximum quantity of a single article in an order
+const MAX_ORDER_ARTICLE_QUANTITY: u32 = 1000;
+/// Imported articles upserted by a single query
+const ARTICLE_IMPORT_BATCH_SIZE: usize = 100;
+/// Articles read by a single query while exporting the catalog; also bounds the articles waiting
+/// to be sent to the client
+const ARTICLE_EXPORT_BATCH_SIZE: i64 = 100;
+
 #[derive(Debug)]
-pub struct StoreService {
+pub struct StoreService<R: Repository = PostgresRepository> {
     address: SocketAddr,
-    database: StoreDb,
+    /// Store data, read and written through Postgres or, in tests, kept in memory
+    repository: R,
+    payment_gateway: Arc<dyn PaymentGateway>,
+    payment_outcomes: Option<UnboundedReceiver<PaymentOutcome>>,
+    order_ttl: Duration,
+    order_expiry_interval: Duration,
+    outbox_relay: Option<OutboxRelay>,
+    recommendation_builder: Option<RecommendationBuilder>,
+    invoice_seller: InvoiceSeller,
 }
 
 impl StoreService {
     /// Configure and initialize store service
-    pub async fn configure(listener_address: &str, database_url: &str) -> StoreResult<Self> {
-        debug!("parsing address {listener_address}...");
-        let address = listener_address
+    pub async fn configure(config: &Config) -> StoreResult<Self> {
+        debug!("parsing address {}...", config.server_url);
+        let address = config
+            .server_url
             .parse()
             .map_err(|_| ServiceError::InvalidAddress)?;
         debug!("parsed address {:?}", address);
-        debug!("connecting to database at {database_url}");
-        let database = StoreDb::connect(database_url).await?;
+        debug!("connecting to database at {}", config.database_url);
+        let database = StoreDb::connect(&config.database_url).await?;
+        debug!(
+            "initializing mock payment gateway with callback delay {}ms",
+            config.payment_callback_delay_ms
+        );
+        let (payment_notifier, payment_outcomes) = mpsc::unbounded_channel();
+        let payment_gateway = Arc::new(MockPaymentGateway::new(
+            Duration::from_millis(config.payment_callback_delay_ms),
+            payment_notifier,
+        ));
+        debug!("initializing outbox relay with {} sink", config.outbox_sink);
+        let outbox_relay = OutboxRelay::new(
+            database.clone(),
+            Self::outbox_sink(config)?,
+            Duration::from_millis(config.outbox_relay_interval_ms),
+            OUTBOX_RELAY_BATCH_SIZE,
+            OUTBOX_RELAY_LEASE,
+        );
+        let recommendation_builder = RecommendationBuilder::new(
+            database.clone(),
+            Duration::from_secs(config.recommendation_rebuild_interval_secs),
+        );
         info!("store service initialized");
-        Ok(Self { address, database })
+        Ok(Self {
+            address,
+            repository: PostgresRepository::new(database),
+            payment_gateway,
+            payment_outcomes: Some(payment_outcomes),
+            order_ttl: Duration::from_secs(config.order_ttl_secs),
+            order_expiry_interval: Duration::from_secs(config.order_expiry_interval_secs),
+            outbox_relay: Some(outbox_relay),
+            recommendation_builder: Some(recommendation_builder),
+            invoice_seller: InvoiceSeller {
+                name: config.invoice_seller_name.clone(),
+                address: config.invoice_seller_address.clone(),
+                vat_number: config.invoice_seller_vat_number.clone(),
+                tax_rate: config.invoice_tax_rate,
+            },
+        })
+    }
+
+    /// Instantiate the outbox sink selected in configuration
+    fn outbox_sink(config: &Config) -> StoreResult<Box<dyn EventSink>> {
+        match config.outbox_sink.as_str() {
+            "stdout" => Ok(Box::new(StdoutSink)),
+            "file" => match &config.outbox_file_path {
+                Some(path) => Ok(Box::new(FileSink::new(path))),
+                None => Err(ServiceError::InvalidOutboxSink(
+                    "file sink requires OUTBOX_FILE_PATH".to_string(),
+                )),
+            },
+            "webhook" => match &config.outbox_webhook_url {
+                Some(url) => WebhookSink::new(url)
+                    .map(|sink| Box::new(sink) as Box<dyn EventSink>)
+                    .map_err(|e| ServiceError::InvalidOutboxSink(e.to_string())),
+                None => Err(ServiceError::InvalidOutboxSink(
+                    "webhook sink requires OUTBOX_WEBHOOK_URL".to_string(),
+                )),
+            },
+            other => Err(ServiceError::InvalidOutboxSink(format!(
+                "unknown sink '{other}'"
+            ))),
+        }
     }
+}
 
-    /// Run store service server
+impl<R: Repository> StoreService<R> {
+    /// Run store service server on the configured address
     pub async fn run(self) -> StoreResult<()> {
-        info!("running server...");
-        let address = self.address;
+        let listener = TcpListener::bind(self.address).await?;
+        self.run_with_listener(listener).await
+    }
+
+    /// Run store service server, accepting the connections of `listener`
+    pub async fn run_with_listener(mut self, listener: TcpListener) -> StoreResult<()> {
+        if let Some(payment_outcomes) = self.payment_outcomes.take() {
+            info!("starting payment outcomes listener");
+            tokio::spawn(Self::process_payment_outcomes(
+                self.repository.clone(),
+                self.invoice_seller.clone(),
+                payment_outcomes,
+            ));
+        }
+        if let Some(outbox_relay) = self.outbox_relay.take() {
+            info!("starting outbox relay");
+            tokio::spawn(outbox_relay.run());
+        }
+        if let Some(recommendation_builder) = self.recommendation_builder.take() {
+            info!("starting recommendation builder");
+            tokio::spawn(recommendation_builder.run());
+        }
+        info!(
+            "starting unpaid orders expiry job (ttl: {:?}, interval: {:?})",
+            self.order_ttl, self.order_expiry_interval
+        );
+        tokio::spawn(Self::expire_unpaid_orders(
+            self.repository.clone(),
+            self.order_ttl,
+            self.order_expiry_interval,
+        ));
+        info!("running server on {:?}...", listener.local_addr());
         GrpcServer::builder()
             .add_service(ProtobufStoreServiceServer::new(self))
-            .serve(address)
+            .serve_with_incoming(TcpListenerStream::new(listener))
             .await?;
 
         warn!("server terminated!");
         Ok(())
     }
 
-    fn hash_password(&self, s: &str) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(s.as_bytes());
-        let result = hasher.finalize();
-        hex::encode(result)
+    /// Apply the payment outcomes reported by the payment gateway
+    async fn process_payment_outcomes(
+        repository: R,
+        invoice_seller: InvoiceSeller,
+        mut payment_outcomes: UnboundedReceiver<PaymentOutcome>,
+    ) {
+        while let Some(outcome) = payment_outcomes.recv().await {
+            debug!("got payment outcome {:?}", outcome);
+            let result = match &outcome {
+                PaymentOutcome::Succeeded {
+                    order_id,
+                    transaction_id,
+                } => {
+                    Self::payment_succeeded(&repository, &invoice_seller, order_id, transaction_id)
+                        .await
+                }
+                PaymentOutcome::Failed { order_id } => {
+                    Self::payment_failed(&repository, order_id).await
+                }
+            };
+            if let Err(err) = result {
+                error!("could not apply payment outcome {:?}: {err}", outcome);
+            }
+        }
+        warn!("payment outcomes listener terminated");
+    }
+
+    /// Periodically set to `Expired` the orders which have not been paid within `ttl`
+    async fn expire_unpaid_orders(repository: R, ttl: Duration, interval: Duration) {
+        let ttl = match chrono::Duration::from_std(ttl) {
+            Ok(ttl) => ttl,
+            Err(err) => {
+                error!("invalid order ttl {:?}: {err}", ttl);
+                return;
+            }
+        };
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            let created_before = Utc::now().naive_utc() - ttl;
+            if let Err(err) = Self::expire_orders_created_before(&repository, created_before).await
+            {
+                error!("could not expire unpaid orders: {err}");
+            }
+        }
+    }
+
+    /// Expire all the unpaid orders created before `created_before`, in batches
+    async fn expire_orders_created_before(
+        repository: &R,
+        created_before: NaiveDateTime,
+    ) -> DatabaseResult<()> {
+        loop {
+            let expired = repository
+                .expire_orders_created_before(created_before, ORDER_EXPIRY_BATCH_SIZE)
+                .await?;
+            for order in expired.iter() {
+                info!(
+                    "order {} of customer {} expired: not paid since {}",
+                    order.id, order.customer_id, order.created_at
+                );
+            }
+            if (expired.len() as i64) < ORDER_EXPIRY_BATCH_SIZE {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Lock the order until the end of `transaction` and check that it is still waiting for its
+    /// payment, so that a late or duplicate payment outcome can't change an expired, refused or
+    /// paid order.
+    ///
+    /// Returns `false` if the outcome has already been applied to the order according to `applied`,
+    /// so that a redelivered outcome is acknowledged without effect
+    async fn lock_unpaid_order(
+        transaction: &mut R::Transaction,
+        order_id: &Uuid,
+        applied: impl FnOnce(&CustomerOrder) -> bool + Send,
+    ) -> Result<bool, RpcError> {
+        let order = transaction
+            .find_order_for_update(order_id)
+            .await?
+            .ok_or_else(|| RpcError::not_found("order", order_id))?;
+        if order.status != OrderStatus::Created {
+            if applied(&order) {
+                debug!("payment outcome already applied to order {order_id}");
+                return Ok(false);
+            }
+            return Err(RpcError::FailedPrecondition(format!(
+                "order {order_id} is not waiting for payment: {:?}",
+                order.status
+            )));
+        }
+
+        Ok(true)
+    }
+
+    /// Set order status from `Created` to `PaymentRefused`
+    async fn payment_failed(repository: &R, order_id: &Uuid) -> Result<(), RpcError> {
+        debug!("setting order status to PaymentRefused and for order {order_id}");
+        let mut transaction = repository.begin().await?;
+        if !Self::lock_unpaid_order(&mut transaction, order_id, |order| {
+            order.status == OrderStatus::PaymentRefused
+        })
+        .await?
+        {
+            return Ok(());
+        }
+        transaction
+            .update_order_status(
+                order_id,
+                OrderStatus::PaymentRefused,
+                OrderEventActor::PaymentGateway,
+            )
+            .await?;
+        transaction
+            .publish_event(&DomainEvent::PaymentFailed {
+                order_id: *order_id,
+            })
+            .await?;
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Set order status from `Created` to `Preparing`, store the payment transaction id and issue
+    /// the invoice
+    async fn payment_succeeded(
+        repository: &R,
+        invoice_seller: &InvoiceSeller,
+        order_id: &Uuid,
+        transaction_id: &str,
+    ) -> Result<(), RpcError> {
+        debug!("setting order status to Preparing and transaction id to {transaction_id} for order {order_id}");
+        // create transaction
+        let mut transaction = repository.begin().await?;
+        if !Self::lock_unpaid_order(&mut transaction, order_id, |order| {
+            order.transaction_id.as_deref() == Some(transaction_id)
+        })
+        .await?
+        {
+            return Ok(());
+        }
+        // update both status and transaction id
+        transaction
+            .update_order_status(
+                order_id,
+                OrderStatus::Preparing,
+                OrderEventActor::PaymentGateway,
+            )
+            .await?;
+        transaction
+            .update_order_transaction_id(order_id, transaction_id, OrderEventActor::PaymentGateway)
+            .await?;
+        // the order leaves `Created` only once, so it gets a single invoice
+        let invoice = transaction
+            .issue_invoice(order_id, invoice_seller, Utc::now().naive_utc())
+            .await?;
+        info!("issued invoice {} for order {order_id}", invoice.code());
+        transaction
+            .publish_event(&DomainEvent::PaymentSucceeded {
+                order_id: *order_id,
+                transaction_id: transaction_id.to_string(),
+            })
+            .await?;
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Place an order for the customer with the provided articles and quantities.
+    /// If `cart_id` is set, the cart is emptied in the same transaction which inserts the order
+    async fn place_order(
+        &self,
+        user_id: &Uuid,
+        articles: Vec<(Uuid, u32)>,
+        shipping_method_id: Option<Uuid>,
+        card_number: Option<&str>,
+        cart_id: Option<&Uuid>,
+    ) -> Result<store::SubmitOrderResponse, Status> {
+        debug!("submitting order for customer with id {user_id}");
+        // resolve current unit price and weight for each article
+        let mut order_articles = Vec::with_capacity(articles.len());
+        let mut subtotal = Decimal::ZERO;
+        let mut weight: i64 = 0;
+        for (article_id, quantity) in articles.into_iter() {
+            let stock_article = match self.repository.find_article_by_id(&article_id).await? {
+                Some(a) if !a.is_archived() => a,
+                _ => {
+                    return Ok(store::SubmitOrderResponse {
+                        status: Some(store::submit_order_response::Status::Error(1)),
+                    })
+                }
+            };
+            subtotal += stock_article.unit_price * Decimal::from(quantity);
+            weight += stock_article.weight as i64 * quantity as i64;
+            order_articles.push((stock_article, quantity as i32));
+        }
+        // compute shipping cost
+        let shipping_cost = match shipping_method_id {
+            None => Decimal::ZERO,
+            Some(shipping_method_id) => {
+                let rules = self
+                    .repository
+                    .find_shipping_cost_rules(&shipping_method_id)
+                    .await?;
+                match ShippingMethod::cost_for(&rules, weight, subtotal) {
+                    Some(cost) => cost,
+                    None => {
+                        debug!("shipping method {shipping_method_id} is not available for order with weight {weight} and subtotal {subtotal}");
+                        return Ok(store::SubmitOrderResponse {
+                            status: Some(store::submit_order_response::Status::Error(2)),
+                        });
+                    }
+                }
+            }
+        };
+        debug!("shipping cost for order: {shipping_cost}");
+        // start transaction
+        let mut transaction = self.repository.begin().await?;
+        // insert order
+        let order = transaction
+            .insert_order(user_id, shipping_method_id.as_ref(), shipping_cost)
+            .await?;
+        debug!("inserted order with ID {}", order.id.to_string());
+        // insert for each article a order-article in the database
+        let mut submitted_articles = Vec::with_capacity(order_articles.len());
+        for (stock_article, quantity) in order_articles.into_iter() {
+            debug!(
+                "inserting new article for order {}: {}",
+                order.id.to_string(),
+                stock_article.id
+            );
+            transaction
+                .insert_order_article(
+                    &order.id,
+                    &stock_article.id,
+                    quantity,
+                    stock_article.unit_price,
+                )
+                .await?;
+            submitted_articles.push(SubmittedArticle {
+                article_id: stock_article.id,
+                quantity,
+                unit_price: stock_article.unit_price,
+            });
+        }
+        transaction
+            .publish_event(&DomainEvent::OrderSubmitted {
+                order_id: order.id,
+                customer_id: *user_id,
+                articles: submitted_articles,
+                shipping_method_id,
+                shipping_cost,
+                total: subtotal + shipping_cost,
+            })
+            .await?;
+        // the articles have been ordered, so they leave the cart
+        if let Some(cart_id) = cart_id {
+            transaction.clear_cart(cart_id).await?;
+        }
+        debug!("all articles have been stored in the database; committing transaction...");
+        transaction.commit().await?;
+        // initiate payment
+        if let Some(card_number) = card_number {
+            let payment = PaymentRequest {
+                order_id: order.id,
+                amount: subtotal + shipping_cost,
+                card_number: card_number.to_string(),
+            };
+            debug!(
+                "initiating payment of {} for order {}",
+                payment.amount, order.id
+            );
+            if let Err(err) = self.payment_gateway.initiate(payment).await {
+                error!("could not initiate payment for order {}: {err}", order.id);
+            }
+        }
+
+        Ok(store::SubmitOrderResponse {
+            status: Some(store::submit_order_response::Status::OrderId(
+                order.id.to_string(),
+            )),
+        })
+    }
+
+    /// Validate the articles of an order, merging the lines of the same article. An order must have
+    /// some articles, each with a quantity from 1 to `MAX_ORDER_ARTICLE_QUANTITY`
+    fn merge_order_articles(
+        articles: Vec<(Uuid, u32)>,
+    ) -> Result<Vec<(Uuid, u32)>, store::submit_order_response::SubmitOrderError> {
+        if articles.is_empty() {
+            return Err(store::submit_order_response::SubmitOrderError::EmptyOrder);
+        }
+        let mut merged: Vec<(Uuid, u32)> = Vec::with_capacity(articles.len());
+        for (article_id, quantity) in articles.into_iter() {
+            if quantity == 0 {
+                return Err(store::submit_order_response::SubmitOrderError::InvalidQuantity);
+            }
+            match merged.iter_mut().find(|(id, _)| *id == article_id) {
+                Some((_, total)) => *total = total.saturating_add(quantity),
+                None => merged.push((article_id, quantity)),
+            }
+        }
+        if merged
+            .iter()
+            .any(|(_, quantity)| *quantity > MAX_ORDER_ARTICLE_QUANTITY)
+        {
+            return Err(store::submit_order_response::SubmitOrderError::InvalidQuantity);
+        }
+
+        Ok(merged)
+    }
+
+    /// Get the cart of the owner, creating it if it doesn't exist yet.
+    /// Returns `None` if the anonymous cart id belongs to a customer's cart
+    async fn owner_cart(&self, owner: &Option<store::CartOwner>) -> Result<Option<Cart>, RpcError> {
+        match owner.as_ref().and_then(|x| x.owner.as_ref()) {
+            Some(store::cart_owner::Owner::UserId(user_id)) => {
+                let user_id = Self::parse_uuid(user_id, "user_id")?;
+                Ok(Some(
+                    self.repository
+                        .get_or_create_customer_cart(&user_id)
+                        .await?,
+                ))
+            }
+            Some(store::cart_owner::Owner::AnonymousCartId(cart_id)) => {
+                let cart_id = Self::parse_uuid(cart_id, "anonymous_cart_id")?;
+                Ok(self
+                    .repository
+                    .get_or_create_anonymous_cart(&cart_id)
+                    .await?)
+            }
+            None => Err(RpcError::missing("owner")),
+        }
+    }
+
+    /// Convert a `Cart` into its protobuf representation, resolving its articles and subtotal
+    async fn cart_to_proto(&self, cart: &Cart) -> Result<store::CartResponse, RpcError> {
+        let items = self.repository.find_cart_items(&cart.id).await?;
+        debug!("got {} articles in cart {}", items.len(), cart.id);
+        let mut articles = Vec::with_capacity(items.len());
+        let mut subtotal = Decimal::ZERO;
+        for item in items.into_iter() {
+            if let Some(article) = self.repository.find_article_by_id(&item.article_id).await? {
+                subtotal += article.unit_price * Decimal::from(item.quantity);
+                articles.push(store::OrderArticle {
+                    id: article.id.to_string(),
+                    name: article.name,
+                    description: article.description,
+                    quantity: item.quantity as u32,
+                    unit_price: Some(store::Decimal {
+                        value: article.unit_price.to_string(),
+                    }),
+                })
+            } else {
+                warn!("could not find any article for {}", item.article_id);
+            }
+        }
+
+        Ok(store::CartResponse {
+            status: Some(store::cart_response::Status::Cart(store::Cart {
+                id: cart.id.to_string(),
+                articles,
+                subtotal: Some(store::Decimal {
+                    value: subtotal.to_string(),
+                }),
+            })),
+        })
+    }
+
+    /// Convert an `Article` into its protobuf representation, resolving its rating
+    async fn article_to_proto(&self, article: Article) -> Result<store::Article, Status> {
+        let rating = self
+            .repository
+            .find_article_rating(&article.id)
+            .await?
+            .filter(|x| x.count > 0)
+            .map(|x| store::ArticleRating {
+                average: Some(store::Decimal {
+                    value: x.average.to_string(),
+                }),
+                count: x.count as u32,
+            });
+
+        Ok(store::Article {
+            id: article.id.to_string(),
+            name: article.name,
+            description: article.description,
+            unit_price: Some(store::Decimal {
+                value: article.unit_price.to_string(),
+            }),
+            rating,
+        })
+    }
+
+    /// Convert a `Wishlist` into its protobuf representation, resolving its articles.
+    /// Archived articles are reported as unavailable
+    async fn wishlist_to_proto(&self, wishlist: Wishlist) -> Result<store::Wishlist, Status> {
+        let items = self.repository.find_wishlist_items(&wishlist.id).await?;
+        debug!("got {} articles in wishlist {}", items.len(), wishlist.id);
+        let mut articles = Vec::with_capacity(items.len());
+        for item in items.into_iter() {
+            if let Some(article) = self.repository.find_article_by_id(&item.article_id).await? {
+                articles.push(store::WishlistArticle {
+                    id: article.id.to_string(),
+                    available: !article.is_archived(),
+                    name: article.name,
+                    description: article.description,
+                    unit_price: Some(store::Decimal {
+                        value: article.unit_price.to_string(),
+                    }),
+                    added_at: Some(Self::iso8601(&item.added_at)),
+                })
+            } else {
+                warn!("could not find any article for {}", item.article_id);
+            }
+        }
+
+        Ok(store::Wishlist {
+            id: wishlist.id.to_string(),
+            name: wishlist.name,
+            articles,
+            created_at: Some(Self::iso8601(&wishlist.created_at)),
+        })
+    }
+
+    /// Convert a `CustomerOrder` into its protobuf representation, resolving its articles and shipping details
+    async fn order_to_proto(repository: &R, order: CustomerOrder) -> Result<store::Order, Status> {
+        debug!("collecting articles for order {}", order.id);
+        let order_articles = repository.find_order_articles(&order.id).await?;
+        debug!("got {} articles in order", order_articles.len());
+        // resolve article type
+        let mut articles = Vec::with_capacity(order_articles.len());
+        for order_article in order_articles.into_iter() {
+            debug!("getting article details for article {}", order_article.id);
+            if let Some(article) = repository
+                .find_article_by_id(&order_article.article_id)
+                .await?
+            {
+                articles.push(store::OrderArticle {
+                    id: article.id.to_string(),
+                    name: article.name,
+                    description: article.description,
+                    quantity: order_article.quantity as u32,
+                    unit_price: Some(store::Decimal {
+                        value: order_article.unit_price.to_string(),
+                    }),
+                })
+            } else {
+                warn!("could not find any article for {}", order_article.id);
+            }
+        }
+        // resolve shipping
+        let shipping_method = match order.shipping_method_id {
+            Some(shipping_method_id) => {
+                repository.find_shipping_method(&shipping_method_id).await?
+            }
+            None => None,
+        };
+        let shipment = repository.find_order_shipment(&order.id).await?;
+
+        Ok(store::Order {
+            id: order.id.to_string(),
+            created_at: Some(Self::iso8601(&order.created_at)),
+            transaction_id: order.transaction_id,
+            status: Self::order_status_to_proto(order.status),
+            articles,
+            shipping_method: shipping_method.map(Self::shipping_method_to_proto),
+            shipping_cost: Some(store::Decimal {
+                value: order.shipping_cost.to_string(),
+            }),
+            shipment: shipment.map(Self::shipment_to_proto),
+        })
+    }
+
+    fn order_status_to_proto(status: OrderStatus) -> i32 {
+        match status {
+            OrderStatus::Created => 0,
+            OrderStatus::PaymentRefused => 2,
+            OrderStatus::Preparing => 1,
+            OrderStatus::Shipped => 3,
+            OrderStatus::ReturnRequested => 4,
+            OrderStatus::Refunded => 5,
+            OrderStatus::PartiallyRefunded => 6,
+            OrderStatus::Expired => 7,
+        }
+    }
+
+    fn order_status_from_proto(status: store::order::OrderStatus) -> OrderStatus {
+        match status {
+            store::order::OrderStatus::Created => OrderStatus::Created,
+            store::order::OrderStatus::PaymentFailed => OrderStatus::PaymentRefused,
+            store::order::OrderStatus::Preparing => OrderStatus::Preparing,
+            store::order::OrderStatus::Shipped => OrderStatus::Shipped,
+            store::order::OrderStatus::ReturnRequested => OrderStatus::ReturnRequested,
+            store::order::OrderStatus::Refunded => OrderStatus::Refunded,
+            store::order::OrderStatus::PartiallyRefunded => OrderStatus::PartiallyRefunded,
+            store::order::OrderStatus::Expired => OrderStatus::Expired,
+        }
+    }
+
+    fn review_to_proto(review: ArticleReview) -> store::ArticleReview {
+        store::ArticleReview {
+            id: review.id.to_string(),
+            article_id: review.article_id.to_string(),
+            rating: review.rating as u32,
+            title: review.title,
+            body: review.body,
+            status: match review.status {
+                ReviewStatus::Pending => 0,
+                ReviewStatus::Approved => 1,
+                ReviewStatus::Rejected => 2,
+            },
+            created_at: Some(Self::iso8601(&review.created_at)),
+        }
+    }
+
+    fn order_event_to_proto(event: OrderEvent) -> store::OrderEvent {
+        store::OrderEvent {
+            id: event.id.to_string(),
+            kind: match event.kind {
+                OrderEventKind::Created => 0,
+                OrderEventKind::StatusChanged => 1,
+                OrderEventKind::TransactionChanged => 2,
+            },
+            actor: match event.actor {
+                OrderEventActor::Customer => 0,
+                OrderEventActor::PaymentGateway => 1,
+                OrderEventActor::BackOffice => 2,
+                OrderEventActor::System => 3,
+            },
+            status: Self::order_status_to_proto(event.status),
+            transaction_id: event.transaction_id,
+            created_at: Some(Self::iso8601(&event.created_at)),
+        }
+    }
+
+    fn shipping_method_to_proto(shipping_method: ShippingMethod) -> store::ShippingMethod {
+        store::ShippingMethod {
+            id: shipping_method.id.to_string(),
+            name: shipping_method.name,
+            description: shipping_method.description,
+        }
+    }
+
+    fn shipment_to_proto(shipment: Shipment) -> store::Shipment {
+        store::Shipment {
+            carrier: shipment.carrier,
+            tracking_code: shipment.tracking_code,
+            shipped_at: Some(Self::iso8601(&shipment.shipped_at)),
+        }
+    }
+
+    fn refund_to_proto(refund: Refund) -> store::Refund {
+        store::Refund {
+            id: refund.id.to_string(),
+            transaction_id: refund.transaction_id,
+            amount: Some(store::Decimal {
+                value: refund.amount.to_string(),
+            }),
+            created_at: Some(Self::iso8601(&refund.created_at)),
+        }
+    }
+
+    fn invoice_to_proto(invoice: Invoice, lines: Vec<InvoiceLine>) -> store::Invoice {
+        store::Invoice {
+            id: invoice.id.to_string(),
+            order_id: invoice.order_id.to_string(),
+            number: invoice.code(),
+            issued_at: Some(Self::iso8601(&invoice.issued_at)),
+            seller: Some(store::InvoiceSeller {
+                name: invoice.seller_name,
+                address: invoice.seller_address,
+                vat_number: invoice.seller_vat_number,
+            }),
+            customer_email: invoice.customer_email,
+            transaction_id: invoice.transaction_id,
+            lines: lines
+                .into_iter()
+                .map(|line| store::InvoiceLine {
+                    article_id: line.article_id.to_string(),
+                    name: line.name,
+                    quantity: line.quantity as u32,
+                    unit_price: Some(store::Decimal {
+                        value: line.unit_price.to_string(),
+                    }),
+                    total: Some(store::Decimal {
+                        value: line.total.to_string(),
+                    }),
+                })
+                .collect(),
+            shipping_cost: Some(store::Decimal {
+                value: invoice.shipping_cost.to_string(),
+            }),
+            tax_rate: Some(store::Decimal {
+                value: invoice.tax_rate.to_string(),
+            }),
+            taxable_amount: Some(store::Decimal {
+                value: invoice.taxable_amount.to_string(),
+            }),
+            tax_amount: Some(store::Decimal {
+                value: invoice.tax_amount.to_string(),
+            }),
+            total: Some(store::Decimal {
+                value: invoice.total.to_string(),
+            }),
+        }
+    }
+
+    fn iso8601(date: &chrono::NaiveDateTime) -> store::Iso8601 {
+        store::Iso8601 {
+            timestamp: date.format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+
+    /// Send the orders created in `[from, to)` to the export stream, a batch at a time.
+    /// The export stops at the first error or when the client goes away
+    async fn export_orders_batches(
+        repository: R,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        sender: Sender<Result<store::ExportedOrder, Status>>,
+    ) {
+        let mut after = None;
+        let mut exported = 0;
+        loop {
+            let orders = match repository
+                .find_orders_created_between(from, to, after, ORDER_EXPORT_BATCH_SIZE)
+                .await
+            {
+                Ok(orders) => orders,
+                Err(err) => {
+                    error!("failed to collect orders to export: {err}");
+                    let _ = sender.send(Err(err.into())).await;
+                    return;
+                }
+            };
+            let last_batch = (orders.len() as i64) < ORDER_EXPORT_BATCH_SIZE;
+            for order in orders.into_iter() {
+                after = Some((order.created_at, order.id));
+                let customer_id = order.customer_id.to_string();
+                let exported_order = Self::order_to_proto(&repository, order).await.map(|order| {
+                    store::ExportedOrder {
+                        customer_id,
+                        order: Some(order),
+                    }
+                });
+                let failed = exported_order.is_err();
+                if sender.send(exported_order).await.is_err() {
+                    debug!("order export client went away after {exported} orders");
+                    return;
+                }
+                if failed {
+                    error!("failed to export order; export aborted");
+                    return;
+                }
+                exported += 1;
+            }
+            if last_batch {
+                break;
+            }
+        }
+        debug!("exported {exported} orders created from {from} to {to}");
+    }
+
+    /// Send the articles which are not archived to `sender`, reading them in batches
+    async fn export_articles_batches(
+        repository: R,
+        sender: Sender<Result<store::CatalogArticle, Status>>,
+    ) {
+        let mut after = None;
+        let mut exported = 0;
+        loop {
+            let articles = match repository
+                .find_articles_after(after, ARTICLE_EXPORT_BATCH_SIZE)
+                .await
+            {
+                Ok(articles) => articles,
+                Err(err) => {
+                    error!("failed to collect articles to export: {err}");
+                    let _ = sender.send(Err(err.into())).await;
+                    return;
+                }
+            };
+            let last_batch = (articles.len() as i64) < ARTICLE_EXPORT_BATCH_SIZE;
+            for article in articles.into_iter() {
+                after = Some(article.id);
+                let article = store::CatalogArticle {
+                    id: article.id.to_string(),
+                    name: article.name,
+                    description: article.description,
+                    unit_price: Some(store::Decimal {
+                        value: article.unit_price.to_string(),
+                    }),
+                    weight: article.weight.max(0) as u32,
+                };
+                if sender.send(Ok(article)).await.is_err() {
+                    debug!("articles export client went away after {exported} articles");
+                    return;
+                }
+                exported += 1;
+            }
+            if last_batch {
+                break;
+            }
+        }
+        debug!("exported {exported} articles");
+    }
+
+    /// Validate an imported article.
+    ///
+    /// Returns the id set by the article, if any, and the article to upsert; when the id is not
+    /// set, a new one is generated
+    fn catalog_article_from_proto(
+        article: store::CatalogArticle,
+    ) -> Result<(Option<Uuid>, Article), String> {
+        let id = match article.id.trim() {
+            "" => None,
+            id => Some(Uuid::parse_str(id).map_err(|e| format!("invalid id '{id}': {e}"))?),
+        };
+        let name = article.name.trim().to_string();
+        if name.is_empty() {
+            return Err("name is empty".to_string());
+        }
+        let unit_price = article
+            .unit_price
+            .as_ref()
+            .map(|x| x.value.trim())
+            .unwrap_or_default();
+        let unit_price = Decimal::from_str(unit_price)
+            .map_err(|e| format!("invalid unit price '{unit_price}': {e}"))?;
+        if unit_price.is_sign_negative() || unit_price.normalize().scale() > 2 {
+            return Err(format!(
+                "unit price {unit_price} must be a positive amount with at most 2 decimals"
+            ));
+        }
+        let weight = i32::try_from(article.weight)
+            .map_err(|_| format!("weight {} is too big", article.weight))?;
+
+        Ok((
+            id,
+            Article {
+                id: id.unwrap_or_else(Uuid::new_v4),
+                name,
+                description: article.description.trim().to_string(),
+                unit_price,
+                weight,
+                archived_at: None,
+            },
+        ))
+    }
+
+    /// Upsert a batch of imported articles and add the outcome of each row to `result`.
+    ///
+    /// Articles without id update the article with the same name, if any. `imported_ids` and
+    /// `imported_names` hold the articles already imported, so that each article is imported once
+    async fn import_articles_batch(
+        transaction: &mut R::Transaction,
+        rows: Vec<(u32, store::CatalogArticle)>,
+        imported_ids: &mut HashSet<Uuid>,
+        imported_names: &mut HashSet<String>,
+        result: &mut store::ImportArticlesResult,
+    ) -> Result<(), Status> {
+        let mut articles = Vec::with_capacity(rows.len());
+        for (row, article) in rows.into_iter() {
+            match Self::catalog_article_from_proto(article) {
+                Ok((id, article)) => articles.push((row, id, article)),
+                Err(message) => result
+                    .errors
+                    .push(store::ImportArticleError { row, message }),
+            }
+        }
+        let names: Vec<String> = articles
+            .iter()
+            .filter(|(_, id, _)| id.is_none())
+            .map(|(_, _, article)| article.name.clone())
+            .collect();
+        if !names.is_empty() {
+            let existing: HashMap<String, Uuid> = transaction
+                .find_article_ids_by_names(&names)
+                .await?
+                .into_iter()
+                .map(|(id, name)| (name, id))
+                .collect();
+            for (_, id, article) in articles.iter_mut() {
+                if let (None, Some(existing_id)) = (id, existing.get(&article.name)) {
+                    article.id = *existing_id;
+                }
+            }
+        }
+        let mut upserts = Vec::with_capacity(articles.len());
+        for (row, id, article) in articles.into_iter() {
+            let duplicated_name = id.is_none() && !imported_names.insert(article.name.clone());
+            if duplicated_name || !imported_ids.insert(article.id) {
+                result.errors.push(store::ImportArticleError {
+                    row,
+                    message: format!("article '{}' is already in the import", article.name),
+                });
+                continue;
+            }
+            upserts.push(article);
+        }
+        if upserts.is_empty() {
+            return Ok(());
+        }
+        let upserted = transaction.upsert_articles(&upserts).await?;
+        let created = upserted.iter().filter(|(_, inserted)| *inserted).count();
+        result.created += created as u32;
+        result.updated += (upserted.len() - created) as u32;
+        result.unchanged += (upserts.len() - upserted.len()) as u32;
+
+        Ok(())
+    }
+
+    /// Parse an id sent by the client in `field`
+    fn parse_uuid(id: &str, field: &str) -> Result<Uuid, RpcError> {
+        Uuid::parse_str(id).map_err(|e| RpcError::invalid_argument(field, e))
+    }
+
+    /// Parse a date sent by the client in `field`
+    fn parse_iso8601(date: &store::Iso8601, field: &str) -> Result<NaiveDateTime, RpcError> {
+        let timestamp = date.timestamp.as_str();
+        NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S").map_err(|e| {
+            RpcError::invalid_argument(field, format!("invalid date '{timestamp}': {e}"))
+        })
+    }
+
+    /// Decode the cursor of a paginated request, if any
+    fn parse_cursor<K: DeserializeOwned>(cursor: Option<&str>) -> Result<Option<K>, RpcError> {
+        cursor
+            .map(cursor::decode)
+            .transpose()
+            .map_err(|err| RpcError::invalid_argument("cursor", err))
+    }
+
+    /// Check the size of the page of a paginated request; pages larger than
+    /// `MAX_RESULTS_PER_PAGE` are shrunk
+    fn parse_results_per_page(results_per_page: u32) -> Result<usize, RpcError> {
+        if results_per_page == 0 {
+            return Err(RpcError::invalid_argument(
+                "results_per_page",
+                "must be greater than 0",
+            ));
+        }
+
+        Ok(results_per_page.min(MAX_RESULTS_PER_PAGE) as usize)
+    }
+
+    /// Parse the bounds of a report date range; `from` must come before `to`
+    fn parse_date_range(
+        range: &Option<store::DateRange>,
+    ) -> Result<(NaiveDateTime, NaiveDateTime), RpcError> {
+        let range = range.as_ref().ok_or_else(|| RpcError::missing("range"))?;
+        let from = range
+            .from
+            .as_ref()
+            .ok_or_else(|| RpcError::missing("range.from"))
+            .and_then(|x| Self::parse_iso8601(x, "range.from"))?;
+        let to = range
+            .to
+            .as_ref()
+            .ok_or_else(|| RpcError::missing("range.to"))
+            .and_then(|x| Self::parse_iso8601(x, "range.to"))?;
+        if from >= to {
+            return Err(RpcError::invalid_argument(
+                "range",
+                "date range start must come before its end",
+            ));
+        }
+
+        Ok((from, to))
+    }
+
+    /// Parse the filter of a query orders request; both creation date bounds are optional
+    fn parse_order_filter(request: &store::QueryOrdersRequest) -> Result<OrderFilter, RpcError> {
+        let created_from = request
+            .created_from
+            .as_ref()
+            .map(|x| Self::parse_iso8601(x, "created_from"))
+            .transpose()?;
+        let created_to = request
+            .created_to
+            .as_ref()
+            .map(|x| Self::parse_iso8601(x, "created_to"))
+            .transpose()?;
+        if let (Some(from), Some(to)) = (created_from, created_to) {
+            if from >= to {
+                return Err(RpcError::invalid_argument(
+                    "created_from",
+                    "date range start must come before its end",
+                ));
+            }
+        }
+
+        Ok(OrderFilter {
+            statuses: request
+                .statuses()
+                .map(Self::order_status_from_proto)
+                .collect(),
+            created_from,
+            created_to,
+        })
     }
 }
 
 #[tonic::async_trait]
-impl ProtobufStoreService for StoreService {
+impl<R: Repository> ProtobufStoreService for StoreService<R> {
     async fn sign_in(
         &self,
         request: Request<store::SignInRequest>,
     ) -> Result<Response<store::AuthResponse>, Status> {
         let email = &request.get_ref().email;
         let password = &request.get_ref().password;
-        let password = self.hash_password(password);
+        let password = Customer::hash_password(password);
         debug!("got signin request with {email} and {password}");
         // sign in
-        let customer =
-            Customer::find_by_email_and_password(&self.database, email, &password).await?;
+        let customer = self
+            .repository
+            .find_customer_by_credentials(email, &password)
+            .await?;
 
         let status = match customer {
             None => store::auth_response::Status::Error(2),
@@ -91,7 +1144,7 @@ impl ProtobufStoreService for StoreService {
     ) -> Result<Response<store::AuthResponse>, Status> {
         let email = &request.get_ref().email;
         let password = &request.get_ref().password;
-        let password = self.hash_password(password);
+        let password = Customer::hash_password(password);
         debug!("got signup request with {email} and {password}");
         // validate email
         if !EmailAddress::is_valid(email) {
@@ -100,7 +1153,9 @@ impl ProtobufStoreService for StoreService {
             }));
         }
         // check whether email is already taken
-        if Customer::find_by_email(&self.database, email)
+        if self
+            .repository
+            .find_customer_by_email(email)
             .await?
             .is_some()
         {
@@ -110,7 +1165,7 @@ impl ProtobufStoreService for StoreService {
             }));
         }
         // create user
-        let customer = Customer::insert(&self.database, email, &password).await?;
+        let customer = self.repository.insert_customer(email, &password).await?;
         debug!("created new customer with id {}", customer.id);
 
         Ok(Response::new(store::AuthResponse {
@@ -124,59 +1179,99 @@ impl ProtobufStoreService for StoreService {
         &self,
         request: Request<store::QueryOrdersRequest>,
     ) -> Result<Response<store::QueryOrdersResult>, Status> {
-        let user_id = Uuid::parse_str(&request.get_ref().user_id)
-            .map_err(|e| Status::new(tonic::Code::Internal, e.to_string()))?;
-        let page = request.get_ref().page_number as i64;
-        let count = request.get_ref().results_per_page as i64;
-        debug!("get orders for user {user_id} from {page}; {count} elements");
-        let orders = CustomerOrder::find_by_customer(&self.database, &user_id, page, count).await?;
+        let user_id = Self::parse_uuid(&request.get_ref().user_id, "user_id")?;
+        let after: Option<(NaiveDateTime, Uuid)> =
+            Self::parse_cursor(request.get_ref().cursor.as_deref())?;
+        let filter = Self::parse_order_filter(request.get_ref())?;
+        let count = Self::parse_results_per_page(request.get_ref().results_per_page)?;
+        debug!(
+            "get orders for user {user_id} matching {:?} after {:?}; {count} elements",
+            filter, after
+        );
+        // one more order tells whether there is a next page
+        let mut orders = self
+            .repository
+            .find_orders_by_customer(&user_id, &filter, after, count as i64 + 1)
+            .await?;
+        let has_next_page = orders.len() > count;
+        orders.truncate(count);
+        let total_count = self
+            .repository
+            .count_orders_by_customer(&user_id, &filter)
+            .await?;
         debug!(
-            "got {} orders; collecting articles for orders",
+            "got {} orders of {total_count}; collecting articles for orders",
             orders.len()
         );
-        let mut orders_with_article = Vec::with_capacity(orders.len());
+        let mut edges = Vec::with_capacity(orders.len());
         for order in orders.into_iter() {
-            debug!("collecting articles for order {}", order.id);
-            let order_articles = OrderArticle::find_by_order_id(&self.database, &order.id).await?;
-            debug!("got {} articles in order", order_articles.len());
-            // resolve article type
-            let mut articles = Vec::with_capacity(order_articles.len());
-            for order_article in order_articles.into_iter() {
-                debug!("getting article details for article {}", order_article.id);
-                if let Some(article) =
-                    Article::find_by_id(&self.database, &order_article.article_id).await?
-                {
-                    articles.push(store::OrderArticle {
-                        id: article.id.to_string(),
-                        name: article.name,
-                        description: article.description,
-                        quantity: order_article.quantity as u32,
-                        unit_price: Some(store::Decimal {
-                            value: order_article.unit_price.to_string(),
-                        }),
-                    })
-                } else {
-                    warn!("could not find any article for {}", order_article.id);
-                }
-            }
-            orders_with_article.push(store::Order {
-                id: order.id.to_string(),
-                created_at: Some(store::Iso8601 {
-                    timestamp: order.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
-                }),
-                transaction_id: order.transaction_id,
-                status: match order.status {
-                    OrderStatus::Created => 0,
-                    OrderStatus::PaymentRefused => 2,
-                    OrderStatus::Preparing => 1,
-                    OrderStatus::Shipped => 3,
-                },
-                articles,
+            let cursor = cursor::encode(&(order.created_at, order.id));
+            edges.push(store::query_orders_result::Edge {
+                order: Some(Self::order_to_proto(&self.repository, order).await?),
+                cursor,
             });
         }
-        debug!("returning {} orders", orders_with_article.len());
+        debug!("returning {} orders", edges.len());
+        let next_cursor = edges
+            .last()
+            .filter(|_| has_next_page)
+            .map(|x| x.cursor.clone());
         Ok(Response::new(store::QueryOrdersResult {
-            orders: orders_with_article,
+            edges,
+            next_cursor,
+            total_count: total_count as u32,
+        }))
+    }
+
+    async fn get_order(
+        &self,
+        request: Request<store::GetOrderRequest>,
+    ) -> Result<Response<store::GetOrderResponse>, Status> {
+        let user_id = Self::parse_uuid(&request.get_ref().user_id, "user_id")?;
+        let order_id = Self::parse_uuid(&request.get_ref().order_id, "order_id")?;
+        debug!("getting order {order_id} of customer {user_id}");
+        let order = match self.repository.find_order_by_id(&order_id).await? {
+            Some(order) if order.customer_id == user_id => {
+                Some(Self::order_to_proto(&self.repository, order).await?)
+            }
+            _ => {
+                debug!("order {order_id} not found for customer {user_id}");
+                None
+            }
+        };
+
+        Ok(Response::new(store::GetOrderResponse { order }))
+    }
+
+    async fn get_order_history(
+        &self,
+        request: Request<store::GetOrderHistoryRequest>,
+    ) -> Result<Response<store::GetOrderHistoryResponse>, Status> {
+        let user_id = Self::parse_uuid(&request.get_ref().user_id, "user_id")?;
+        let order_id = Self::parse_uuid(&request.get_ref().order_id, "order_id")?;
+        debug!("getting history for order {order_id} of customer {user_id}");
+        match self.repository.find_order_by_id(&order_id).await? {
+            Some(order) if order.customer_id == user_id => {}
+            _ => {
+                debug!("order {order_id} not found for customer {user_id}");
+                return Ok(Response::new(store::GetOrderHistoryResponse {
+                    status: Some(store::get_order_history_response::Status::Error(1)),
+                }));
+            }
+        }
+        let events: Vec<store::OrderEvent> = self
+            .repository
+            .find_order_events(&order_id)
+            .await?
+            .into_iter()
+            .map(Self::order_event_to_proto)
+            .collect();
+        debug!("found {} events for order {order_id}", events.len());
+
+        Ok(Response::new(store::GetOrderHistoryResponse {
+            status: Some(store::get_order_history_response::Status::History(
+                store::get_order_history_response::OrderHistory { events },
+            )),
         }))
     }
 
@@ -184,113 +1279,285 @@ impl ProtobufStoreService for StoreService {
         &self,
         request: Request<store::QueryArticlesRequest>,
     ) -> Result<Response<store::QueryArticlesResult>, Status> {
-        let query = &request.get_ref().query;
-        let page = request.get_ref().page_number as i64;
-        let count = request.get_ref().results_per_page as i64;
+        let query = request.get_ref().query.as_deref();
+        let after: Option<(String, Uuid)> =
+            Self::parse_cursor(request.get_ref().cursor.as_deref())?;
+        let count = Self::parse_results_per_page(request.get_ref().results_per_page)?;
         debug!(
-            "getting articles by query '{:?}' from {page}; {count} elements",
-            query
-        );
-        let articles: Vec<store::Article> = match query {
-            Some(q) => Article::find_by_name(&self.database, q, page, count).await,
-            None => Article::get_all(&self.database, page, count).await,
-        }?
-        .into_iter()
-        .map(|x| store::Article {
-            id: x.id.to_string(),
-            name: x.name,
-            description: x.description,
-            unit_price: Some(store::Decimal {
-                value: x.unit_price.to_string(),
-            }),
-        })
-        .collect();
-        debug!("found {} articles", articles.len());
+            "getting articles by query '{:?}' after {:?}; {count} elements",
+            query, after
+        );
+        // one more article tells whether there is a next page
+        let mut stock_articles = self
+            .repository
+            .find_articles(query, after, count as i64 + 1)
+            .await?;
+        let has_next_page = stock_articles.len() > count;
+        stock_articles.truncate(count);
+        let total_count = self.repository.count_articles(query).await?;
+        debug!("found {} articles of {total_count}", stock_articles.len());
+        let mut edges = Vec::with_capacity(stock_articles.len());
+        for article in stock_articles.into_iter() {
+            let cursor = cursor::encode(&(&article.name, article.id));
+            edges.push(store::query_articles_result::Edge {
+                article: Some(self.article_to_proto(article).await?),
+                cursor,
+            });
+        }
+        let next_cursor = edges
+            .last()
+            .filter(|_| has_next_page)
+            .map(|x| x.cursor.clone());
 
-        Ok(Response::new(store::QueryArticlesResult { articles }))
+        Ok(Response::new(store::QueryArticlesResult {
+            edges,
+            next_cursor,
+            total_count: total_count as u32,
+        }))
     }
 
-    async fn submit_order(
+    async fn recommend_articles(
         &self,
-        request: Request<store::SubmitOrderRequest>,
-    ) -> Result<Response<store::SubmitOrderResponse>, Status> {
-        let user_id = Uuid::parse_str(&request.get_ref().user_id)
-            .map_err(|e| Status::new(tonic::Code::Internal, e.to_string()))?;
-        let articles = &request.get_ref().articles;
-        debug!("submitting order for customer with id {user_id}");
-        // start transaction
-        let mut transaction = self
-            .database
-            .pool()
-            .begin()
-            .await
-            .map_err(|e| Status::new(tonic::Code::Internal, e.to_string()))?;
-        // insert order
-        let order = CustomerOrder::insert_order(&mut transaction, &user_id).await?;
-        debug!("inserted order with ID {}", order.id.to_string());
-        // insert for each article a order-article in the database
-        for article in articles.iter() {
-            debug!(
-                "inserting new article for order {}: {}",
-                order.id.to_string(),
-                article.article_id
-            );
-            let article_id = Uuid::parse_str(&article.article_id)
-                .map_err(|e| Status::new(tonic::Code::Internal, e.to_string()))?;
-            // get current unit price for article
-            let stock_article = match Article::find_by_id(&self.database, &article_id).await? {
-                Some(a) => a,
-                None => {
-                    return Ok(Response::new(store::SubmitOrderResponse {
-                        status: Some(store::submit_order_response::Status::Error(1)),
-                    }))
+        request: Request<store::RecommendArticlesRequest>,
+    ) -> Result<Response<store::RecommendArticlesResult>, Status> {
+        let limit = match request.get_ref().limit {
+            Some(limit) => (limit as i64).min(MAX_RECOMMENDATIONS),
+            None => DEFAULT_RECOMMENDATIONS,
+        };
+        let article_ids = match &request.get_ref().source {
+            Some(store::recommend_articles_request::Source::ArticleId(article_id)) => {
+                vec![Self::parse_uuid(article_id, "article_id")?]
+            }
+            Some(store::recommend_articles_request::Source::Cart(owner)) => {
+                match self.owner_cart(&Some(owner.clone())).await? {
+                    Some(cart) => self
+                        .repository
+                        .find_cart_items(&cart.id)
+                        .await?
+                        .into_iter()
+                        .map(|x| x.article_id)
+                        .collect(),
+                    None => Vec::new(),
                 }
-            };
-            OrderArticle::insert(
-                &mut transaction,
-                &order.id,
+            }
+            None => return Err(RpcError::missing("source").into()),
+        };
+        debug!(
+            "recommending {limit} articles for {} articles",
+            article_ids.len()
+        );
+        let related = self
+            .repository
+            .find_related_articles(&article_ids, limit)
+            .await?;
+        debug!("found {} related articles", related.len());
+        let mut articles = Vec::with_capacity(related.len());
+        for article in related.into_iter() {
+            articles.push(self.article_to_proto(article).await?);
+        }
+
+        Ok(Response::new(store::RecommendArticlesResult { articles }))
+    }
+
+    async fn query_article_reviews(
+        &self,
+        request: Request<store::QueryArticleReviewsRequest>,
+    ) -> Result<Response<store::QueryArticleReviewsResult>, Status> {
+        let article_id = Self::parse_uuid(&request.get_ref().article_id, "article_id")?;
+        let after: Option<(NaiveDateTime, Uuid)> =
+            Self::parse_cursor(request.get_ref().cursor.as_deref())?;
+        let count = Self::parse_results_per_page(request.get_ref().results_per_page)?;
+        debug!(
+            "getting reviews for article {article_id} after {:?}; {count} elements",
+            after
+        );
+        // one more review tells whether there is a next page
+        let mut reviews = self
+            .repository
+            .find_approved_reviews(&article_id, after, count as i64 + 1)
+            .await?;
+        let has_next_page = reviews.len() > count;
+        reviews.truncate(count);
+        debug!("found {} reviews", reviews.len());
+        let total_count = self
+            .repository
+            .find_article_rating(&article_id)
+            .await?
+            .map(|x| x.count as u32)
+            .unwrap_or_default();
+        let edges: Vec<_> = reviews
+            .into_iter()
+            .map(|review| store::query_article_reviews_result::Edge {
+                cursor: cursor::encode(&(review.created_at, review.id)),
+                review: Some(Self::review_to_proto(review)),
+            })
+            .collect();
+        let next_cursor = edges
+            .last()
+            .filter(|_| has_next_page)
+            .map(|x| x.cursor.clone());
+
+        Ok(Response::new(store::QueryArticleReviewsResult {
+            edges,
+            next_cursor,
+            total_count,
+        }))
+    }
+
+    async fn submit_review(
+        &self,
+        request: Request<store::SubmitReviewRequest>,
+    ) -> Result<Response<store::SubmitReviewResponse>, Status> {
+        let user_id = Self::parse_uuid(&request.get_ref().user_id, "user_id")?;
+        let article_id = Self::parse_uuid(&request.get_ref().article_id, "article_id")?;
+        let rating = request.get_ref().rating;
+        debug!("submitting review with rating {rating} for {article_id} by {user_id}");
+        if !(1..=5).contains(&rating) {
+            return Ok(Response::new(store::SubmitReviewResponse {
+                status: Some(store::submit_review_response::Status::Error(3)),
+            }));
+        }
+        if self
+            .repository
+            .find_article_by_id(&article_id)
+            .await?
+            .is_none()
+        {
+            debug!("article {article_id} not found");
+            return Ok(Response::new(store::SubmitReviewResponse {
+                status: Some(store::submit_review_response::Status::Error(1)),
+            }));
+        }
+        // only customers who received the article can review it
+        if !self
+            .repository
+            .is_verified_purchase(&user_id, &article_id)
+            .await?
+        {
+            debug!("article {article_id} has never been shipped to {user_id}");
+            return Ok(Response::new(store::SubmitReviewResponse {
+                status: Some(store::submit_review_response::Status::Error(2)),
+            }));
+        }
+        match self
+            .repository
+            .insert_review(
                 &article_id,
-                article.quantity as i32,
-                stock_article.unit_price,
+                &user_id,
+                rating as i16,
+                &request.get_ref().title,
+                &request.get_ref().body,
             )
-            .await?;
+            .await?
+        {
+            Some(review) => Ok(Response::new(store::SubmitReviewResponse {
+                status: Some(store::submit_review_response::Status::ReviewId(
+                    review.id.to_string(),
+                )),
+            })),
+            None => {
+                debug!("article {article_id} has already been reviewed by {user_id}");
+                Ok(Response::new(store::SubmitReviewResponse {
+                    status: Some(store::submit_review_response::Status::Error(4)),
+                }))
+            }
         }
-        debug!("all articles have been stored in the database; committing transaction...");
+    }
+
+    async fn moderate_review(
+        &self,
+        request: Request<store::ModerateReviewRequest>,
+    ) -> Result<Response<store::ModerateReviewResponse>, Status> {
+        let review_id = Self::parse_uuid(&request.get_ref().review_id, "review_id")?;
+        let approved = request.get_ref().approved;
+        debug!("moderating review {review_id}; approved: {approved}");
+        let mut transaction = self.repository.begin().await?;
+        let mut review = match transaction.find_review_for_update(&review_id).await? {
+            Some(review) => review,
+            None => {
+                debug!("review {review_id} not found");
+                return Ok(Response::new(store::ModerateReviewResponse {
+                    status: Some(store::moderate_review_response::Status::Error(1)),
+                }));
+            }
+        };
+        if review.status != ReviewStatus::Pending {
+            debug!("review {review_id} has already been moderated");
+            return Ok(Response::new(store::ModerateReviewResponse {
+                status: Some(store::moderate_review_response::Status::Error(2)),
+            }));
+        }
+        review.status = if approved {
+            ReviewStatus::Approved
+        } else {
+            ReviewStatus::Rejected
+        };
         transaction
-            .commit()
-            .await
-            .map_err(|e| Status::new(tonic::Code::Internal, e.to_string()))?;
+            .moderate_review(&review_id, review.status)
+            .await?;
+        transaction
+            .refresh_article_rating(&review.article_id)
+            .await?;
+        transaction.commit().await?;
 
-        Ok(Response::new(store::SubmitOrderResponse {
-            status: Some(store::submit_order_response::Status::OrderId(
-                order.id.to_string(),
+        Ok(Response::new(store::ModerateReviewResponse {
+            status: Some(store::moderate_review_response::Status::Review(
+                Self::review_to_proto(review),
             )),
         }))
     }
 
+    async fn submit_order(
+        &self,
+        request: Request<store::SubmitOrderRequest>,
+    ) -> Result<Response<store::SubmitOrderResponse>, Status> {
+        let user_id = Self::parse_uuid(&request.get_ref().user_id, "user_id")?;
+        let shipping_method_id = match &request.get_ref().shipping_method_id {
+            Some(id) => Some(Self::parse_uuid(id, "shipping_method_id")?),
+            None => None,
+        };
+        let articles = request
+            .get_ref()
+            .articles
+            .iter()
+            .map(|article| {
+                Self::parse_uuid(&article.article_id, "article_id")
+                    .map(|article_id| (article_id, article.quantity))
+            })
+            .collect::<Result<Vec<(Uuid, u32)>, RpcError>>()?;
+        let articles = match Self::merge_order_articles(articles) {
+            Ok(articles) => articles,
+            Err(err) => {
+                debug!("rejecting order of customer {user_id}: {:?}", err);
+                return Ok(Response::new(store::SubmitOrderResponse {
+                    status: Some(store::submit_order_response::Status::Error(err as i32)),
+                }));
+            }
+        };
+        let response = self
+            .place_order(
+                &user_id,
+                articles,
+                shipping_method_id,
+                request.get_ref().card_number.as_deref(),
+                None,
+            )
+            .await?;
+
+        Ok(Response::new(response))
+    }
+
     async fn submit_order_payment(
         &self,
         request: Request<store::SubmitOrderPaymentRequest>,
     ) -> Result<Response<store::SubmitOrderResponse>, Status> {
         match &request.get_ref().status {
-            None => {
-                return Err(Status::new(
-                    tonic::Code::InvalidArgument,
-                    "missing status".to_string(),
-                ))
-            }
+            None => return Err(RpcError::missing("status").into()),
             Some(store::submit_order_payment_request::Status::Failed(
                 store::submit_order_payment_request::SubmitOrderPaymentFailedRequest { order_id },
             )) => {
-                let order_id = Uuid::parse_str(order_id)
-                    .map_err(|e| Status::new(tonic::Code::Internal, e.to_string()))?;
-                debug!("setting order status to PaymentRefused and for order {order_id}");
-                CustomerOrder::update_status(
-                    &self.database,
-                    &order_id,
-                    OrderStatus::PaymentRefused,
-                )
-                .await?;
+                let order_id = Self::parse_uuid(order_id, "order_id")?;
+                Self::payment_failed(&self.repository, &order_id).await?;
 
                 Ok(Response::new(store::SubmitOrderResponse {
                     status: Some(store::submit_order_response::Status::OrderId(
@@ -304,25 +1571,14 @@ impl ProtobufStoreService for StoreService {
                     transaction_id,
                 },
             )) => {
-                let order_id = Uuid::parse_str(order_id)
-                    .map_err(|e| Status::new(tonic::Code::Internal, e.to_string()))?;
-                debug!("setting order status to Preparing and transaction id to {transaction_id} for order {order_id}");
-                // create transaction
-                let mut transaction = self
-                    .database
-                    .pool()
-                    .begin()
-                    .await
-                    .map_err(|e| Status::new(tonic::Code::Internal, e.to_string()))?;
-                // update both status and transaction id
-                CustomerOrder::update_status(&mut transaction, &order_id, OrderStatus::Preparing)
-                    .await?;
-                CustomerOrder::update_transaction_id(&mut transaction, &order_id, transaction_id)
-                    .await?;
-                transaction
-                    .commit()
-                    .await
-                    .map_err(|e| Status::new(tonic::Code::Internal, e.to_string()))?;
+                let order_id = Self::parse_uuid(order_id, "order_id")?;
+                Self::payment_succeeded(
+                    &self.repository,
+                    &self.invoice_seller,
+                    &order_id,
+                    transaction_id,
+                )
+                .await?;
 
                 Ok(Response::new(store::SubmitOrderResponse {
                     status: Some(store::submit_order_response::Status::OrderId(
@@ -332,4 +1588,2182 @@ impl ProtobufStoreService for StoreService {
             }
         }
     }
+
+    async fn query_shipping_methods(
+        &self,
+        _request: Request<store::QueryShippingMethodsRequest>,
+    ) -> Result<Response<store::QueryShippingMethodsResult>, Status> {
+        debug!("getting shipping methods");
+        let shipping_methods: Vec<store::ShippingMethod> = self
+            .repository
+            .find_shipping_methods()
+            .await?
+            .into_iter()
+            .map(Self::shipping_method_to_proto)
+            .collect();
+        debug!("found {} shipping methods", shipping_methods.len());
+
+        Ok(Response::new(store::QueryShippingMethodsResult {
+            shipping_methods,
+        }))
+    }
+
+    async fn mark_order_shipped(
+        &self,
+        request: Request<store::MarkOrderShippedRequest>,
+    ) -> Result<Response<store::MarkOrderShippedResponse>, Status> {
+        let order_id = Self::parse_uuid(&request.get_ref().order_id, "order_id")?;
+        let carrier = request.get_ref().carrier.trim();
+        let tracking_code = request.get_ref().tracking_code.trim();
+        if carrier.is_empty() || tracking_code.is_empty() {
+            let field = if carrier.is_empty() {
+                "carrier"
+            } else {
+                "tracking_code"
+            };
+            return Err(RpcError::missing(field).into());
+        }
+        debug!("marking order {order_id} as shipped with {carrier}: {tracking_code}");
+        let mut transaction = self.repository.begin().await?;
+        // lock order and check whether it can be shipped
+        let order = match transaction.find_order_for_update(&order_id).await? {
+            Some(order) => order,
+            None => {
+                debug!("order {order_id} not found");
+                return Ok(Response::new(store::MarkOrderShippedResponse {
+                    status: Some(store::mark_order_shipped_response::Status::Error(1)),
+                }));
+            }
+        };
+        if order.status != OrderStatus::Preparing {
+            debug!(
+                "order {order_id} can't be shipped; current status is {:?}",
+                order.status
+            );
+            return Ok(Response::new(store::MarkOrderShippedResponse {
+                status: Some(store::mark_order_shipped_response::Status::Error(2)),
+            }));
+        }
+        transaction
+            .update_order_status(&order_id, OrderStatus::Shipped, OrderEventActor::BackOffice)
+            .await?;
+        let shipment = transaction
+            .insert_shipment(&order_id, carrier, tracking_code)
+            .await?;
+        transaction.commit().await?;
+
+        Ok(Response::new(store::MarkOrderShippedResponse {
+            status: Some(store::mark_order_shipped_response::Status::Shipment(
+                Self::shipment_to_proto(shipment),
+            )),
+        }))
+    }
+
+    async fn get_cart(
+        &self,
+        request: Request<store::GetCartRequest>,
+    ) -> Result<Response<store::CartResponse>, Status> {
+        debug!("getting cart for {:?}", request.get_ref().owner);
+        match self.owner_cart(&request.get_ref().owner).await? {
+            Some(cart) => Ok(Response::new(self.cart_to_proto(&cart).await?)),
+            None => Ok(Response::new(store::CartResponse {
+                status: Some(store::cart_response::Status::Error(1)),
+            })),
+        }
+    }
+
+    async fn add_to_cart(
+        &self,
+        request: Request<store::AddToCartRequest>,
+    ) -> Result<Response<store::CartResponse>, Status> {
+        let article_id = Self::parse_uuid(&request.get_ref().article_id, "article_id")?;
+        let quantity = request.get_ref().quantity;
+        debug!(
+            "adding {quantity} items of {article_id} to cart of {:?}",
+            request.get_ref().owner
+        );
+        let quantity = match i32::try_from(quantity) {
+            Ok(quantity) if quantity > 0 => quantity,
+            _ => {
+                return Ok(Response::new(store::CartResponse {
+                    status: Some(store::cart_response::Status::Error(3)),
+                }))
+            }
+        };
+        if self
+            .repository
+            .find_article_by_id(&article_id)
+            .await?
+            .map(|x| x.is_archived())
+            .unwrap_or(true)
+        {
+            debug!("article {article_id} not found or archived");
+            return Ok(Response::new(store::CartResponse {
+                status: Some(store::cart_response::Status::Error(2)),
+            }));
+        }
+        let cart = match self.owner_cart(&request.get_ref().owner).await? {
+            Some(cart) => cart,
+            None => {
+                return Ok(Response::new(store::CartResponse {
+                    status: Some(store::cart_response::Status::Error(1)),
+                }))
+            }
+        };
+        self.repository
+            .add_cart_item(&cart.id, &article_id, quantity)
+            .await?;
+        self.repository.touch_cart(&cart.id).await?;
+
+        Ok(Response::new(self.cart_to_proto(&cart).await?))
+    }
+
+    async fn update_cart_item(
+        &self,
+        request: Request<store::UpdateCartItemRequest>,
+    ) -> Result<Response<store::CartResponse>, Status> {
+        let article_id = Self::parse_uuid(&request.get_ref().article_id, "article_id")?;
+        let quantity = request.get_ref().quantity;
+        debug!(
+            "setting quantity of {article_id} to {quantity} in cart of {:?}",
+            request.get_ref().owner
+        );
+        let quantity = match i32::try_from(quantity) {
+            Ok(quantity) => quantity,
+            Err(_) => {
+                return Ok(Response::new(store::CartResponse {
+                    status: Some(store::cart_response::Status::Error(3)),
+                }))
+            }
+        };
+        if quantity > 0
+            && self
+                .repository
+                .find_article_by_id(&article_id)
+                .await?
+                .map(|x| x.is_archived())
+                .unwrap_or(true)
+        {
+            debug!("article {article_id} not found or archived");
+            return Ok(Response::new(store::CartResponse {
+                status: Some(store::cart_response::Status::Error(2)),
+            }));
+        }
+        let cart = match self.owner_cart(&request.get_ref().owner).await? {
+            Some(cart) => cart,
+            None => {
+                return Ok(Response::new(store::CartResponse {
+                    status: Some(store::cart_response::Status::Error(1)),
+                }))
+            }
+        };
+        if quantity == 0 {
+            self.repository
+                .remove_cart_item(&cart.id, &article_id)
+                .await?;
+        } else {
+            self.repository
+                .set_cart_item_quantity(&cart.id, &article_id, quantity)
+                .await?;
+        }
+        self.repository.touch_cart(&cart.id).await?;
+
+        Ok(Response::new(self.cart_to_proto(&cart).await?))
+    }
+
+    async fn remove_from_cart(
+        &self,
+        request: Request<store::RemoveFromCartRequest>,
+    ) -> Result<Response<store::CartResponse>, Status> {
+        let article_id = Self::parse_uuid(&request.get_ref().article_id, "article_id")?;
+        debug!(
+            "removing {article_id} from cart of {:?}",
+            request.get_ref().owner
+        );
+        let cart = match self.owner_cart(&request.get_ref().owner).await? {
+            Some(cart) => cart,
+            None => {
+                return Ok(Response::new(store::CartResponse {
+                    status: Some(store::cart_response::Status::Error(1)),
+                }))
+            }
+        };
+        self.repository
+            .remove_cart_item(&cart.id, &article_id)
+            .await?;
+        self.repository.touch_cart(&cart.id).await?;
+
+        Ok(Response::new(self.cart_to_proto(&cart).await?))
+    }
+
+    async fn merge_cart(
+        &self,
+        request: Request<store::MergeCartRequest>,
+    ) -> Result<Response<store::CartResponse>, Status> {
+        let user_id = Self::parse_uuid(&request.get_ref().user_id, "user_id")?;
+        let anonymous_cart_id =
+            Self::parse_uuid(&request.get_ref().anonymous_cart_id, "anonymous_cart_id")?;
+        debug!("merging anonymous cart {anonymous_cart_id} into cart of customer {user_id}");
+        let mut transaction = self.repository.begin().await?;
+        let cart = transaction.get_or_create_customer_cart(&user_id).await?;
+        if transaction
+            .find_anonymous_cart(&anonymous_cart_id)
+            .await?
+            .is_some()
+        {
+            transaction
+                .merge_cart_items(&anonymous_cart_id, &cart.id)
+                .await?;
+            transaction.delete_cart(&anonymous_cart_id).await?;
+            transaction.touch_cart(&cart.id).await?;
+        } else {
+            debug!("anonymous cart {anonymous_cart_id} not found; nothing to merge");
+        }
+        transaction.commit().await?;
+
+        Ok(Response::new(self.cart_to_proto(&cart).await?))
+    }
+
+    async fn checkout_cart(
+        &self,
+        request: Request<store::CheckoutCartRequest>,
+    ) -> Result<Response<store::SubmitOrderResponse>, Status> {
+        let user_id = Self::parse_uuid(&request.get_ref().user_id, "user_id")?;
+        let shipping_method_id = match &request.get_ref().shipping_method_id {
+            Some(id) => Some(Self::parse_uuid(id, "shipping_method_id")?),
+            None => None,
+        };
+        debug!("checking out cart of customer {user_id}");
+        let cart = self
+            .repository
+            .get_or_create_customer_cart(&user_id)
+            .await?;
+        let articles: Vec<(Uuid, u32)> = self
+            .repository
+            .find_cart_items(&cart.id)
+            .await?
+            .into_iter()
+            .map(|item| (item.article_id, item.quantity as u32))
+            .collect();
+        let articles = match Self::merge_order_articles(articles) {
+            Ok(articles) => articles,
+            Err(err) => {
+                debug!("rejecting cart checkout of customer {user_id}: {:?}", err);
+                return Ok(Response::new(store::SubmitOrderResponse {
+                    status: Some(store::submit_order_response::Status::Error(err as i32)),
+                }));
+            }
+        };
+        let response = self
+            .place_order(
+                &user_id,
+                articles,
+                shipping_method_id,
+                request.get_ref().card_number.as_deref(),
+                Some(&cart.id),
+            )
+            .await?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn request_return(
+        &self,
+        request: Request<store::RequestReturnRequest>,
+    ) -> Result<Response<store::RequestReturnResponse>, Status> {
+        let user_id = Self::parse_uuid(&request.get_ref().user_id, "user_id")?;
+        let order_id = Self::parse_uuid(&request.get_ref().order_id, "order_id")?;
+        let reason = request.get_ref().reason.trim();
+        debug!("requesting return for order {order_id} of customer {user_id}");
+        let mut transaction = self.repository.begin().await?;
+        // lock order and check whether it can be returned
+        let order = match transaction.find_order_for_update(&order_id).await? {
+            Some(order) if order.customer_id == user_id => order,
+            _ => {
+                debug!("order {order_id} not found for customer {user_id}");
+                return Ok(Response::new(store::RequestReturnResponse {
+                    status: Some(store::request_return_response::Status::Error(1)),
+                }));
+            }
+        };
+        if !matches!(
+            order.status,
+            OrderStatus::Shipped | OrderStatus::PartiallyRefunded
+        ) {
+            debug!(
+                "order {order_id} can't be returned; current status is {:?}",
+                order.status
+            );
+            return Ok(Response::new(store::RequestReturnResponse {
+                status: Some(store::request_return_response::Status::Error(2)),
+            }));
+        }
+        if request.get_ref().articles.is_empty() {
+            debug!("no article to return in request for order {order_id}");
+            return Ok(Response::new(store::RequestReturnResponse {
+                status: Some(store::request_return_response::Status::Error(3)),
+            }));
+        }
+        // check that each article is in the order and there are enough items left to return
+        let order_articles = self.repository.find_order_articles(&order_id).await?;
+        let already_returned = transaction
+            .find_not_rejected_return_articles(&order_id)
+            .await?;
+        let mut return_lines: Vec<(Uuid, i32)> =
+            Vec::with_capacity(request.get_ref().articles.len());
+        for article in request.get_ref().articles.iter() {
+            let article_id = Self::parse_uuid(&article.article_id, "article_id")?;
+            let order_article = match order_articles.iter().find(|x| x.article_id == article_id) {
+                Some(order_article) => order_article,
+                None => {
+                    debug!("article {article_id} is not part of order {order_id}");
+                    return Ok(Response::new(store::RequestReturnResponse {
+                        status: Some(store::request_return_response::Status::Error(3)),
+                    }));
+                }
+            };
+            let returned: i64 = already_returned
+                .iter()
+                .filter(|x| x.order_article_id == order_article.id)
+                .map(|x| x.quantity as i64)
+                .chain(
+                    return_lines
+                        .iter()
+                        .filter(|(id, _)| *id == order_article.id)
+                        .map(|(_, quantity)| *quantity as i64),
+                )
+                .sum();
+            if article.quantity == 0
+                || returned + article.quantity as i64 > order_article.quantity as i64
+            {
+                debug!(
+                    "can't return {} items of article {article_id}; {returned} out of {} have already been returned",
+                    article.quantity, order_article.quantity
+                );
+                return Ok(Response::new(store::RequestReturnResponse {
+                    status: Some(store::request_return_response::Status::Error(4)),
+                }));
+            }
+            return_lines.push((order_article.id, article.quantity as i32));
+        }
+        // insert return
+        let order_return = transaction.insert_return(&order_id, reason).await?;
+        debug!("inserted return with ID {}", order_return.id);
+        for (order_article_id, quantity) in return_lines.into_iter() {
+            transaction
+                .insert_return_article(&order_return.id, &order_article_id, quantity)
+                .await?;
+        }
+        transaction
+            .update_order_status(
+                &order_id,
+                OrderStatus::ReturnRequested,
+                OrderEventActor::Customer,
+            )
+            .await?;
+        transaction.commit().await?;
+
+        Ok(Response::new(store::RequestReturnResponse {
+            status: Some(store::request_return_response::Status::ReturnId(
+                order_return.id.to_string(),
+            )),
+        }))
+    }
+
+    async fn resolve_return(
+        &self,
+        request: Request<store::ResolveReturnRequest>,
+    ) -> Result<Response<store::ResolveReturnResponse>, Status> {
+        let return_id = Self::parse_uuid(&request.get_ref().return_id, "return_id")?;
+        let approved = request.get_ref().approved;
+        debug!("resolving return {return_id}; approved: {approved}");
+        let mut transaction = self.repository.begin().await?;
+        // lock return and order; refunds and returned articles are read within the same transaction
+        let order_return = match transaction.find_return_for_update(&return_id).await? {
+            Some(order_return) => order_return,
+            None => {
+                debug!("return {return_id} not found");
+                return Ok(Response::new(store::ResolveReturnResponse {
+                    status: Some(store::resolve_return_response::Status::Error(1)),
+                }));
+            }
+        };
+        if order_return.status != ReturnStatus::Requested {
+            debug!("return {return_id} has already been resolved");
+            return Ok(Response::new(store::ResolveReturnResponse {
+                status: Some(store::resolve_return_response::Status::Error(2)),
+            }));
+        }
+        let order = transaction
+            .find_order_for_update(&order_return.order_id)
+            .await?
+            .ok_or_else(|| RpcError::not_found("order", order_return.order_id))?;
+        // rejected: restore previous order status
+        if !approved {
+            transaction
+                .resolve_return(&return_id, ReturnStatus::Rejected)
+                .await?;
+            let status = if transaction.find_refunds(&order.id).await?.is_empty() {
+                OrderStatus::Shipped
+            } else {
+                OrderStatus::PartiallyRefunded
+            };
+            transaction
+                .update_order_status(&order.id, status, OrderEventActor::BackOffice)
+                .await?;
+            transaction.commit().await?;
+
+            return Ok(Response::new(store::ResolveReturnResponse {
+                status: Some(store::resolve_return_response::Status::Resolved(
+                    store::resolve_return_response::ReturnResolved {
+                        return_id: return_id.to_string(),
+                        refund: None,
+                    },
+                )),
+            }));
+        }
+        // approved: refund returned articles against the order payment
+        let transaction_id = match order.transaction_id.as_deref() {
+            Some(transaction_id) => transaction_id.to_string(),
+            None => {
+                error!("order {} has no transaction id to refund", order.id);
+                return Ok(Response::new(store::ResolveReturnResponse {
+                    status: Some(store::resolve_return_response::Status::Error(3)),
+                }));
+            }
+        };
+        let order_articles = self.repository.find_order_articles(&order.id).await?;
+        let returned_articles = transaction.find_return_articles(&return_id).await?;
+        let mut amount = Decimal::ZERO;
+        for returned_article in returned_articles.iter() {
+            if let Some(order_article) = order_articles
+                .iter()
+                .find(|x| x.id == returned_article.order_article_id)
+            {
+                amount += order_article.unit_price * Decimal::from(returned_article.quantity);
+            }
+        }
+        // shipping cost is refunded only once the whole order has been returned
+        let not_rejected = transaction
+            .find_not_rejected_return_articles(&order.id)
+            .await?;
+        let fully_returned = order_articles.iter().all(|order_article| {
+            not_rejected
+                .iter()
+                .filter(|x| x.order_article_id == order_article.id)
+                .map(|x| x.quantity as i64)
+                .sum::<i64>()
+                >= order_article.quantity as i64
+        });
+        if fully_returned {
+            amount += order.shipping_cost;
+        }
+        debug!("refunding {amount} for return {return_id} on transaction {transaction_id}");
+        transaction
+            .resolve_return(&return_id, ReturnStatus::Approved)
+            .await?;
+        let refund = transaction
+            .insert_refund(&order.id, &return_id, &transaction_id, amount)
+            .await?;
+        let status = if fully_returned {
+            OrderStatus::Refunded
+        } else {
+            OrderStatus::PartiallyRefunded
+        };
+        transaction
+            .update_order_status(&order.id, status, OrderEventActor::BackOffice)
+            .await?;
+        transaction.commit().await?;
+
+        Ok(Response::new(store::ResolveReturnResponse {
+            status: Some(store::resolve_return_response::Status::Resolved(
+                store::resolve_return_response::ReturnResolved {
+                    return_id: return_id.to_string(),
+                    refund: Some(Self::refund_to_proto(refund)),
+                },
+            )),
+        }))
+    }
+
+    async fn query_wishlists(
+        &self,
+        request: Request<store::QueryWishlistsRequest>,
+    ) -> Result<Response<store::QueryWishlistsResult>, Status> {
+        let user_id = Self::parse_uuid(&request.get_ref().user_id, "user_id")?;
+        debug!("querying wishlists for {user_id}");
+        let wishlists = self.repository.find_customer_wishlists(&user_id).await?;
+        debug!("found {} wishlists", wishlists.len());
+        let mut result = Vec::with_capacity(wishlists.len());
+        for wishlist in wishlists.into_iter() {
+            result.push(self.wishlist_to_proto(wishlist).await?);
+        }
+
+        Ok(Response::new(store::QueryWishlistsResult {
+            wishlists: result,
+        }))
+    }
+
+    async fn create_wishlist(
+        &self,
+        request: Request<store::CreateWishlistRequest>,
+    ) -> Result<Response<store::WishlistResponse>, Status> {
+        let user_id = Self::parse_uuid(&request.get_ref().user_id, "user_id")?;
+        let name = request.get_ref().name.trim();
+        debug!("creating wishlist {name} for {user_id}");
+        if name.is_empty() {
+            return Ok(Response::new(store::WishlistResponse {
+                status: Some(store::wishlist_response::Status::Error(3)),
+            }));
+        }
+        match self.repository.insert_wishlist(&user_id, name).await? {
+            Some(wishlist) => Ok(Response::new(store::WishlistResponse {
+                status: Some(store::wishlist_response::Status::Wishlist(
+                    self.wishlist_to_proto(wishlist).await?,
+                )),
+            })),
+            None => {
+                debug!("wishlist name {name} already taken for {user_id}");
+                Ok(Response::new(store::WishlistResponse {
+                    status: Some(store::wishlist_response::Status::Error(2)),
+                }))
+            }
+        }
+    }
+
+    async fn delete_wishlist(
+        &self,
+        request: Request<store::DeleteWishlistRequest>,
+    ) -> Result<Response<store::WishlistResponse>, Status> {
+        let user_id = Self::parse_uuid(&request.get_ref().user_id, "user_id")?;
+        let wishlist_id = Self::parse_uuid(&request.get_ref().wishlist_id, "wishlist_id")?;
+        debug!("deleting wishlist {wishlist_id} of {user_id}");
+        let wishlist = match self
+            .repository
+            .find_customer_wishlist(&wishlist_id, &user_id)
+            .await?
+        {
+            Some(wishlist) => wishlist,
+            None => {
+                return Ok(Response::new(store::WishlistResponse {
+                    status: Some(store::wishlist_response::Status::Error(1)),
+                }))
+            }
+        };
+        // the response reports the wishlist as it was before being deleted
+        let deleted = self.wishlist_to_proto(wishlist).await?;
+        self.repository.delete_wishlist(&wishlist_id).await?;
+
+        Ok(Response::new(store::WishlistResponse {
+            status: Some(store::wishlist_response::Status::Wishlist(deleted)),
+        }))
+    }
+
+    async fn add_to_wishlist(
+        &self,
+        request: Request<store::AddToWishlistRequest>,
+    ) -> Result<Response<store::WishlistResponse>, Status> {
+        let user_id = Self::parse_uuid(&request.get_ref().user_id, "user_id")?;
+        let wishlist_id = Self::parse_uuid(&request.get_ref().wishlist_id, "wishlist_id")?;
+        let article_id = Self::parse_uuid(&request.get_ref().article_id, "article_id")?;
+        debug!("adding {article_id} to wishlist {wishlist_id} of {user_id}");
+        let wishlist = match self
+            .repository
+            .find_customer_wishlist(&wishlist_id, &user_id)
+            .await?
+        {
+            Some(wishlist) => wishlist,
+            None => {
+                return Ok(Response::new(store::WishlistResponse {
+                    status: Some(store::wishlist_response::Status::Error(1)),
+                }))
+            }
+        };
+        match self.repository.find_article_by_id(&article_id).await? {
+            None => {
+                debug!("article {article_id} not found");
+                return Ok(Response::new(store::WishlistResponse {
+                    status: Some(store::wishlist_response::Status::Error(4)),
+                }));
+            }
+            Some(article) if article.is_archived() => {
+                debug!("article {article_id} is archived");
+                return Ok(Response::new(store::WishlistResponse {
+                    status: Some(store::wishlist_response::Status::Error(5)),
+                }));
+            }
+            Some(_) => {}
+        }
+        self.repository
+            .add_wishlist_item(&wishlist_id, &article_id)
+            .await?;
+
+        Ok(Response::new(store::WishlistResponse {
+            status: Some(store::wishlist_response::Status::Wishlist(
+                self.wishlist_to_proto(wishlist).await?,
+            )),
+        }))
+    }
+
+    async fn remove_from_wishlist(
+        &self,
+        request: Request<store::RemoveFromWishlistRequest>,
+    ) -> Result<Response<store::WishlistResponse>, Status> {
+        let user_id = Self::parse_uuid(&request.get_ref().user_id, "user_id")?;
+        let wishlist_id = Self::parse_uuid(&request.get_ref().wishlist_id, "wishlist_id")?;
+        let article_id = Self::parse_uuid(&request.get_ref().article_id, "article_id")?;
+        debug!("removing {article_id} from wishlist {wishlist_id} of {user_id}");
+        let wishlist = match self
+            .repository
+            .find_customer_wishlist(&wishlist_id, &user_id)
+            .await?
+        {
+            Some(wishlist) => wishlist,
+            None => {
+                return Ok(Response::new(store::WishlistResponse {
+                    status: Some(store::wishlist_response::Status::Error(1)),
+                }))
+            }
+        };
+        if !self
+            .repository
+            .remove_wishlist_item(&wishlist_id, &article_id)
+            .await?
+        {
+            debug!("article {article_id} not in wishlist {wishlist_id}");
+            return Ok(Response::new(store::WishlistResponse {
+                status: Some(store::wishlist_response::Status::Error(4)),
+            }));
+        }
+
+        Ok(Response::new(store::WishlistResponse {
+            status: Some(store::wishlist_response::Status::Wishlist(
+                self.wishlist_to_proto(wishlist).await?,
+            )),
+        }))
+    }
+
+    async fn move_wishlist_item_to_cart(
+        &self,
+        request: Request<store::MoveWishlistItemToCartRequest>,
+    ) -> Result<Response<store::WishlistResponse>, Status> {
+        let user_id = Self::parse_uuid(&request.get_ref().user_id, "user_id")?;
+        let wishlist_id = Self::parse_uuid(&request.get_ref().wishlist_id, "wishlist_id")?;
+        let article_id = Self::parse_uuid(&request.get_ref().article_id, "article_id")?;
+        debug!("moving {article_id} from wishlist {wishlist_id} of {user_id} to cart");
+        let article = match self.repository.find_article_by_id(&article_id).await? {
+            Some(article) => article,
+            None => {
+                return Ok(Response::new(store::WishlistResponse {
+                    status: Some(store::wishlist_response::Status::Error(4)),
+                }))
+            }
+        };
+        // archived articles stay in the wishlist, since they can't be bought
+        if article.is_archived() {
+            debug!("article {article_id} is archived");
+            return Ok(Response::new(store::WishlistResponse {
+                status: Some(store::wishlist_response::Status::Error(5)),
+            }));
+        }
+        let mut transaction = self.repository.begin().await?;
+        let wishlist = match transaction
+            .find_customer_wishlist(&wishlist_id, &user_id)
+            .await?
+        {
+            Some(wishlist) => wishlist,
+            None => {
+                return Ok(Response::new(store::WishlistResponse {
+                    status: Some(store::wishlist_response::Status::Error(1)),
+                }))
+            }
+        };
+        if !transaction
+            .remove_wishlist_item(&wishlist_id, &article_id)
+            .await?
+        {
+            debug!("article {article_id} not in wishlist {wishlist_id}");
+            return Ok(Response::new(store::WishlistResponse {
+                status: Some(store::wishlist_response::Status::Error(4)),
+            }));
+        }
+        let cart = transaction.get_or_create_customer_cart(&user_id).await?;
+        transaction.add_cart_item(&cart.id, &article_id, 1).await?;
+        transaction.touch_cart(&cart.id).await?;
+        transaction.commit().await?;
+
+        Ok(Response::new(store::WishlistResponse {
+            status: Some(store::wishlist_response::Status::Wishlist(
+                self.wishlist_to_proto(wishlist).await?,
+            )),
+        }))
+    }
+
+    async fn get_revenue_report(
+        &self,
+        request: Request<store::GetRevenueReportRequest>,
+    ) -> Result<Response<store::GetRevenueReportResult>, Status> {
+        let (from, to) = Self::parse_date_range(&request.get_ref().range)?;
+        let granularity = match request.get_ref().granularity() {
+            store::get_revenue_report_request::Granularity::Day => ReportGranularity::Day,
+            store::get_revenue_report_request::Granularity::Week => ReportGranularity::Week,
+            store::get_revenue_report_request::Granularity::Month => ReportGranularity::Month,
+        };
+        debug!(
+            "getting revenue report by {:?} from {from} to {to}",
+            granularity
+        );
+        let periods = self
+            .repository
+            .revenue_report(granularity, from, to)
+            .await?
+            .into_iter()
+            .map(|x| store::RevenueReportPeriod {
+                period_start: Some(Self::iso8601(&x.period_start)),
+                paid_orders: x.paid_orders as u64,
+                revenue: Some(store::Decimal {
+                    value: x.revenue.to_string(),
+                }),
+                refunds: Some(store::Decimal {
+                    value: x.refunds.to_string(),
+                }),
+            })
+            .collect();
+
+        Ok(Response::new(store::GetRevenueReportResult { periods }))
+    }
+
+    async fn get_top_articles_report(
+        &self,
+        request: Request<store::GetTopArticlesReportRequest>,
+    ) -> Result<Response<store::GetTopArticlesReportResult>, Status> {
+        let (from, to) = Self::parse_date_range(&request.get_ref().range)?;
+        let limit = match request.get_ref().limit {
+            Some(limit) => (limit as i64).min(MAX_TOP_ARTICLES),
+            None => DEFAULT_TOP_ARTICLES,
+        };
+        debug!("getting top {limit} articles report from {from} to {to}");
+        let articles = self
+            .repository
+            .top_articles_report(from, to, limit)
+            .await?
+            .into_iter()
+            .map(|x| store::TopArticleReport {
+                article_id: x.article_id.to_string(),
+                name: x.name,
+                quantity: x.quantity as u64,
+                revenue: Some(store::Decimal {
+                    value: x.revenue.to_string(),
+                }),
+            })
+            .collect();
+
+        Ok(Response::new(store::GetTopArticlesReportResult {
+            articles,
+        }))
+    }
+
+    async fn get_order_stats_report(
+        &self,
+        request: Request<store::GetOrderStatsReportRequest>,
+    ) -> Result<Response<store::GetOrderStatsReportResult>, Status> {
+        let (from, to) = Self::parse_date_range(&request.get_ref().range)?;
+        debug!("getting order stats report from {from} to {to}");
+        let stats = self.repository.order_stats_report(from, to).await?;
+
+        Ok(Response::new(store::GetOrderStatsReportResult {
+            orders: stats.orders as u64,
+            paid_orders: stats.paid_orders as u64,
+            payment_failures: stats.payment_failures as u64,
+            average_order_value: Some(store::Decimal {
+                value: stats.average_order_value.to_string(),
+            }),
+            payment_failure_rate: Some(store::Decimal {
+                value: stats.payment_failure_rate().to_string(),
+            }),
+        }))
+    }
+
+    type ExportOrdersStream = ReceiverStream<Result<store::ExportedOrder, Status>>;
+
+    async fn export_orders(
+        &self,
+        request: Request<store::ExportOrdersRequest>,
+    ) -> Result<Response<Self::ExportOrdersStream>, Status> {
+        let (from, to) = Self::parse_date_range(&request.get_ref().range)?;
+        debug!("exporting orders created from {from} to {to}");
+        // the bounded channel holds back the export until the client reads the sent orders
+        let (sender, receiver) = mpsc::channel(ORDER_EXPORT_BATCH_SIZE as usize);
+        tokio::spawn(Self::export_orders_batches(
+            self.repository.clone(),
+            from,
+            to,
+            sender,
+        ));
+
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn import_articles(
+        &self,
+        request: Request<tonic::Streaming<store::ImportArticlesRequest>>,
+    ) -> Result<Response<store::ImportArticlesResult>, Status> {
+        let mut stream = request.into_inner();
+        // the whole import runs in a transaction, which is rolled back on dry runs
+        let mut transaction = self.repository.begin().await?;
+        let mut result = store::ImportArticlesResult::default();
+        let mut imported_ids = HashSet::new();
+        let mut imported_names = HashSet::new();
+        let mut batch = Vec::with_capacity(ARTICLE_IMPORT_BATCH_SIZE);
+        let mut rows = 0;
+        while let Some(message) = stream.message().await? {
+            match message.item {
+                Some(store::import_articles_request::Item::Options(options)) => {
+                    if rows > 0 {
+                        return Err(RpcError::invalid_argument(
+                            "options",
+                            "import options must be sent before the articles",
+                        )
+                        .into());
+                    }
+                    result.dry_run = options.dry_run;
+                }
+                Some(store::import_articles_request::Item::Article(article)) => {
+                    rows += 1;
+                    batch.push((rows, article));
+                    if batch.len() == ARTICLE_IMPORT_BATCH_SIZE {
+                        Self::import_articles_batch(
+                            &mut transaction,
+                            std::mem::take(&mut batch),
+                            &mut imported_ids,
+                            &mut imported_names,
+                            &mut result,
+                        )
+                        .await?;
+                    }
+                }
+                None => {}
+            }
+        }
+        if !batch.is_empty() {
+            Self::import_articles_batch(
+                &mut transaction,
+                batch,
+                &mut imported_ids,
+                &mut imported_names,
+                &mut result,
+            )
+            .await?;
+        }
+        result.errors.sort_by_key(|x| x.row);
+        debug!(
+            "imported {rows} articles: {} created, {} updated, {} unchanged, {} rejected; dry run: {}",
+            result.created,
+            result.updated,
+            result.unchanged,
+            result.errors.len(),
+            result.dry_run
+        );
+        // dropping the transaction without committing rolls it back
+        if !result.dry_run {
+            transaction.commit().await?;
+        }
+
+        Ok(Response::new(result))
+    }
+
+    type ExportArticlesStream = ReceiverStream<Result<store::CatalogArticle, Status>>;
+
+    async fn export_articles(
+        &self,
+        _request: Request<store::ExportArticlesRequest>,
+    ) -> Result<Response<Self::ExportArticlesStream>, Status> {
+        debug!("exporting articles");
+        // the bounded channel holds back the export until the client reads the sent articles
+        let (sender, receiver) = mpsc::channel(ARTICLE_EXPORT_BATCH_SIZE as usize);
+        tokio::spawn(Self::export_articles_batches(
+            self.repository.clone(),
+            sender,
+        ));
+
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn get_invoice(
+        &self,
+        request: Request<store::GetInvoiceRequest>,
+    ) -> Result<Response<store::GetInvoiceResponse>, Status> {
+        let user_id = Self::parse_uuid(&request.get_ref().user_id, "user_id")?;
+        let order_id = Self::parse_uuid(&request.get_ref().order_id, "order_id")?;
+        debug!("getting invoice for order {order_id} of customer {user_id}");
+        match self.repository.find_order_by_id(&order_id).await? {
+            Some(order) if order.customer_id == user_id => {}
+            _ => {
+                debug!("order {order_id} not found for customer {user_id}");
+                return Ok(Response::new(store::GetInvoiceResponse {
+                    status: Some(store::get_invoice_response::Status::Error(1)),
+                }));
+            }
+        }
+        let invoice = match self.repository.find_order_invoice(&order_id).await? {
+            Some(invoice) => invoice,
+            None => {
+                debug!("no invoice issued for order {order_id}");
+                return Ok(Response::new(store::GetInvoiceResponse {
+                    status: Some(store::get_invoice_response::Status::Error(2)),
+                }));
+            }
+        };
+        let lines = self.repository.find_invoice_lines(&invoice.id).await?;
+
+        Ok(Response::new(store::GetInvoiceResponse {
+            status: Some(store::get_invoice_response::Status::Invoice(Box::new(
+                Self::invoice_to_proto(invoice, lines),
+            ))),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::{ArticleCoPurchase, ArticleRating, OrderArticle, ShippingCostRule};
+    use crate::repository::{
+        ArticleRepository, CartRepository, CustomerRepository, InMemoryRepository,
+        InvoiceRepository, OrderArticleRepository, OrderRepository, TransactionalRepository,
+    };
+
+    use pretty_assertions::assert_eq;
+    use rust_decimal_macros::dec;
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn should_sign_up_and_sign_in() {
+        let repository = InMemoryRepository::default();
+        let service = service(repository.clone());
+
+        let user_id = match sign_up(&service, "customer@prima.it").await {
+            Some(store::auth_response::Status::UserId(user_id)) => user_id,
+            status => panic!("unexpected sign up status {status:?}"),
+        };
+        assert_eq!(
+            repository.published_events(),
+            vec![DomainEvent::CustomerSignedUp {
+                customer_id: Uuid::parse_str(&user_id).unwrap(),
+                email: "customer@prima.it".to_string(),
+            }]
+        );
+        let signed_in = service
+            .sign_in(Request::new(store::SignInRequest {
+                email: "customer@prima.it".to_string(),
+                password: "Password123!".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .status;
+        assert_eq!(
+            signed_in,
+            Some(store::auth_response::Status::UserId(user_id))
+        );
+        let refused = service
+            .sign_in(Request::new(store::SignInRequest {
+                email: "customer@prima.it".to_string(),
+                password: "password".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .status;
+        assert_eq!(refused, Some(store::auth_response::Status::Error(2)));
+    }
+
+    #[tokio::test]
+    async fn should_not_sign_up_twice() {
+        let service = service(InMemoryRepository::default());
+
+        sign_up(&service, "customer@prima.it").await;
+        assert_eq!(
+            sign_up(&service, "customer@prima.it").await,
+            Some(store::auth_response::Status::Error(0))
+        );
+        assert_eq!(
+            sign_up(&service, "not an email").await,
+            Some(store::auth_response::Status::Error(1))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_query_articles() {
+        let repository = InMemoryRepository::default();
+        let mug = article("Coffee mug", dec!(10));
+        repository.add_article(mug.clone());
+        repository.add_article(article("Pen", dec!(2)));
+        repository.add_article(Article {
+            archived_at: Some(Utc::now().naive_utc()),
+            ..article("Tea mug", dec!(8))
+        });
+        repository.add_rating(ArticleRating {
+            article_id: mug.id,
+            average: dec!(4.5),
+            count: 2,
+        });
+        let service = service(repository);
+
+        let result = service
+            .query_articles(Request::new(store::QueryArticlesRequest {
+                query: Some("mug".to_string()),
+                cursor: None,
+                results_per_page: 10,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(result.total_count, 1);
+        assert!(result.next_cursor.is_none());
+        let articles: Vec<store::Article> =
+            result.edges.into_iter().filter_map(|x| x.article).collect();
+        assert_eq!(articles.len(), 1);
+        assert_eq!(articles[0].id, mug.id.to_string());
+        assert_eq!(articles[0].unit_price.as_ref().unwrap().value, "10");
+        let rating = articles[0].rating.as_ref().unwrap();
+        assert_eq!(rating.average.as_ref().unwrap().value, "4.5");
+        assert_eq!(rating.count, 2);
+    }
+
+    #[tokio::test]
+    async fn should_query_articles_page_by_page() {
+        let repository = InMemoryRepository::default();
+        for name in ["Pen", "Coffee mug", "Notebook", "Tea mug", "Lamp"] {
+            repository.add_article(article(name, dec!(1)));
+        }
+        let service = service(repository);
+
+        let mut names = Vec::new();
+        let mut cursor = None;
+        loop {
+            let result = service
+                .query_articles(Request::new(store::QueryArticlesRequest {
+                    query: None,
+                    cursor: cursor.take(),
+                    results_per_page: 2,
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(result.total_count, 5);
+            assert!(result.edges.len() <= 2);
+            names.extend(
+                result
+                    .edges
+                    .into_iter()
+                    .filter_map(|x| x.article)
+                    .map(|x| x.name),
+            );
+            match result.next_cursor {
+                Some(next_cursor) => cursor = Some(next_cursor),
+                None => break,
+            }
+        }
+        assert_eq!(
+            names,
+            vec!["Coffee mug", "Lamp", "Notebook", "Pen", "Tea mug"]
+        );
+
+        let invalid_cursor = service
+            .query_articles(Request::new(store::QueryArticlesRequest {
+                query: None,
+                cursor: Some("page 2".to_string()),
+                results_per_page: 2,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(invalid_cursor.code(), tonic::Code::InvalidArgument);
+        let info = <store::ErrorInfo as prost::Message>::decode(invalid_cursor.details()).unwrap();
+        assert_eq!(info.metadata["field"], "cursor");
+    }
+
+    #[tokio::test]
+    async fn should_bound_results_per_page() {
+        let repository = InMemoryRepository::default();
+        for i in 0..MAX_RESULTS_PER_PAGE + 5 {
+            repository.add_article(article(&format!("Pen {i:03}"), dec!(1)));
+        }
+        let service = service(repository);
+        let query_articles = |results_per_page: u32| {
+            service.query_articles(Request::new(store::QueryArticlesRequest {
+                query: None,
+                cursor: None,
+                results_per_page,
+            }))
+        };
+
+        let result = query_articles(1000).await.unwrap().into_inner();
+        assert_eq!(result.edges.len(), MAX_RESULTS_PER_PAGE as usize);
+        assert!(result.next_cursor.is_some());
+        let status = query_articles(0).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        let info = <store::ErrorInfo as prost::Message>::decode(status.details()).unwrap();
+        assert_eq!(info.metadata["field"], "results_per_page");
+        let status = service
+            .query_orders(Request::new(store::QueryOrdersRequest {
+                user_id: Uuid::new_v4().to_string(),
+                cursor: None,
+                results_per_page: 0,
+                statuses: vec![],
+                created_from: None,
+                created_to: None,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn should_query_orders_of_customer() {
+        let repository = InMemoryRepository::default();
+        let mug = article("Coffee mug", dec!(10));
+        repository.add_article(mug.clone());
+        let customer_id = Uuid::new_v4();
+        let order = order(&customer_id);
+        repository.add_order(
+            order.clone(),
+            vec![OrderArticle {
+                id: Uuid::new_v4(),
+                order_id: order.id,
+                article_id: mug.id,
+                quantity: 2,
+                unit_price: dec!(9.50),
+            }],
+        );
+        repository.add_order(self::order(&Uuid::new_v4()), vec![]);
+        let service = service(repository);
+
+        let result = service
+            .query_orders(Request::new(store::QueryOrdersRequest {
+                user_id: customer_id.to_string(),
+                cursor: None,
+                results_per_page: 10,
+                statuses: vec![],
+                created_from: None,
+                created_to: None,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(result.total_count, 1);
+        let orders: Vec<store::Order> = result.edges.into_iter().filter_map(|x| x.order).collect();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].id, order.id.to_string());
+        assert_eq!(orders[0].articles.len(), 1);
+        assert_eq!(orders[0].articles[0].name, "Coffee mug");
+        assert_eq!(orders[0].articles[0].quantity, 2);
+        assert_eq!(
+            orders[0].articles[0].unit_price.as_ref().unwrap().value,
+            "9.50"
+        );
+        assert!(orders[0].shipment.is_none());
+    }
+
+    #[tokio::test]
+    async fn should_reject_invalid_user_id_as_invalid_argument() {
+        let service = service(InMemoryRepository::default());
+
+        let status = service
+            .query_orders(Request::new(store::QueryOrdersRequest {
+                user_id: "not-an-uuid".to_string(),
+                cursor: None,
+                results_per_page: 10,
+                statuses: vec![],
+                created_from: None,
+                created_to: None,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        let info = <store::ErrorInfo as prost::Message>::decode(status.details()).unwrap();
+        assert_eq!(info.reason, "INVALID_ARGUMENT");
+        assert_eq!(info.metadata["field"], "user_id");
+    }
+
+    #[tokio::test]
+    async fn should_get_order_of_customer_only() {
+        let repository = InMemoryRepository::default();
+        let customer_id = Uuid::new_v4();
+        let order = order(&customer_id);
+        repository.add_order(order.clone(), vec![]);
+        let service = service(repository);
+        let get_order = |user_id: Uuid, order_id: Uuid| {
+            service.get_order(Request::new(store::GetOrderRequest {
+                user_id: user_id.to_string(),
+                order_id: order_id.to_string(),
+            }))
+        };
+
+        let found = get_order(customer_id, order.id).await.unwrap().into_inner();
+        assert_eq!(found.order.unwrap().id, order.id.to_string());
+        let foreign = get_order(Uuid::new_v4(), order.id)
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(foreign.order.is_none());
+        let missing = get_order(customer_id, Uuid::new_v4())
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(missing.order.is_none());
+    }
+
+    #[tokio::test]
+    async fn should_query_orders_newest_first_with_filters() {
+        let repository = InMemoryRepository::default();
+        let customer_id = Uuid::new_v4();
+        let now = Utc::now().naive_utc();
+        let orders: Vec<CustomerOrder> = (0..4)
+            .map(|days| CustomerOrder {
+                created_at: now - chrono::Duration::days(days),
+                status: if days % 2 == 0 {
+                    OrderStatus::Shipped
+                } else {
+                    OrderStatus::Created
+                },
+                ..order(&customer_id)
+            })
+            .collect();
+        for order in orders.iter() {
+            repository.add_order(order.clone(), vec![]);
+        }
+        let service = service(repository);
+        let query = |statuses: Vec<store::order::OrderStatus>, created_from: Option<i64>| {
+            let service = &service;
+            async move {
+                let result = service
+                    .query_orders(Request::new(store::QueryOrdersRequest {
+                        user_id: customer_id.to_string(),
+                        cursor: None,
+                        results_per_page: 10,
+                        statuses: statuses.into_iter().map(|x| x as i32).collect(),
+                        created_from: created_from.map(|days| {
+                            StoreService::<InMemoryRepository>::iso8601(
+                                &(now - chrono::Duration::days(days)),
+                            )
+                        }),
+                        created_to: None,
+                    }))
+                    .await
+                    .unwrap()
+                    .into_inner();
+                (
+                    result.total_count,
+                    result
+                        .edges
+                        .into_iter()
+                        .filter_map(|x| x.order)
+                        .map(|x| x.id)
+                        .collect::<Vec<_>>(),
+                )
+            }
+        };
+
+        let ids = |indexes: &[usize]| -> Vec<String> {
+            indexes.iter().map(|x| orders[*x].id.to_string()).collect()
+        };
+        assert_eq!(query(vec![], None).await, (4, ids(&[0, 1, 2, 3])));
+        assert_eq!(
+            query(vec![store::order::OrderStatus::Shipped], None).await,
+            (2, ids(&[0, 2]))
+        );
+        assert_eq!(
+            query(vec![store::order::OrderStatus::Created], Some(2)).await,
+            (1, ids(&[1]))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reject_order_with_invalid_articles() {
+        let service = service(InMemoryRepository::default());
+        let article_id = Uuid::new_v4().to_string();
+        let submit = |quantities: &[u32]| {
+            service.submit_order(Request::new(store::SubmitOrderRequest {
+                articles: quantities
+                    .iter()
+                    .map(|quantity| store::submit_order_request::OrderArticle {
+                        article_id: article_id.clone(),
+                        quantity: *quantity,
+                    })
+                    .collect(),
+                user_id: Uuid::new_v4().to_string(),
+                shipping_method_id: None,
+                card_number: None,
+            }))
+        };
+        let error = |code: store::submit_order_response::SubmitOrderError| {
+            Some(store::submit_order_response::Status::Error(code as i32))
+        };
+
+        let response = submit(&[]).await.unwrap().into_inner();
+        assert_eq!(
+            response.status,
+            error(store::submit_order_response::SubmitOrderError::EmptyOrder)
+        );
+        let response = submit(&[1, 0]).await.unwrap().into_inner();
+        assert_eq!(
+            response.status,
+            error(store::submit_order_response::SubmitOrderError::InvalidQuantity)
+        );
+        let response = submit(&[u32::MAX]).await.unwrap().into_inner();
+        assert_eq!(
+            response.status,
+            error(store::submit_order_response::SubmitOrderError::InvalidQuantity)
+        );
+        // duplicated lines add up beyond the maximum quantity
+        let response = submit(&[MAX_ORDER_ARTICLE_QUANTITY, 1])
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            response.status,
+            error(store::submit_order_response::SubmitOrderError::InvalidQuantity)
+        );
+    }
+
+    #[test]
+    fn should_merge_order_articles() {
+        let mug = Uuid::new_v4();
+        let pen = Uuid::new_v4();
+
+        assert_eq!(
+            StoreService::<InMemoryRepository>::merge_order_articles(vec![
+                (mug, 2),
+                (pen, 1),
+                (mug, 3)
+            ])
+            .unwrap(),
+            vec![(mug, 5), (pen, 1)]
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_report_date_range() {
+        let range = |from: &str, to: &str| {
+            Some(store::DateRange {
+                from: Some(store::Iso8601 {
+                    timestamp: from.to_string(),
+                }),
+                to: Some(store::Iso8601 {
+                    timestamp: to.to_string(),
+                }),
+            })
+        };
+        let field = |range: &Option<store::DateRange>| {
+            match StoreService::<InMemoryRepository>::parse_date_range(range) {
+                Err(RpcError::InvalidArgument { field, .. }) => field,
+                result => panic!("unexpected result {result:?}"),
+            }
+        };
+
+        assert!(StoreService::<InMemoryRepository>::parse_date_range(&range(
+            "2023-01-01 00:00:00",
+            "2023-02-01 00:00:00"
+        ))
+        .is_ok());
+        assert_eq!(field(&None), "range");
+        assert_eq!(
+            field(&range("2023-01-01", "2023-02-01 00:00:00")),
+            "range.from"
+        );
+        assert_eq!(
+            field(&range("2023-02-01 00:00:00", "2023-01-01 00:00:00")),
+            "range"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_export_articles_ordered_by_id() {
+        let repository = InMemoryRepository::default();
+        for index in 0..(ARTICLE_EXPORT_BATCH_SIZE + 5) {
+            repository.add_article(article(&format!("Article {index}"), dec!(1)));
+        }
+        let service = service(repository);
+
+        let articles: Vec<store::CatalogArticle> = service
+            .export_articles(Request::new(store::ExportArticlesRequest {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .map(|x| x.unwrap())
+            .collect()
+            .await;
+        assert_eq!(articles.len(), (ARTICLE_EXPORT_BATCH_SIZE + 5) as usize);
+        let ids: Vec<Uuid> = articles
+            .iter()
+            .map(|x| Uuid::parse_str(&x.id).unwrap())
+            .collect();
+        assert!(ids.windows(2).all(|x| x[0] < x[1]));
+    }
+
+    #[tokio::test]
+    async fn should_not_pay_expired_order() {
+        let repository = InMemoryRepository::default();
+        let order = unpaid_order(&repository).await;
+        repository
+            .expire_orders_created_before(Utc::now().naive_utc(), i64::MAX)
+            .await
+            .unwrap();
+
+        let result = StoreService::payment_succeeded(
+            &repository,
+            &invoice_seller(),
+            &order.id,
+            "transaction",
+        )
+        .await;
+        assert!(matches!(result, Err(RpcError::FailedPrecondition(_))));
+        let order = repository
+            .find_order_by_id(&order.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::Expired);
+        assert_eq!(order.transaction_id, None);
+        assert!(repository
+            .find_order_invoice(&order.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn should_apply_duplicate_payment_once() {
+        let repository = InMemoryRepository::default();
+        let order = unpaid_order(&repository).await;
+
+        StoreService::payment_succeeded(&repository, &invoice_seller(), &order.id, "transaction")
+            .await
+            .unwrap();
+        let result = StoreService::payment_succeeded(
+            &repository,
+            &invoice_seller(),
+            &order.id,
+            "another transaction",
+        )
+        .await;
+        assert!(matches!(result, Err(RpcError::FailedPrecondition(_))));
+        let order = repository
+            .find_order_by_id(&order.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::Preparing);
+        assert_eq!(order.transaction_id.as_deref(), Some("transaction"));
+        let transaction_changes = repository
+            .find_order_events(&order.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|x| x.kind == OrderEventKind::TransactionChanged)
+            .count();
+        assert_eq!(transaction_changes, 1);
+    }
+
+    #[tokio::test]
+    async fn should_issue_invoice_of_paid_order() {
+        let repository = InMemoryRepository::default();
+        let pen = article("pen", dec!(2));
+        repository.add_article(pen.clone());
+        let order = unpaid_order(&repository).await;
+        let mut transaction = repository.begin().await.unwrap();
+        transaction
+            .insert_order_article(&order.id, &pen.id, 5, pen.unit_price)
+            .await
+            .unwrap();
+        transaction.commit().await.unwrap();
+
+        StoreService::payment_succeeded(&repository, &invoice_seller(), &order.id, "transaction")
+            .await
+            .unwrap();
+        let invoice = service(repository.clone())
+            .get_invoice(Request::new(store::GetInvoiceRequest {
+                user_id: order.customer_id.to_string(),
+                order_id: order.id.to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .status;
+        let invoice = match invoice {
+            Some(store::get_invoice_response::Status::Invoice(invoice)) => invoice,
+            other => panic!("expected invoice, got {:?}", other),
+        };
+        assert_eq!(invoice.transaction_id, "transaction");
+        assert_eq!(invoice.lines.len(), 1);
+        assert_eq!(
+            invoice.total,
+            Some(store::Decimal {
+                value: "10".to_string()
+            })
+        );
+        assert!(repository
+            .published_events()
+            .contains(&DomainEvent::PaymentSucceeded {
+                order_id: order.id,
+                transaction_id: "transaction".to_string(),
+            }));
+    }
+
+    #[tokio::test]
+    async fn should_acknowledge_redelivered_payment() {
+        let repository = InMemoryRepository::default();
+        let paid = unpaid_order(&repository).await;
+        let mut transaction = repository.begin().await.unwrap();
+        let refused = transaction
+            .insert_order(&paid.customer_id, None, Decimal::ZERO)
+            .await
+            .unwrap();
+        transaction.commit().await.unwrap();
+
+        for _ in 0..2 {
+            StoreService::payment_succeeded(
+                &repository,
+                &invoice_seller(),
+                &paid.id,
+                "transaction",
+            )
+            .await
+            .unwrap();
+            StoreService::payment_failed(&repository, &refused.id)
+                .await
+                .unwrap();
+        }
+        let payments = repository
+            .find_order_events(&paid.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|x| x.kind == OrderEventKind::TransactionChanged)
+            .count();
+        assert_eq!(payments, 1);
+        assert_eq!(
+            repository
+                .find_order_by_id(&refused.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .status,
+            OrderStatus::PaymentRefused
+        );
+    }
+
+    #[tokio::test]
+    async fn should_not_refuse_payment_of_paid_order() {
+        let repository = InMemoryRepository::default();
+        let order = unpaid_order(&repository).await;
+
+        StoreService::payment_succeeded(&repository, &invoice_seller(), &order.id, "transaction")
+            .await
+            .unwrap();
+        let result = StoreService::payment_failed(&repository, &order.id).await;
+        assert!(matches!(result, Err(RpcError::FailedPrecondition(_))));
+        assert_eq!(
+            repository
+                .find_order_by_id(&order.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .status,
+            OrderStatus::Preparing
+        );
+    }
+
+    #[tokio::test]
+    async fn should_recommend_articles_bought_together() {
+        let repository = InMemoryRepository::default();
+        let pen = article("pen", dec!(2));
+        let ink = article("ink", dec!(10));
+        let paper = article("paper", dec!(1));
+        let mut eraser = article("eraser", dec!(1));
+        eraser.archived_at = Some(Utc::now().naive_utc());
+        for (related, score) in [(&ink, 2), (&paper, 1), (&eraser, 5)] {
+            repository.add_article(related.clone());
+            repository.add_co_purchase(ArticleCoPurchase {
+                article_id: pen.id,
+                related_article_id: related.id,
+                score,
+            });
+        }
+        repository.add_article(pen.clone());
+
+        let recommended: Vec<String> = service(repository)
+            .recommend_articles(Request::new(store::RecommendArticlesRequest {
+                source: Some(store::recommend_articles_request::Source::ArticleId(
+                    pen.id.to_string(),
+                )),
+                limit: None,
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .articles
+            .into_iter()
+            .map(|x| x.name)
+            .collect();
+        assert_eq!(recommended, vec!["ink".to_string(), "paper".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn should_update_anonymous_cart() {
+        let repository = InMemoryRepository::default();
+        let pen = article("pen", dec!(2));
+        let ink = article("ink", dec!(10));
+        repository.add_article(pen.clone());
+        repository.add_article(ink.clone());
+        let service = service(repository);
+        let owner = Some(store::CartOwner {
+            owner: Some(store::cart_owner::Owner::AnonymousCartId(
+                Uuid::new_v4().to_string(),
+            )),
+        });
+
+        for (article_id, quantity) in [(&pen.id, 2), (&ink.id, 1)] {
+            service
+                .add_to_cart(Request::new(store::AddToCartRequest {
+                    owner: owner.clone(),
+                    article_id: article_id.to_string(),
+                    quantity,
+                }))
+                .await
+                .unwrap();
+        }
+        service
+            .update_cart_item(Request::new(store::UpdateCartItemRequest {
+                owner: owner.clone(),
+                article_id: pen.id.to_string(),
+                quantity: 5,
+            }))
+            .await
+            .unwrap();
+        let cart = service
+            .remove_from_cart(Request::new(store::RemoveFromCartRequest {
+                owner,
+                article_id: ink.id.to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .status;
+        let cart = match cart {
+            Some(store::cart_response::Status::Cart(cart)) => cart,
+            other => panic!("expected cart, got {:?}", other),
+        };
+        assert_eq!(cart.articles.len(), 1);
+        assert_eq!(cart.articles[0].quantity, 5);
+        assert_eq!(
+            cart.subtotal,
+            Some(store::Decimal {
+                value: "10".to_string()
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn should_manage_wishlists() {
+        let repository = InMemoryRepository::default();
+        let pen = article("pen", dec!(2));
+        repository.add_article(pen.clone());
+        let service = service(repository);
+        let user_id = Uuid::new_v4();
+
+        let wishlist = match service
+            .create_wishlist(Request::new(store::CreateWishlistRequest {
+                user_id: user_id.to_string(),
+                name: "school".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .status
+        {
+            Some(store::wishlist_response::Status::Wishlist(wishlist)) => wishlist,
+            other => panic!("expected wishlist, got {:?}", other),
+        };
+        assert_eq!(
+            service
+                .create_wishlist(Request::new(store::CreateWishlistRequest {
+                    user_id: user_id.to_string(),
+                    name: "school".to_string(),
+                }))
+                .await
+                .unwrap()
+                .into_inner()
+                .status,
+            Some(store::wishlist_response::Status::Error(2))
+        );
+        service
+            .add_to_wishlist(Request::new(store::AddToWishlistRequest {
+                user_id: user_id.to_string(),
+                wishlist_id: wishlist.id.clone(),
+                article_id: pen.id.to_string(),
+            }))
+            .await
+            .unwrap();
+        let wishlists = service
+            .query_wishlists(Request::new(store::QueryWishlistsRequest {
+                user_id: user_id.to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .wishlists;
+        assert_eq!(wishlists.len(), 1);
+        assert_eq!(wishlists[0].articles.len(), 1);
+        assert_eq!(wishlists[0].articles[0].id, pen.id.to_string());
+
+        let removed = service
+            .remove_from_wishlist(Request::new(store::RemoveFromWishlistRequest {
+                user_id: user_id.to_string(),
+                wishlist_id: wishlist.id.clone(),
+                article_id: pen.id.to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .status;
+        assert!(matches!(
+            removed,
+            Some(store::wishlist_response::Status::Wishlist(wishlist)) if wishlist.articles.is_empty()
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_publish_review_once_approved() {
+        let repository = InMemoryRepository::default();
+        let pen = article("pen", dec!(2));
+        repository.add_article(pen.clone());
+        let customer_id = Uuid::new_v4();
+        let mut shipped = order(&customer_id);
+        shipped.status = OrderStatus::Shipped;
+        repository.add_order(
+            shipped.clone(),
+            vec![OrderArticle {
+                id: Uuid::new_v4(),
+                order_id: shipped.id,
+                article_id: pen.id,
+                quantity: 1,
+                unit_price: pen.unit_price,
+            }],
+        );
+        let service = service(repository.clone());
+
+        let review_id = match service
+            .submit_review(Request::new(store::SubmitReviewRequest {
+                user_id: customer_id.to_string(),
+                article_id: pen.id.to_string(),
+                rating: 4,
+                title: "Good pen".to_string(),
+                body: "Writes well".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .status
+        {
+            Some(store::submit_review_response::Status::ReviewId(review_id)) => review_id,
+            other => panic!("expected review id, got {:?}", other),
+        };
+        assert!(query_article_reviews(&service, &pen.id)
+            .await
+            .edges
+            .is_empty());
+        service
+            .moderate_review(Request::new(store::ModerateReviewRequest {
+                review_id,
+                approved: true,
+            }))
+            .await
+            .unwrap();
+        let reviews = query_article_reviews(&service, &pen.id).await;
+        assert_eq!(reviews.edges.len(), 1);
+        assert_eq!(reviews.total_count, 1);
+        assert_eq!(
+            repository
+                .find_article_rating(&pen.id)
+                .await
+                .unwrap()
+                .map(|x| x.average),
+            Some(dec!(4))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_report_order_stats() {
+        let repository = InMemoryRepository::default();
+        let pen = article("pen", dec!(2));
+        repository.add_article(pen.clone());
+        let customer_id = Uuid::new_v4();
+        for status in [
+            OrderStatus::Preparing,
+            OrderStatus::PaymentRefused,
+            OrderStatus::Created,
+        ] {
+            let mut order = order(&customer_id);
+            order.status = status;
+            let order_article = OrderArticle {
+                id: Uuid::new_v4(),
+                order_id: order.id,
+                article_id: pen.id,
+                quantity: 1,
+                unit_price: pen.unit_price,
+            };
+            repository.add_order(order, vec![order_article]);
+        }
+        let now = Utc::now().naive_utc();
+
+        let stats = service(repository)
+            .get_order_stats_report(Request::new(store::GetOrderStatsReportRequest {
+                range: Some(store::DateRange {
+                    from: Some(StoreService::<InMemoryRepository>::iso8601(
+                        &(now - chrono::Duration::days(1)),
+                    )),
+                    to: Some(StoreService::<InMemoryRepository>::iso8601(
+                        &(now + chrono::Duration::days(1)),
+                    )),
+                }),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(stats.orders, 3);
+        assert_eq!(stats.paid_orders, 1);
+        assert_eq!(stats.payment_failures, 1);
+        assert_eq!(
+            stats.average_order_value,
+            Some(store::Decimal {
+                value: "7".to_string()
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn should_submit_order_with_shipping_cost() {
+        let repository = InMemoryRepository::default();
+        let pen = article("pen", dec!(2));
+        let ink = article("ink", dec!(10));
+        repository.add_article(pen.clone());
+        repository.add_article(ink.clone());
+        let courier = shipping_method("courier");
+        repository.add_shipping_method(
+            courier.clone(),
+            vec![shipping_cost_rule(&courier.id, dec!(5))],
+        );
+        let service = service(repository.clone());
+        let customer_id = Uuid::new_v4();
+
+        let order_id = match submit_order(
+            &service,
+            &customer_id,
+            &[(pen.id, 1), (ink.id, 1), (pen.id, 2)],
+            Some(courier.id),
+        )
+        .await
+        {
+            Some(store::submit_order_response::Status::OrderId(order_id)) => {
+                Uuid::parse_str(&order_id).unwrap()
+            }
+            status => panic!("unexpected submit order status {status:?}"),
+        };
+        let order = repository
+            .find_order_by_id(&order_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(order.customer_id, customer_id);
+        assert_eq!(order.status, OrderStatus::Created);
+        assert_eq!(order.shipping_method_id, Some(courier.id));
+        assert_eq!(order.shipping_cost, dec!(5));
+        let order_articles: Vec<(Uuid, i32, Decimal)> = repository
+            .find_order_articles(&order_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|x| (x.article_id, x.quantity, x.unit_price))
+            .collect();
+        assert_eq!(
+            order_articles,
+            vec![(pen.id, 3, dec!(2)), (ink.id, 1, dec!(10))]
+        );
+        assert_eq!(
+            repository.published_events(),
+            vec![DomainEvent::OrderSubmitted {
+                order_id,
+                customer_id,
+                articles: vec![
+                    SubmittedArticle {
+                        article_id: pen.id,
+                        quantity: 3,
+                        unit_price: dec!(2),
+                    },
+                    SubmittedArticle {
+                        article_id: ink.id,
+                        quantity: 1,
+                        unit_price: dec!(10),
+                    },
+                ],
+                shipping_method_id: Some(courier.id),
+                shipping_cost: dec!(5),
+                total: dec!(21),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reject_order_with_unavailable_shipping_method() {
+        let repository = InMemoryRepository::default();
+        let pen = article("pen", dec!(2));
+        repository.add_article(pen.clone());
+        let courier = shipping_method("courier");
+        repository.add_shipping_method(
+            courier.clone(),
+            vec![ShippingCostRule {
+                max_weight: Some(100),
+                ..shipping_cost_rule(&courier.id, dec!(5))
+            }],
+        );
+        let service = service(repository.clone());
+
+        assert_eq!(
+            submit_order(&service, &Uuid::new_v4(), &[(pen.id, 2)], Some(courier.id)).await,
+            Some(store::submit_order_response::Status::Error(2))
+        );
+        assert_eq!(
+            submit_order(
+                &service,
+                &Uuid::new_v4(),
+                &[(pen.id, 1)],
+                Some(Uuid::new_v4())
+            )
+            .await,
+            Some(store::submit_order_response::Status::Error(2))
+        );
+        assert!(repository.published_events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_reject_order_with_archived_article() {
+        let repository = InMemoryRepository::default();
+        let pen = article("pen", dec!(2));
+        let ink = Article {
+            archived_at: Some(Utc::now().naive_utc()),
+            ..article("ink", dec!(10))
+        };
+        repository.add_article(pen.clone());
+        repository.add_article(ink.clone());
+        let service = service(repository.clone());
+        let customer_id = Uuid::new_v4();
+
+        assert_eq!(
+            submit_order(&service, &customer_id, &[(pen.id, 1), (ink.id, 1)], None).await,
+            Some(store::submit_order_response::Status::Error(1))
+        );
+        assert_eq!(
+            submit_order(&service, &customer_id, &[(Uuid::new_v4(), 1)], None).await,
+            Some(store::submit_order_response::Status::Error(1))
+        );
+        assert_eq!(
+            repository
+                .count_orders_by_customer(&customer_id, &OrderFilter::default())
+                .await
+                .unwrap(),
+            0
+        );
+        assert!(repository.published_events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_reject_checkout_of_empty_cart() {
+        let service = service(InMemoryRepository::default());
+
+        assert_eq!(
+            checkout_cart(&service, &Uuid::new_v4()).await,
+            Some(store::submit_order_response::Status::Error(
+                store::submit_order_response::SubmitOrderError::EmptyOrder as i32
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reject_checkout_of_cart_with_too_many_items() {
+        let repository = InMemoryRepository::default();
+        let mug = article("Coffee mug", dec!(10));
+        repository.add_article(mug.clone());
+        let customer_id = Uuid::new_v4();
+        let cart =
+            repository.add_cart_item(&customer_id, &mug.id, MAX_ORDER_ARTICLE_QUANTITY as i32 + 1);
+        let service = service(repository.clone());
+
+        assert_eq!(
+            checkout_cart(&service, &customer_id).await,
+            Some(store::submit_order_response::Status::Error(
+                store::submit_order_response::SubmitOrderError::InvalidQuantity as i32
+            ))
+        );
+        assert_eq!(repository.find_cart_items(&cart.id).await.unwrap().len(), 1);
+    }
+
+    /// Store service backed by `repository`
+    fn service(repository: InMemoryRepository) -> StoreService<InMemoryRepository> {
+        let (payment_notifier, _) = mpsc::unbounded_channel();
+        StoreService {
+            address: "127.0.0.1:50051".parse().unwrap(),
+            repository,
+            payment_gateway: Arc::new(MockPaymentGateway::new(Duration::ZERO, payment_notifier)),
+            payment_outcomes: None,
+            order_ttl: Duration::from_secs(3600),
+            order_expiry_interval: Duration::from_secs(60),
+            outbox_relay: None,
+            recommendation_builder: None,
+            invoice_seller: invoice_seller(),
+        }
+    }
+
+    async fn checkout_cart(
+        service: &StoreService<InMemoryRepository>,
+        user_id: &Uuid,
+    ) -> Option<store::submit_order_response::Status> {
+        service
+            .checkout_cart(Request::new(store::CheckoutCartRequest {
+                user_id: user_id.to_string(),
+                shipping_method_id: None,
+                card_number: None,
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .status
+    }
+
+    async fn query_article_reviews(
+        service: &StoreService<InMemoryRepository>,
+        article_id: &Uuid,
+    ) -> store::QueryArticleReviewsResult {
+        service
+            .query_article_reviews(Request::new(store::QueryArticleReviewsRequest {
+                article_id: article_id.to_string(),
+                cursor: None,
+                results_per_page: 10,
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+    }
+
+    async fn submit_order(
+        service: &StoreService<InMemoryRepository>,
+        user_id: &Uuid,
+        articles: &[(Uuid, u32)],
+        shipping_method_id: Option<Uuid>,
+    ) -> Option<store::submit_order_response::Status> {
+        service
+            .submit_order(Request::new(store::SubmitOrderRequest {
+                articles: articles
+                    .iter()
+                    .map(
+                        |(article_id, quantity)| store::submit_order_request::OrderArticle {
+                            article_id: article_id.to_string(),
+                            quantity: *quantity,
+                        },
+                    )
+                    .collect(),
+                user_id: user_id.to_string(),
+                shipping_method_id: shipping_method_id.map(|id| id.to_string()),
+                card_number: None,
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .status
+    }
+
+    fn invoice_seller() -> InvoiceSeller {
+        InvoiceSeller {
+            name: "Prima".to_string(),
+            address: "Via Roma 1, Milano".to_string(),
+            vat_number: "IT01234567890".to_string(),
+            tax_rate: dec!(22),
+        }
+    }
+
+    /// Order of a new customer, waiting for its payment
+    async fn unpaid_order(repository: &InMemoryRepository) -> CustomerOrder {
+        let customer = repository
+            .insert_customer("customer@prima.it", "Password123!")
+            .await
+            .unwrap();
+        let mut transaction = repository.begin().await.unwrap();
+        let order = transaction
+            .insert_order(&customer.id, None, Decimal::ZERO)
+            .await
+            .unwrap();
+        transaction.commit().await.unwrap();
+
+        order
+    }
+
+    async fn sign_up(
+        service: &StoreService<InMemoryRepository>,
+        email: &str,
+    ) -> Option<store::auth_response::Status> {
+        service
+            .sign_up(Request::new(store::SignUpRequest {
+                email: email.to_string(),
+                password: "Password123!".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .status
+    }
+
+    fn article(name: &str, unit_price: Decimal) -> Article {
+        Article {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: "Lorem Ipsum".to_string(),
+            unit_price,
+            weight: 100,
+            archived_at: None,
+        }
+    }
+
+    fn shipping_method(name: &str) -> ShippingMethod {
+        ShippingMethod {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: "Lorem Ipsum".to_string(),
+        }
+    }
+
+    /// Rule applying `cost` to any order shipped with the method
+    fn shipping_cost_rule(shipping_method_id: &Uuid, cost: Decimal) -> ShippingCostRule {
+        ShippingCostRule {
+            id: Uuid::new_v4(),
+            shipping_method_id: *shipping_method_id,
+            min_weight: 0,
+            max_weight: None,
+            min_subtotal: Decimal::ZERO,
+            max_subtotal: None,
+            cost,
+        }
+    }
+
+    fn order(customer_id: &Uuid) -> CustomerOrder {
+        CustomerOrder {
+            id: Uuid::new_v4(),
+            customer_id: *customer_id,
+            created_at: Utc::now().naive_utc(),
+            status: OrderStatus::Created,
+            transaction_id: None,
+            shipping_method_id: None,
+            shipping_cost: dec!(5),
+        }
+    }
 }
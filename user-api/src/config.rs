@@ -2,17 +2,47 @@
 //!
 //! App configuration
 
+/// Placeholder webhook secret of the sample configurations, which must never be used
+const PLACEHOLDER_WEBHOOK_SECRET: &str = "changeme";
+
 /// App configuration read from environment
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
     pub web_port: u16,
     pub grpc_server_url: String,
+    /// Secret shared with the payment provider used to sign webhook events
+    pub webhook_secret: String,
+    /// Maximum age in seconds of a webhook event timestamp
+    #[serde(default = "Config::default_webhook_tolerance_secs")]
+    pub webhook_tolerance_secs: i64,
+    /// Emails of the users allowed to access the admin queries, comma separated
+    #[serde(default)]
+    pub admin_emails: Vec<String>,
 }
 
 impl Config {
     pub fn try_from_env() -> anyhow::Result<Self> {
-        envy::from_env()
-            .map_err(|e| anyhow::anyhow!("could not load config from environment: {}", e))
+        let config: Self = envy::from_env()
+            .map_err(|e| anyhow::anyhow!("could not load config from environment: {}", e))?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Refuse configurations which would let anyone forge payment events
+    fn validate(&self) -> anyhow::Result<()> {
+        let secret = self.webhook_secret.trim();
+        if secret.is_empty() || secret == PLACEHOLDER_WEBHOOK_SECRET {
+            anyhow::bail!(
+                "WEBHOOK_SECRET must be set to the secret shared with the payment provider"
+            );
+        }
+
+        Ok(())
+    }
+
+    fn default_webhook_tolerance_secs() -> i64 {
+        300
     }
 }
 
@@ -25,4 +55,18 @@ mod test {
     fn should_parse_config_from_env() {
         assert!(Config::try_from_env().is_ok());
     }
+
+    #[test]
+    fn should_reject_placeholder_webhook_secret() {
+        let config = |webhook_secret: &str| Config {
+            web_port: 3005,
+            grpc_server_url: "http://localhost:50051".to_string(),
+            webhook_secret: webhook_secret.to_string(),
+            webhook_tolerance_secs: 300,
+            admin_emails: vec![],
+        };
+        assert!(config("changeme").validate().is_err());
+        assert!(config("").validate().is_err());
+        assert!(config("s3cr3t").validate().is_ok());
+    }
 }
@@ -0,0 +1,268 @@
+use super::TestEnvironment;
+
+use actix_web::rt::time::sleep;
+use hyper::StatusCode;
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+use std::time::Duration;
+use uuid::Uuid;
+
+const SUBMIT_ORDER: &str = r#"
+    mutation SubmitOrder($articles: [OrderArticle!]!, $cardNumber: String) {
+        submitOrder(articles: $articles, cardNumber: $cardNumber) {
+            __typename
+            ... on OrderAccepted { id }
+            ... on OrderRejected { code message }
+        }
+    }
+"#;
+
+const ORDERS: &str = r#"
+    query {
+        orders(first: 50) {
+            nodes {
+                id
+                status
+                articles { id quantity }
+            }
+        }
+    }
+"#;
+
+const ORDER: &str = r#"
+    query Order($id: Uuid!) {
+        order(id: $id) { id status }
+    }
+"#;
+
+const CARD_NUMBER: &str = "4242 4242 4242 4242";
+const DECLINED_CARD_NUMBER: &str = "4000000000000002";
+
+#[actix_web::test]
+async fn should_submit_order_and_find_it_in_orders() {
+    let environment = TestEnvironment::start().await;
+    let article_id = environment.add_article("E2E mug", "12.50").await;
+    let mut client = environment.client();
+    client.sign_up().await;
+
+    let response = client
+        .graphql(
+            SUBMIT_ORDER,
+            json!({ "articles": [{ "id": article_id, "quantity": 2 }] }),
+        )
+        .await;
+    let submission = &response["data"]["submitOrder"];
+    assert_eq!(submission["__typename"], "OrderAccepted", "{response}");
+    let order_id = submission["id"].clone();
+
+    let order = find_order(&mut client, &order_id).await;
+    assert_eq!(order["status"], "CREATED");
+    assert_eq!(
+        order["articles"],
+        json!([{ "id": article_id, "quantity": 2 }])
+    );
+}
+
+#[actix_web::test]
+async fn should_find_order_by_id_of_its_customer_only() {
+    let environment = TestEnvironment::start().await;
+    let article_id = environment.add_article("E2E cup", "3.40").await;
+    let mut client = environment.client();
+    client.sign_up().await;
+    let response = client
+        .graphql(
+            SUBMIT_ORDER,
+            json!({ "articles": [{ "id": article_id, "quantity": 1 }] }),
+        )
+        .await;
+    let order_id = response["data"]["submitOrder"]["id"].clone();
+
+    let response = client.graphql(ORDER, json!({ "id": order_id })).await;
+    assert_eq!(response["data"]["order"]["id"], order_id, "{response}");
+    let response = client.graphql(ORDER, json!({ "id": Uuid::new_v4() })).await;
+    assert_eq!(response["data"]["order"], Value::Null, "{response}");
+
+    let mut other_client = environment.client();
+    other_client.sign_up().await;
+    let response = other_client.graphql(ORDER, json!({ "id": order_id })).await;
+    assert_eq!(response["data"]["order"], Value::Null, "{response}");
+    assert_eq!(response["errors"], Value::Null);
+}
+
+#[actix_web::test]
+async fn should_keep_session_when_signing_in_again() {
+    let environment = TestEnvironment::start().await;
+    let article_id = environment.add_article("E2E pen", "1.20").await;
+    let email = environment.client().sign_up().await;
+    let mut client = environment.client();
+    client.sign_in(&email).await;
+
+    let response = client
+        .graphql(
+            SUBMIT_ORDER,
+            json!({ "articles": [{ "id": article_id, "quantity": 1 }] }),
+        )
+        .await;
+    let submission = &response["data"]["submitOrder"];
+    assert_eq!(submission["__typename"], "OrderAccepted", "{response}");
+    find_order(&mut client, &submission["id"]).await;
+}
+
+#[actix_web::test]
+async fn should_pay_order_with_card() {
+    let environment = TestEnvironment::start().await;
+    let article_id = environment.add_article("E2E notebook", "4.90").await;
+    let mut client = environment.client();
+    client.sign_up().await;
+
+    let response = client
+        .graphql(
+            SUBMIT_ORDER,
+            json!({
+                "articles": [{ "id": article_id, "quantity": 1 }],
+                "cardNumber": CARD_NUMBER,
+            }),
+        )
+        .await;
+    let submission = &response["data"]["submitOrder"];
+    assert_eq!(submission["__typename"], "OrderAccepted", "{response}");
+
+    wait_for_status(&mut client, &submission["id"], "PREPARING").await;
+}
+
+#[actix_web::test]
+async fn should_fail_payment_with_declined_card() {
+    let environment = TestEnvironment::start().await;
+    let article_id = environment.add_article("E2E backpack", "39.00").await;
+    let mut client = environment.client();
+    client.sign_up().await;
+
+    let response = client
+        .graphql(
+            SUBMIT_ORDER,
+            json!({
+                "articles": [{ "id": article_id, "quantity": 1 }],
+                "cardNumber": DECLINED_CARD_NUMBER,
+            }),
+        )
+        .await;
+    let submission = &response["data"]["submitOrder"];
+    assert_eq!(submission["__typename"], "OrderAccepted", "{response}");
+
+    wait_for_status(&mut client, &submission["id"], "PAYMENT_FAILED").await;
+}
+
+#[actix_web::test]
+async fn should_apply_redelivered_payment_events_once() {
+    let environment = TestEnvironment::start().await;
+    let article_id = environment.add_article("E2E pencil", "1.20").await;
+    let mut client = environment.client();
+    client.sign_up().await;
+    let response = client
+        .graphql(
+            SUBMIT_ORDER,
+            json!({ "articles": [{ "id": article_id, "quantity": 3 }] }),
+        )
+        .await;
+    let order_id = response["data"]["submitOrder"]["id"].clone();
+    let succeeded = json!({
+        "id": "evt_succeeded",
+        "type": "payment.succeeded",
+        "order_id": order_id,
+        "transaction_id": "tx_e2e",
+    });
+
+    for _ in 0..2 {
+        let status = client.send_payment_event(succeeded.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let failed = json!({ "id": "evt_failed", "type": "payment.failed", "order_id": order_id });
+    assert_eq!(client.send_payment_event(failed).await, StatusCode::OK);
+    assert_eq!(
+        find_order(&mut client, &order_id).await["status"],
+        "PREPARING"
+    );
+}
+
+#[actix_web::test]
+async fn should_reject_order_with_unknown_article() {
+    let environment = TestEnvironment::start().await;
+    let mut client = environment.client();
+    client.sign_up().await;
+
+    let response = client
+        .graphql(
+            SUBMIT_ORDER,
+            json!({ "articles": [{ "id": Uuid::new_v4(), "quantity": 1 }] }),
+        )
+        .await;
+    let submission = &response["data"]["submitOrder"];
+    assert_eq!(submission["__typename"], "OrderRejected", "{response}");
+    assert_eq!(submission["code"], "INVALID_ARTICLE");
+}
+
+#[actix_web::test]
+async fn should_reject_order_without_articles_or_with_zero_quantity() {
+    let environment = TestEnvironment::start().await;
+    let article_id = environment.add_article("E2E stapler", "7.80").await;
+    let mut client = environment.client();
+    client.sign_up().await;
+
+    let response = client
+        .graphql(SUBMIT_ORDER, json!({ "articles": [] }))
+        .await;
+    let submission = &response["data"]["submitOrder"];
+    assert_eq!(submission["__typename"], "OrderRejected", "{response}");
+    assert_eq!(submission["code"], "EMPTY_ORDER");
+
+    let response = client
+        .graphql(
+            SUBMIT_ORDER,
+            json!({ "articles": [{ "id": article_id, "quantity": 0 }] }),
+        )
+        .await;
+    let submission = &response["data"]["submitOrder"];
+    assert_eq!(submission["__typename"], "OrderRejected", "{response}");
+    assert_eq!(submission["code"], "INVALID_QUANTITY");
+}
+
+#[actix_web::test]
+async fn should_not_submit_order_when_signed_out() {
+    let environment = TestEnvironment::start().await;
+    let article_id = environment.add_article("E2E lamp", "25.00").await;
+    let mut client = environment.client();
+
+    let response = client
+        .graphql(
+            SUBMIT_ORDER,
+            json!({ "articles": [{ "id": article_id, "quantity": 1 }] }),
+        )
+        .await;
+    assert_eq!(response["data"], Value::Null);
+    assert_eq!(response["errors"][0]["message"], "UNAUTHORIZED");
+    assert_eq!(
+        response["errors"][0]["extensions"]["code"],
+        "UNAUTHENTICATED"
+    );
+}
+
+/// Find the order with `order_id` among the orders of the signed in customer
+async fn find_order(client: &mut super::TestClient, order_id: &Value) -> Value {
+    let response = client.graphql(ORDERS, Value::Null).await;
+    response["data"]["orders"]["nodes"]
+        .as_array()
+        .and_then(|orders| orders.iter().find(|x| &x["id"] == order_id))
+        .cloned()
+        .unwrap_or_else(|| panic!("order {order_id} not found in {response}"))
+}
+
+/// Poll the orders of the signed in customer until the order with `order_id` reaches `status`
+async fn wait_for_status(client: &mut super::TestClient, order_id: &Value, status: &str) {
+    for _ in 0..50 {
+        if find_order(client, order_id).await["status"] == status {
+            return;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    panic!("order {order_id} never reached status {status}");
+}
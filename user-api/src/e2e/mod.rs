@@ -0,0 +1,217 @@
+//! # End-to-end tests
+//!
+//! Harness running the store service and the web server in-process, each on an ephemeral port,
+//! so that tests go from the HTTP endpoints down to the store database.
+//! Each test runs the store on its own database, created on the server at `DATABASE_URL` and
+//! dropped at the end of the test, as the store tests do, so that tests can run in parallel.
+
+mod checkout;
+
+use crate::proto::store_client::store::{
+    import_articles_request, store_service_client::StoreServiceClient, CatalogArticle, Decimal,
+    ImportArticlesRequest,
+};
+use crate::web::WebServer;
+
+use hmac::{Hmac, Mac};
+use hyper::{body, client::HttpConnector, header, Body, Client, Method, Request, StatusCode};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::net::TcpListener;
+use store::TestDb;
+use uuid::Uuid;
+
+/// Password of the customers signed up by the tests
+const PASSWORD: &str = "Password123!";
+const WEBHOOK_SECRET: &str = "e2e-secret";
+
+/// Store service and web server running for a test
+pub struct TestEnvironment {
+    store_url: String,
+    web_url: String,
+    /// Database of the store, dropped along with the environment
+    _database: TestDb,
+}
+
+impl TestEnvironment {
+    /// Start the store service and the web server; they stop with the test runtime
+    pub async fn start() -> Self {
+        let database = TestDb::create().await;
+        let store_url = Self::start_store(database.url()).await;
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind web server");
+        let web_url = format!("http://{}", listener.local_addr().unwrap());
+        let web_server = WebServer::with_listener(listener, &store_url, WEBHOOK_SECRET, 300, &[])
+            .expect("failed to initialize web server");
+        actix_web::rt::spawn(web_server.run());
+
+        Self {
+            store_url,
+            web_url,
+            _database: database,
+        }
+    }
+
+    async fn start_store(database_url: &str) -> String {
+        let config = store::Config {
+            database_url: database_url.to_string(),
+            server_url: "127.0.0.1:0".to_string(),
+            payment_callback_delay_ms: 10,
+            order_ttl_secs: 3600,
+            order_expiry_interval_secs: 60,
+            outbox_sink: "stdout".to_string(),
+            outbox_file_path: None,
+            outbox_webhook_url: None,
+            outbox_relay_interval_ms: 1000,
+            recommendation_rebuild_interval_secs: 3600,
+            invoice_seller_name: "Prima Store S.r.l.".to_string(),
+            invoice_seller_address: "Via Roma 1, 20121 Milano, Italy".to_string(),
+            invoice_seller_vat_number: "IT01234567890".to_string(),
+            invoice_tax_rate: rust_decimal::Decimal::from(22),
+        };
+        let address = store::spawn_server(&config)
+            .await
+            .expect("failed to start store service");
+
+        format!("http://{address}")
+    }
+
+    /// A new client, with its own session
+    pub fn client(&self) -> TestClient {
+        TestClient {
+            web_url: self.web_url.clone(),
+            http: Client::new(),
+            cookie: None,
+        }
+    }
+
+    /// Add an article to the catalog; returns its id
+    pub async fn add_article(&self, name: &str, unit_price: &str) -> Uuid {
+        let id = Uuid::new_v4();
+        let mut store_client = StoreServiceClient::connect(self.store_url.clone())
+            .await
+            .expect("failed to connect to store");
+        let article = ImportArticlesRequest {
+            item: Some(import_articles_request::Item::Article(CatalogArticle {
+                id: id.to_string(),
+                name: name.to_string(),
+                description: format!("{name} for end-to-end tests"),
+                unit_price: Some(Decimal {
+                    value: unit_price.to_string(),
+                }),
+                weight: 100,
+            })),
+        };
+        let result = store_client
+            .import_articles(futures_util::stream::iter(vec![article]))
+            .await
+            .expect("failed to import article")
+            .into_inner();
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+        id
+    }
+}
+
+/// HTTP client of the web server, keeping the session cookie between requests
+pub struct TestClient {
+    web_url: String,
+    http: Client<HttpConnector>,
+    cookie: Option<String>,
+}
+
+impl TestClient {
+    /// Sign up a new customer with an unique email; returns the email
+    pub async fn sign_up(&mut self) -> String {
+        let email = format!("e2e-{}@prima.it", Uuid::new_v4().simple());
+        let (status, _) = self
+            .post(
+                "/auth/sign-up",
+                json!({ "email": email, "password": PASSWORD }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+
+        email
+    }
+
+    /// Sign in as the customer with `email`
+    pub async fn sign_in(&mut self, email: &str) {
+        let (status, _) = self
+            .post(
+                "/auth/sign-in",
+                json!({ "email": email, "password": PASSWORD }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    /// Run a GraphQL query; returns the whole response, with both `data` and `errors`
+    pub async fn graphql(&mut self, query: &str, variables: Value) -> Value {
+        let (status, response) = self
+            .post(
+                "/graphql",
+                json!({ "query": query, "variables": variables }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+
+        response
+    }
+
+    /// Deliver a payment event to the payment webhook, signed with the webhook secret
+    pub async fn send_payment_event(&mut self, event: Value) -> StatusCode {
+        let body = event.to_string();
+        let timestamp = chrono::Utc::now().timestamp();
+        let mut mac = Hmac::<Sha256>::new_from_slice(WEBHOOK_SECRET.as_bytes()).unwrap();
+        mac.update(format!("{timestamp}.{body}").as_bytes());
+        let signature = hex::encode(mac.finalize().into_bytes());
+        let (status, _) = self
+            .send(
+                "/webhooks/payment",
+                body,
+                Some(format!("t={timestamp},v1={signature}")),
+            )
+            .await;
+
+        status
+    }
+
+    async fn post(&mut self, path: &str, payload: Value) -> (StatusCode, Value) {
+        self.send(path, payload.to_string(), None).await
+    }
+
+    async fn send(
+        &mut self,
+        path: &str,
+        body: String,
+        signature: Option<String>,
+    ) -> (StatusCode, Value) {
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(format!("{}{path}", self.web_url))
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(cookie) = self.cookie.as_deref() {
+            request = request.header(header::COOKIE, cookie);
+        }
+        if let Some(signature) = signature {
+            request = request.header("X-Webhook-Signature", signature);
+        }
+        let response = self
+            .http
+            .request(request.body(Body::from(body)).unwrap())
+            .await
+            .expect("request to web server failed");
+        // the cookie session store sends the whole session again whenever it changes
+        if let Some(cookie) = response.headers().get(header::SET_COOKIE) {
+            self.cookie = cookie
+                .to_str()
+                .ok()
+                .and_then(|x| x.split(';').next())
+                .map(str::to_string);
+        }
+        let status = response.status();
+        let body = body::to_bytes(response.into_body()).await.unwrap();
+
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+}
@@ -0,0 +1,182 @@
+//! # GraphQL errors
+//!
+//! Every error sent to the clients has a stable code in `extensions.code`, so that frontends
+//! don't need to parse messages. Errors of the store client get the code of their cause when the
+//! resolvers propagate them with `.extend()?`; their internal details are logged instead.
+
+use crate::proto::ProtobufError;
+
+use async_graphql::{ErrorExtensions, Response};
+use tonic::Code;
+
+/// Code of an error, sent in `extensions.code`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// The user is not signed in
+    Unauthenticated,
+    /// The user is not allowed to access the resource
+    Forbidden,
+    /// The resource doesn't exist, or it belongs to someone else
+    NotFound,
+    /// The request is invalid
+    Validation,
+    /// The store service can't be reached
+    UpstreamUnavailable,
+    /// Unexpected error; its details are logged, not sent
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unauthenticated => "UNAUTHENTICATED",
+            Self::Forbidden => "FORBIDDEN",
+            Self::NotFound => "NOT_FOUND",
+            Self::Validation => "VALIDATION",
+            Self::UpstreamUnavailable => "UPSTREAM_UNAVAILABLE",
+            Self::Internal => "INTERNAL",
+        }
+    }
+
+    /// Error with this code and `message`
+    pub fn error(self, message: impl Into<String>) -> async_graphql::Error {
+        async_graphql::Error::new(message).extend_with(|_, e| e.set("code", self.as_str()))
+    }
+}
+
+/// Code of a store client error and the message which can be sent to the client
+fn protobuf_error(error: &ProtobufError) -> (ErrorCode, String) {
+    match error {
+        ProtobufError::InvalidArgument { .. }
+        | ProtobufError::FailedPrecondition(_)
+        | ProtobufError::AlreadyExists { .. } => (ErrorCode::Validation, error.to_string()),
+        ProtobufError::NotFound { .. } => (ErrorCode::NotFound, error.to_string()),
+        ProtobufError::Transport(_) => {
+            error!("store service unavailable: {error}");
+            (
+                ErrorCode::UpstreamUnavailable,
+                "store service unavailable".to_string(),
+            )
+        }
+        ProtobufError::Protobuf(status)
+            if matches!(status.code(), Code::Unavailable | Code::DeadlineExceeded) =>
+        {
+            error!("store service unavailable: {error}");
+            (
+                ErrorCode::UpstreamUnavailable,
+                "store service unavailable".to_string(),
+            )
+        }
+        ProtobufError::Protobuf(_) | ProtobufError::Syntax(_) => {
+            error!("store error: {error}");
+            (ErrorCode::Internal, "internal error".to_string())
+        }
+    }
+}
+
+impl ErrorExtensions for ProtobufError {
+    fn extend(&self) -> async_graphql::Error {
+        let (code, message) = protobuf_error(self);
+        code.error(message)
+    }
+}
+
+/// Set the code of the errors of `response` which have none: errors without a path are raised by
+/// the GraphQL engine when it rejects the request, the others are unexpected
+pub fn with_error_codes(mut response: Response) -> Response {
+    for error in response.errors.iter_mut() {
+        let has_code = error
+            .extensions
+            .as_ref()
+            .and_then(|x| x.get("code"))
+            .is_some();
+        if has_code {
+            continue;
+        }
+        let code = if error.path.is_empty() {
+            ErrorCode::Validation
+        } else {
+            error!("unexpected error: {}", error.message);
+            error.message = "internal error".to_string();
+            ErrorCode::Internal
+        };
+        error
+            .extensions
+            .get_or_insert_with(Default::default)
+            .set("code", code.as_str());
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use async_graphql::{PathSegment, Pos, ServerError, Value};
+    use pretty_assertions::assert_eq;
+    use tonic::Status;
+
+    fn respond(error: async_graphql::Error) -> ServerError {
+        let error = error.into_server_error(Pos::default());
+        with_error_codes(Response::from_errors(vec![error]))
+            .errors
+            .remove(0)
+    }
+
+    fn code(error: &ServerError) -> Option<&Value> {
+        error.extensions.as_ref().and_then(|x| x.get("code"))
+    }
+
+    #[test]
+    fn should_keep_error_code() {
+        let error = respond(ErrorCode::Unauthenticated.error("UNAUTHORIZED"));
+        assert_eq!(error.message, "UNAUTHORIZED");
+        assert_eq!(code(&error), Some(&Value::from("UNAUTHENTICATED")));
+    }
+
+    #[test]
+    fn should_set_code_of_store_errors() {
+        let error = respond(
+            ProtobufError::NotFound {
+                resource: "order".to_string(),
+                id: "42".to_string(),
+            }
+            .extend(),
+        );
+        assert_eq!(error.message, "order 42 not found");
+        assert_eq!(code(&error), Some(&Value::from("NOT_FOUND")));
+
+        let error =
+            respond(ProtobufError::from(Status::unavailable("connection refused")).extend());
+        assert_eq!(error.message, "store service unavailable");
+        assert_eq!(code(&error), Some(&Value::from("UPSTREAM_UNAVAILABLE")));
+    }
+
+    #[test]
+    fn should_hide_internal_store_errors() {
+        let error = respond(
+            ProtobufError::from(Status::internal("relation \"customer\" does not exist")).extend(),
+        );
+        assert_eq!(error.message, "internal error");
+        assert_eq!(code(&error), Some(&Value::from("INTERNAL")));
+    }
+
+    #[test]
+    fn should_set_internal_code_of_resolver_errors_without_code() {
+        let mut error = async_graphql::Error::new("oops").into_server_error(Pos::default());
+        error.path = vec![PathSegment::Field("orders".to_string())];
+        let error = with_error_codes(Response::from_errors(vec![error]))
+            .errors
+            .remove(0);
+        assert_eq!(error.message, "internal error");
+        assert_eq!(code(&error), Some(&Value::from("INTERNAL")));
+    }
+
+    #[test]
+    fn should_set_validation_code_of_rejected_requests() {
+        let error = respond(async_graphql::Error::new("Unknown field \"foo\""));
+        assert_eq!(code(&error), Some(&Value::from("VALIDATION")));
+    }
+}
@@ -1,5 +1,6 @@
 //! # GraphQL
 
+pub mod error;
 mod request_params;
 pub mod resolvers;
 pub mod schema;
@@ -2,6 +2,23 @@
 
 use uuid::Uuid;
 
+use crate::proto::store_client::types::CartOwner;
+
 pub struct GraphqlRequestParams {
     pub user_id: Option<Uuid>,
+    /// Anonymous cart of the visitor; set only when no user is signed in
+    pub cart_id: Option<Uuid>,
+    /// Whether the signed in user can access the admin queries
+    pub is_admin: bool,
+}
+
+impl GraphqlRequestParams {
+    /// Owner of the cart for this request
+    pub fn cart_owner(&self) -> Option<CartOwner> {
+        match (self.user_id, self.cart_id) {
+            (Some(user_id), _) => Some(CartOwner::Customer(user_id)),
+            (None, Some(cart_id)) => Some(CartOwner::Anonymous(cart_id)),
+            (None, None) => None,
+        }
+    }
 }
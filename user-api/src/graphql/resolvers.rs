@@ -1,11 +1,32 @@
 //! # GraphQL resolvers
 
 pub const UNAUTHORIZED: &str = "UNAUTHORIZED";
+pub const FORBIDDEN: &str = "FORBIDDEN";
 
+mod article_reviews;
 mod articles;
+mod cart;
+mod checkout_cart;
 mod order;
+mod order_history;
+mod related_articles;
+mod reports;
+mod request_return;
+mod shipping_methods;
 mod submit_order;
+mod submit_review;
+mod wishlist;
 
+pub use article_reviews::ArticleReviews;
 pub use articles::Articles;
+pub use cart::Cart;
+pub use checkout_cart::CheckoutCart;
 pub use order::Orders;
+pub use order_history::OrderHistory;
+pub use related_articles::RelatedArticles;
+pub use reports::Reports;
+pub use request_return::RequestReturn;
+pub use shipping_methods::ShippingMethods;
 pub use submit_order::SubmitOrder;
+pub use submit_review::SubmitReview;
+pub use wishlist::Wishlists;
@@ -0,0 +1,71 @@
+use async_graphql::{
+    connection::{query, Connection, Edge},
+    ResultExt,
+};
+use uuid::Uuid;
+
+use crate::{
+    graphql::types::{Review, ReviewConnectionFields},
+    proto::StoreClient,
+};
+
+/// Reviews returned when `first` is not provided
+const DEFAULT_PAGE_SIZE: usize = 10;
+/// Maximum amount of reviews returned at once
+const MAX_PAGE_SIZE: usize = 100;
+
+/// Article reviews query
+pub struct ArticleReviews {
+    store_server_url: String,
+}
+
+impl ArticleReviews {
+    /// Instantiates a new `ArticleReviews`
+    pub fn new(store_server_url: &str) -> Self {
+        Self {
+            store_server_url: store_server_url.to_string(),
+        }
+    }
+
+    /// Resolve the approved reviews of an article as a connection; cursors are opaque store cursors
+    pub async fn resolve(
+        &self,
+        article_id: Uuid,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> async_graphql::Result<Connection<String, Review, ReviewConnectionFields>> {
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<String>, _: Option<String>, first, _| async move {
+                let count = first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+                let mut client = StoreClient::connect(self.store_server_url.clone())
+                    .await
+                    .extend()?;
+                let has_previous_page = after.is_some();
+                let page = client
+                    .query_article_reviews(article_id, after, count as u32)
+                    .await
+                    .extend()?;
+
+                let mut connection = Connection::with_additional_fields(
+                    has_previous_page,
+                    page.next_cursor.is_some(),
+                    ReviewConnectionFields {
+                        total_count: page.total_count,
+                    },
+                );
+                connection.edges.extend(
+                    page.edges
+                        .into_iter()
+                        .map(|(cursor, review)| Edge::new(cursor, Review::from(review))),
+                );
+
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+}
@@ -1,4 +1,17 @@
-use crate::{graphql::types::Article, proto::StoreClient};
+use async_graphql::{
+    connection::{query, Connection, Edge},
+    ResultExt,
+};
+
+use crate::{
+    graphql::types::{Article, ArticleConnectionFields},
+    proto::StoreClient,
+};
+
+/// Articles returned when `first` is not provided
+const DEFAULT_PAGE_SIZE: usize = 20;
+/// Maximum amount of articles returned at once
+const MAX_PAGE_SIZE: usize = 100;
 
 /// Articles query
 pub struct Articles {
@@ -13,21 +26,45 @@ impl Articles {
         }
     }
 
-    /// Resolve query articles
+    /// Resolve query articles as a connection; cursors are opaque store cursors
     pub async fn resolve(
         &self,
-        query: Option<String>,
-        page: u32,
-        count: u32,
-    ) -> async_graphql::Result<Vec<Article>> {
-        let mut client = StoreClient::connect(self.store_server_url.clone()).await?;
-        let articles = client
-            .query_articles(query, page, count)
-            .await?
-            .into_iter()
-            .map(Article::from)
-            .collect();
+        query_text: Option<String>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> async_graphql::Result<Connection<String, Article, ArticleConnectionFields>> {
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<String>, _: Option<String>, first, _| async move {
+                let count = first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+                let mut client = StoreClient::connect(self.store_server_url.clone())
+                    .await
+                    .extend()?;
+                let has_previous_page = after.is_some();
+                let page = client
+                    .query_articles(query_text, after, count as u32)
+                    .await
+                    .extend()?;
+
+                let mut connection = Connection::with_additional_fields(
+                    has_previous_page,
+                    page.next_cursor.is_some(),
+                    ArticleConnectionFields {
+                        total_count: page.total_count,
+                    },
+                );
+                connection.edges.extend(
+                    page.edges
+                        .into_iter()
+                        .map(|(cursor, article)| Edge::new(cursor, Article::from(article))),
+                );
 
-        Ok(articles)
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
     }
 }
@@ -0,0 +1,102 @@
+use crate::{
+    graphql::{error::ErrorCode, types::Cart as GraphqlCart},
+    proto::{
+        store_client::types::{CartError, CartOwner, CartResponse},
+        StoreClient,
+    },
+};
+
+use async_graphql::ResultExt;
+use uuid::Uuid;
+
+pub const CART_NOT_FOUND: &str = "CART_NOT_FOUND";
+pub const INVALID_ARTICLE: &str = "INVALID_ARTICLE";
+pub const INVALID_QUANTITY: &str = "INVALID_QUANTITY";
+
+/// Cart query and mutations
+pub struct Cart {
+    store_server_url: String,
+}
+
+impl Cart {
+    /// Instantiates a new `Cart`
+    pub fn new(store_server_url: &str) -> Self {
+        Self {
+            store_server_url: store_server_url.to_string(),
+        }
+    }
+
+    /// Resolve the cart of the owner
+    pub async fn resolve(&self, owner: CartOwner) -> async_graphql::Result<GraphqlCart> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        Self::into_result(client.get_cart(owner).await.extend()?)
+    }
+
+    /// Resolve mutation for adding an article to the cart
+    pub async fn add(
+        &self,
+        owner: CartOwner,
+        article_id: Uuid,
+        quantity: u32,
+    ) -> async_graphql::Result<GraphqlCart> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        Self::into_result(
+            client
+                .add_to_cart(owner, article_id, quantity)
+                .await
+                .extend()?,
+        )
+    }
+
+    /// Resolve mutation for setting the quantity of an article in the cart
+    pub async fn update(
+        &self,
+        owner: CartOwner,
+        article_id: Uuid,
+        quantity: u32,
+    ) -> async_graphql::Result<GraphqlCart> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        Self::into_result(
+            client
+                .update_cart_item(owner, article_id, quantity)
+                .await
+                .extend()?,
+        )
+    }
+
+    /// Resolve mutation for removing an article from the cart
+    pub async fn remove(
+        &self,
+        owner: CartOwner,
+        article_id: Uuid,
+    ) -> async_graphql::Result<GraphqlCart> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        Self::into_result(client.remove_from_cart(owner, article_id).await.extend()?)
+    }
+
+    fn into_result(response: CartResponse) -> async_graphql::Result<GraphqlCart> {
+        match response {
+            CartResponse::Ok(cart) => Ok(cart.into()),
+            CartResponse::Err(CartError::CartNotFound) => {
+                Err(ErrorCode::NotFound.error(CART_NOT_FOUND))
+            }
+            CartResponse::Err(CartError::InvalidArticle) => {
+                Err(ErrorCode::Validation.error(INVALID_ARTICLE))
+            }
+            CartResponse::Err(CartError::InvalidQuantity) => {
+                Err(ErrorCode::Validation.error(INVALID_QUANTITY))
+            }
+            CartResponse::Err(CartError::Unknown) => {
+                Err(ErrorCode::Internal.error("unknown error"))
+            }
+        }
+    }
+}
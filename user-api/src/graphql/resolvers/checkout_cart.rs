@@ -0,0 +1,36 @@
+use async_graphql::ResultExt;
+use uuid::Uuid;
+
+use crate::{graphql::types::OrderSubmission, proto::StoreClient};
+
+/// Checkout cart mutation
+pub struct CheckoutCart {
+    store_server_url: String,
+}
+
+impl CheckoutCart {
+    /// Instantiates a new `CheckoutCart`
+    pub fn new(store_server_url: &str) -> Self {
+        Self {
+            store_server_url: store_server_url.to_string(),
+        }
+    }
+
+    /// Resolve mutation for submitting an order with the articles in the cart of the user
+    pub async fn resolve(
+        &self,
+        user_id: Uuid,
+        shipping_method: Option<Uuid>,
+        card_number: Option<String>,
+    ) -> async_graphql::Result<OrderSubmission> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        let checkout_result = client
+            .checkout_cart(user_id, shipping_method, card_number)
+            .await
+            .extend()?;
+
+        Ok(checkout_result.into())
+    }
+}
@@ -1,7 +1,19 @@
-use crate::{graphql::types::Order, proto::StoreClient};
-
+use async_graphql::{
+    connection::{query, Connection, Edge},
+    ResultExt,
+};
 use uuid::Uuid;
 
+use crate::{
+    graphql::types::{Order, OrderConnectionFields},
+    proto::{store_client::types::OrderFilter, StoreClient},
+};
+
+/// Orders returned when `first` is not provided
+const DEFAULT_PAGE_SIZE: usize = 10;
+/// Maximum amount of orders returned at once
+const MAX_PAGE_SIZE: usize = 100;
+
 /// Orders query
 pub struct Orders {
     store_server_url: String,
@@ -15,22 +27,64 @@ impl Orders {
         }
     }
 
-    /// Resolve query orders
+    /// Resolve an order of the user by id; `None` if it doesn't exist or belongs to another user
+    pub async fn resolve_one(
+        &self,
+        user_id: Uuid,
+        order_id: Uuid,
+    ) -> async_graphql::Result<Option<Order>> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+
+        Ok(client
+            .get_order(user_id, order_id)
+            .await
+            .extend()?
+            .map(Order::from))
+    }
+
+    /// Resolve query orders matching `filter` as a connection, newest first;
+    /// cursors are opaque store cursors
     pub async fn resolve(
         &self,
         user_id: Uuid,
-        page: u32,
-        count: u32,
-    ) -> async_graphql::Result<Vec<Order>> {
-        let mut client = StoreClient::connect(self.store_server_url.clone()).await?;
-
-        let orders = client
-            .query_orders(user_id, page, count)
-            .await?
-            .into_iter()
-            .map(Order::from)
-            .collect();
-
-        Ok(orders)
+        filter: OrderFilter,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> async_graphql::Result<Connection<String, Order, OrderConnectionFields>> {
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<String>, _: Option<String>, first, _| async move {
+                let count = first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+                let mut client = StoreClient::connect(self.store_server_url.clone())
+                    .await
+                    .extend()?;
+                let has_previous_page = after.is_some();
+                let page = client
+                    .query_orders(user_id, filter, after, count as u32)
+                    .await
+                    .extend()?;
+
+                let mut connection = Connection::with_additional_fields(
+                    has_previous_page,
+                    page.next_cursor.is_some(),
+                    OrderConnectionFields {
+                        total_count: page.total_count,
+                    },
+                );
+                connection.edges.extend(
+                    page.edges
+                        .into_iter()
+                        .map(|(cursor, order)| Edge::new(cursor, Order::from(order))),
+                );
+
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
     }
 }
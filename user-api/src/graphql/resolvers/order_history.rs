@@ -0,0 +1,49 @@
+use crate::{
+    graphql::{error::ErrorCode, types::OrderEvent},
+    proto::{
+        store_client::types::{OrderHistoryError, OrderHistoryResponse},
+        StoreClient,
+    },
+};
+
+use async_graphql::ResultExt;
+use uuid::Uuid;
+
+pub const ORDER_NOT_FOUND: &str = "ORDER_NOT_FOUND";
+
+/// Order history query
+pub struct OrderHistory {
+    store_server_url: String,
+}
+
+impl OrderHistory {
+    /// Instantiates a new `OrderHistory`
+    pub fn new(store_server_url: &str) -> Self {
+        Self {
+            store_server_url: store_server_url.to_string(),
+        }
+    }
+
+    /// Resolve the history of an order of the user
+    pub async fn resolve(
+        &self,
+        user_id: Uuid,
+        order_id: Uuid,
+    ) -> async_graphql::Result<Vec<OrderEvent>> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+
+        match client.get_order_history(user_id, order_id).await.extend()? {
+            OrderHistoryResponse::Ok(events) => {
+                Ok(events.into_iter().map(OrderEvent::from).collect())
+            }
+            OrderHistoryResponse::Err(OrderHistoryError::OrderNotFound) => {
+                Err(ErrorCode::NotFound.error(ORDER_NOT_FOUND))
+            }
+            OrderHistoryResponse::Err(OrderHistoryError::Unknown) => {
+                Err(ErrorCode::Internal.error("unknown error"))
+            }
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use async_graphql::ResultExt;
+use uuid::Uuid;
+
+use crate::{graphql::types::Article, proto::StoreClient};
+
+/// Related articles query
+pub struct RelatedArticles {
+    store_server_url: String,
+}
+
+impl RelatedArticles {
+    /// Instantiates a new `RelatedArticles`
+    pub fn new(store_server_url: &str) -> Self {
+        Self {
+            store_server_url: store_server_url.to_string(),
+        }
+    }
+
+    /// Resolve the articles frequently bought together with the article
+    pub async fn resolve(
+        &self,
+        article_id: Uuid,
+        count: Option<u32>,
+    ) -> async_graphql::Result<Vec<Article>> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        let articles = client
+            .recommend_articles(article_id, count)
+            .await
+            .extend()?
+            .into_iter()
+            .map(Article::from)
+            .collect();
+
+        Ok(articles)
+    }
+}
@@ -0,0 +1,77 @@
+use async_graphql::ResultExt;
+use chrono::NaiveDateTime;
+
+use crate::{
+    graphql::types::{OrderStats, ReportGranularity, RevenuePeriod, TopArticle},
+    proto::StoreClient,
+};
+
+/// Sales reports query; available to admins only
+pub struct Reports {
+    store_server_url: String,
+}
+
+impl Reports {
+    /// Instantiates a new `Reports`
+    pub fn new(store_server_url: &str) -> Self {
+        Self {
+            store_server_url: store_server_url.to_string(),
+        }
+    }
+
+    /// Resolve the revenue of the orders placed in `[from, to)` grouped by period
+    pub async fn revenue(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        granularity: ReportGranularity,
+    ) -> async_graphql::Result<Vec<RevenuePeriod>> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        let periods = client
+            .get_revenue_report(from, to, granularity.into())
+            .await
+            .extend()?
+            .into_iter()
+            .map(RevenuePeriod::from)
+            .collect();
+
+        Ok(periods)
+    }
+
+    /// Resolve the best selling articles in the orders placed in `[from, to)`
+    pub async fn top_articles(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        count: Option<u32>,
+    ) -> async_graphql::Result<Vec<TopArticle>> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        let articles = client
+            .get_top_articles_report(from, to, count)
+            .await
+            .extend()?
+            .into_iter()
+            .map(TopArticle::from)
+            .collect();
+
+        Ok(articles)
+    }
+
+    /// Resolve the statistics of the orders placed in `[from, to)`
+    pub async fn order_stats(
+        &self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> async_graphql::Result<OrderStats> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        let stats = client.get_order_stats_report(from, to).await.extend()?;
+
+        Ok(stats.into())
+    }
+}
@@ -0,0 +1,45 @@
+use async_graphql::ResultExt;
+use uuid::Uuid;
+
+use crate::{
+    graphql::types::{ReturnArticle, ReturnSubmission},
+    proto::{store_client::types::ReturnedArticle, StoreClient},
+};
+
+/// Request return mutation
+pub struct RequestReturn {
+    store_server_url: String,
+}
+
+impl RequestReturn {
+    /// Instantiates a new `RequestReturn`
+    pub fn new(store_server_url: &str) -> Self {
+        Self {
+            store_server_url: store_server_url.to_string(),
+        }
+    }
+
+    /// Resolve mutation for request return
+    pub async fn resolve(
+        &self,
+        user_id: Uuid,
+        order_id: Uuid,
+        articles: Vec<ReturnArticle>,
+        reason: String,
+    ) -> async_graphql::Result<ReturnSubmission> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        let request_result = client
+            .request_return(
+                user_id,
+                order_id,
+                articles.into_iter().map(ReturnedArticle::from).collect(),
+                reason,
+            )
+            .await
+            .extend()?;
+
+        Ok(request_result.into())
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{graphql::types::ShippingMethod, proto::StoreClient};
+
+use async_graphql::ResultExt;
+
+/// Shipping methods query
+pub struct ShippingMethods {
+    store_server_url: String,
+}
+
+impl ShippingMethods {
+    /// Instantiates a new `ShippingMethods`
+    pub fn new(store_server_url: &str) -> Self {
+        Self {
+            store_server_url: store_server_url.to_string(),
+        }
+    }
+
+    /// Resolve query shipping methods
+    pub async fn resolve(&self) -> async_graphql::Result<Vec<ShippingMethod>> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        let shipping_methods = client
+            .query_shipping_methods()
+            .await
+            .extend()?
+            .into_iter()
+            .map(ShippingMethod::from)
+            .collect();
+
+        Ok(shipping_methods)
+    }
+}
@@ -1,3 +1,4 @@
+use async_graphql::ResultExt;
 use uuid::Uuid;
 
 use crate::{
@@ -23,14 +24,21 @@ impl SubmitOrder {
         &self,
         user_id: Uuid,
         articles: Vec<OrderArticle>,
+        shipping_method: Option<Uuid>,
+        card_number: Option<String>,
     ) -> async_graphql::Result<OrderSubmission> {
-        let mut client = StoreClient::connect(self.store_server_url.clone()).await?;
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
         let submit_result = client
             .submit_order(
                 user_id,
                 articles.into_iter().map(OrderedArticle::from).collect(),
+                shipping_method,
+                card_number,
             )
-            .await?;
+            .await
+            .extend()?;
 
         Ok(submit_result.into())
     }
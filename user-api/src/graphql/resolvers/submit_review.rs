@@ -0,0 +1,38 @@
+use async_graphql::ResultExt;
+use uuid::Uuid;
+
+use crate::{graphql::types::ReviewSubmission, proto::StoreClient};
+
+/// Submit review mutation
+pub struct SubmitReview {
+    store_server_url: String,
+}
+
+impl SubmitReview {
+    /// Instantiates a new `SubmitReview`
+    pub fn new(store_server_url: &str) -> Self {
+        Self {
+            store_server_url: store_server_url.to_string(),
+        }
+    }
+
+    /// Resolve mutation for submit review
+    pub async fn resolve(
+        &self,
+        user_id: Uuid,
+        article_id: Uuid,
+        rating: u32,
+        title: String,
+        body: String,
+    ) -> async_graphql::Result<ReviewSubmission> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        let submit_result = client
+            .submit_review(user_id, article_id, rating, title, body)
+            .await
+            .extend()?;
+
+        Ok(submit_result.into())
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{
+    graphql::{error::ErrorCode, types::Wishlist as GraphqlWishlist},
+    proto::{
+        store_client::types::{WishlistError, WishlistResponse},
+        StoreClient,
+    },
+};
+
+use async_graphql::ResultExt;
+use uuid::Uuid;
+
+pub const WISHLIST_NOT_FOUND: &str = "WISHLIST_NOT_FOUND";
+pub const NAME_ALREADY_TAKEN: &str = "NAME_ALREADY_TAKEN";
+pub const INVALID_NAME: &str = "INVALID_NAME";
+pub const ARTICLE_UNAVAILABLE: &str = "ARTICLE_UNAVAILABLE";
+
+/// Wishlists query and mutations
+pub struct Wishlists {
+    store_server_url: String,
+}
+
+impl Wishlists {
+    /// Instantiates a new `Wishlists`
+    pub fn new(store_server_url: &str) -> Self {
+        Self {
+            store_server_url: store_server_url.to_string(),
+        }
+    }
+
+    /// Resolve the wishlists of the user
+    pub async fn resolve(&self, user_id: Uuid) -> async_graphql::Result<Vec<GraphqlWishlist>> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        let wishlists = client.query_wishlists(user_id).await.extend()?;
+
+        Ok(wishlists.into_iter().map(GraphqlWishlist::from).collect())
+    }
+
+    /// Resolve mutation for creating a wishlist
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        name: String,
+    ) -> async_graphql::Result<GraphqlWishlist> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        Self::into_result(client.create_wishlist(user_id, name).await.extend()?)
+    }
+
+    /// Resolve mutation for deleting a wishlist
+    pub async fn delete(
+        &self,
+        user_id: Uuid,
+        wishlist_id: Uuid,
+    ) -> async_graphql::Result<GraphqlWishlist> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        Self::into_result(
+            client
+                .delete_wishlist(user_id, wishlist_id)
+                .await
+                .extend()?,
+        )
+    }
+
+    /// Resolve mutation for adding an article to a wishlist
+    pub async fn add(
+        &self,
+        user_id: Uuid,
+        wishlist_id: Uuid,
+        article_id: Uuid,
+    ) -> async_graphql::Result<GraphqlWishlist> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        Self::into_result(
+            client
+                .add_to_wishlist(user_id, wishlist_id, article_id)
+                .await
+                .extend()?,
+        )
+    }
+
+    /// Resolve mutation for removing an article from a wishlist
+    pub async fn remove(
+        &self,
+        user_id: Uuid,
+        wishlist_id: Uuid,
+        article_id: Uuid,
+    ) -> async_graphql::Result<GraphqlWishlist> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        Self::into_result(
+            client
+                .remove_from_wishlist(user_id, wishlist_id, article_id)
+                .await
+                .extend()?,
+        )
+    }
+
+    /// Resolve mutation for moving an article from a wishlist to the cart
+    pub async fn move_to_cart(
+        &self,
+        user_id: Uuid,
+        wishlist_id: Uuid,
+        article_id: Uuid,
+    ) -> async_graphql::Result<GraphqlWishlist> {
+        let mut client = StoreClient::connect(self.store_server_url.clone())
+            .await
+            .extend()?;
+        Self::into_result(
+            client
+                .move_wishlist_item_to_cart(user_id, wishlist_id, article_id)
+                .await
+                .extend()?,
+        )
+    }
+
+    fn into_result(response: WishlistResponse) -> async_graphql::Result<GraphqlWishlist> {
+        match response {
+            WishlistResponse::Ok(wishlist) => Ok(wishlist.into()),
+            WishlistResponse::Err(WishlistError::WishlistNotFound) => {
+                Err(ErrorCode::NotFound.error(WISHLIST_NOT_FOUND))
+            }
+            WishlistResponse::Err(WishlistError::NameAlreadyTaken) => {
+                Err(ErrorCode::Validation.error(NAME_ALREADY_TAKEN))
+            }
+            WishlistResponse::Err(WishlistError::InvalidName) => {
+                Err(ErrorCode::Validation.error(INVALID_NAME))
+            }
+            WishlistResponse::Err(WishlistError::InvalidArticle) => {
+                Err(ErrorCode::Validation.error(super::cart::INVALID_ARTICLE))
+            }
+            WishlistResponse::Err(WishlistError::ArticleUnavailable) => {
+                Err(ErrorCode::Validation.error(ARTICLE_UNAVAILABLE))
+            }
+            WishlistResponse::Err(WishlistError::Unknown) => {
+                Err(ErrorCode::Internal.error("unknown error"))
+            }
+        }
+    }
+}
@@ -1,15 +1,24 @@
 //! # GraphQL schema
 
 use super::{
+    error::ErrorCode,
     resolvers::{
-        Articles as ArticlesResolver, Orders as OrdersResolver, SubmitOrder as SubmitOrderResolver,
+        Articles as ArticlesResolver, Cart as CartResolver, CheckoutCart as CheckoutCartResolver,
+        Orders as OrdersResolver, RequestReturn as RequestReturnResolver,
+        ShippingMethods as ShippingMethodsResolver, SubmitOrder as SubmitOrderResolver,
+        SubmitReview as SubmitReviewResolver, Wishlists as WishlistsResolver, FORBIDDEN,
         UNAUTHORIZED,
     },
-    types::{Article, Order, OrderArticle, OrderSubmission},
+    types::{
+        Article, ArticleConnectionFields, Cart, NaiveDateTime, Order, OrderArticle,
+        OrderConnectionFields, OrderStatus, OrderSubmission, Reports, ReturnArticle,
+        ReturnSubmission, ReviewSubmission, ShippingMethod, Uuid, Wishlist,
+    },
     GraphqlRequestParams,
 };
+use crate::proto::store_client::types::OrderFilter;
 
-use async_graphql::{Context, EmptySubscription, Object, Schema};
+use async_graphql::{connection::Connection, Context, EmptySubscription, Object, Schema};
 
 pub type ApiSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
 
@@ -17,29 +26,104 @@ pub struct QueryRoot;
 
 #[Object]
 impl QueryRoot {
+    /// Articles whose name ends with `query`, sorted by name
     async fn articles<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         query: Option<String>,
-        page: u32,
-        count: u32,
-    ) -> async_graphql::Result<Vec<Article>> {
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> async_graphql::Result<Connection<String, Article, ArticleConnectionFields>> {
         let resolver = ctx.data_unchecked::<ArticlesResolver>();
-        resolver.resolve(query, page, count).await
+        resolver.resolve(query, after, first).await
     }
 
+    /// Orders of the customer, newest first, in one of `statuses` if set and created from
+    /// `created_from` included to `created_to` excluded
     async fn orders<'ctx>(
         &self,
         ctx: &Context<'ctx>,
-        page: u32,
-        count: u32,
-    ) -> async_graphql::Result<Vec<Order>> {
+        after: Option<String>,
+        first: Option<i32>,
+        statuses: Option<Vec<OrderStatus>>,
+        created_from: Option<NaiveDateTime>,
+        created_to: Option<NaiveDateTime>,
+    ) -> async_graphql::Result<Connection<String, Order, OrderConnectionFields>> {
         let resolver = ctx.data_unchecked::<OrdersResolver>();
         let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
         if let Some(user_id) = request_params.user_id {
-            resolver.resolve(user_id, page, count).await
+            let filter = OrderFilter {
+                statuses: statuses
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+                created_from: created_from.map(Into::into),
+                created_to: created_to.map(Into::into),
+            };
+            resolver.resolve(user_id, filter, after, first).await
+        } else {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
+
+    /// Order of the customer by id; null if it doesn't exist or belongs to another customer
+    async fn order<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        id: Uuid,
+    ) -> async_graphql::Result<Option<Order>> {
+        let resolver = ctx.data_unchecked::<OrdersResolver>();
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if let Some(user_id) = request_params.user_id {
+            resolver.resolve_one(user_id, id.uuid()).await
+        } else {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
+
+    async fn shipping_methods<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Vec<ShippingMethod>> {
+        let resolver = ctx.data_unchecked::<ShippingMethodsResolver>();
+        resolver.resolve().await
+    }
+
+    async fn cart<'ctx>(&self, ctx: &Context<'ctx>) -> async_graphql::Result<Cart> {
+        let resolver = ctx.data_unchecked::<CartResolver>();
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if let Some(owner) = request_params.cart_owner() {
+            resolver.resolve(owner).await
+        } else {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
+
+    async fn wishlists<'ctx>(&self, ctx: &Context<'ctx>) -> async_graphql::Result<Vec<Wishlist>> {
+        let resolver = ctx.data_unchecked::<WishlistsResolver>();
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if let Some(user_id) = request_params.user_id {
+            resolver.resolve(user_id).await
         } else {
-            Err(async_graphql::Error::new(UNAUTHORIZED))
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
+
+    /// Sales reports of the orders placed from `from` included to `to` excluded
+    async fn reports<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> async_graphql::Result<Reports> {
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if request_params.user_id.is_none() {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        } else if !request_params.is_admin {
+            Err(ErrorCode::Forbidden.error(FORBIDDEN))
+        } else {
+            Ok(Reports::new(from, to))
         }
     }
 }
@@ -52,13 +136,199 @@ impl MutationRoot {
         &self,
         ctx: &Context<'ctx>,
         articles: Vec<OrderArticle>,
+        shipping_method: Option<Uuid>,
+        card_number: Option<String>,
     ) -> async_graphql::Result<OrderSubmission> {
         let resolver = ctx.data_unchecked::<SubmitOrderResolver>();
         let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
         if let Some(user_id) = request_params.user_id {
-            resolver.resolve(user_id, articles).await
+            resolver
+                .resolve(
+                    user_id,
+                    articles,
+                    shipping_method.map(Uuid::uuid),
+                    card_number,
+                )
+                .await
+        } else {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
+
+    async fn request_return<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        order_id: Uuid,
+        articles: Vec<ReturnArticle>,
+        reason: String,
+    ) -> async_graphql::Result<ReturnSubmission> {
+        let resolver = ctx.data_unchecked::<RequestReturnResolver>();
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if let Some(user_id) = request_params.user_id {
+            resolver
+                .resolve(user_id, order_id.uuid(), articles, reason)
+                .await
+        } else {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
+
+    async fn add_to_cart<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        article_id: Uuid,
+        quantity: u32,
+    ) -> async_graphql::Result<Cart> {
+        let resolver = ctx.data_unchecked::<CartResolver>();
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if let Some(owner) = request_params.cart_owner() {
+            resolver.add(owner, article_id.uuid(), quantity).await
+        } else {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
+
+    async fn update_cart_item<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        article_id: Uuid,
+        quantity: u32,
+    ) -> async_graphql::Result<Cart> {
+        let resolver = ctx.data_unchecked::<CartResolver>();
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if let Some(owner) = request_params.cart_owner() {
+            resolver.update(owner, article_id.uuid(), quantity).await
+        } else {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
+
+    async fn remove_from_cart<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        article_id: Uuid,
+    ) -> async_graphql::Result<Cart> {
+        let resolver = ctx.data_unchecked::<CartResolver>();
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if let Some(owner) = request_params.cart_owner() {
+            resolver.remove(owner, article_id.uuid()).await
+        } else {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
+
+    async fn checkout_cart<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        shipping_method: Option<Uuid>,
+        card_number: Option<String>,
+    ) -> async_graphql::Result<OrderSubmission> {
+        let resolver = ctx.data_unchecked::<CheckoutCartResolver>();
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if let Some(user_id) = request_params.user_id {
+            resolver
+                .resolve(user_id, shipping_method.map(Uuid::uuid), card_number)
+                .await
+        } else {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
+
+    async fn create_wishlist<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        name: String,
+    ) -> async_graphql::Result<Wishlist> {
+        let resolver = ctx.data_unchecked::<WishlistsResolver>();
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if let Some(user_id) = request_params.user_id {
+            resolver.create(user_id, name).await
+        } else {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
+
+    async fn delete_wishlist<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        wishlist_id: Uuid,
+    ) -> async_graphql::Result<Wishlist> {
+        let resolver = ctx.data_unchecked::<WishlistsResolver>();
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if let Some(user_id) = request_params.user_id {
+            resolver.delete(user_id, wishlist_id.uuid()).await
+        } else {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
+
+    async fn add_to_wishlist<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        wishlist_id: Uuid,
+        article_id: Uuid,
+    ) -> async_graphql::Result<Wishlist> {
+        let resolver = ctx.data_unchecked::<WishlistsResolver>();
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if let Some(user_id) = request_params.user_id {
+            resolver
+                .add(user_id, wishlist_id.uuid(), article_id.uuid())
+                .await
+        } else {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
+
+    async fn remove_from_wishlist<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        wishlist_id: Uuid,
+        article_id: Uuid,
+    ) -> async_graphql::Result<Wishlist> {
+        let resolver = ctx.data_unchecked::<WishlistsResolver>();
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if let Some(user_id) = request_params.user_id {
+            resolver
+                .remove(user_id, wishlist_id.uuid(), article_id.uuid())
+                .await
+        } else {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
+
+    async fn move_wishlist_item_to_cart<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        wishlist_id: Uuid,
+        article_id: Uuid,
+    ) -> async_graphql::Result<Wishlist> {
+        let resolver = ctx.data_unchecked::<WishlistsResolver>();
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if let Some(user_id) = request_params.user_id {
+            resolver
+                .move_to_cart(user_id, wishlist_id.uuid(), article_id.uuid())
+                .await
+        } else {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
+
+    async fn submit_review<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        article_id: Uuid,
+        rating: u32,
+        title: String,
+        body: String,
+    ) -> async_graphql::Result<ReviewSubmission> {
+        let resolver = ctx.data_unchecked::<SubmitReviewResolver>();
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if let Some(user_id) = request_params.user_id {
+            resolver
+                .resolve(user_id, article_id.uuid(), rating, title, body)
+                .await
         } else {
-            Err(async_graphql::Error::new(UNAUTHORIZED))
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
         }
     }
 }
@@ -1,19 +1,39 @@
 mod article;
 mod article_in_order;
+mod article_review;
+mod cart;
 mod decimal;
 mod naive_date_time;
 mod order;
 mod order_article;
+mod order_event;
 mod order_status;
 mod order_submission;
+mod return_article;
+mod return_submission;
+mod review_submission;
+mod sales_report;
+mod shipment;
+mod shipping_method;
 mod uuid;
+mod wishlist;
 
 pub use self::uuid::Uuid;
-pub use article::Article;
+pub use article::{Article, ArticleConnectionFields};
 pub use article_in_order::ArticleInOrder;
+pub use article_review::{ArticleRating, Review, ReviewConnectionFields};
+pub use cart::Cart;
 pub use decimal::Decimal;
 pub use naive_date_time::NaiveDateTime;
-pub use order::Order;
+pub use order::{Order, OrderConnectionFields};
 pub use order_article::OrderArticle;
+pub use order_event::OrderEvent;
 pub use order_status::OrderStatus;
 pub use order_submission::OrderSubmission;
+pub use return_article::ReturnArticle;
+pub use return_submission::ReturnSubmission;
+pub use review_submission::ReviewSubmission;
+pub use sales_report::{OrderStats, ReportGranularity, Reports, RevenuePeriod, TopArticle};
+pub use shipment::Shipment;
+pub use shipping_method::ShippingMethod;
+pub use wishlist::Wishlist;
@@ -1,14 +1,44 @@
-use async_graphql::SimpleObject;
+use async_graphql::{connection::Connection, ComplexObject, Context, SimpleObject};
 
-use super::{Decimal, Uuid};
+use super::{ArticleRating, Decimal, Review, ReviewConnectionFields, Uuid};
+use crate::graphql::resolvers::{
+    ArticleReviews as ArticleReviewsResolver, RelatedArticles as RelatedArticlesResolver,
+};
 use crate::proto::store_client::types::Article as ProtoArticle;
 
 #[derive(SimpleObject, Clone, PartialEq, Eq)]
+#[graphql(complex)]
 pub struct Article {
     id: Uuid,
     name: String,
     description: String,
     unit_price: Decimal,
+    /// Average rating of the approved reviews; null if the article has no reviews yet
+    rating: Option<ArticleRating>,
+}
+
+#[ComplexObject]
+impl Article {
+    /// Approved reviews of the article, newest first
+    async fn reviews<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> async_graphql::Result<Connection<String, Review, ReviewConnectionFields>> {
+        let resolver = ctx.data_unchecked::<ArticleReviewsResolver>();
+        resolver.resolve(self.id.uuid(), after, first).await
+    }
+
+    /// Articles frequently bought together with this article
+    async fn related_articles<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        count: Option<u32>,
+    ) -> async_graphql::Result<Vec<Article>> {
+        let resolver = ctx.data_unchecked::<RelatedArticlesResolver>();
+        resolver.resolve(self.id.uuid(), count).await
+    }
 }
 
 impl From<ProtoArticle> for Article {
@@ -18,6 +48,14 @@ impl From<ProtoArticle> for Article {
             name: value.name,
             description: value.description,
             unit_price: value.unit_price.into(),
+            rating: value.rating.map(ArticleRating::from),
         }
     }
 }
+
+/// Additional fields of the articles connection
+#[derive(SimpleObject)]
+pub struct ArticleConnectionFields {
+    /// Total amount of articles matching the query
+    pub total_count: u32,
+}
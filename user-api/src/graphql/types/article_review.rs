@@ -0,0 +1,51 @@
+use async_graphql::SimpleObject;
+
+use super::{Decimal, NaiveDateTime, Uuid};
+use crate::proto::store_client::types::{
+    ArticleRating as ProtoArticleRating, ArticleReview as ProtoArticleReview,
+};
+
+/// Average rating of the approved reviews of an article
+#[derive(SimpleObject, Clone, PartialEq, Eq)]
+pub struct ArticleRating {
+    average: Decimal,
+    count: u32,
+}
+
+impl From<ProtoArticleRating> for ArticleRating {
+    fn from(value: ProtoArticleRating) -> Self {
+        Self {
+            average: value.average.into(),
+            count: value.count,
+        }
+    }
+}
+
+/// Approved review of an article
+#[derive(SimpleObject, Clone, PartialEq, Eq)]
+pub struct Review {
+    id: Uuid,
+    rating: u32,
+    title: String,
+    body: String,
+    created_at: NaiveDateTime,
+}
+
+impl From<ProtoArticleReview> for Review {
+    fn from(value: ProtoArticleReview) -> Self {
+        Self {
+            id: value.id.into(),
+            rating: value.rating,
+            title: value.title,
+            body: value.body,
+            created_at: value.created_at.into(),
+        }
+    }
+}
+
+/// Additional fields of the reviews connection
+#[derive(SimpleObject)]
+pub struct ReviewConnectionFields {
+    /// Total amount of approved reviews of the article
+    pub total_count: u32,
+}
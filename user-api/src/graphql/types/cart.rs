@@ -0,0 +1,25 @@
+use async_graphql::SimpleObject;
+
+use super::{ArticleInOrder, Decimal, Uuid};
+use crate::proto::store_client::types::Cart as ProtoCart;
+
+#[derive(SimpleObject, Clone, PartialEq, Eq)]
+pub struct Cart {
+    id: Uuid,
+    articles: Vec<ArticleInOrder>,
+    subtotal: Decimal,
+}
+
+impl From<ProtoCart> for Cart {
+    fn from(value: ProtoCart) -> Self {
+        Self {
+            id: value.id.into(),
+            articles: value
+                .articles
+                .into_iter()
+                .map(ArticleInOrder::from)
+                .collect(),
+            subtotal: value.subtotal.into(),
+        }
+    }
+}
@@ -1,14 +1,39 @@
-use async_graphql::SimpleObject;
+use async_graphql::{ComplexObject, Context, SimpleObject};
 
-use super::{ArticleInOrder, NaiveDateTime, OrderStatus, Uuid};
+use super::{
+    ArticleInOrder, Decimal, NaiveDateTime, OrderEvent, OrderStatus, Shipment, ShippingMethod, Uuid,
+};
+use crate::graphql::{
+    error::ErrorCode,
+    resolvers::{OrderHistory as OrderHistoryResolver, UNAUTHORIZED},
+    GraphqlRequestParams,
+};
 use crate::proto::store_client::types::Order as ProtoOrder;
 
 #[derive(SimpleObject, Clone, PartialEq, Eq)]
+#[graphql(complex)]
 pub struct Order {
     id: Uuid,
     created_at: NaiveDateTime,
     status: OrderStatus,
     articles: Vec<ArticleInOrder>,
+    shipping_method: Option<ShippingMethod>,
+    shipping_cost: Decimal,
+    shipment: Option<Shipment>,
+}
+
+#[ComplexObject]
+impl Order {
+    /// Order status timeline, oldest event first
+    async fn history<'ctx>(&self, ctx: &Context<'ctx>) -> async_graphql::Result<Vec<OrderEvent>> {
+        let resolver = ctx.data_unchecked::<OrderHistoryResolver>();
+        let request_params = ctx.data_unchecked::<GraphqlRequestParams>();
+        if let Some(user_id) = request_params.user_id {
+            resolver.resolve(user_id, self.id.uuid()).await
+        } else {
+            Err(ErrorCode::Unauthenticated.error(UNAUTHORIZED))
+        }
+    }
 }
 
 impl From<ProtoOrder> for Order {
@@ -22,6 +47,16 @@ impl From<ProtoOrder> for Order {
                 .into_iter()
                 .map(ArticleInOrder::from)
                 .collect(),
+            shipping_method: value.shipping_method.map(ShippingMethod::from),
+            shipping_cost: value.shipping_cost.into(),
+            shipment: value.shipment.map(Shipment::from),
         }
     }
 }
+
+/// Additional fields of the orders connection
+#[derive(SimpleObject)]
+pub struct OrderConnectionFields {
+    /// Total amount of orders of the customer matching the filters
+    pub total_count: u32,
+}
@@ -0,0 +1,67 @@
+use async_graphql::{Enum, SimpleObject};
+
+use super::{NaiveDateTime, OrderStatus, Uuid};
+use crate::proto::store_client::types::{
+    OrderEvent as ProtoOrderEvent, OrderEventActor as ProtoOrderEventActor,
+    OrderEventKind as ProtoOrderEventKind,
+};
+
+/// Entry of the order history, with the order state after the change
+#[derive(SimpleObject, Clone, PartialEq, Eq)]
+pub struct OrderEvent {
+    id: Uuid,
+    kind: OrderEventKind,
+    actor: OrderEventActor,
+    status: OrderStatus,
+    transaction_id: Option<String>,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum OrderEventKind {
+    Created,
+    StatusChanged,
+    TransactionChanged,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum OrderEventActor {
+    Customer,
+    PaymentGateway,
+    BackOffice,
+    System,
+}
+
+impl From<ProtoOrderEvent> for OrderEvent {
+    fn from(value: ProtoOrderEvent) -> Self {
+        Self {
+            id: value.id.into(),
+            kind: value.kind.into(),
+            actor: value.actor.into(),
+            status: value.status.into(),
+            transaction_id: value.transaction_id,
+            created_at: value.created_at.into(),
+        }
+    }
+}
+
+impl From<ProtoOrderEventKind> for OrderEventKind {
+    fn from(value: ProtoOrderEventKind) -> Self {
+        match value {
+            ProtoOrderEventKind::Created => Self::Created,
+            ProtoOrderEventKind::StatusChanged => Self::StatusChanged,
+            ProtoOrderEventKind::TransactionChanged => Self::TransactionChanged,
+        }
+    }
+}
+
+impl From<ProtoOrderEventActor> for OrderEventActor {
+    fn from(value: ProtoOrderEventActor) -> Self {
+        match value {
+            ProtoOrderEventActor::Customer => Self::Customer,
+            ProtoOrderEventActor::PaymentGateway => Self::PaymentGateway,
+            ProtoOrderEventActor::BackOffice => Self::BackOffice,
+            ProtoOrderEventActor::System => Self::System,
+        }
+    }
+}
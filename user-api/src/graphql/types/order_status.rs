@@ -8,6 +8,10 @@ pub enum OrderStatus {
     Preparing,
     Shipped,
     PaymentFailed,
+    ReturnRequested,
+    Refunded,
+    PartiallyRefunded,
+    Expired,
 }
 
 impl From<ProtoOrderStatus> for OrderStatus {
@@ -17,6 +21,25 @@ impl From<ProtoOrderStatus> for OrderStatus {
             ProtoOrderStatus::PaymentFailed => Self::PaymentFailed,
             ProtoOrderStatus::Preparing => Self::Preparing,
             ProtoOrderStatus::Shipped => Self::Shipped,
+            ProtoOrderStatus::ReturnRequested => Self::ReturnRequested,
+            ProtoOrderStatus::Refunded => Self::Refunded,
+            ProtoOrderStatus::PartiallyRefunded => Self::PartiallyRefunded,
+            ProtoOrderStatus::Expired => Self::Expired,
+        }
+    }
+}
+
+impl From<OrderStatus> for ProtoOrderStatus {
+    fn from(value: OrderStatus) -> Self {
+        match value {
+            OrderStatus::Created => Self::Created,
+            OrderStatus::PaymentFailed => Self::PaymentFailed,
+            OrderStatus::Preparing => Self::Preparing,
+            OrderStatus::Shipped => Self::Shipped,
+            OrderStatus::ReturnRequested => Self::ReturnRequested,
+            OrderStatus::Refunded => Self::Refunded,
+            OrderStatus::PartiallyRefunded => Self::PartiallyRefunded,
+            OrderStatus::Expired => Self::Expired,
         }
     }
 }
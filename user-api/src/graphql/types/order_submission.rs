@@ -27,6 +27,14 @@ pub enum OrderRejectedCode {
     UnknownError,
     #[error("an invalid article was found in the order articles")]
     InvalidArticle,
+    #[error("the selected shipping method is not available for this order")]
+    InvalidShippingMethod,
+    #[error("the cart is empty")]
+    EmptyCart,
+    #[error("the order has no articles")]
+    EmptyOrder,
+    #[error("the quantity of an article is zero or too large")]
+    InvalidQuantity,
 }
 
 impl From<SubmitOrderResponse> for OrderSubmission {
@@ -45,6 +53,10 @@ impl From<SubmitOrderError> for OrderRejectedCode {
     fn from(value: SubmitOrderError) -> Self {
         match value {
             SubmitOrderError::InvalidArticle => Self::InvalidArticle,
+            SubmitOrderError::InvalidShippingMethod => Self::InvalidShippingMethod,
+            SubmitOrderError::EmptyCart => Self::EmptyCart,
+            SubmitOrderError::EmptyOrder => Self::EmptyOrder,
+            SubmitOrderError::InvalidQuantity => Self::InvalidQuantity,
             SubmitOrderError::Unknown => Self::UnknownError,
         }
     }
@@ -0,0 +1,21 @@
+//! # Return article
+
+use async_graphql::InputObject;
+
+use super::Uuid;
+use crate::proto::store_client::types::ReturnedArticle as ProtoReturnedArticle;
+
+#[derive(InputObject)]
+pub struct ReturnArticle {
+    id: Uuid,
+    quantity: u32,
+}
+
+impl From<ReturnArticle> for ProtoReturnedArticle {
+    fn from(value: ReturnArticle) -> Self {
+        Self {
+            id: value.id.uuid(),
+            quantity: value.quantity,
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use async_graphql::{Enum, SimpleObject, Union};
+use thiserror::Error;
+
+use super::Uuid;
+use crate::proto::store_client::types::{RequestReturnError, RequestReturnResponse};
+
+#[derive(Union)]
+pub enum ReturnSubmission {
+    ReturnAccepted(ReturnAccepted),
+    ReturnRejected(ReturnRejected),
+}
+
+#[derive(SimpleObject)]
+pub struct ReturnAccepted {
+    id: Uuid,
+}
+
+#[derive(SimpleObject)]
+pub struct ReturnRejected {
+    code: ReturnRejectedCode,
+    message: String,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq, Error, Debug)]
+pub enum ReturnRejectedCode {
+    #[error("unknown error")]
+    UnknownError,
+    #[error("order not found")]
+    OrderNotFound,
+    #[error("the order can't be returned in its current status")]
+    InvalidOrderStatus,
+    #[error("an article which is not part of the order was found in the return articles")]
+    InvalidArticle,
+    #[error("the quantity to return exceeds the quantity left to return")]
+    InvalidQuantity,
+}
+
+impl From<RequestReturnResponse> for ReturnSubmission {
+    fn from(value: RequestReturnResponse) -> Self {
+        match value {
+            RequestReturnResponse::Ok(id) => Self::ReturnAccepted(ReturnAccepted { id: id.into() }),
+            RequestReturnResponse::Err(err) => Self::ReturnRejected(ReturnRejected {
+                message: ReturnRejectedCode::from(err).to_string(),
+                code: err.into(),
+            }),
+        }
+    }
+}
+
+impl From<RequestReturnError> for ReturnRejectedCode {
+    fn from(value: RequestReturnError) -> Self {
+        match value {
+            RequestReturnError::Unknown => Self::UnknownError,
+            RequestReturnError::OrderNotFound => Self::OrderNotFound,
+            RequestReturnError::InvalidOrderStatus => Self::InvalidOrderStatus,
+            RequestReturnError::InvalidArticle => Self::InvalidArticle,
+            RequestReturnError::InvalidQuantity => Self::InvalidQuantity,
+        }
+    }
+}
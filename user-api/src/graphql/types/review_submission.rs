@@ -0,0 +1,61 @@
+use async_graphql::{Enum, SimpleObject, Union};
+use thiserror::Error;
+
+use super::Uuid;
+use crate::proto::store_client::types::{SubmitReviewError, SubmitReviewResponse};
+
+#[derive(Union)]
+pub enum ReviewSubmission {
+    ReviewAccepted(ReviewAccepted),
+    ReviewRejected(ReviewRejected),
+}
+
+/// Review accepted; it will be published once approved by the moderators
+#[derive(SimpleObject)]
+pub struct ReviewAccepted {
+    id: Uuid,
+}
+
+#[derive(SimpleObject)]
+pub struct ReviewRejected {
+    code: ReviewRejectedCode,
+    message: String,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq, Error, Debug)]
+pub enum ReviewRejectedCode {
+    #[error("unknown error")]
+    UnknownError,
+    #[error("article not found")]
+    ArticleNotFound,
+    #[error("the article has never been shipped to the customer")]
+    ArticleNotShipped,
+    #[error("rating must be between 1 and 5")]
+    InvalidRating,
+    #[error("the article has already been reviewed by the customer")]
+    AlreadyReviewed,
+}
+
+impl From<SubmitReviewResponse> for ReviewSubmission {
+    fn from(value: SubmitReviewResponse) -> Self {
+        match value {
+            SubmitReviewResponse::Ok(id) => Self::ReviewAccepted(ReviewAccepted { id: id.into() }),
+            SubmitReviewResponse::Err(err) => Self::ReviewRejected(ReviewRejected {
+                message: ReviewRejectedCode::from(err).to_string(),
+                code: err.into(),
+            }),
+        }
+    }
+}
+
+impl From<SubmitReviewError> for ReviewRejectedCode {
+    fn from(value: SubmitReviewError) -> Self {
+        match value {
+            SubmitReviewError::Unknown => Self::UnknownError,
+            SubmitReviewError::ArticleNotFound => Self::ArticleNotFound,
+            SubmitReviewError::ArticleNotShipped => Self::ArticleNotShipped,
+            SubmitReviewError::InvalidRating => Self::InvalidRating,
+            SubmitReviewError::AlreadyReviewed => Self::AlreadyReviewed,
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+
+use super::{Decimal, NaiveDateTime, Uuid};
+use crate::graphql::resolvers::Reports as ReportsResolver;
+use crate::proto::store_client::types::{
+    OrderStats as ProtoOrderStats, ReportGranularity as ProtoReportGranularity,
+    RevenuePeriod as ProtoRevenuePeriod, TopArticle as ProtoTopArticle,
+};
+
+/// Sales reports of the orders placed from `from` included to `to` excluded
+#[derive(SimpleObject, Clone, PartialEq, Eq)]
+#[graphql(complex)]
+pub struct Reports {
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+}
+
+impl Reports {
+    pub fn new(from: NaiveDateTime, to: NaiveDateTime) -> Self {
+        Self { from, to }
+    }
+}
+
+#[ComplexObject]
+impl Reports {
+    /// Revenue of the paid orders grouped by period, oldest first
+    async fn revenue<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        granularity: ReportGranularity,
+    ) -> async_graphql::Result<Vec<RevenuePeriod>> {
+        let resolver = ctx.data_unchecked::<ReportsResolver>();
+        resolver
+            .revenue(self.from.into(), self.to.into(), granularity)
+            .await
+    }
+
+    /// Articles with the most items sold in the paid orders
+    async fn top_articles<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        count: Option<u32>,
+    ) -> async_graphql::Result<Vec<TopArticle>> {
+        let resolver = ctx.data_unchecked::<ReportsResolver>();
+        resolver
+            .top_articles(self.from.into(), self.to.into(), count)
+            .await
+    }
+
+    async fn order_stats<'ctx>(&self, ctx: &Context<'ctx>) -> async_graphql::Result<OrderStats> {
+        let resolver = ctx.data_unchecked::<ReportsResolver>();
+        resolver.order_stats(self.from.into(), self.to.into()).await
+    }
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum ReportGranularity {
+    Day,
+    Week,
+    Month,
+}
+
+impl From<ReportGranularity> for ProtoReportGranularity {
+    fn from(value: ReportGranularity) -> Self {
+        match value {
+            ReportGranularity::Day => Self::Day,
+            ReportGranularity::Week => Self::Week,
+            ReportGranularity::Month => Self::Month,
+        }
+    }
+}
+
+#[derive(SimpleObject, Clone, PartialEq, Eq)]
+pub struct RevenuePeriod {
+    period_start: NaiveDateTime,
+    paid_orders: u64,
+    /// Articles and shipping cost of the paid orders
+    revenue: Decimal,
+    /// Refunds issued in the period
+    refunds: Decimal,
+}
+
+impl From<ProtoRevenuePeriod> for RevenuePeriod {
+    fn from(value: ProtoRevenuePeriod) -> Self {
+        Self {
+            period_start: value.period_start.into(),
+            paid_orders: value.paid_orders,
+            revenue: value.revenue.into(),
+            refunds: value.refunds.into(),
+        }
+    }
+}
+
+#[derive(SimpleObject, Clone, PartialEq, Eq)]
+pub struct TopArticle {
+    id: Uuid,
+    name: String,
+    quantity: u64,
+    revenue: Decimal,
+}
+
+impl From<ProtoTopArticle> for TopArticle {
+    fn from(value: ProtoTopArticle) -> Self {
+        Self {
+            id: value.id.into(),
+            name: value.name,
+            quantity: value.quantity,
+            revenue: value.revenue.into(),
+        }
+    }
+}
+
+#[derive(SimpleObject, Clone, PartialEq, Eq)]
+pub struct OrderStats {
+    orders: u64,
+    paid_orders: u64,
+    payment_failures: u64,
+    /// Average total of the paid orders, shipping included
+    average_order_value: Decimal,
+    /// Ratio of the payment attempts which were refused, from 0 to 1
+    payment_failure_rate: Decimal,
+}
+
+impl From<ProtoOrderStats> for OrderStats {
+    fn from(value: ProtoOrderStats) -> Self {
+        Self {
+            orders: value.orders,
+            paid_orders: value.paid_orders,
+            payment_failures: value.payment_failures,
+            average_order_value: value.average_order_value.into(),
+            payment_failure_rate: value.payment_failure_rate.into(),
+        }
+    }
+}
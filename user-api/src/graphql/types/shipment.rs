@@ -0,0 +1,21 @@
+use async_graphql::SimpleObject;
+
+use super::NaiveDateTime;
+use crate::proto::store_client::types::Shipment as ProtoShipment;
+
+#[derive(SimpleObject, Clone, PartialEq, Eq)]
+pub struct Shipment {
+    carrier: String,
+    tracking_code: String,
+    shipped_at: NaiveDateTime,
+}
+
+impl From<ProtoShipment> for Shipment {
+    fn from(value: ProtoShipment) -> Self {
+        Self {
+            carrier: value.carrier,
+            tracking_code: value.tracking_code,
+            shipped_at: value.shipped_at.into(),
+        }
+    }
+}
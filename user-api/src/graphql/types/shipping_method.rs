@@ -0,0 +1,21 @@
+use async_graphql::SimpleObject;
+
+use super::Uuid;
+use crate::proto::store_client::types::ShippingMethod as ProtoShippingMethod;
+
+#[derive(SimpleObject, Clone, PartialEq, Eq)]
+pub struct ShippingMethod {
+    id: Uuid,
+    name: String,
+    description: String,
+}
+
+impl From<ProtoShippingMethod> for ShippingMethod {
+    fn from(value: ProtoShippingMethod) -> Self {
+        Self {
+            id: value.id.into(),
+            name: value.name,
+            description: value.description,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use async_graphql::SimpleObject;
+
+use super::{Decimal, NaiveDateTime, Uuid};
+use crate::proto::store_client::types::{
+    Wishlist as ProtoWishlist, WishlistArticle as ProtoWishlistArticle,
+};
+
+#[derive(SimpleObject, Clone, PartialEq, Eq)]
+pub struct Wishlist {
+    id: Uuid,
+    name: String,
+    articles: Vec<WishlistArticle>,
+    created_at: NaiveDateTime,
+}
+
+impl From<ProtoWishlist> for Wishlist {
+    fn from(value: ProtoWishlist) -> Self {
+        Self {
+            id: value.id.into(),
+            name: value.name,
+            articles: value
+                .articles
+                .into_iter()
+                .map(WishlistArticle::from)
+                .collect(),
+            created_at: value.created_at.into(),
+        }
+    }
+}
+
+#[derive(SimpleObject, Clone, PartialEq, Eq)]
+pub struct WishlistArticle {
+    id: Uuid,
+    name: String,
+    description: String,
+    unit_price: Decimal,
+    /// Archived articles are kept in the wishlist, but they can't be bought anymore
+    available: bool,
+    added_at: NaiveDateTime,
+}
+
+impl From<ProtoWishlistArticle> for WishlistArticle {
+    fn from(value: ProtoWishlistArticle) -> Self {
+        Self {
+            id: value.article.id.into(),
+            name: value.article.name,
+            description: value.article.description,
+            unit_price: value.article.unit_price.into(),
+            available: value.available,
+            added_at: value.added_at.into(),
+        }
+    }
+}
@@ -7,6 +7,8 @@ extern crate serde;
 extern crate tracing;
 
 mod config;
+#[cfg(test)]
+mod e2e;
 mod graphql;
 mod proto;
 mod web;
@@ -17,7 +19,14 @@ async fn main() -> anyhow::Result<()> {
     info!("user-api v{} - developed by {}", APP_VERSION, APP_AUTHORS);
     let config = config::Config::try_from_env()?;
     debug!("initializing web service...");
-    let web_service = web::WebServer::init(&config.grpc_server_url, config.web_port).await?;
+    let web_service = web::WebServer::init(
+        &config.grpc_server_url,
+        config.web_port,
+        &config.webhook_secret,
+        config.webhook_tolerance_secs,
+        &config.admin_emails,
+    )
+    .await?;
     info!("web service OK; running web server...");
     web_service.run().await?;
 
@@ -1,18 +1,34 @@
 //! # Error
 
+use super::store_client::store::ErrorInfo;
+
 use chrono::ParseError;
+use prost::Message;
 use rust_decimal::Error as DecimalError;
 use thiserror::Error;
 use tonic::transport::Error as TransportError;
-use tonic::Status;
+use tonic::{Code, Status};
 use uuid::Error as UuidError;
 
+/// Domain of the errors raised by the store service
+const STORE_ERROR_DOMAIN: &str = "store";
+
 #[derive(Debug, Error)]
 pub enum ProtobufError {
     #[error("transport error: {0}")]
     Transport(TransportError),
+    #[error("invalid {field}: {message}")]
+    InvalidArgument { field: String, message: String },
+    #[error("{resource} {id} not found")]
+    NotFound { resource: String, id: String },
+    #[error("failed precondition: {0}")]
+    FailedPrecondition(String),
+    #[error("{resource} already exists")]
+    AlreadyExists { resource: String },
+    /// Status without store error details, or internal store error; boxed as it's much larger
+    /// than the other variants
     #[error("protobuf error: {0}")]
-    Protobuf(Status),
+    Protobuf(Box<Status>),
     #[error("syntax error: {0}")]
     Syntax(SyntaxError),
 }
@@ -24,8 +40,30 @@ impl From<TransportError> for ProtobufError {
 }
 
 impl From<Status> for ProtobufError {
+    /// Decode the `ErrorInfo` sent by the store in the status details into a typed error
     fn from(value: Status) -> Self {
-        Self::Protobuf(value)
+        let info = match ErrorInfo::decode(value.details()) {
+            Ok(info) if info.domain == STORE_ERROR_DOMAIN => info,
+            _ => return Self::Protobuf(Box::new(value)),
+        };
+        let metadata = |key: &str| info.metadata.get(key).cloned().unwrap_or_default();
+        match (value.code(), info.reason.as_str()) {
+            (Code::InvalidArgument, "INVALID_ARGUMENT") => Self::InvalidArgument {
+                field: metadata("field"),
+                message: value.message().to_string(),
+            },
+            (Code::NotFound, "NOT_FOUND") => Self::NotFound {
+                resource: metadata("resource"),
+                id: metadata("id"),
+            },
+            (Code::FailedPrecondition, "FAILED_PRECONDITION") => {
+                Self::FailedPrecondition(value.message().to_string())
+            }
+            (Code::AlreadyExists, "ALREADY_EXISTS") => Self::AlreadyExists {
+                resource: metadata("resource"),
+            },
+            _ => Self::Protobuf(Box::new(value)),
+        }
     }
 }
 
@@ -68,3 +106,53 @@ impl From<UuidError> for SyntaxError {
         Self::Uuid(value)
     }
 }
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn status(code: Code, reason: &str, metadata: &[(&str, &str)]) -> Status {
+        let info = ErrorInfo {
+            reason: reason.to_string(),
+            domain: STORE_ERROR_DOMAIN.to_string(),
+            metadata: metadata
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        };
+        Status::with_details(code, "store error", info.encode_to_vec().into())
+    }
+
+    #[test]
+    fn should_decode_store_error_details() {
+        let error = ProtobufError::from(status(
+            Code::NotFound,
+            "NOT_FOUND",
+            &[("resource", "order"), ("id", "42")],
+        ));
+        assert!(
+            matches!(&error, ProtobufError::NotFound { resource, id } if resource == "order" && id == "42")
+        );
+        let error = ProtobufError::from(status(
+            Code::InvalidArgument,
+            "INVALID_ARGUMENT",
+            &[("field", "user_id")],
+        ));
+        assert!(
+            matches!(&error, ProtobufError::InvalidArgument { field, .. } if field == "user_id")
+        );
+    }
+
+    #[test]
+    fn should_keep_status_without_store_error_details() {
+        let error = ProtobufError::from(Status::unavailable("connection refused"));
+        assert!(
+            matches!(&error, ProtobufError::Protobuf(status) if status.code() == Code::Unavailable)
+        );
+        let error = ProtobufError::from(status(Code::Internal, "INTERNAL", &[]));
+        assert!(
+            matches!(&error, ProtobufError::Protobuf(status) if status.message() == "store error")
+        );
+    }
+}
@@ -4,14 +4,29 @@ pub mod types;
 pub mod store {
     tonic::include_proto!("store");
 }
-use self::types::{Article, AuthResponse, Order, OrderedArticle, SubmitOrderResponse};
+use self::types::{
+    Article, ArticleReview, AuthResponse, CartOwner, CartResponse, ExportedOrder, InvoiceResponse,
+    Order, OrderFilter, OrderHistoryResponse, OrderPayment, OrderStats, OrderedArticle, Page,
+    ReportGranularity, RequestReturnResponse, ReturnedArticle, RevenuePeriod, ShippingMethod,
+    SubmitOrderResponse, SubmitReviewResponse, TopArticle, Wishlist, WishlistResponse,
+};
 
-use super::ProtobufResult;
+use super::{ProtobufResult, SyntaxError};
 use store::store_service_client::StoreServiceClient;
 use store::{
-    QueryArticlesRequest, QueryOrdersRequest, SignInRequest, SignUpRequest, SubmitOrderRequest,
+    AddToCartRequest, AddToWishlistRequest, CheckoutCartRequest, CreateWishlistRequest, DateRange,
+    DeleteWishlistRequest, ExportOrdersRequest, GetCartRequest, GetInvoiceRequest,
+    GetOrderHistoryRequest, GetOrderRequest, GetOrderStatsReportRequest, GetRevenueReportRequest,
+    GetTopArticlesReportRequest, Iso8601, MergeCartRequest, MoveWishlistItemToCartRequest,
+    QueryArticleReviewsRequest, QueryArticlesRequest, QueryOrdersRequest,
+    QueryShippingMethodsRequest, QueryWishlistsRequest, RecommendArticlesRequest,
+    RemoveFromCartRequest, RemoveFromWishlistRequest, RequestReturnRequest, SignInRequest,
+    SignUpRequest, SubmitOrderPaymentRequest, SubmitOrderRequest, SubmitReviewRequest,
+    UpdateCartItemRequest,
 };
 
+use chrono::NaiveDateTime;
+use futures_util::{Stream, StreamExt};
 use tonic::transport::Channel;
 use uuid::Uuid;
 
@@ -53,55 +68,151 @@ impl StoreClient {
         Ok(AuthResponse::try_from(response.into_inner())?)
     }
 
-    /// Query orders for customer
+    /// Query orders for customer matching `filter`, newest first, starting after `cursor`
     pub async fn query_orders(
         &mut self,
         user_id: Uuid,
-        page_number: u32,
+        filter: OrderFilter,
+        cursor: Option<String>,
         results_per_page: u32,
-    ) -> ProtobufResult<Vec<Order>> {
-        debug!("trying collect order for {user_id} from {page_number} to {results_per_page}");
+    ) -> ProtobufResult<Page<Order>> {
+        debug!(
+            "trying collect order for {user_id} after {:?}; {results_per_page} elements",
+            cursor
+        );
         let request = tonic::Request::new(QueryOrdersRequest {
             user_id: user_id.to_string(),
-            page_number,
+            cursor,
             results_per_page,
+            statuses: filter
+                .statuses
+                .into_iter()
+                .map(|x| store::order::OrderStatus::from(x) as i32)
+                .collect(),
+            created_from: filter.created_from.as_ref().map(Self::iso8601),
+            created_to: filter.created_to.as_ref().map(Self::iso8601),
+        });
+        let response = self.store_client.query_orders(request).await?.into_inner();
+
+        let mut edges = Vec::with_capacity(response.edges.len());
+        for edge in response.edges.into_iter() {
+            let order = edge.order.ok_or(SyntaxError::ValueIsMissing)?;
+            edges.push((edge.cursor, Order::try_from(order)?));
+        }
+
+        debug!("got {} orders of {}", edges.len(), response.total_count);
+        Ok(Page {
+            edges,
+            next_cursor: response.next_cursor,
+            total_count: response.total_count,
+        })
+    }
+
+    /// Get an order of the user; `None` if it doesn't exist or belongs to another user
+    pub async fn get_order(
+        &mut self,
+        user_id: Uuid,
+        order_id: Uuid,
+    ) -> ProtobufResult<Option<Order>> {
+        debug!("trying to get order {order_id} of {user_id}");
+        let request = tonic::Request::new(GetOrderRequest {
+            user_id: user_id.to_string(),
+            order_id: order_id.to_string(),
+        });
+        let response = self.store_client.get_order(request).await?.into_inner();
+
+        Ok(response.order.map(Order::try_from).transpose()?)
+    }
+
+    /// Get the history of an order of the user
+    pub async fn get_order_history(
+        &mut self,
+        user_id: Uuid,
+        order_id: Uuid,
+    ) -> ProtobufResult<OrderHistoryResponse> {
+        debug!("trying to collect history for order {order_id} of {user_id}");
+        let request = tonic::Request::new(GetOrderHistoryRequest {
+            user_id: user_id.to_string(),
+            order_id: order_id.to_string(),
         });
         let response = self
             .store_client
-            .query_orders(request)
+            .get_order_history(request)
             .await?
-            .into_inner()
-            .orders;
+            .into_inner();
 
-        let mut orders = Vec::with_capacity(response.len());
-        for order in response.into_iter() {
-            orders.push(Order::try_from(order)?);
-        }
+        Ok(OrderHistoryResponse::try_from(response)?)
+    }
 
-        debug!("got {} orders", orders.len());
-        Ok(orders)
+    /// Get the invoice of an order of the customer
+    pub async fn get_invoice(
+        &mut self,
+        user_id: Uuid,
+        order_id: Uuid,
+    ) -> ProtobufResult<InvoiceResponse> {
+        debug!("trying to get invoice for order {order_id} of {user_id}");
+        let request = tonic::Request::new(GetInvoiceRequest {
+            user_id: user_id.to_string(),
+            order_id: order_id.to_string(),
+        });
+        let response = self.store_client.get_invoice(request).await?.into_inner();
+
+        Ok(InvoiceResponse::try_from(response)?)
     }
 
-    /// Query articles from store
+    /// Query articles from store, starting after `cursor`
     pub async fn query_articles(
         &mut self,
         query: Option<String>,
-        page_number: u32,
+        cursor: Option<String>,
         results_per_page: u32,
-    ) -> ProtobufResult<Vec<Article>> {
+    ) -> ProtobufResult<Page<Article>> {
         debug!(
-            "trying collect order for {:?} from {page_number} to {results_per_page}",
-            query
+            "trying collect articles for {:?} after {:?}; {results_per_page} elements",
+            query, cursor
         );
         let request = tonic::Request::new(QueryArticlesRequest {
             query,
-            page_number,
+            cursor,
             results_per_page,
         });
         let response = self
             .store_client
             .query_articles(request)
             .await?
+            .into_inner();
+
+        let mut edges = Vec::with_capacity(response.edges.len());
+        for edge in response.edges.into_iter() {
+            let article = edge.article.ok_or(SyntaxError::ValueIsMissing)?;
+            edges.push((edge.cursor, Article::try_from(article)?));
+        }
+
+        debug!("got {} articles of {}", edges.len(), response.total_count);
+        Ok(Page {
+            edges,
+            next_cursor: response.next_cursor,
+            total_count: response.total_count,
+        })
+    }
+
+    /// Query the articles frequently bought together with an article
+    pub async fn recommend_articles(
+        &mut self,
+        article_id: Uuid,
+        limit: Option<u32>,
+    ) -> ProtobufResult<Vec<Article>> {
+        debug!("trying to collect articles related to {article_id}");
+        let request = tonic::Request::new(RecommendArticlesRequest {
+            source: Some(store::recommend_articles_request::Source::ArticleId(
+                article_id.to_string(),
+            )),
+            limit,
+        });
+        let response = self
+            .store_client
+            .recommend_articles(request)
+            .await?
             .into_inner()
             .articles;
 
@@ -110,15 +221,75 @@ impl StoreClient {
             articles.push(Article::try_from(article)?);
         }
 
-        debug!("got {} articles", articles.len());
+        debug!("got {} related articles", articles.len());
         Ok(articles)
     }
 
+    /// Query the approved reviews of an article, newest first
+    pub async fn query_article_reviews(
+        &mut self,
+        article_id: Uuid,
+        cursor: Option<String>,
+        results_per_page: u32,
+    ) -> ProtobufResult<Page<ArticleReview>> {
+        debug!(
+            "trying to collect reviews for article {article_id} after {:?}; {results_per_page} elements",
+            cursor
+        );
+        let request = tonic::Request::new(QueryArticleReviewsRequest {
+            article_id: article_id.to_string(),
+            cursor,
+            results_per_page,
+        });
+        let response = self
+            .store_client
+            .query_article_reviews(request)
+            .await?
+            .into_inner();
+
+        let mut edges = Vec::with_capacity(response.edges.len());
+        for edge in response.edges.into_iter() {
+            let review = edge.review.ok_or(SyntaxError::ValueIsMissing)?;
+            edges.push((edge.cursor, ArticleReview::try_from(review)?));
+        }
+
+        debug!("got {} reviews of {}", edges.len(), response.total_count);
+        Ok(Page {
+            edges,
+            next_cursor: response.next_cursor,
+            total_count: response.total_count,
+        })
+    }
+
+    /// Submit a review for an article shipped to the customer
+    pub async fn submit_review(
+        &mut self,
+        user_id: Uuid,
+        article_id: Uuid,
+        rating: u32,
+        title: String,
+        body: String,
+    ) -> ProtobufResult<SubmitReviewResponse> {
+        debug!("submitting review with rating {rating} for {article_id} by {user_id}");
+        let request = tonic::Request::new(SubmitReviewRequest {
+            user_id: user_id.to_string(),
+            article_id: article_id.to_string(),
+            rating,
+            title,
+            body,
+        });
+        let response = self.store_client.submit_review(request).await?.into_inner();
+
+        Ok(SubmitReviewResponse::try_from(response)?)
+    }
+
     /// Submit order
     pub async fn submit_order(
         &mut self,
         user_id: Uuid,
         articles: Vec<OrderedArticle>,
+        shipping_method_id: Option<Uuid>,
+        card_number: Option<String>,
     ) -> ProtobufResult<SubmitOrderResponse> {
         debug!(
             "submitting order for {user_id} for {} articles",
@@ -133,9 +304,442 @@ impl StoreClient {
                 })
                 .collect(),
             user_id: user_id.to_string(),
+            shipping_method_id: shipping_method_id.map(|x| x.to_string()),
+            card_number,
         });
         let response = self.store_client.submit_order(request).await?.into_inner();
 
         Ok(SubmitOrderResponse::try_from(response)?)
     }
+
+    /// Submit the payment outcome for an order
+    pub async fn submit_order_payment(
+        &mut self,
+        order_id: Uuid,
+        payment: OrderPayment,
+    ) -> ProtobufResult<SubmitOrderResponse> {
+        debug!("submitting payment {:?} for order {order_id}", payment);
+        let order_id = order_id.to_string();
+        let status = match payment {
+            OrderPayment::Succeeded { transaction_id } => {
+                store::submit_order_payment_request::Status::Success(
+                    store::submit_order_payment_request::SubmitOrderPaymentSucceedRequest {
+                        order_id,
+                        transaction_id,
+                    },
+                )
+            }
+            OrderPayment::Failed => store::submit_order_payment_request::Status::Failed(
+                store::submit_order_payment_request::SubmitOrderPaymentFailedRequest { order_id },
+            ),
+        };
+        let request = tonic::Request::new(SubmitOrderPaymentRequest {
+            status: Some(status),
+        });
+        let response = self
+            .store_client
+            .submit_order_payment(request)
+            .await?
+            .into_inner();
+
+        Ok(SubmitOrderResponse::try_from(response)?)
+    }
+
+    /// Get the cart of the owner
+    pub async fn get_cart(&mut self, owner: CartOwner) -> ProtobufResult<CartResponse> {
+        debug!("trying to get cart of {:?}", owner);
+        let request = tonic::Request::new(GetCartRequest {
+            owner: Some(owner.into()),
+        });
+        let response = self.store_client.get_cart(request).await?.into_inner();
+
+        Ok(CartResponse::try_from(response)?)
+    }
+
+    /// Add `quantity` items of the article to the cart of the owner
+    pub async fn add_to_cart(
+        &mut self,
+        owner: CartOwner,
+        article_id: Uuid,
+        quantity: u32,
+    ) -> ProtobufResult<CartResponse> {
+        debug!(
+            "adding {quantity} items of {article_id} to cart of {:?}",
+            owner
+        );
+        let request = tonic::Request::new(AddToCartRequest {
+            owner: Some(owner.into()),
+            article_id: article_id.to_string(),
+            quantity,
+        });
+        let response = self.store_client.add_to_cart(request).await?.into_inner();
+
+        Ok(CartResponse::try_from(response)?)
+    }
+
+    /// Set the quantity of the article in the cart of the owner; a zero quantity removes the article
+    pub async fn update_cart_item(
+        &mut self,
+        owner: CartOwner,
+        article_id: Uuid,
+        quantity: u32,
+    ) -> ProtobufResult<CartResponse> {
+        debug!(
+            "setting quantity of {article_id} to {quantity} in cart of {:?}",
+            owner
+        );
+        let request = tonic::Request::new(UpdateCartItemRequest {
+            owner: Some(owner.into()),
+            article_id: article_id.to_string(),
+            quantity,
+        });
+        let response = self
+            .store_client
+            .update_cart_item(request)
+            .await?
+            .into_inner();
+
+        Ok(CartResponse::try_from(response)?)
+    }
+
+    /// Remove the article from the cart of the owner
+    pub async fn remove_from_cart(
+        &mut self,
+        owner: CartOwner,
+        article_id: Uuid,
+    ) -> ProtobufResult<CartResponse> {
+        debug!("removing {article_id} from cart of {:?}", owner);
+        let request = tonic::Request::new(RemoveFromCartRequest {
+            owner: Some(owner.into()),
+            article_id: article_id.to_string(),
+        });
+        let response = self
+            .store_client
+            .remove_from_cart(request)
+            .await?
+            .into_inner();
+
+        Ok(CartResponse::try_from(response)?)
+    }
+
+    /// Merge the anonymous cart into the cart of the customer
+    pub async fn merge_cart(
+        &mut self,
+        user_id: Uuid,
+        anonymous_cart_id: Uuid,
+    ) -> ProtobufResult<CartResponse> {
+        debug!("merging anonymous cart {anonymous_cart_id} into cart of {user_id}");
+        let request = tonic::Request::new(MergeCartRequest {
+            user_id: user_id.to_string(),
+            anonymous_cart_id: anonymous_cart_id.to_string(),
+        });
+        let response = self.store_client.merge_cart(request).await?.into_inner();
+
+        Ok(CartResponse::try_from(response)?)
+    }
+
+    /// Submit an order with the articles in the cart of the customer
+    pub async fn checkout_cart(
+        &mut self,
+        user_id: Uuid,
+        shipping_method_id: Option<Uuid>,
+        card_number: Option<String>,
+    ) -> ProtobufResult<SubmitOrderResponse> {
+        debug!("checking out cart of {user_id}");
+        let request = tonic::Request::new(CheckoutCartRequest {
+            user_id: user_id.to_string(),
+            shipping_method_id: shipping_method_id.map(|x| x.to_string()),
+            card_number,
+        });
+        let response = self.store_client.checkout_cart(request).await?.into_inner();
+
+        Ok(SubmitOrderResponse::try_from(response)?)
+    }
+
+    /// Query the wishlists of the customer
+    pub async fn query_wishlists(&mut self, user_id: Uuid) -> ProtobufResult<Vec<Wishlist>> {
+        debug!("trying to collect wishlists for {user_id}");
+        let request = tonic::Request::new(QueryWishlistsRequest {
+            user_id: user_id.to_string(),
+        });
+        let response = self
+            .store_client
+            .query_wishlists(request)
+            .await?
+            .into_inner()
+            .wishlists;
+
+        let mut wishlists = Vec::with_capacity(response.len());
+        for wishlist in response.into_iter() {
+            wishlists.push(Wishlist::try_from(wishlist)?);
+        }
+
+        debug!("got {} wishlists", wishlists.len());
+        Ok(wishlists)
+    }
+
+    /// Create a new wishlist for the customer
+    pub async fn create_wishlist(
+        &mut self,
+        user_id: Uuid,
+        name: String,
+    ) -> ProtobufResult<WishlistResponse> {
+        debug!("creating wishlist {name} for {user_id}");
+        let request = tonic::Request::new(CreateWishlistRequest {
+            user_id: user_id.to_string(),
+            name,
+        });
+        let response = self
+            .store_client
+            .create_wishlist(request)
+            .await?
+            .into_inner();
+
+        Ok(WishlistResponse::try_from(response)?)
+    }
+
+    /// Delete a wishlist of the customer
+    pub async fn delete_wishlist(
+        &mut self,
+        user_id: Uuid,
+        wishlist_id: Uuid,
+    ) -> ProtobufResult<WishlistResponse> {
+        debug!("deleting wishlist {wishlist_id} of {user_id}");
+        let request = tonic::Request::new(DeleteWishlistRequest {
+            user_id: user_id.to_string(),
+            wishlist_id: wishlist_id.to_string(),
+        });
+        let response = self
+            .store_client
+            .delete_wishlist(request)
+            .await?
+            .into_inner();
+
+        Ok(WishlistResponse::try_from(response)?)
+    }
+
+    /// Add an article to a wishlist of the customer
+    pub async fn add_to_wishlist(
+        &mut self,
+        user_id: Uuid,
+        wishlist_id: Uuid,
+        article_id: Uuid,
+    ) -> ProtobufResult<WishlistResponse> {
+        debug!("adding {article_id} to wishlist {wishlist_id} of {user_id}");
+        let request = tonic::Request::new(AddToWishlistRequest {
+            user_id: user_id.to_string(),
+            wishlist_id: wishlist_id.to_string(),
+            article_id: article_id.to_string(),
+        });
+        let response = self
+            .store_client
+            .add_to_wishlist(request)
+            .await?
+            .into_inner();
+
+        Ok(WishlistResponse::try_from(response)?)
+    }
+
+    /// Remove an article from a wishlist of the customer
+    pub async fn remove_from_wishlist(
+        &mut self,
+        user_id: Uuid,
+        wishlist_id: Uuid,
+        article_id: Uuid,
+    ) -> ProtobufResult<WishlistResponse> {
+        debug!("removing {article_id} from wishlist {wishlist_id} of {user_id}");
+        let request = tonic::Request::new(RemoveFromWishlistRequest {
+            user_id: user_id.to_string(),
+            wishlist_id: wishlist_id.to_string(),
+            article_id: article_id.to_string(),
+        });
+        let response = self
+            .store_client
+            .remove_from_wishlist(request)
+            .await?
+            .into_inner();
+
+        Ok(WishlistResponse::try_from(response)?)
+    }
+
+    /// Move an article from a wishlist of the customer to their cart
+    pub async fn move_wishlist_item_to_cart(
+        &mut self,
+        user_id: Uuid,
+        wishlist_id: Uuid,
+        article_id: Uuid,
+    ) -> ProtobufResult<WishlistResponse> {
+        debug!("moving {article_id} from wishlist {wishlist_id} of {user_id} to cart");
+        let request = tonic::Request::new(MoveWishlistItemToCartRequest {
+            user_id: user_id.to_string(),
+            wishlist_id: wishlist_id.to_string(),
+            article_id: article_id.to_string(),
+        });
+        let response = self
+            .store_client
+            .move_wishlist_item_to_cart(request)
+            .await?
+            .into_inner();
+
+        Ok(WishlistResponse::try_from(response)?)
+    }
+
+    /// Query available shipping methods
+    pub async fn query_shipping_methods(&mut self) -> ProtobufResult<Vec<ShippingMethod>> {
+        debug!("trying to collect shipping methods");
+        let request = tonic::Request::new(QueryShippingMethodsRequest {});
+        let response = self
+            .store_client
+            .query_shipping_methods(request)
+            .await?
+            .into_inner()
+            .shipping_methods;
+
+        let mut shipping_methods = Vec::with_capacity(response.len());
+        for shipping_method in response.into_iter() {
+            shipping_methods.push(ShippingMethod::try_from(shipping_method)?);
+        }
+
+        debug!("got {} shipping methods", shipping_methods.len());
+        Ok(shipping_methods)
+    }
+
+    /// Request a return for some articles of an order
+    pub async fn request_return(
+        &mut self,
+        user_id: Uuid,
+        order_id: Uuid,
+        articles: Vec<ReturnedArticle>,
+        reason: String,
+    ) -> ProtobufResult<RequestReturnResponse> {
+        debug!(
+            "requesting return for order {order_id} of {user_id} for {} articles",
+            articles.len()
+        );
+        let request = tonic::Request::new(RequestReturnRequest {
+            user_id: user_id.to_string(),
+            order_id: order_id.to_string(),
+            articles: articles
+                .into_iter()
+                .map(|x| store::request_return_request::ReturnArticle {
+                    article_id: x.id.to_string(),
+                    quantity: x.quantity,
+                })
+                .collect(),
+            reason,
+        });
+        let response = self
+            .store_client
+            .request_return(request)
+            .await?
+            .into_inner();
+
+        Ok(RequestReturnResponse::try_from(response)?)
+    }
+
+    /// Get the revenue of the orders placed in `[from, to)` grouped by period
+    pub async fn get_revenue_report(
+        &mut self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        granularity: ReportGranularity,
+    ) -> ProtobufResult<Vec<RevenuePeriod>> {
+        debug!(
+            "getting revenue report by {:?} from {from} to {to}",
+            granularity
+        );
+        let request = tonic::Request::new(GetRevenueReportRequest {
+            range: Some(Self::date_range(from, to)),
+            granularity: store::get_revenue_report_request::Granularity::from(granularity) as i32,
+        });
+        let response = self
+            .store_client
+            .get_revenue_report(request)
+            .await?
+            .into_inner()
+            .periods;
+
+        let mut periods = Vec::with_capacity(response.len());
+        for period in response.into_iter() {
+            periods.push(RevenuePeriod::try_from(period)?);
+        }
+
+        debug!("got {} revenue periods", periods.len());
+        Ok(periods)
+    }
+
+    /// Get the articles with the most items sold in the orders placed in `[from, to)`
+    pub async fn get_top_articles_report(
+        &mut self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        limit: Option<u32>,
+    ) -> ProtobufResult<Vec<TopArticle>> {
+        debug!("getting top articles report from {from} to {to}");
+        let request = tonic::Request::new(GetTopArticlesReportRequest {
+            range: Some(Self::date_range(from, to)),
+            limit,
+        });
+        let response = self
+            .store_client
+            .get_top_articles_report(request)
+            .await?
+            .into_inner()
+            .articles;
+
+        let mut articles = Vec::with_capacity(response.len());
+        for article in response.into_iter() {
+            articles.push(TopArticle::try_from(article)?);
+        }
+
+        debug!("got {} top articles", articles.len());
+        Ok(articles)
+    }
+
+    /// Get the statistics of the orders placed in `[from, to)`
+    pub async fn get_order_stats_report(
+        &mut self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> ProtobufResult<OrderStats> {
+        debug!("getting order stats report from {from} to {to}");
+        let request = tonic::Request::new(GetOrderStatsReportRequest {
+            range: Some(Self::date_range(from, to)),
+        });
+        let response = self
+            .store_client
+            .get_order_stats_report(request)
+            .await?
+            .into_inner();
+
+        Ok(OrderStats::try_from(response)?)
+    }
+
+    /// Export the orders created in `[from, to)`, oldest first, as they are streamed by the store
+    pub async fn export_orders(
+        &mut self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> ProtobufResult<impl Stream<Item = ProtobufResult<ExportedOrder>>> {
+        debug!("exporting orders created from {from} to {to}");
+        let request = tonic::Request::new(ExportOrdersRequest {
+            range: Some(Self::date_range(from, to)),
+        });
+        let orders = self.store_client.export_orders(request).await?.into_inner();
+
+        Ok(orders.map(|order| Ok(ExportedOrder::try_from(order?)?)))
+    }
+
+    fn date_range(from: NaiveDateTime, to: NaiveDateTime) -> DateRange {
+        DateRange {
+            from: Some(Self::iso8601(&from)),
+            to: Some(Self::iso8601(&to)),
+        }
+    }
+
+    fn iso8601(date: &NaiveDateTime) -> Iso8601 {
+        Iso8601 {
+            timestamp: date.format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
 }
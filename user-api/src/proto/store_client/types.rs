@@ -4,9 +4,36 @@ use super::store;
 use crate::proto::SyntaxError;
 
 mod article;
+mod article_review;
 mod auth_response;
+mod cart;
+mod invoice;
 mod order;
+mod order_event;
+mod order_export;
+mod order_return;
+mod page;
+mod sales_report;
+mod shipping;
+mod wishlist;
 
 pub use article::{Article, OrderedArticle};
+pub use article_review::{ArticleRating, ArticleReview, SubmitReviewError, SubmitReviewResponse};
 pub use auth_response::{AuthError, AuthResponse};
-pub use order::{Order, OrderArticle, OrderStatus, SubmitOrderError, SubmitOrderResponse};
+pub use cart::{Cart, CartError, CartOwner, CartResponse};
+pub use invoice::{Invoice, InvoiceError, InvoiceResponse};
+#[cfg(test)]
+pub use invoice::{InvoiceLine, InvoiceSeller};
+pub use order::{
+    Order, OrderArticle, OrderFilter, OrderPayment, OrderStatus, SubmitOrderError,
+    SubmitOrderResponse,
+};
+pub use order_event::{
+    OrderEvent, OrderEventActor, OrderEventKind, OrderHistoryError, OrderHistoryResponse,
+};
+pub use order_export::ExportedOrder;
+pub use order_return::{RequestReturnError, RequestReturnResponse, ReturnedArticle};
+pub use page::Page;
+pub use sales_report::{OrderStats, ReportGranularity, RevenuePeriod, TopArticle};
+pub use shipping::{Shipment, ShippingMethod};
+pub use wishlist::{Wishlist, WishlistArticle, WishlistError, WishlistResponse};
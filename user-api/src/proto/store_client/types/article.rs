@@ -2,6 +2,7 @@ use rust_decimal::Decimal;
 use std::str::FromStr;
 use uuid::Uuid;
 
+use super::ArticleRating;
 use crate::proto::SyntaxError;
 
 pub struct Article {
@@ -9,6 +10,8 @@ pub struct Article {
     pub name: String,
     pub description: String,
     pub unit_price: Decimal,
+    /// `None` if the article has no approved reviews
+    pub rating: Option<ArticleRating>,
 }
 
 impl TryFrom<super::store::Article> for Article {
@@ -20,6 +23,7 @@ impl TryFrom<super::store::Article> for Article {
             name: value.name,
             description: value.description,
             unit_price: Decimal::from_str(&value.unit_price.map(|x| x.value).unwrap_or_default())?,
+            rating: value.rating.map(ArticleRating::try_from).transpose()?,
         })
     }
 }
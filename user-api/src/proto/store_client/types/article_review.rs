@@ -0,0 +1,95 @@
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::SyntaxError;
+
+/// Average rating of the approved reviews of an article
+pub struct ArticleRating {
+    pub average: Decimal,
+    pub count: u32,
+}
+
+impl TryFrom<super::store::ArticleRating> for ArticleRating {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::ArticleRating) -> Result<Self, Self::Error> {
+        Ok(Self {
+            average: Decimal::from_str(&value.average.map(|x| x.value).unwrap_or_default())?,
+            count: value.count,
+        })
+    }
+}
+
+/// Approved review of an article
+pub struct ArticleReview {
+    pub id: Uuid,
+    pub rating: u32,
+    pub title: String,
+    pub body: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl TryFrom<super::store::ArticleReview> for ArticleReview {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::ArticleReview) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: Uuid::from_str(&value.id)?,
+            rating: value.rating,
+            title: value.title,
+            body: value.body,
+            created_at: NaiveDateTime::parse_from_str(
+                &value.created_at.map(|x| x.timestamp).unwrap_or_default(),
+                "%Y-%m-%d %H:%M:%S",
+            )?,
+        })
+    }
+}
+
+pub enum SubmitReviewResponse {
+    Ok(Uuid),
+    Err(SubmitReviewError),
+}
+
+impl TryFrom<super::store::SubmitReviewResponse> for SubmitReviewResponse {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::SubmitReviewResponse) -> Result<Self, Self::Error> {
+        match value.status {
+            Some(super::store::submit_review_response::Status::ReviewId(id)) => {
+                Ok(Self::Ok(Uuid::from_str(&id)?))
+            }
+            Some(super::store::submit_review_response::Status::Error(err)) => {
+                Ok(Self::Err(SubmitReviewError::try_from(err)?))
+            }
+            None => Err(SyntaxError::ValueIsMissing),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum SubmitReviewError {
+    Unknown,
+    ArticleNotFound,
+    ArticleNotShipped,
+    InvalidRating,
+    AlreadyReviewed,
+}
+
+impl TryFrom<i32> for SubmitReviewError {
+    type Error = SyntaxError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Unknown),
+            1 => Ok(Self::ArticleNotFound),
+            2 => Ok(Self::ArticleNotShipped),
+            3 => Ok(Self::InvalidRating),
+            4 => Ok(Self::AlreadyReviewed),
+            _ => Err(SyntaxError::UnknownValue),
+        }
+    }
+}
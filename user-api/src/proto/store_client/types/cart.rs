@@ -0,0 +1,91 @@
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::{OrderArticle, SyntaxError};
+
+/// Owner of a cart: either a signed in customer or an anonymous visitor
+#[derive(Clone, Copy, Debug)]
+pub enum CartOwner {
+    Customer(Uuid),
+    Anonymous(Uuid),
+}
+
+impl From<CartOwner> for super::store::CartOwner {
+    fn from(value: CartOwner) -> Self {
+        let owner = match value {
+            CartOwner::Customer(id) => super::store::cart_owner::Owner::UserId(id.to_string()),
+            CartOwner::Anonymous(id) => {
+                super::store::cart_owner::Owner::AnonymousCartId(id.to_string())
+            }
+        };
+        Self { owner: Some(owner) }
+    }
+}
+
+pub struct Cart {
+    pub id: Uuid,
+    pub articles: Vec<OrderArticle>,
+    pub subtotal: Decimal,
+}
+
+impl TryFrom<super::store::Cart> for Cart {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::Cart) -> Result<Self, Self::Error> {
+        let mut articles = Vec::with_capacity(value.articles.len());
+        for article in value.articles.into_iter() {
+            articles.push(OrderArticle::try_from(article)?);
+        }
+
+        Ok(Self {
+            id: Uuid::from_str(&value.id)?,
+            articles,
+            subtotal: Decimal::from_str(&value.subtotal.map(|x| x.value).unwrap_or_default())?,
+        })
+    }
+}
+
+pub enum CartResponse {
+    Ok(Cart),
+    Err(CartError),
+}
+
+impl TryFrom<super::store::CartResponse> for CartResponse {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::CartResponse) -> Result<Self, Self::Error> {
+        match value.status {
+            Some(super::store::cart_response::Status::Cart(cart)) => {
+                Ok(Self::Ok(Cart::try_from(cart)?))
+            }
+            Some(super::store::cart_response::Status::Error(err)) => {
+                Ok(Self::Err(CartError::try_from(err)?))
+            }
+            None => Err(SyntaxError::ValueIsMissing),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum CartError {
+    Unknown,
+    CartNotFound,
+    InvalidArticle,
+    InvalidQuantity,
+}
+
+impl TryFrom<i32> for CartError {
+    type Error = SyntaxError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Unknown),
+            1 => Ok(Self::CartNotFound),
+            2 => Ok(Self::InvalidArticle),
+            3 => Ok(Self::InvalidQuantity),
+            _ => Err(SyntaxError::UnknownValue),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::SyntaxError;
+
+/// Invoice of a paid order; prices include taxes
+pub struct Invoice {
+    pub order_id: Uuid,
+    pub number: String,
+    pub issued_at: NaiveDateTime,
+    pub seller: InvoiceSeller,
+    pub customer_email: String,
+    pub transaction_id: String,
+    pub lines: Vec<InvoiceLine>,
+    pub shipping_cost: Decimal,
+    pub tax_rate: Decimal,
+    pub taxable_amount: Decimal,
+    pub tax_amount: Decimal,
+    pub total: Decimal,
+}
+
+impl TryFrom<super::store::Invoice> for Invoice {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::Invoice) -> Result<Self, Self::Error> {
+        let mut lines = Vec::with_capacity(value.lines.len());
+        for line in value.lines.into_iter() {
+            lines.push(InvoiceLine::try_from(line)?);
+        }
+        let seller = value.seller.ok_or(SyntaxError::ValueIsMissing)?;
+
+        Ok(Self {
+            order_id: Uuid::from_str(&value.order_id)?,
+            number: value.number,
+            issued_at: NaiveDateTime::parse_from_str(
+                &value.issued_at.map(|x| x.timestamp).unwrap_or_default(),
+                "%Y-%m-%d %H:%M:%S",
+            )?,
+            seller: InvoiceSeller {
+                name: seller.name,
+                address: seller.address,
+                vat_number: seller.vat_number,
+            },
+            customer_email: value.customer_email,
+            transaction_id: value.transaction_id,
+            lines,
+            shipping_cost: Decimal::from_str(
+                &value.shipping_cost.map(|x| x.value).unwrap_or_default(),
+            )?,
+            tax_rate: Decimal::from_str(&value.tax_rate.map(|x| x.value).unwrap_or_default())?,
+            taxable_amount: Decimal::from_str(
+                &value.taxable_amount.map(|x| x.value).unwrap_or_default(),
+            )?,
+            tax_amount: Decimal::from_str(&value.tax_amount.map(|x| x.value).unwrap_or_default())?,
+            total: Decimal::from_str(&value.total.map(|x| x.value).unwrap_or_default())?,
+        })
+    }
+}
+
+/// Seller details printed on an invoice
+pub struct InvoiceSeller {
+    pub name: String,
+    pub address: String,
+    pub vat_number: String,
+}
+
+/// Article billed in an invoice
+pub struct InvoiceLine {
+    pub name: String,
+    pub quantity: u32,
+    pub unit_price: Decimal,
+    pub total: Decimal,
+}
+
+impl TryFrom<super::store::InvoiceLine> for InvoiceLine {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::InvoiceLine) -> Result<Self, Self::Error> {
+        Ok(Self {
+            name: value.name,
+            quantity: value.quantity,
+            unit_price: Decimal::from_str(&value.unit_price.map(|x| x.value).unwrap_or_default())?,
+            total: Decimal::from_str(&value.total.map(|x| x.value).unwrap_or_default())?,
+        })
+    }
+}
+
+pub enum InvoiceResponse {
+    Ok(Box<Invoice>),
+    Err(InvoiceError),
+}
+
+impl TryFrom<super::store::GetInvoiceResponse> for InvoiceResponse {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::GetInvoiceResponse) -> Result<Self, Self::Error> {
+        match value.status {
+            Some(super::store::get_invoice_response::Status::Invoice(invoice)) => {
+                Ok(Self::Ok(Box::new(Invoice::try_from(*invoice)?)))
+            }
+            Some(super::store::get_invoice_response::Status::Error(err)) => {
+                Ok(Self::Err(InvoiceError::try_from(err)?))
+            }
+            None => Err(SyntaxError::ValueIsMissing),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum InvoiceError {
+    Unknown,
+    OrderNotFound,
+    NotIssued,
+}
+
+impl TryFrom<i32> for InvoiceError {
+    type Error = SyntaxError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Unknown),
+            1 => Ok(Self::OrderNotFound),
+            2 => Ok(Self::NotIssued),
+            _ => Err(SyntaxError::UnknownValue),
+        }
+    }
+}
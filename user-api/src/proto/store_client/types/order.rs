@@ -4,7 +4,7 @@ use chrono::NaiveDateTime;
 use rust_decimal::Decimal;
 use uuid::Uuid;
 
-use super::{Article, SyntaxError};
+use super::{Article, Shipment, ShippingMethod, SyntaxError};
 
 pub struct Order {
     pub id: Uuid,
@@ -12,6 +12,9 @@ pub struct Order {
     pub transaction_id: Option<String>,
     pub status: OrderStatus,
     pub articles: Vec<OrderArticle>,
+    pub shipping_method: Option<ShippingMethod>,
+    pub shipping_cost: Decimal,
+    pub shipment: Option<Shipment>,
 }
 
 impl TryFrom<super::store::Order> for Order {
@@ -32,6 +35,14 @@ impl TryFrom<super::store::Order> for Order {
             transaction_id: value.transaction_id,
             status: OrderStatus::try_from(value.status)?,
             articles,
+            shipping_method: value
+                .shipping_method
+                .map(ShippingMethod::try_from)
+                .transpose()?,
+            shipping_cost: Decimal::from_str(
+                &value.shipping_cost.map(|x| x.value).unwrap_or_default(),
+            )?,
+            shipment: value.shipment.map(Shipment::try_from).transpose()?,
         })
     }
 }
@@ -41,6 +52,10 @@ pub enum OrderStatus {
     Preparing,
     PaymentFailed,
     Shipped,
+    ReturnRequested,
+    Refunded,
+    PartiallyRefunded,
+    Expired,
 }
 
 impl TryFrom<i32> for OrderStatus {
@@ -52,11 +67,41 @@ impl TryFrom<i32> for OrderStatus {
             1 => Ok(Self::Preparing),
             2 => Ok(Self::PaymentFailed),
             3 => Ok(Self::Shipped),
+            4 => Ok(Self::ReturnRequested),
+            5 => Ok(Self::Refunded),
+            6 => Ok(Self::PartiallyRefunded),
+            7 => Ok(Self::Expired),
             _ => Err(SyntaxError::UnknownValue),
         }
     }
 }
 
+impl From<OrderStatus> for super::store::order::OrderStatus {
+    fn from(value: OrderStatus) -> Self {
+        match value {
+            OrderStatus::Created => Self::Created,
+            OrderStatus::Preparing => Self::Preparing,
+            OrderStatus::PaymentFailed => Self::PaymentFailed,
+            OrderStatus::Shipped => Self::Shipped,
+            OrderStatus::ReturnRequested => Self::ReturnRequested,
+            OrderStatus::Refunded => Self::Refunded,
+            OrderStatus::PartiallyRefunded => Self::PartiallyRefunded,
+            OrderStatus::Expired => Self::Expired,
+        }
+    }
+}
+
+/// Filter of the orders of a customer
+#[derive(Default)]
+pub struct OrderFilter {
+    /// Orders in one of these statuses; orders in any status when empty
+    pub statuses: Vec<OrderStatus>,
+    /// Orders created from this date included
+    pub created_from: Option<NaiveDateTime>,
+    /// Orders created before this date
+    pub created_to: Option<NaiveDateTime>,
+}
+
 /// Article inside a order (order x quantity)
 pub struct OrderArticle {
     pub article: Article,
@@ -76,6 +121,7 @@ impl TryFrom<super::store::OrderArticle> for OrderArticle {
                 unit_price: Decimal::from_str(
                     &value.unit_price.map(|x| x.value).unwrap_or_default(),
                 )?,
+                rating: None,
             },
         })
     }
@@ -102,10 +148,21 @@ impl TryFrom<super::store::SubmitOrderResponse> for SubmitOrderResponse {
     }
 }
 
+/// Outcome of an order payment reported by the payment provider
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OrderPayment {
+    Succeeded { transaction_id: String },
+    Failed,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum SubmitOrderError {
     Unknown,
     InvalidArticle,
+    InvalidShippingMethod,
+    EmptyCart,
+    EmptyOrder,
+    InvalidQuantity,
 }
 
 impl TryFrom<i32> for SubmitOrderError {
@@ -115,6 +172,10 @@ impl TryFrom<i32> for SubmitOrderError {
         match value {
             0 => Ok(Self::Unknown),
             1 => Ok(Self::InvalidArticle),
+            2 => Ok(Self::InvalidShippingMethod),
+            3 => Ok(Self::EmptyCart),
+            4 => Ok(Self::EmptyOrder),
+            5 => Ok(Self::InvalidQuantity),
             _ => Err(SyntaxError::UnknownValue),
         }
     }
@@ -0,0 +1,117 @@
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use super::{OrderStatus, SyntaxError};
+
+/// Entry of the order history, with the order state after the change
+pub struct OrderEvent {
+    pub id: Uuid,
+    pub kind: OrderEventKind,
+    pub actor: OrderEventActor,
+    pub status: OrderStatus,
+    pub transaction_id: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl TryFrom<super::store::OrderEvent> for OrderEvent {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::OrderEvent) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: Uuid::from_str(&value.id)?,
+            kind: OrderEventKind::try_from(value.kind)?,
+            actor: OrderEventActor::try_from(value.actor)?,
+            status: OrderStatus::try_from(value.status)?,
+            transaction_id: value.transaction_id,
+            created_at: NaiveDateTime::parse_from_str(
+                &value.created_at.map(|x| x.timestamp).unwrap_or_default(),
+                "%Y-%m-%d %H:%M:%S",
+            )?,
+        })
+    }
+}
+
+pub enum OrderEventKind {
+    Created,
+    StatusChanged,
+    TransactionChanged,
+}
+
+impl TryFrom<i32> for OrderEventKind {
+    type Error = SyntaxError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Created),
+            1 => Ok(Self::StatusChanged),
+            2 => Ok(Self::TransactionChanged),
+            _ => Err(SyntaxError::UnknownValue),
+        }
+    }
+}
+
+pub enum OrderEventActor {
+    Customer,
+    PaymentGateway,
+    BackOffice,
+    System,
+}
+
+impl TryFrom<i32> for OrderEventActor {
+    type Error = SyntaxError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Customer),
+            1 => Ok(Self::PaymentGateway),
+            2 => Ok(Self::BackOffice),
+            3 => Ok(Self::System),
+            _ => Err(SyntaxError::UnknownValue),
+        }
+    }
+}
+
+pub enum OrderHistoryResponse {
+    Ok(Vec<OrderEvent>),
+    Err(OrderHistoryError),
+}
+
+impl TryFrom<super::store::GetOrderHistoryResponse> for OrderHistoryResponse {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::GetOrderHistoryResponse) -> Result<Self, Self::Error> {
+        match value.status {
+            Some(super::store::get_order_history_response::Status::History(history)) => {
+                let mut events = Vec::with_capacity(history.events.len());
+                for event in history.events.into_iter() {
+                    events.push(OrderEvent::try_from(event)?);
+                }
+                Ok(Self::Ok(events))
+            }
+            Some(super::store::get_order_history_response::Status::Error(err)) => {
+                Ok(Self::Err(OrderHistoryError::try_from(err)?))
+            }
+            None => Err(SyntaxError::ValueIsMissing),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum OrderHistoryError {
+    Unknown,
+    OrderNotFound,
+}
+
+impl TryFrom<i32> for OrderHistoryError {
+    type Error = SyntaxError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Unknown),
+            1 => Ok(Self::OrderNotFound),
+            _ => Err(SyntaxError::UnknownValue),
+        }
+    }
+}
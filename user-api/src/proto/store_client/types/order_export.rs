@@ -0,0 +1,22 @@
+use std::str::FromStr;
+
+use uuid::Uuid;
+
+use super::{Order, SyntaxError};
+
+/// Order exported for accounting
+pub struct ExportedOrder {
+    pub customer_id: Uuid,
+    pub order: Order,
+}
+
+impl TryFrom<super::store::ExportedOrder> for ExportedOrder {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::ExportedOrder) -> Result<Self, Self::Error> {
+        Ok(Self {
+            customer_id: Uuid::from_str(&value.customer_id)?,
+            order: Order::try_from(value.order.ok_or(SyntaxError::ValueIsMissing)?)?,
+        })
+    }
+}
@@ -0,0 +1,56 @@
+use std::str::FromStr;
+
+use uuid::Uuid;
+
+use super::SyntaxError;
+
+/// Article of an order to return
+pub struct ReturnedArticle {
+    pub id: Uuid,
+    pub quantity: u32,
+}
+
+pub enum RequestReturnResponse {
+    Ok(Uuid),
+    Err(RequestReturnError),
+}
+
+impl TryFrom<super::store::RequestReturnResponse> for RequestReturnResponse {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::RequestReturnResponse) -> Result<Self, Self::Error> {
+        match value.status {
+            Some(super::store::request_return_response::Status::ReturnId(id)) => {
+                Ok(Self::Ok(Uuid::from_str(&id)?))
+            }
+            Some(super::store::request_return_response::Status::Error(err)) => {
+                Ok(Self::Err(RequestReturnError::try_from(err)?))
+            }
+            None => Err(SyntaxError::ValueIsMissing),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum RequestReturnError {
+    Unknown,
+    OrderNotFound,
+    InvalidOrderStatus,
+    InvalidArticle,
+    InvalidQuantity,
+}
+
+impl TryFrom<i32> for RequestReturnError {
+    type Error = SyntaxError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Unknown),
+            1 => Ok(Self::OrderNotFound),
+            2 => Ok(Self::InvalidOrderStatus),
+            3 => Ok(Self::InvalidArticle),
+            4 => Ok(Self::InvalidQuantity),
+            _ => Err(SyntaxError::UnknownValue),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+/// A page of the results of a query paginated with cursors
+pub struct Page<T> {
+    /// Results with the cursor to query the results after them
+    pub edges: Vec<(String, T)>,
+    /// Cursor of the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+    /// Total amount of results of the query
+    pub total_count: u32,
+}
@@ -0,0 +1,103 @@
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::SyntaxError;
+
+/// Length of the periods revenue is grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportGranularity {
+    Day,
+    Week,
+    Month,
+}
+
+impl From<ReportGranularity> for super::store::get_revenue_report_request::Granularity {
+    fn from(value: ReportGranularity) -> Self {
+        match value {
+            ReportGranularity::Day => Self::Day,
+            ReportGranularity::Week => Self::Week,
+            ReportGranularity::Month => Self::Month,
+        }
+    }
+}
+
+/// Revenue of the orders placed in a period
+pub struct RevenuePeriod {
+    pub period_start: NaiveDateTime,
+    pub paid_orders: u64,
+    pub revenue: Decimal,
+    pub refunds: Decimal,
+}
+
+impl TryFrom<super::store::RevenueReportPeriod> for RevenuePeriod {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::RevenueReportPeriod) -> Result<Self, Self::Error> {
+        Ok(Self {
+            period_start: NaiveDateTime::parse_from_str(
+                &value.period_start.map(|x| x.timestamp).unwrap_or_default(),
+                "%Y-%m-%d %H:%M:%S",
+            )?,
+            paid_orders: value.paid_orders,
+            revenue: Decimal::from_str(&value.revenue.map(|x| x.value).unwrap_or_default())?,
+            refunds: Decimal::from_str(&value.refunds.map(|x| x.value).unwrap_or_default())?,
+        })
+    }
+}
+
+/// Article with the amount of items sold
+pub struct TopArticle {
+    pub id: Uuid,
+    pub name: String,
+    pub quantity: u64,
+    pub revenue: Decimal,
+}
+
+impl TryFrom<super::store::TopArticleReport> for TopArticle {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::TopArticleReport) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: Uuid::from_str(&value.article_id)?,
+            name: value.name,
+            quantity: value.quantity,
+            revenue: Decimal::from_str(&value.revenue.map(|x| x.value).unwrap_or_default())?,
+        })
+    }
+}
+
+/// Statistics of the orders placed in a date range
+pub struct OrderStats {
+    pub orders: u64,
+    pub paid_orders: u64,
+    pub payment_failures: u64,
+    pub average_order_value: Decimal,
+    pub payment_failure_rate: Decimal,
+}
+
+impl TryFrom<super::store::GetOrderStatsReportResult> for OrderStats {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::GetOrderStatsReportResult) -> Result<Self, Self::Error> {
+        Ok(Self {
+            orders: value.orders,
+            paid_orders: value.paid_orders,
+            payment_failures: value.payment_failures,
+            average_order_value: Decimal::from_str(
+                &value
+                    .average_order_value
+                    .map(|x| x.value)
+                    .unwrap_or_default(),
+            )?,
+            payment_failure_rate: Decimal::from_str(
+                &value
+                    .payment_failure_rate
+                    .map(|x| x.value)
+                    .unwrap_or_default(),
+            )?,
+        })
+    }
+}
@@ -0,0 +1,47 @@
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use super::SyntaxError;
+
+/// Shipping method selectable when submitting an order
+pub struct ShippingMethod {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+}
+
+impl TryFrom<super::store::ShippingMethod> for ShippingMethod {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::ShippingMethod) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: Uuid::from_str(&value.id)?,
+            name: value.name,
+            description: value.description,
+        })
+    }
+}
+
+/// Shipment details for a shipped order
+pub struct Shipment {
+    pub carrier: String,
+    pub tracking_code: String,
+    pub shipped_at: NaiveDateTime,
+}
+
+impl TryFrom<super::store::Shipment> for Shipment {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::Shipment) -> Result<Self, Self::Error> {
+        Ok(Self {
+            carrier: value.carrier,
+            tracking_code: value.tracking_code,
+            shipped_at: NaiveDateTime::parse_from_str(
+                &value.shipped_at.map(|x| x.timestamp).unwrap_or_default(),
+                "%Y-%m-%d %H:%M:%S",
+            )?,
+        })
+    }
+}
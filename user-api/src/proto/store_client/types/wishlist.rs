@@ -0,0 +1,113 @@
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::{Article, SyntaxError};
+
+/// Named list of articles bookmarked by a customer
+pub struct Wishlist {
+    pub id: Uuid,
+    pub name: String,
+    pub articles: Vec<WishlistArticle>,
+    pub created_at: NaiveDateTime,
+}
+
+impl TryFrom<super::store::Wishlist> for Wishlist {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::Wishlist) -> Result<Self, Self::Error> {
+        let mut articles = Vec::with_capacity(value.articles.len());
+        for article in value.articles.into_iter() {
+            articles.push(WishlistArticle::try_from(article)?);
+        }
+
+        Ok(Self {
+            id: Uuid::from_str(&value.id)?,
+            name: value.name,
+            articles,
+            created_at: NaiveDateTime::parse_from_str(
+                &value.created_at.map(|x| x.timestamp).unwrap_or_default(),
+                "%Y-%m-%d %H:%M:%S",
+            )?,
+        })
+    }
+}
+
+/// Article inside a wishlist; archived articles are not available
+pub struct WishlistArticle {
+    pub article: Article,
+    pub available: bool,
+    pub added_at: NaiveDateTime,
+}
+
+impl TryFrom<super::store::WishlistArticle> for WishlistArticle {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::WishlistArticle) -> Result<Self, Self::Error> {
+        Ok(Self {
+            article: Article {
+                id: Uuid::from_str(&value.id)?,
+                name: value.name,
+                description: value.description,
+                unit_price: Decimal::from_str(
+                    &value.unit_price.map(|x| x.value).unwrap_or_default(),
+                )?,
+                rating: None,
+            },
+            available: value.available,
+            added_at: NaiveDateTime::parse_from_str(
+                &value.added_at.map(|x| x.timestamp).unwrap_or_default(),
+                "%Y-%m-%d %H:%M:%S",
+            )?,
+        })
+    }
+}
+
+pub enum WishlistResponse {
+    Ok(Wishlist),
+    Err(WishlistError),
+}
+
+impl TryFrom<super::store::WishlistResponse> for WishlistResponse {
+    type Error = SyntaxError;
+
+    fn try_from(value: super::store::WishlistResponse) -> Result<Self, Self::Error> {
+        match value.status {
+            Some(super::store::wishlist_response::Status::Wishlist(wishlist)) => {
+                Ok(Self::Ok(Wishlist::try_from(wishlist)?))
+            }
+            Some(super::store::wishlist_response::Status::Error(err)) => {
+                Ok(Self::Err(WishlistError::try_from(err)?))
+            }
+            None => Err(SyntaxError::ValueIsMissing),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum WishlistError {
+    Unknown,
+    WishlistNotFound,
+    NameAlreadyTaken,
+    InvalidName,
+    InvalidArticle,
+    ArticleUnavailable,
+}
+
+impl TryFrom<i32> for WishlistError {
+    type Error = SyntaxError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Unknown),
+            1 => Ok(Self::WishlistNotFound),
+            2 => Ok(Self::NameAlreadyTaken),
+            3 => Ok(Self::InvalidName),
+            4 => Ok(Self::InvalidArticle),
+            5 => Ok(Self::ArticleUnavailable),
+            _ => Err(SyntaxError::UnknownValue),
+        }
+    }
+}
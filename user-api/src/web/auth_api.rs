@@ -1,6 +1,6 @@
 use super::{SessionClient, WebserverData};
 use crate::proto::{
-    store_client::types::{AuthError, AuthResponse as StoreAuthResponse},
+    store_client::types::{AuthError, AuthResponse as StoreAuthResponse, CartResponse},
     StoreClient,
 };
 
@@ -45,6 +45,7 @@ async fn sign_in(
     if let Some(id) = sign_in_result.user_id() {
         // put into session
         session.set_user(&id, &payload.email);
+        merge_anonymous_cart(&mut store_client, &session, id).await;
         Ok(HttpResponse::Ok().json(AuthResponse {
             id,
             email: payload.email,
@@ -72,6 +73,7 @@ async fn sign_up(
         StoreAuthResponse::Authenticated(id) => {
             // put into session
             session.set_user(&id, &payload.email);
+            merge_anonymous_cart(&mut store_client, &session, id).await;
             Ok(HttpResponse::Ok().json(AuthResponse {
                 id,
                 email: payload.email,
@@ -84,6 +86,32 @@ async fn sign_up(
     }
 }
 
+/// Move the articles of the anonymous cart in session into the cart of the customer.
+/// Failures are logged only, since they must not prevent the customer from signing in
+async fn merge_anonymous_cart(
+    store_client: &mut StoreClient,
+    session: &SessionClient,
+    user_id: Uuid,
+) {
+    if let Some(cart_id) = session.get_cart() {
+        match store_client.merge_cart(user_id, cart_id).await {
+            Ok(CartResponse::Ok(_)) => {
+                debug!("merged anonymous cart {cart_id} into cart of {user_id}")
+            }
+            Ok(CartResponse::Err(err)) => {
+                error!(
+                    "failed to merge anonymous cart {cart_id} into cart of {user_id}: {:?}",
+                    err
+                )
+            }
+            Err(err) => {
+                error!("failed to merge anonymous cart {cart_id} into cart of {user_id}: {err}")
+            }
+        }
+        session.remove_cart();
+    }
+}
+
 #[get("/auth")]
 async fn auth(session: Session) -> Result<HttpResponse, Error> {
     let session = SessionClient::from(session);
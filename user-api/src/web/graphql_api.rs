@@ -1,7 +1,14 @@
-use super::SessionClient;
+use super::{SessionClient, WebserverData};
 use crate::graphql::{
+    error::with_error_codes,
     resolvers::{
-        Articles as ArticlesResolver, Orders as OrdersResolver, SubmitOrder as SubmitOrderResolver,
+        ArticleReviews as ArticleReviewsResolver, Articles as ArticlesResolver,
+        Cart as CartResolver, CheckoutCart as CheckoutCartResolver,
+        OrderHistory as OrderHistoryResolver, Orders as OrdersResolver,
+        RelatedArticles as RelatedArticlesResolver, Reports as ReportsResolver,
+        RequestReturn as RequestReturnResolver, ShippingMethods as ShippingMethodsResolver,
+        SubmitOrder as SubmitOrderResolver, SubmitReview as SubmitReviewResolver,
+        Wishlists as WishlistsResolver,
     },
     schema::{ApiSchema, MutationRoot, QueryRoot},
     GraphqlRequestParams,
@@ -11,6 +18,7 @@ use actix_session::Session;
 use actix_web::{web, web::Data, Resource};
 use async_graphql::{EmptySubscription, Schema};
 use async_graphql_actix_web::{GraphQLRequest, GraphQLResponse};
+use uuid::Uuid;
 
 pub fn service_factory(
     protobuf_url: &str,
@@ -25,8 +33,18 @@ pub fn service_factory(
 > {
     let schema = Schema::build(QueryRoot, MutationRoot, EmptySubscription)
         .data(ArticlesResolver::new(protobuf_url))
+        .data(ArticleReviewsResolver::new(protobuf_url))
+        .data(RelatedArticlesResolver::new(protobuf_url))
         .data(OrdersResolver::new(protobuf_url))
+        .data(OrderHistoryResolver::new(protobuf_url))
         .data(SubmitOrderResolver::new(protobuf_url))
+        .data(ShippingMethodsResolver::new(protobuf_url))
+        .data(RequestReturnResolver::new(protobuf_url))
+        .data(CartResolver::new(protobuf_url))
+        .data(CheckoutCartResolver::new(protobuf_url))
+        .data(WishlistsResolver::new(protobuf_url))
+        .data(SubmitReviewResolver::new(protobuf_url))
+        .data(ReportsResolver::new(protobuf_url))
         .finish();
 
     web::resource("/graphql")
@@ -39,13 +57,30 @@ async fn graphql_action(
     schema: Data<ApiSchema>,
     req: GraphQLRequest,
     session: Session,
+    data: Data<WebserverData>,
 ) -> GraphQLResponse {
     let session = SessionClient::from(session);
-    let user_id = session.get_user().map(|x| x.id);
-    let graphql_request_params = GraphqlRequestParams { user_id };
+    let user = session.get_user();
+    let user_id = user.as_ref().map(|x| x.id);
+    let is_admin = user.map(|x| data.is_admin(&x.email)).unwrap_or(false);
+    // anonymous visitors get a cart id in session, so that their cart survives between requests
+    let cart_id = match (user_id, session.get_cart()) {
+        (Some(_), _) => None,
+        (None, Some(cart_id)) => Some(cart_id),
+        (None, None) => {
+            let cart_id = Uuid::new_v4();
+            session.set_cart(&cart_id);
+            Some(cart_id)
+        }
+    };
+    let graphql_request_params = GraphqlRequestParams {
+        user_id,
+        cart_id,
+        is_admin,
+    };
 
-    schema
+    let response = schema
         .execute(req.into_inner().data(graphql_request_params))
-        .await
-        .into()
+        .await;
+    with_error_codes(response).into()
 }
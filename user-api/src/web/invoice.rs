@@ -0,0 +1,240 @@
+use super::{SessionClient, WebserverData};
+use crate::proto::{
+    store_client::types::{Invoice, InvoiceError, InvoiceResponse},
+    StoreClient,
+};
+
+use actix_session::Session;
+use actix_web::{get, web, Error, HttpResponse, Result};
+use std::fmt::Write as _;
+use uuid::Uuid;
+
+#[derive(Deserialize, Debug)]
+struct InvoiceQuery {
+    #[serde(default)]
+    format: InvoiceFormat,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum InvoiceFormat {
+    #[default]
+    Html,
+    Text,
+}
+
+/// Render the invoice of an order of the signed in customer as HTML or plain text
+#[get("/orders/{order_id}/invoice")]
+async fn get_invoice(
+    order_id: web::Path<Uuid>,
+    query: web::Query<InvoiceQuery>,
+    data: web::Data<WebserverData>,
+    session: Session,
+) -> Result<HttpResponse, Error> {
+    let session = SessionClient::from(session);
+    let user = match session.get_user() {
+        Some(user) => user,
+        None => return Ok(HttpResponse::Unauthorized().finish()),
+    };
+    let order_id = order_id.into_inner();
+    debug!("invoice request for order {order_id} of {}", user.id);
+    let mut store_client = StoreClient::connect(data.store_client_url.clone()).await?;
+    match store_client.get_invoice(user.id, order_id).await? {
+        InvoiceResponse::Ok(invoice) => Ok(match query.format {
+            InvoiceFormat::Html => HttpResponse::Ok()
+                .content_type("text/html; charset=utf-8")
+                .body(render_html(&invoice)),
+            InvoiceFormat::Text => HttpResponse::Ok()
+                .content_type("text/plain; charset=utf-8")
+                .body(render_text(&invoice)),
+        }),
+        InvoiceResponse::Err(InvoiceError::OrderNotFound | InvoiceError::NotIssued) => {
+            Ok(HttpResponse::NotFound().finish())
+        }
+        InvoiceResponse::Err(err) => {
+            error!("could not get invoice for order {order_id}: {:?}", err);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+fn render_text(invoice: &Invoice) -> String {
+    let mut text = String::new();
+    let _ = writeln!(text, "INVOICE {}", invoice.number);
+    let _ = writeln!(text, "Issued at: {}", invoice.issued_at);
+    let _ = writeln!(text);
+    let _ = writeln!(text, "Seller: {}", invoice.seller.name);
+    let _ = writeln!(text, "{}", invoice.seller.address);
+    let _ = writeln!(text, "VAT number: {}", invoice.seller.vat_number);
+    let _ = writeln!(text);
+    let _ = writeln!(text, "Customer: {}", invoice.customer_email);
+    let _ = writeln!(text, "Order: {}", invoice.order_id);
+    let _ = writeln!(text, "Transaction: {}", invoice.transaction_id);
+    let _ = writeln!(text);
+    let _ = writeln!(
+        text,
+        "{:<40} {:>8} {:>12} {:>12}",
+        "Article", "Quantity", "Unit price", "Total"
+    );
+    for line in invoice.lines.iter() {
+        let _ = writeln!(
+            text,
+            "{:<40} {:>8} {:>12} {:>12}",
+            line.name, line.quantity, line.unit_price, line.total
+        );
+    }
+    let _ = writeln!(text);
+    let _ = writeln!(text, "{:<62}{:>12}", "Shipping", invoice.shipping_cost);
+    let _ = writeln!(
+        text,
+        "{:<62}{:>12}",
+        "Taxable amount", invoice.taxable_amount
+    );
+    let _ = writeln!(
+        text,
+        "{:<62}{:>12}",
+        format!("Tax ({}%)", invoice.tax_rate),
+        invoice.tax_amount
+    );
+    let _ = writeln!(text, "{:<62}{:>12}", "Total", invoice.total);
+    text
+}
+
+fn render_html(invoice: &Invoice) -> String {
+    let mut html = String::new();
+    let _ = writeln!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Invoice {number}</title></head>\n<body>\n<h1>Invoice {number}</h1>\n<p>Issued at {issued_at}</p>",
+        number = escape_html(&invoice.number),
+        issued_at = invoice.issued_at
+    );
+    let _ = writeln!(
+        html,
+        "<section class=\"seller\"><strong>{}</strong><br>{}<br>VAT number {}</section>",
+        escape_html(&invoice.seller.name),
+        escape_html(&invoice.seller.address),
+        escape_html(&invoice.seller.vat_number)
+    );
+    let _ = writeln!(
+        html,
+        "<section class=\"customer\">Customer {}<br>Order {}<br>Transaction {}</section>",
+        escape_html(&invoice.customer_email),
+        invoice.order_id,
+        escape_html(&invoice.transaction_id)
+    );
+    html.push_str("<table>\n<thead><tr><th>Article</th><th>Quantity</th><th>Unit price</th><th>Total</th></tr></thead>\n<tbody>\n");
+    for line in invoice.lines.iter() {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape_html(&line.name),
+            line.quantity,
+            line.unit_price,
+            line.total
+        );
+    }
+    html.push_str("</tbody>\n<tfoot>\n");
+    let _ = writeln!(
+        html,
+        "<tr><td colspan=\"3\">Shipping</td><td>{}</td></tr>",
+        invoice.shipping_cost
+    );
+    let _ = writeln!(
+        html,
+        "<tr><td colspan=\"3\">Taxable amount</td><td>{}</td></tr>",
+        invoice.taxable_amount
+    );
+    let _ = writeln!(
+        html,
+        "<tr><td colspan=\"3\">Tax ({}%)</td><td>{}</td></tr>",
+        invoice.tax_rate, invoice.tax_amount
+    );
+    let _ = writeln!(
+        html,
+        "<tr><td colspan=\"3\"><strong>Total</strong></td><td><strong>{}</strong></td></tr>",
+        invoice.total
+    );
+    html.push_str("</tfoot>\n</table>\n</body>\n</html>\n");
+    html
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::proto::store_client::types::{InvoiceLine, InvoiceSeller};
+
+    use chrono::NaiveDateTime;
+    use pretty_assertions::assert_eq;
+    use rust_decimal_macros::dec;
+    use std::str::FromStr;
+
+    #[test]
+    fn should_escape_html() {
+        assert_eq!(
+            escape_html(r#"<b>"Tom" & 'Jerry'</b>"#),
+            "&lt;b&gt;&quot;Tom&quot; &amp; &#39;Jerry&#39;&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn should_render_invoice_as_text() {
+        let text = render_text(&invoice());
+        assert!(text.starts_with("INVOICE 2023/000042\n"));
+        assert!(text.contains("VAT number: IT01234567890"));
+        assert!(text.contains("Customer: customer@prima.it"));
+        assert!(text.contains("Mug <large>"));
+        assert!(text.contains("Tax (22%)"));
+        assert!(text.lines().last().unwrap().ends_with("25.00"));
+    }
+
+    #[test]
+    fn should_render_invoice_as_html() {
+        let html = render_html(&invoice());
+        assert!(html.contains("<h1>Invoice 2023/000042</h1>"));
+        assert!(html.contains("<td>Mug &lt;large&gt;</td><td>2</td><td>10.00</td><td>20.00</td>"));
+        assert!(html.contains("<strong>25.00</strong>"));
+        assert!(!html.contains("<large>"));
+    }
+
+    fn invoice() -> Invoice {
+        Invoice {
+            order_id: Uuid::new_v4(),
+            number: "2023/000042".to_string(),
+            issued_at: NaiveDateTime::from_str("2023-04-07T10:00:00").unwrap(),
+            seller: InvoiceSeller {
+                name: "Prima Store S.r.l.".to_string(),
+                address: "Via Roma 1, 20121 Milano, Italy".to_string(),
+                vat_number: "IT01234567890".to_string(),
+            },
+            customer_email: "customer@prima.it".to_string(),
+            transaction_id: "tx_1".to_string(),
+            lines: vec![InvoiceLine {
+                name: "Mug <large>".to_string(),
+                quantity: 2,
+                unit_price: dec!(10.00),
+                total: dec!(20.00),
+            }],
+            shipping_cost: dec!(5.00),
+            tax_rate: dec!(22),
+            taxable_amount: dec!(20.49),
+            tax_amount: dec!(4.51),
+            total: dec!(25.00),
+        }
+    }
+}
@@ -3,8 +3,12 @@
 mod auth_api;
 mod graphql_api;
 mod health_check;
+mod invoice;
+mod order_export;
+mod payment_webhook;
 mod session;
 
+use payment_webhook::PaymentWebhook;
 use session::SessionClient;
 
 use actix_session::{storage::CookieSessionStore, SessionMiddleware};
@@ -18,23 +22,63 @@ pub struct WebServer {
 
 struct WebserverData {
     pub store_client_url: String,
+    /// Emails of the users allowed to access the admin queries
+    pub admin_emails: Vec<String>,
+}
+
+impl WebserverData {
+    /// Returns whether the user with `email` can access the admin queries
+    fn is_admin(&self, email: &str) -> bool {
+        self.admin_emails
+            .iter()
+            .any(|admin| admin.eq_ignore_ascii_case(email))
+    }
 }
 
 impl WebServer {
     /// Initialize web server
-    pub async fn init(protobuf_url: &str, web_port: u16) -> anyhow::Result<Self> {
+    pub async fn init(
+        protobuf_url: &str,
+        web_port: u16,
+        webhook_secret: &str,
+        webhook_tolerance_secs: i64,
+        admin_emails: &[String],
+    ) -> anyhow::Result<Self> {
+        debug!("web port: {web_port}");
+        let listener = TcpListener::bind(&format!("0.0.0.0:{web_port}"))?;
+        Self::with_listener(
+            listener,
+            protobuf_url,
+            webhook_secret,
+            webhook_tolerance_secs,
+            admin_emails,
+        )
+    }
+
+    /// Initialize web server, accepting the connections of `listener`
+    pub fn with_listener(
+        listener: TcpListener,
+        protobuf_url: &str,
+        webhook_secret: &str,
+        webhook_tolerance_secs: i64,
+        admin_emails: &[String],
+    ) -> anyhow::Result<Self> {
         debug!("webserver initialized");
         debug!("protobuf url: {protobuf_url}");
-        debug!("web port: {web_port}");
+        debug!("listening on {:?}", listener.local_addr());
 
-        let listener = TcpListener::bind(&format!("0.0.0.0:{web_port}"))?;
         let secret_key = Key::generate();
+        // shared among the workers of this instance, so that they deduplicate events together
+        let payment_webhook =
+            Data::new(PaymentWebhook::new(webhook_secret, webhook_tolerance_secs));
 
         let server = {
             let protobuf_url = protobuf_url.to_string();
+            let admin_emails = admin_emails.to_vec();
             HttpServer::new(move || {
                 let web_data = Data::new(WebserverData {
                     store_client_url: protobuf_url.to_string(),
+                    admin_emails: admin_emails.clone(),
                 });
                 ActixApp::new()
                     .service(graphql_api::service_factory(&protobuf_url))
@@ -42,7 +86,11 @@ impl WebServer {
                     .service(auth_api::sign_in)
                     .service(auth_api::sign_up)
                     .service(auth_api::auth)
+                    .service(order_export::export_orders)
+                    .service(invoice::get_invoice)
+                    .service(payment_webhook::payment)
                     .app_data(web_data)
+                    .app_data(payment_webhook.clone())
                     .wrap(SessionMiddleware::new(
                         CookieSessionStore::default(),
                         secret_key.clone(),
@@ -0,0 +1,314 @@
+use super::{SessionClient, WebserverData};
+use crate::proto::{
+    store_client::types::{ExportedOrder, OrderStatus},
+    StoreClient,
+};
+
+use actix_session::Session;
+use actix_web::{get, http::header, web, Error, HttpResponse, Result};
+use chrono::{Duration, NaiveDate};
+use futures_util::{stream, StreamExt};
+use rust_decimal::Decimal;
+use std::borrow::Cow;
+use uuid::Uuid;
+
+/// Format of the `from` and `to` days of the export
+const DAY_FORMAT: &str = "%Y-%m-%d";
+
+const CSV_HEADER: &str = "order_id,created_at,customer_id,status,transaction_id,shipping_cost,article_id,article_name,quantity,unit_price,line_total\n";
+
+#[derive(Deserialize, Debug)]
+struct ExportOrdersQuery {
+    from: String,
+    to: String,
+    #[serde(default)]
+    format: ExportFormat,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum ExportFormat {
+    #[default]
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+
+    /// Encode an order; CSV has a row for each article, NDJSON a line for each order
+    fn encode(&self, order: ExportedOrder) -> String {
+        match self {
+            Self::Csv => Self::csv_rows(order),
+            Self::Ndjson => {
+                let mut line = serde_json::to_string(&ExportedOrderRecord::from(order))
+                    .expect("order record is always serializable");
+                line.push('\n');
+                line
+            }
+        }
+    }
+
+    fn csv_rows(order: ExportedOrder) -> String {
+        let record = ExportedOrderRecord::from(order);
+        let order_fields = [
+            record.id.to_string(),
+            record.created_at.clone(),
+            record.customer_id.to_string(),
+            record.status.to_string(),
+            record.transaction_id.clone().unwrap_or_default(),
+            record.shipping_cost.to_string(),
+        ]
+        .iter()
+        .map(|x| csv_field(x).into_owned())
+        .collect::<Vec<_>>()
+        .join(",");
+        // orders without articles still get a row, so that their shipping cost is accounted
+        if record.lines.is_empty() {
+            return format!("{order_fields},,,,,\n");
+        }
+        record
+            .lines
+            .iter()
+            .map(|line| {
+                format!(
+                    "{order_fields},{},{},{},{},{}\n",
+                    line.article_id,
+                    csv_field(&line.article_name),
+                    line.quantity,
+                    line.unit_price,
+                    line.total
+                )
+            })
+            .collect()
+    }
+}
+
+/// Quote a CSV field if it contains separators, quotes or line breaks.
+/// Fields which a spreadsheet would evaluate as a formula are prefixed with `'`
+fn csv_field(value: &str) -> Cow<'_, str> {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        Cow::Owned(format!("'{value}"))
+    } else {
+        Cow::Borrowed(value)
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        value
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct ExportedOrderRecord {
+    id: Uuid,
+    created_at: String,
+    customer_id: Uuid,
+    status: &'static str,
+    transaction_id: Option<String>,
+    shipping_cost: Decimal,
+    lines: Vec<ExportedOrderLine>,
+}
+
+#[derive(Serialize, Debug)]
+struct ExportedOrderLine {
+    article_id: Uuid,
+    article_name: String,
+    quantity: u32,
+    unit_price: Decimal,
+    total: Decimal,
+}
+
+impl From<ExportedOrder> for ExportedOrderRecord {
+    fn from(value: ExportedOrder) -> Self {
+        let order = value.order;
+        Self {
+            id: order.id,
+            created_at: order.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            customer_id: value.customer_id,
+            status: match order.status {
+                OrderStatus::Created => "created",
+                OrderStatus::Preparing => "preparing",
+                OrderStatus::PaymentFailed => "payment_failed",
+                OrderStatus::Shipped => "shipped",
+                OrderStatus::ReturnRequested => "return_requested",
+                OrderStatus::Refunded => "refunded",
+                OrderStatus::PartiallyRefunded => "partially_refunded",
+                OrderStatus::Expired => "expired",
+            },
+            transaction_id: order.transaction_id,
+            shipping_cost: order.shipping_cost,
+            lines: order
+                .articles
+                .into_iter()
+                .map(|x| ExportedOrderLine {
+                    article_id: x.article.id,
+                    article_name: x.article.name,
+                    quantity: x.quantity,
+                    unit_price: x.article.unit_price,
+                    total: x.article.unit_price * Decimal::from(x.quantity),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Stream the orders created from day `from` to day `to` included as CSV or NDJSON; admins only.
+/// Days are in the `YYYY-MM-DD` format.
+/// Orders are written as they are received from the store, without collecting the whole export
+#[get("/admin/orders/export")]
+async fn export_orders(
+    query: web::Query<ExportOrdersQuery>,
+    data: web::Data<WebserverData>,
+    session: Session,
+) -> Result<HttpResponse, Error> {
+    let session = SessionClient::from(session);
+    match session.get_user() {
+        None => return Ok(HttpResponse::Unauthorized().finish()),
+        Some(user) if !data.is_admin(&user.email) => return Ok(HttpResponse::Forbidden().finish()),
+        Some(_) => {}
+    }
+    let query = query.into_inner();
+    debug!("export orders request {:?}", query);
+    let (from, to) = match (
+        NaiveDate::parse_from_str(&query.from, DAY_FORMAT),
+        NaiveDate::parse_from_str(&query.to, DAY_FORMAT),
+    ) {
+        (Ok(from), Ok(to)) if from <= to => (from, to),
+        _ => {
+            return Ok(HttpResponse::BadRequest().body(
+                "`from` and `to` must be days in the YYYY-MM-DD format, `from` not after `to`",
+            ))
+        }
+    };
+    let format = query.format;
+    let mut store_client = StoreClient::connect(data.store_client_url.clone()).await?;
+    let orders = store_client
+        .export_orders(
+            from.and_hms_opt(0, 0, 0).expect("midnight is a valid time"),
+            (to + Duration::days(1))
+                .and_hms_opt(0, 0, 0)
+                .expect("midnight is a valid time"),
+        )
+        .await?;
+    let csv_header = match format {
+        ExportFormat::Csv => Some(Ok(web::Bytes::from_static(CSV_HEADER.as_bytes()))),
+        ExportFormat::Ndjson => None,
+    };
+    let body = stream::iter(csv_header).chain(orders.map(move |order| {
+        // errors abort the response, so that a partial export can't be mistaken for a complete one
+        order.map(|order| web::Bytes::from(format.encode(order)))
+    }));
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"orders-{}-{}.{}\"",
+                from.format("%Y%m%d"),
+                to.format("%Y%m%d"),
+                format.extension()
+            ),
+        ))
+        .streaming(body))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::proto::store_client::types::{Article, Order, OrderArticle};
+
+    use pretty_assertions::assert_eq;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn should_quote_csv_fields() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("15\" screen"), "\"15\"\" screen\"");
+    }
+
+    #[test]
+    fn should_escape_csv_formulas() {
+        assert_eq!(csv_field("=SUM(A1:A2)"), "'=SUM(A1:A2)");
+        assert_eq!(csv_field("+39 Mug"), "'+39 Mug");
+        assert_eq!(csv_field("-1"), "'-1");
+        assert_eq!(csv_field("@cmd"), "'@cmd");
+        assert_eq!(csv_field("=1,2"), "\"'=1,2\"");
+        assert_eq!(csv_field("Mug = cup"), "Mug = cup");
+    }
+
+    #[test]
+    fn should_encode_order_as_csv_rows() {
+        let order = exported_order(vec![(dec!(10.50), 2, "Mug, large"), (dec!(3), 1, "Pen")]);
+        let rows = ExportFormat::Csv.encode(order);
+        let rows: Vec<&str> = rows.lines().collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].ends_with(",\"Mug, large\",2,10.50,21.00"));
+        assert!(rows[0].contains(",shipped,tx_1,5,"));
+        assert!(rows[1].ends_with(",Pen,1,3,3"));
+    }
+
+    #[test]
+    fn should_encode_order_without_articles_as_csv_row() {
+        let rows = ExportFormat::Csv.encode(exported_order(vec![]));
+        assert_eq!(rows.matches('\n').count(), 1);
+        assert!(rows.ends_with(",tx_1,5,,,,,\n"));
+    }
+
+    #[test]
+    fn should_encode_order_as_ndjson_line() {
+        let line = ExportFormat::Ndjson.encode(exported_order(vec![(dec!(3), 2, "Pen")]));
+        assert!(line.ends_with('\n'));
+        let value: serde_json::Value = serde_json::from_str(line.trim_end()).unwrap();
+        assert_eq!(value["status"], "shipped");
+        assert_eq!(value["transaction_id"], "tx_1");
+        assert_eq!(value["lines"][0]["article_name"], "Pen");
+        assert_eq!(value["lines"][0]["total"], "6");
+    }
+
+    fn exported_order(articles: Vec<(Decimal, u32, &str)>) -> ExportedOrder {
+        ExportedOrder {
+            customer_id: Uuid::new_v4(),
+            order: Order {
+                id: Uuid::new_v4(),
+                created_at: NaiveDate::from_ymd_opt(2023, 4, 1)
+                    .unwrap()
+                    .and_hms_opt(10, 0, 0)
+                    .unwrap(),
+                transaction_id: Some("tx_1".to_string()),
+                status: OrderStatus::Shipped,
+                articles: articles
+                    .into_iter()
+                    .map(|(unit_price, quantity, name)| OrderArticle {
+                        article: Article {
+                            id: Uuid::new_v4(),
+                            name: name.to_string(),
+                            description: String::new(),
+                            unit_price,
+                            rating: None,
+                        },
+                        quantity,
+                    })
+                    .collect(),
+                shipping_method: None,
+                shipping_cost: dec!(5),
+                shipment: None,
+            },
+        }
+    }
+}
@@ -0,0 +1,306 @@
+//! # Payment webhook
+//!
+//! Endpoint called by the payment provider to report the outcome of an order payment.
+//!
+//! Each request must carry the `X-Webhook-Signature` header in the form `t=<unix-timestamp>,v1=<signature>`,
+//! where the signature is the hex encoded HMAC-SHA256 of `<timestamp>.<body>` keyed with the shared webhook secret.
+//!
+//! Events may be delivered more than once, by any instance. Each instance deduplicates events by id;
+//! a redelivery reaching another instance is acknowledged by the store, which applies a payment
+//! outcome to an order only once.
+//!
+//! A payment captured for an order which can't accept it anymore, e.g. because it has expired, is
+//! answered with `409 Conflict`, so that the provider keeps the payment on record to be refunded.
+
+use super::WebserverData;
+use crate::proto::{
+    store_client::types::{OrderPayment, SubmitOrderResponse},
+    ProtobufError, StoreClient,
+};
+
+use actix_web::{post, web, Error, HttpRequest, HttpResponse, Result};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Payment event sent by the payment provider
+#[derive(Deserialize, Debug)]
+struct PaymentEvent {
+    id: String,
+    #[serde(flatten)]
+    kind: PaymentEventKind,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type")]
+enum PaymentEventKind {
+    #[serde(rename = "payment.succeeded")]
+    Succeeded {
+        order_id: Uuid,
+        transaction_id: String,
+    },
+    #[serde(rename = "payment.failed")]
+    Failed { order_id: Uuid },
+}
+
+impl PaymentEventKind {
+    fn order_id(&self) -> Uuid {
+        match self {
+            Self::Succeeded { order_id, .. } | Self::Failed { order_id } => *order_id,
+        }
+    }
+}
+
+impl From<PaymentEventKind> for OrderPayment {
+    fn from(value: PaymentEventKind) -> Self {
+        match value {
+            PaymentEventKind::Succeeded { transaction_id, .. } => {
+                OrderPayment::Succeeded { transaction_id }
+            }
+            PaymentEventKind::Failed { .. } => OrderPayment::Failed,
+        }
+    }
+}
+
+/// Verifies webhook signatures and keeps track of the events already processed
+pub struct PaymentWebhook {
+    secret: String,
+    tolerance_secs: i64,
+    /// Processed event ids with their timestamp
+    processed_events: Mutex<HashMap<String, i64>>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum SignatureError {
+    Malformed,
+    Expired,
+    Mismatch,
+}
+
+impl PaymentWebhook {
+    pub fn new(secret: &str, tolerance_secs: i64) -> Self {
+        Self {
+            secret: secret.to_string(),
+            tolerance_secs,
+            processed_events: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Verify signature header against body. Returns the signed timestamp
+    fn verify(&self, header: &str, body: &[u8], now: i64) -> Result<i64, SignatureError> {
+        let mut timestamp = None;
+        let mut signature = None;
+        for part in header.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+                Some(("v1", value)) => signature = hex::decode(value).ok(),
+                _ => {}
+            }
+        }
+        let (timestamp, signature) = match (timestamp, signature) {
+            (Some(timestamp), Some(signature)) => (timestamp, signature),
+            _ => return Err(SignatureError::Malformed),
+        };
+        if (now - timestamp).abs() > self.tolerance_secs {
+            return Err(SignatureError::Expired);
+        }
+        self.mac(timestamp, body)
+            .verify_slice(&signature)
+            .map_err(|_| SignatureError::Mismatch)?;
+
+        Ok(timestamp)
+    }
+
+    fn mac(&self, timestamp: i64, body: &[u8]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(self.secret.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        mac
+    }
+
+    /// Mark event as processed. Returns false if the event had already been processed.
+    ///
+    /// Events older than the tolerance are forgotten, since they would be rejected anyway.
+    fn mark_processed(&self, event_id: &str, timestamp: i64, now: i64) -> bool {
+        let mut processed_events = self.processed_events.lock().unwrap();
+        processed_events.retain(|_, seen_at| now - *seen_at <= self.tolerance_secs);
+        if processed_events.contains_key(event_id) {
+            return false;
+        }
+        processed_events.insert(event_id.to_string(), timestamp);
+        true
+    }
+
+    /// Forget event, so that it can be delivered again
+    fn unmark_processed(&self, event_id: &str) {
+        self.processed_events.lock().unwrap().remove(event_id);
+    }
+}
+
+#[post("/webhooks/payment")]
+async fn payment(
+    request: HttpRequest,
+    body: web::Bytes,
+    webhook: web::Data<PaymentWebhook>,
+    data: web::Data<WebserverData>,
+) -> Result<HttpResponse, Error> {
+    let now = chrono::Utc::now().timestamp();
+    let header = match request
+        .headers()
+        .get(SIGNATURE_HEADER)
+        .and_then(|x| x.to_str().ok())
+    {
+        Some(header) => header,
+        None => {
+            debug!("payment webhook without signature");
+            return Ok(HttpResponse::Unauthorized().finish());
+        }
+    };
+    let timestamp = match webhook.verify(header, &body, now) {
+        Ok(timestamp) => timestamp,
+        Err(err) => {
+            debug!("payment webhook rejected: {:?}", err);
+            return Ok(HttpResponse::Unauthorized().finish());
+        }
+    };
+    let event: PaymentEvent = match serde_json::from_slice(&body) {
+        Ok(event) => event,
+        Err(err) => {
+            debug!("bad payment webhook payload: {err}");
+            return Ok(HttpResponse::BadRequest().finish());
+        }
+    };
+    debug!("payment webhook event {:?}", event);
+    if !webhook.mark_processed(&event.id, timestamp, now) {
+        debug!("payment event {} already processed", event.id);
+        return Ok(HttpResponse::Ok().finish());
+    }
+    let order_id = event.kind.order_id();
+    let captured = matches!(event.kind, PaymentEventKind::Succeeded { .. });
+    // forward to store
+    let result = match StoreClient::connect(data.store_client_url.clone()).await {
+        Ok(mut store_client) => {
+            store_client
+                .submit_order_payment(order_id, event.kind.into())
+                .await
+        }
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(SubmitOrderResponse::Ok(_)) => Ok(HttpResponse::Ok().finish()),
+        Ok(SubmitOrderResponse::Err(err)) => {
+            error!("store rejected payment for order {order_id}: {:?}", err);
+            webhook.unmark_processed(&event.id);
+            Ok(HttpResponse::UnprocessableEntity().finish())
+        }
+        // the order is no longer waiting for payment, e.g. it has expired: a captured payment
+        // must be refunded, while a failed one has nothing left to do
+        Err(ProtobufError::FailedPrecondition(message)) if captured => {
+            error!(
+                "payment event {} captured a payment the store refused: {message}",
+                event.id
+            );
+            webhook.unmark_processed(&event.id);
+            Ok(HttpResponse::Conflict().finish())
+        }
+        Err(ProtobufError::FailedPrecondition(message)) => {
+            warn!("payment event {} ignored: {message}", event.id);
+            Ok(HttpResponse::Ok().finish())
+        }
+        Err(err) => {
+            error!("could not submit payment for order {order_id}: {err}");
+            webhook.unmark_processed(&event.id);
+            Err(err.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    const NOW: i64 = 1_679_000_000;
+
+    fn sign(webhook: &PaymentWebhook, timestamp: i64, body: &[u8]) -> String {
+        let signature = hex::encode(webhook.mac(timestamp, body).finalize().into_bytes());
+        format!("t={timestamp},v1={signature}")
+    }
+
+    #[test]
+    fn should_verify_signature() {
+        let webhook = PaymentWebhook::new("secret", 300);
+        let body = br#"{"id":"evt_1","type":"payment.failed"}"#;
+        let header = sign(&webhook, NOW - 10, body);
+        assert_eq!(webhook.verify(&header, body, NOW), Ok(NOW - 10));
+    }
+
+    #[test]
+    fn should_reject_bad_signature() {
+        let webhook = PaymentWebhook::new("secret", 300);
+        let body = br#"{"id":"evt_1","type":"payment.failed"}"#;
+        let header = sign(&PaymentWebhook::new("other", 300), NOW, body);
+        assert_eq!(
+            webhook.verify(&header, body, NOW),
+            Err(SignatureError::Mismatch)
+        );
+        let header = sign(&webhook, NOW, body);
+        assert_eq!(
+            webhook.verify(&header, b"{}", NOW),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            webhook.verify("v1=deadbeef", body, NOW),
+            Err(SignatureError::Malformed)
+        );
+    }
+
+    #[test]
+    fn should_reject_expired_signature() {
+        let webhook = PaymentWebhook::new("secret", 300);
+        let body = br#"{"id":"evt_1","type":"payment.failed"}"#;
+        let header = sign(&webhook, NOW - 301, body);
+        assert_eq!(
+            webhook.verify(&header, body, NOW),
+            Err(SignatureError::Expired)
+        );
+    }
+
+    #[test]
+    fn should_deduplicate_events() {
+        let webhook = PaymentWebhook::new("secret", 300);
+        assert!(webhook.mark_processed("evt_1", NOW, NOW));
+        assert!(!webhook.mark_processed("evt_1", NOW, NOW));
+        webhook.unmark_processed("evt_1");
+        assert!(webhook.mark_processed("evt_1", NOW, NOW));
+        // old events are forgotten
+        assert!(webhook.mark_processed("evt_1", NOW, NOW + 301));
+    }
+
+    #[test]
+    fn should_parse_payment_event() {
+        let order_id = Uuid::new_v4();
+        let event: PaymentEvent = serde_json::from_str(&format!(
+            r#"{{"id":"evt_1","type":"payment.succeeded","order_id":"{order_id}","transaction_id":"tx_1"}}"#
+        ))
+        .unwrap();
+        assert_eq!(event.id, "evt_1");
+        assert_eq!(event.kind.order_id(), order_id);
+        assert_eq!(
+            OrderPayment::from(event.kind),
+            OrderPayment::Succeeded {
+                transaction_id: "tx_1".to_string()
+            }
+        );
+    }
+}
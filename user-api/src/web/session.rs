@@ -2,6 +2,7 @@ use actix_session::Session as ActixSession;
 use uuid::Uuid;
 
 const SESSION_USER: &str = "auth-user";
+const SESSION_CART: &str = "anonymous-cart";
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SessionUser {
@@ -39,4 +40,24 @@ impl SessionClient {
         debug!("GET {SESSION_USER}");
         self.session.get(SESSION_USER).unwrap()
     }
+
+    /// Set the anonymous cart of the visitor into session
+    pub fn set_cart(&self, id: &Uuid) {
+        debug!("SET {SESSION_CART}: {id}");
+        if let Err(err) = self.session.insert(SESSION_CART, id) {
+            error!("SET ERROR: {err}");
+        }
+    }
+
+    /// Retrieve the anonymous cart of the visitor from session
+    pub fn get_cart(&self) -> Option<Uuid> {
+        debug!("GET {SESSION_CART}");
+        self.session.get(SESSION_CART).unwrap()
+    }
+
+    /// Remove the anonymous cart of the visitor from session
+    pub fn remove_cart(&self) {
+        debug!("REMOVE {SESSION_CART}");
+        self.session.remove(SESSION_CART);
+    }
 }